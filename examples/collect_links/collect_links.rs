@@ -14,11 +14,13 @@ async fn main() -> Result<()> {
             )),
             file_type_hint: None,
             excluded_paths: None,
+            follow_symlinks: false,
         },
         Input {
             source: InputSource::FsPath(PathBuf::from("fixtures/TEST.md")),
             file_type_hint: None,
             excluded_paths: None,
+            follow_symlinks: false,
         },
     ];
 
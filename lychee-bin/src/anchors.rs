@@ -0,0 +1,134 @@
+//! Report anchors which are defined in local inputs, but never linked to.
+//!
+//! This is purely informational and helps to find dead sections in a set of
+//! documents. It never influences the exit code.
+
+use std::{
+    collections::{BTreeSet, HashSet},
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+use futures::{Stream, StreamExt};
+use lychee_lib::{extract::Extractor, Input, InputSource, Request, Uri};
+use percent_encoding::percent_decode_str;
+use reqwest::Url;
+
+/// Fragments of all links to local files, together with the linked file
+type References = Arc<Mutex<HashSet<(PathBuf, String)>>>;
+
+/// Collects the anchors referenced by links while checking, so that the
+/// anchors defined in the inputs but never referenced can be reported
+/// afterwards.
+#[derive(Debug)]
+pub(crate) struct AnchorReport {
+    inputs: Vec<Input>,
+    referenced: References,
+}
+
+impl AnchorReport {
+    /// Create a report over the given inputs. Only local files are
+    /// considered, as the anchors of remote inputs can't be linked to with a
+    /// relative link anyway.
+    pub(crate) fn new(inputs: Vec<Input>) -> Self {
+        Self {
+            inputs,
+            referenced: References::default(),
+        }
+    }
+
+    /// Write all anchors which none of the recorded links point to to stderr
+    pub(crate) async fn report(self, skip_missing: bool) -> Result<()> {
+        let unreferenced = self.unreferenced(skip_missing).await?;
+        if unreferenced.is_empty() {
+            return Ok(());
+        }
+
+        let mut handle = io::stderr();
+        writeln!(handle, "\nUnreferenced anchors ({}):", unreferenced.len())?;
+        for anchor in unreferenced {
+            writeln!(handle, "  {anchor}")?;
+        }
+        Ok(())
+    }
+
+    /// Returns all unreferenced anchors as `path#anchor`, sorted
+    async fn unreferenced(self, skip_missing: bool) -> Result<BTreeSet<String>> {
+        let referenced = self
+            .referenced
+            .lock()
+            .expect("anchor references lock poisoned")
+            .clone();
+        let extractor = Extractor::default();
+        let mut unreferenced = BTreeSet::new();
+
+        let local_inputs = self.inputs.into_iter().filter(|input| {
+            matches!(
+                input.source,
+                InputSource::FsPath(_) | InputSource::FsGlob { .. }
+            )
+        });
+
+        for input in local_inputs {
+            let mut contents = Box::pin(input.get_contents(skip_missing));
+            while let Some(content) = contents.next().await {
+                let content = content?;
+                let InputSource::FsPath(ref path) = content.source else {
+                    continue;
+                };
+                let canonical = canonicalize(path);
+                for anchor in extractor.extract_fragments(&content) {
+                    if !referenced.contains(&(canonical.clone(), anchor.clone())) {
+                        unreferenced.insert(format!("{}#{anchor}", path.display()));
+                    }
+                }
+            }
+        }
+
+        Ok(unreferenced)
+    }
+}
+
+/// Record the anchors referenced by `requests` in `report` (if any) while
+/// they are passed on for checking
+pub(crate) fn record_references<S>(
+    requests: S,
+    report: Option<&AnchorReport>,
+) -> impl Stream<Item = lychee_lib::Result<Request>>
+where
+    S: Stream<Item = lychee_lib::Result<Request>>,
+{
+    let referenced = report.map(|report| report.referenced.clone());
+    requests.inspect(move |request| {
+        if let (Some(referenced), Ok(request)) = (&referenced, request) {
+            record(referenced, &request.uri);
+        }
+    })
+}
+
+/// Record the fragment of `uri` if it links to a local file
+fn record(referenced: &References, uri: &Uri) {
+    if !uri.is_file() {
+        return;
+    }
+    let Ok(url) = Url::parse(uri.as_str()) else {
+        return;
+    };
+    let (Some(fragment), Ok(path)) = (url.fragment(), url.to_file_path()) else {
+        return;
+    };
+    let fragment = percent_decode_str(fragment).decode_utf8_lossy();
+    referenced
+        .lock()
+        .expect("anchor references lock poisoned")
+        .insert((canonicalize(&path), fragment.to_string()));
+}
+
+/// Canonicalize `path` so that links and inputs can be compared, falling back
+/// to the original path if it doesn't exist
+fn canonicalize(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
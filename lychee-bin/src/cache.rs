@@ -9,7 +9,22 @@ use std::path::Path;
 #[derive(Serialize, Deserialize)]
 pub(crate) struct CacheValue {
     pub(crate) status: CacheStatus,
+    /// When the link was checked
     pub(crate) timestamp: Timestamp,
+    /// When the entry was last used, either by checking the link or by
+    /// serving it from the cache. Missing in cache files of older versions.
+    pub(crate) last_access: Option<Timestamp>,
+}
+
+impl CacheValue {
+    /// Mark the entry as used now
+    pub(crate) fn touch(&mut self) {
+        self.last_access = Some(timestamp());
+    }
+
+    fn last_access(&self) -> Timestamp {
+        self.last_access.unwrap_or(self.timestamp)
+    }
 }
 
 impl From<&Status> for CacheValue {
@@ -18,6 +33,7 @@ impl From<&Status> for CacheValue {
         CacheValue {
             status: s.into(),
             timestamp,
+            last_access: Some(timestamp),
         }
     }
 }
@@ -30,18 +46,31 @@ pub(crate) type Cache = DashMap<Uri, CacheValue>;
 
 pub(crate) trait StoreExt {
     /// Store the cache under the given path. Update access timestamps
-    fn store<T: AsRef<Path>>(&self, path: T) -> Result<()>;
+    ///
+    /// If `max_entries` is set, only the most recently used entries are kept
+    /// and the least recently used ones are evicted.
+    fn store<T: AsRef<Path>>(&self, path: T, max_entries: Option<usize>) -> Result<()>;
 
-    /// Load cache from path. Discard entries older than `max_age_secs`
+    /// Load cache from path. Discard entries older than `max_age_secs` and
+    /// entries without a timestamp
     fn load<T: AsRef<Path>>(path: T, max_age_secs: u64) -> Result<Cache>;
 }
 
 impl StoreExt for Cache {
-    fn store<T: AsRef<Path>>(&self, path: T) -> Result<()> {
+    fn store<T: AsRef<Path>>(&self, path: T, max_entries: Option<usize>) -> Result<()> {
         let mut wtr = csv::WriterBuilder::new()
             .has_headers(false)
             .from_path(path)?;
-        for result in self {
+
+        let mut entries: Vec<_> = self.iter().collect();
+        if let Some(max_entries) = max_entries {
+            // Most recently used first, so that truncating evicts the least
+            // recently used entries
+            entries.sort_by_key(|entry| std::cmp::Reverse(entry.value().last_access()));
+            entries.truncate(max_entries);
+        }
+
+        for result in entries {
             wtr.serialize((result.key(), result.value()))?;
         }
         Ok(())
@@ -50,12 +79,24 @@ impl StoreExt for Cache {
     fn load<T: AsRef<Path>>(path: T, max_age_secs: u64) -> Result<Cache> {
         let mut rdr = csv::ReaderBuilder::new()
             .has_headers(false)
+            .flexible(true)
             .from_path(path)?;
 
         let map = DashMap::new();
         let current_ts = timestamp();
-        for result in rdr.deserialize() {
-            let (uri, value): (Uri, CacheValue) = result?;
+        for result in rdr.records() {
+            let mut record = result?;
+            // Older versions stored entries as `url,status` without a
+            // timestamp. Their age is unknown, so they are checked again.
+            if record.len() < 3 {
+                continue;
+            }
+            // Entries without the time of the last access count as last used
+            // when they were checked
+            if record.len() == 3 {
+                record.push_field("");
+            }
+            let (uri, value): (Uri, CacheValue) = record.deserialize(None)?;
             // Discard entries older than `max_age_secs`.
             // This allows gradually updating the cache over multiple runs.
             if current_ts - value.timestamp < max_age_secs {
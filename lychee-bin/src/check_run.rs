@@ -0,0 +1,185 @@
+//! Report broken links as a GitHub Check Run.
+//!
+//! The check run is created through the GitHub API for the commit lychee runs
+//! on, as given by the `GITHUB_REPOSITORY` and `GITHUB_SHA` environment
+//! variables of GitHub Actions. Each broken link in a file becomes an
+//! annotation at its position. Failing to create the check run only logs a
+//! warning, as the link check itself succeeded.
+
+use std::env;
+
+use anyhow::{anyhow, Context, Result};
+use log::warn;
+use lychee_lib::{InputSource, ResponseBody};
+use secrecy::{ExposeSecret, SecretString};
+use serde_json::{json, Value};
+
+use crate::{formatters::relative_path, stats::ResponseStats};
+
+const DEFAULT_API_URL: &str = "https://api.github.com";
+
+/// GitHub accepts at most this many annotations per request. The remaining
+/// ones are added by updating the check run.
+const MAX_ANNOTATIONS_PER_REQUEST: usize = 50;
+
+/// The commit to create a check run for
+#[derive(Debug)]
+struct CheckRunTarget {
+    api_url: String,
+    repository: String,
+    sha: String,
+}
+
+impl CheckRunTarget {
+    fn from_env() -> Result<Self> {
+        let var = |name: &str| env::var(name).with_context(|| format!("`{name}` is not set"));
+        Ok(Self {
+            api_url: env::var("GITHUB_API_URL").unwrap_or_else(|_| DEFAULT_API_URL.to_string()),
+            repository: var("GITHUB_REPOSITORY")?,
+            sha: var("GITHUB_SHA")?,
+        })
+    }
+}
+
+/// Create a check run with the broken links of `stats` as annotations, which
+/// concludes as successful if `success` is set. Errors are logged as warnings.
+pub(crate) async fn report(stats: &ResponseStats, success: bool, token: Option<&SecretString>) {
+    if let Err(e) = create(stats, success, token).await {
+        warn!("Cannot create GitHub check run: {e:#}");
+    }
+}
+
+async fn create(stats: &ResponseStats, success: bool, token: Option<&SecretString>) -> Result<()> {
+    let token = token.ok_or_else(|| anyhow!("a GitHub token is required, set `GITHUB_TOKEN`"))?;
+    let target = CheckRunTarget::from_env()?;
+    let client = reqwest::Client::builder()
+        .user_agent(concat!("lychee/", env!("CARGO_PKG_VERSION")))
+        .build()?;
+    let send = |request: reqwest::RequestBuilder, body: &Value| {
+        request
+            .bearer_auth(token.expose_secret())
+            .header("Accept", "application/vnd.github+json")
+            .json(body)
+            .send()
+    };
+
+    let annotations = annotations(stats);
+    let mut chunks = annotations.chunks(MAX_ANNOTATIONS_PER_REQUEST);
+    let first = chunks.next().unwrap_or_default();
+
+    let mut body = payload(stats, first);
+    body["name"] = json!("lychee");
+    body["head_sha"] = json!(target.sha);
+    body["status"] = json!("completed");
+    body["conclusion"] = json!(if success { "success" } else { "failure" });
+    let url = format!("{}/repos/{}/check-runs", target.api_url, target.repository);
+    let response = send(client.post(&url), &body).await?.error_for_status()?;
+    let check_run: Value = response.json().await?;
+    let id = check_run["id"]
+        .as_u64()
+        .ok_or_else(|| anyhow!("the response contains no check run id"))?;
+
+    for chunk in chunks {
+        send(client.patch(format!("{url}/{id}")), &payload(stats, chunk))
+            .await?
+            .error_for_status()?;
+    }
+    Ok(())
+}
+
+/// The `output` of the check run with the given annotations
+fn payload(stats: &ResponseStats, annotations: &[Value]) -> Value {
+    json!({
+        "output": {
+            "title": format!("{} broken links", stats.errors),
+            "summary": format!(
+                "Checked {} links: {} successful, {} excluded, {} broken",
+                stats.total, stats.successful, stats.excludes, stats.errors
+            ),
+            "annotations": annotations,
+        }
+    })
+}
+
+/// An annotation for each broken link in a file, sorted by file and link
+fn annotations(stats: &ResponseStats) -> Vec<Value> {
+    let mut failures: Vec<(String, &ResponseBody)> = stats
+        .fail_map
+        .iter()
+        .filter_map(|(source, responses)| match source {
+            InputSource::FsPath(path) => Some((relative_path(path), responses)),
+            _ => None,
+        })
+        .flat_map(|(path, responses)| {
+            responses
+                .iter()
+                .map(move |response| (path.clone(), response))
+        })
+        .collect();
+    failures.sort_by_key(|(path, response)| (path.clone(), response.uri.to_string()));
+
+    failures
+        .into_iter()
+        .map(|(path, response)| annotation(&path, response))
+        .collect()
+}
+
+fn annotation(path: &str, response: &ResponseBody) -> Value {
+    let message = match response.status.details() {
+        Some(details) => format!("{}: {} ({details})", response.uri, response.status),
+        None => format!("{}: {}", response.uri, response.status),
+    };
+    let line = response.span.map_or(1, |span| span.line);
+    let mut annotation = json!({
+        "path": path,
+        "start_line": line,
+        "end_line": line,
+        "annotation_level": "failure",
+        "title": "Broken link",
+        "message": message,
+    });
+    if let Some(span) = response.span {
+        annotation["start_column"] = json!(span.column);
+        annotation["end_column"] = json!(span.column);
+    }
+    annotation
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use lychee_lib::{CacheStatus, RawUriSpan, Response, Status, Uri};
+
+    use super::*;
+
+    #[test]
+    fn test_annotations() {
+        let mut stats = ResponseStats::default();
+        stats.add(
+            Response::new(
+                Uri::try_from("https://example.com/404").unwrap(),
+                Status::Cached(CacheStatus::Error(Some(404))),
+                InputSource::FsPath(PathBuf::from("docs/README.md")),
+            )
+            .with_span(Some(RawUriSpan { line: 3, column: 7 })),
+        );
+        stats.add(Response(
+            InputSource::Stdin,
+            ResponseBody::new(
+                Uri::try_from("https://example.com/stdin").unwrap(),
+                Status::Cached(CacheStatus::Error(Some(404))),
+            ),
+        ));
+
+        let annotations = annotations(&stats);
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(annotations[0]["path"], "docs/README.md");
+        assert_eq!(annotations[0]["start_line"], 3);
+        assert_eq!(annotations[0]["start_column"], 7);
+        assert!(annotations[0]["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("https://example.com/404"));
+    }
+}
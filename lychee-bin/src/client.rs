@@ -1,10 +1,12 @@
 use crate::options::Config;
 use crate::parse::{parse_duration_secs, parse_headers, parse_remaps};
+use crate::read_lines;
 use anyhow::{Context, Result};
 use http::StatusCode;
-use lychee_lib::{Client, ClientBuilder};
+use lychee_lib::{remap::Remaps, Client, ClientBuilder, HostTokens};
 use regex::RegexSet;
 use reqwest_cookie_store::CookieStoreMutex;
+use std::fs::{self, File};
 use std::sync::Arc;
 use std::{collections::HashSet, str::FromStr};
 
@@ -15,12 +17,16 @@ pub(crate) fn create(cfg: &Config, cookie_jar: Option<&Arc<CookieStoreMutex>>) -
     let retry_wait_time = parse_duration_secs(cfg.retry_wait_time);
     let method: reqwest::Method = reqwest::Method::from_str(&cfg.method.to_uppercase())?;
 
-    let remaps = parse_remaps(&cfg.remap)?;
+    let remaps = remaps(cfg)?;
+    let user_agents = user_agents(cfg)?;
+    let host_tokens = host_tokens(cfg)?;
     let includes = RegexSet::new(&cfg.include)?;
     let excludes = RegexSet::new(&cfg.exclude)?;
 
-    // Offline mode overrides the scheme
-    let schemes = if cfg.offline {
+    // Offline mode overrides the scheme. Relative links in local inputs
+    // resolve to `file://` URIs, so checking only relative links is the same
+    // as excluding every other scheme.
+    let schemes = if cfg.offline || cfg.check_relative_only {
         vec!["file".to_string()]
     } else {
         cfg.scheme.clone()
@@ -34,49 +40,136 @@ pub(crate) fn create(cfg: &Config, cookie_jar: Option<&Arc<CookieStoreMutex>>) -
         .map(|value| StatusCode::from_u16(*value))
         .collect::<Result<HashSet<_>, _>>()?;
 
-    // `exclude_mail` will be removed in 1.0. Until then, we need to support it.
-    // Therefore, we need to check if both `include_mail` and `exclude_mail` are set to `true`
-    // and return an error if that's the case.
-    if cfg.include_mail && cfg.exclude_mail {
-        return Err(anyhow::anyhow!(
-            "Cannot set both `include-mail` and `exclude-mail` to true"
-        ));
-    }
-
-    // By default, clap sets `exclude_mail` to `false`.
-    // Therefore, we need to check if `exclude_mail` is explicitly set to
-    // `true`. If so, we need to set `include_mail` to `false`.
-    // Otherwise, we use the value of `include_mail`.
-    let include_mail = if cfg.exclude_mail {
-        false
-    } else {
-        cfg.include_mail
-    };
+    let include_mail = include_mail(cfg)?;
+    warn_about_missing_features(cfg);
 
     ClientBuilder::builder()
         .remaps(remaps)
         .includes(includes)
+        .strict_includes(cfg.strict_include)
         .excludes(excludes)
         .exclude_all_private(cfg.exclude_all_private)
         .exclude_private_ips(cfg.exclude_private)
         .exclude_link_local_ips(cfg.exclude_link_local)
         .exclude_loopback_ips(cfg.exclude_loopback)
         .include_mail(include_mail)
+        .verify_mail(cfg.verify_mail)
+        .include_ftp(cfg.include_ftp)
+        .include_gemini(cfg.include_gemini)
+        .include_websocket(cfg.include_websocket)
+        .include_identifiers(cfg.include_identifiers)
         .max_redirects(cfg.max_redirects)
+        .warn_redirects_over(cfg.warn_redirects_over)
+        .fail_on_https_downgrade(cfg.fail_on_https_downgrade)
+        .verify_image_content_type(cfg.verify_image_content_type)
+        .record(cfg.record.clone())
+        .replay(cfg.replay.clone())
+        .follow_meta_refresh(cfg.follow_meta_refresh)
         .user_agent(cfg.user_agent.clone())
+        .user_agents(user_agents)
         .allow_insecure(cfg.insecure)
         .custom_headers(headers)
+        .host_tokens(host_tokens)
+        .accept_encoding(cfg.accept_encoding.clone())
         .method(method)
+        .request_methods(cfg.request_methods.clone())
         .timeout(timeout)
+        .total_timeout(cfg.total_timeout.map(parse_duration_secs))
+        .max_retries(cfg.max_retries)
         .retry_wait_time(retry_wait_time)
         .github_token(cfg.github_token.clone())
         .schemes(HashSet::from_iter(schemes))
         .accepted(accepted)
+        .accepted_patterns(cfg.accept_pattern.clone())
+        .accepted_schemes(cfg.accept_scheme.clone())
         .require_https(cfg.require_https)
+        .fail_on_private(cfg.fail_on_private)
+        .disallowed_hosts(cfg.disallow_host.clone())
+        .require_external_noopener(cfg.require_external_noopener)
         .cookie_jar(cookie_jar.cloned())
         .include_fragments(cfg.include_fragments)
         .fallback_extensions(cfg.fallback_extensions.clone())
+        .root_dir(cfg.root_dir.clone())
+        .fs_concurrency(cfg.fs_concurrency)
         .build()
         .client()
         .context("Failed to create request client")
 }
+
+/// The `--remap` rules followed by those of the `--remap-file`
+fn remaps(cfg: &Config) -> Result<Remaps> {
+    let mut remaps = cfg.remap.clone();
+    if let Some(path) = &cfg.remap_file {
+        let file = File::open(path)
+            .with_context(|| format!("Cannot open remap file `{}`", path.display()))?;
+        remaps.extend(
+            read_lines(&file)?
+                .into_iter()
+                .filter(|line| !line.trim().is_empty()),
+        );
+    }
+    parse_remaps(&remaps)
+}
+
+/// The user agents to rotate through, read from `--rotate-user-agents`
+fn user_agents(cfg: &Config) -> Result<Vec<String>> {
+    let Some(path) = &cfg.rotate_user_agents else {
+        return Ok(Vec::new());
+    };
+    let file = File::open(path)
+        .with_context(|| format!("Cannot open user agent file `{}`", path.display()))?;
+    read_lines(&file)
+}
+
+/// The bearer tokens per host, read from `--auth-env-file`
+fn host_tokens(cfg: &Config) -> Result<HostTokens> {
+    let Some(path) = &cfg.auth_env_file else {
+        return Ok(HostTokens::default());
+    };
+    fs::read_to_string(path)
+        .with_context(|| format!("Cannot read auth env file `{}`", path.display()))?
+        .parse::<HostTokens>()
+        .with_context(|| format!("Cannot parse auth env file `{}`", path.display()))
+}
+
+/// Whether mail addresses should be checked
+fn include_mail(cfg: &Config) -> Result<bool> {
+    // `exclude_mail` will be removed in 1.0. Until then, we need to support it.
+    // Therefore, we need to check if both `include_mail` and `exclude_mail` are set to `true`
+    // and return an error if that's the case.
+    if cfg.include_mail && cfg.exclude_mail {
+        return Err(anyhow::anyhow!(
+            "Cannot set both `include-mail` and `exclude-mail` to true"
+        ));
+    }
+
+    // By default, clap sets `exclude_mail` to `false`.
+    // Therefore, we need to check if `exclude_mail` is explicitly set to
+    // `true`. If so, we need to set `include_mail` to `false`.
+    // Otherwise, we use the value of `include_mail`, which is implied by
+    // `verify_mail`.
+    Ok(!cfg.exclude_mail && (cfg.include_mail || cfg.verify_mail))
+}
+
+/// Warn about options which have no effect, because lychee was built without
+/// the feature they need
+fn warn_about_missing_features(cfg: &Config) {
+    if cfg.include_ftp && !cfg!(feature = "ftp") {
+        log::warn!("`--include-ftp` has no effect, as lychee was built without the `ftp` feature");
+    }
+    if cfg.include_gemini && !cfg!(feature = "gemini") {
+        log::warn!(
+            "`--include-gemini` has no effect, as lychee was built without the `gemini` feature"
+        );
+    }
+    if cfg.verify_mail && !cfg!(feature = "verify-mail") {
+        log::warn!(
+            "`--verify-mail` has no effect, as lychee was built without the `verify-mail` feature"
+        );
+    }
+    if cfg.include_websocket && !cfg!(feature = "websocket") {
+        log::warn!(
+            "`--include-websocket` has no effect, as lychee was built without the `websocket` feature"
+        );
+    }
+}
@@ -1,13 +1,16 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
 use std::io::{self, Write};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::time::Duration;
 
 use futures::StreamExt;
+use glob::{Pattern, PatternError};
 use indicatif::ProgressBar;
 use indicatif::ProgressStyle;
 use reqwest::Url;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinHandle;
 use tokio_stream::wrappers::ReceiverStream;
 
 use lychee_lib::{Client, ErrorKind, Request, Response};
@@ -15,7 +18,12 @@ use lychee_lib::{InputSource, Result};
 use lychee_lib::{ResponseBody, Status};
 
 use crate::archive::{Archive, Suggestion};
+use crate::consistency::ConsistencyReport;
 use crate::formatters::response::ResponseFormatter;
+use crate::hosts::external_host;
+use crate::options::{Config, Format};
+use crate::stream::{json_line, EventStream};
+use crate::transform;
 use crate::verbosity::Verbosity;
 use crate::{cache::Cache, stats::ResponseStats, ExitCode};
 
@@ -35,16 +43,39 @@ where
     // Measure check time
     let start = std::time::Instant::now();
 
-    let stats = if params.cfg.verbose.log_level() >= log::Level::Info {
+    // The host report is derived from the successful links, which are only
+    // recorded in the extended stats
+    let stats = if params.cfg.verbose.log_level() >= log::Level::Info || params.cfg.host_report {
         ResponseStats::extended()
     } else {
         ResponseStats::default()
     };
     let cache_ref = params.cache.clone();
 
-    let client = params.client;
-    let cache = params.cache;
-    let accept = params.cfg.accept.into_set();
+    let request_task = spawn_request_task(
+        recv_req,
+        send_resp,
+        params.client,
+        params.cache,
+        &params.cfg,
+    )
+    .map_err(ErrorKind::InvalidGlobPattern)?;
+
+    // Start with an empty file, so that the results of a previous run don't
+    // get mixed with the ones of this run
+    let output = match (&params.cfg.output, params.cfg.output_append) {
+        (Some(path), true) => Some(File::create(path)?),
+        _ => None,
+    };
+    let stream = match &params.cfg.stream_to {
+        Some(path) => EventStream::connect(path).await,
+        None => None,
+    };
+    let sinks = ResultSinks {
+        output,
+        format: params.cfg.format.clone(),
+        stream,
+    };
 
     let pb = if params.cfg.no_progress || params.cfg.verbose.log_level() >= log::Level::Info {
         None
@@ -52,30 +83,42 @@ where
         Some(init_progress_bar("Extracting links"))
     };
 
-    // Start receiving requests
-    tokio::spawn(request_channel_task(
-        recv_req,
-        send_resp,
-        max_concurrency,
-        client,
-        cache,
-        accept,
-    ));
-
     let show_results_task = tokio::spawn(progress_bar_task(
         recv_resp,
         params.cfg.verbose,
         pb.clone(),
         Arc::new(params.formatter),
         stats,
+        sinks,
     ));
 
-    // Wait until all messages are sent
-    send_inputs_loop(params.requests, send_req, pb).await?;
+    // Wait until all messages are sent and all requests are checked
+    let abort_requests = request_task.abort_handle();
+    let checking = async {
+        send_inputs_loop(params.requests, send_req, pb).await?;
+        request_task.await?;
+        Result::Ok(())
+    };
+
+    let mut deadline_reached = false;
+    if let Some(deadline) = params.cfg.deadline {
+        // Hung sockets might ignore read timeouts, so once the deadline is
+        // reached, in-flight requests get dropped instead of awaited
+        tokio::select! {
+            result = checking => result?,
+            () = tokio::time::sleep(Duration::from_secs(deadline as u64)) => {
+                log::warn!("Deadline of {deadline}s reached, cancelling all remaining requests");
+                abort_requests.abort();
+                deadline_reached = true;
+            }
+        }
+    } else {
+        checking.await?;
+    }
 
     // Wait until all responses are received
     let result = show_results_task.await?;
-    let (pb, mut stats) = result?;
+    let (pb, mut stats, consistency) = result?;
 
     // Store elapsed time in stats
     stats.duration_secs = start.elapsed().as_secs();
@@ -85,6 +128,7 @@ where
     if let Some(pb) = &pb {
         pb.finish_with_message("Finished extracting links");
     }
+    consistency.warn();
 
     if params.cfg.suggest {
         suggest_archived_links(
@@ -96,11 +140,17 @@ where
         .await;
     }
 
-    let code = if stats.is_success() {
-        ExitCode::Success
-    } else {
-        ExitCode::LinkCheckFailure
-    };
+    let ignored_failures = params
+        .cfg
+        .ignore_error_status
+        .as_ref()
+        .map_or(0, |codes| stats.failures_with_status(codes));
+    let code =
+        if stats.is_success(params.cfg.fail_on_warnings, ignored_failures) && !deadline_reached {
+            ExitCode::Success
+        } else {
+            ExitCode::LinkCheckFailure
+        };
     Ok((stats, cache_ref, code))
 }
 
@@ -160,15 +210,20 @@ where
 {
     tokio::pin!(requests);
     while let Some(request) = requests.next().await {
-        let request = request?;
+        // Links the transform command failed on are reported like any other
+        // failed link, all other errors abort the run
+        let request = match request {
+            Ok(request) => Ok(request),
+            Err(e @ ErrorKind::TransformCommand(..)) => Err(e),
+            Err(e) => return Err(e),
+        };
         if let Some(pb) = &bar {
             pb.inc_length(1);
-            pb.set_message(request.to_string());
-        };
-        send_req
-            .send(Ok(request))
-            .await
-            .expect("Cannot send request");
+            if let Ok(request) = &request {
+                pb.set_message(request.to_string());
+            }
+        }
+        send_req.send(request).await.expect("Cannot send request");
     }
     Ok(())
 }
@@ -180,12 +235,53 @@ async fn progress_bar_task(
     pb: Option<ProgressBar>,
     formatter: Arc<Box<dyn ResponseFormatter>>,
     mut stats: ResponseStats,
-) -> Result<(Option<ProgressBar>, ResponseStats)> {
+    mut sinks: ResultSinks,
+) -> Result<(Option<ProgressBar>, ResponseStats, ConsistencyReport)> {
+    let mut consistency = ConsistencyReport::default();
     while let Some(response) = recv_resp.recv().await {
         show_progress(&mut io::stderr(), &pb, &response, &formatter, &verbose)?;
+        sinks.write(&response).await?;
+        consistency.add(&response);
         stats.add(response);
     }
-    Ok((pb, stats))
+    Ok((pb, stats, consistency))
+}
+
+/// Destinations which receive each result as soon as it is checked, as
+/// opposed to the final report
+struct ResultSinks {
+    /// File for `--output-append`
+    output: Option<File>,
+    format: Format,
+    /// Socket or named pipe for `--stream-to`
+    stream: Option<EventStream>,
+}
+
+impl ResultSinks {
+    async fn write(&mut self, response: &Response) -> Result<()> {
+        if let Some(output) = &mut self.output {
+            writeln!(output, "{}", output_line(response, &self.format))?;
+            output.flush()?;
+        }
+        if let Some(stream) = &mut self.stream {
+            if !stream.send(response).await {
+                self.stream = None;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Format a single response as a line for `--output-append`.
+///
+/// JSON output is written as JSON lines, including the input source,
+/// everything else uses the uncolored response format.
+fn output_line(response: &Response, format: &Format) -> String {
+    if matches!(format, Format::Json) {
+        json_line(response)
+    } else {
+        response.1.to_string()
+    }
 }
 
 fn init_progress_bar(initial_message: &'static str) -> ProgressBar {
@@ -201,20 +297,70 @@ fn init_progress_bar(initial_message: &'static str) -> ProgressBar {
     bar
 }
 
+/// Start checking the requests received from `recv_req`, with the limits on
+/// concurrent checks overall and per host given in `cfg`
+fn spawn_request_task(
+    recv_req: mpsc::Receiver<Result<Request>>,
+    send_resp: mpsc::Sender<Response>,
+    client: Client,
+    cache: Arc<Cache>,
+    cfg: &Config,
+) -> std::result::Result<JoinHandle<()>, PatternError> {
+    let accept = cfg.accept.clone().into_set();
+    let cache_exclude_path = cfg
+        .cache_exclude_path
+        .iter()
+        .map(|pattern| Pattern::new(pattern))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let permits = Permits::new(cfg.max_concurrency, cfg.max_concurrency_per_host);
+
+    Ok(tokio::spawn(request_channel_task(
+        recv_req,
+        send_resp,
+        permits,
+        client,
+        cache,
+        accept,
+        cache_exclude_path,
+    )))
+}
+
 async fn request_channel_task(
     recv_req: mpsc::Receiver<Result<Request>>,
     send_resp: mpsc::Sender<Response>,
-    max_concurrency: usize,
+    permits: Permits,
     client: Client,
     cache: Arc<Cache>,
     accept: HashSet<u16>,
+    cache_exclude_path: Vec<Pattern>,
 ) {
+    // Without a per-host limit, every request simply takes one of the global
+    // slots. With one, requests first wait for their host without taking a
+    // slot, so that a slow host doesn't hold up all the others. The number of
+    // waiting requests is still bounded, so that memory usage doesn't grow
+    // with the number of links
+    let mut limit = permits.global.available_permits();
+    if permits.per_host.is_some() {
+        limit *= WAITING_REQUESTS_PER_SLOT;
+    }
     StreamExt::for_each_concurrent(
         ReceiverStream::new(recv_req),
-        max_concurrency,
+        limit,
         |request: Result<Request>| async {
-            let request = request.expect("cannot read request");
-            let response = handle(&client, cache.clone(), request, accept.clone()).await;
+            let response = match request {
+                Ok(request) => {
+                    handle(
+                        &client,
+                        cache.clone(),
+                        request,
+                        accept.clone(),
+                        &cache_exclude_path,
+                        &permits,
+                    )
+                    .await
+                }
+                Err(e) => transform::failed_response(e),
+            };
 
             send_resp
                 .send(response)
@@ -225,6 +371,105 @@ async fn request_channel_task(
     .await;
 }
 
+/// How many requests may wait for their host per global slot when
+/// `--max-concurrency-per-host` is set
+const WAITING_REQUESTS_PER_SLOT: usize = 4;
+
+/// Limits the number of concurrent checks overall and per host
+struct Permits {
+    global: Arc<Semaphore>,
+    per_host: Option<HostPermits>,
+}
+
+impl Permits {
+    fn new(max_concurrency: usize, max_concurrency_per_host: Option<usize>) -> Self {
+        Self {
+            global: Arc::new(Semaphore::new(max_concurrency.max(1))),
+            per_host: max_concurrency_per_host.map(HostPermits::new),
+        }
+    }
+
+    /// Wait until a request to the host of `request` may be sent, and only
+    /// then for one of the global slots. Both permits must be held until the
+    /// check is done.
+    async fn acquire(&self, request: &Request) -> (Option<HostPermit<'_>>, OwnedSemaphorePermit) {
+        let host_permit = match (&self.per_host, external_host(&request.uri)) {
+            (Some(per_host), Some(host)) => Some(per_host.acquire(host).await),
+            _ => None,
+        };
+        // The semaphores are never closed, so acquiring a permit can't fail
+        let permit = self
+            .global
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("semaphore is never closed");
+        (host_permit, permit)
+    }
+}
+
+/// A semaphore for each host which currently has requests in flight or
+/// waiting. Semaphores are created on the first request to a host and
+/// removed again once the last of its requests is done.
+struct HostPermits {
+    limit: usize,
+    semaphores: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl HostPermits {
+    fn new(limit: usize) -> Self {
+        Self {
+            limit: limit.max(1),
+            semaphores: Mutex::default(),
+        }
+    }
+
+    fn semaphores(&self) -> MutexGuard<'_, HashMap<String, Arc<Semaphore>>> {
+        self.semaphores
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    async fn acquire(&self, host: String) -> HostPermit<'_> {
+        let semaphore = self
+            .semaphores()
+            .entry(host.clone())
+            .or_insert_with(|| Arc::new(Semaphore::new(self.limit)))
+            .clone();
+        let permit = semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("semaphore is never closed");
+        HostPermit {
+            permit: Some(permit),
+            semaphore,
+            host,
+            permits: self,
+        }
+    }
+}
+
+/// Permission to send a request to a host, see [`HostPermits`]
+struct HostPermit<'a> {
+    permit: Option<OwnedSemaphorePermit>,
+    semaphore: Arc<Semaphore>,
+    host: String,
+    permits: &'a HostPermits,
+}
+
+impl Drop for HostPermit<'_> {
+    fn drop(&mut self) {
+        drop(self.permit.take());
+        // References are only cloned while holding the lock, so if the map
+        // holds the only other one, no other request uses this host
+        let mut semaphores = self.permits.semaphores();
+        if Arc::strong_count(&self.semaphore) == 2 {
+            semaphores.remove(&self.host);
+        }
+    }
+}
+
 /// Check a URL and return a response.
 ///
 /// # Errors
@@ -234,6 +479,7 @@ async fn check_url(client: &Client, request: Request) -> Response {
     // Request was not cached; run a normal check
     let uri = request.uri.clone();
     let source = request.source.clone();
+    let span = request.span;
     client.check(request).await.unwrap_or_else(|e| {
         log::error!("Error checking URL {}: Cannot parse URL to URI: {}", uri, e);
         Response::new(
@@ -241,6 +487,7 @@ async fn check_url(client: &Client, request: Request) -> Response {
             Status::Error(ErrorKind::InvalidURI(uri.clone())),
             source,
         )
+        .with_span(span)
     })
 }
 
@@ -250,10 +497,13 @@ async fn handle(
     cache: Arc<Cache>,
     request: Request,
     accept: HashSet<u16>,
+    cache_exclude_path: &[Pattern],
+    permits: &Permits,
 ) -> Response {
     let uri = request.uri.clone();
-    if let Some(v) = cache.get(&uri) {
+    if let Some(mut v) = cache.get_mut(&uri) {
         // Found a cached request
+        v.touch();
         // Overwrite cache status in case the URI is excluded in the
         // current run
         let status = if client.is_excluded(&uri) {
@@ -265,19 +515,31 @@ async fn handle(
             // code.
             Status::from_cache_status(v.value().status, &accept)
         };
-        return Response::new(uri.clone(), status, request.source);
+        return Response::new(uri.clone(), status, request.source).with_span(request.span);
     }
 
+    let cache_excluded = is_cache_excluded(&request.source, cache_exclude_path);
+
     // Request was not cached; run a normal check
+    let permits = permits.acquire(&request).await;
     let response = check_url(client, request).await;
+    drop(permits);
 
     // - Never cache filesystem access as it is fast already so caching has no
     //   benefit.
     // - Skip caching unsupported URLs as they might be supported in a
     //   future run.
     // - Skip caching excluded links; they might not be excluded in the next run
+    // - Skip caching warnings; they should be reported again in the next run
+    // - Skip caching links of inputs excluded with `--cache-exclude-path`
     let status = response.status();
-    if uri.is_file() || status.is_excluded() || status.is_unsupported() || status.is_unknown() {
+    if uri.is_file()
+        || cache_excluded
+        || status.is_excluded()
+        || status.is_unsupported()
+        || status.is_unknown()
+        || status.is_warning()
+    {
         return response;
     }
 
@@ -285,6 +547,17 @@ async fn handle(
     response
 }
 
+/// Whether links found in `source` must not be cached, because the input
+/// file matches one of the `--cache-exclude-path` patterns
+fn is_cache_excluded(source: &InputSource, cache_exclude_path: &[Pattern]) -> bool {
+    match source {
+        InputSource::FsPath(path) => cache_exclude_path
+            .iter()
+            .any(|pattern| pattern.matches_path(path)),
+        _ => false,
+    }
+}
+
 fn show_progress(
     output: &mut dyn Write,
     progress_bar: &Option<ProgressBar>,
@@ -313,7 +586,7 @@ fn get_failed_urls(stats: &mut ResponseStats) -> Vec<(InputSource, Url)> {
         .iter()
         .flat_map(|(source, set)| {
             set.iter()
-                .map(move |ResponseBody { uri, status: _ }| (source, uri))
+                .map(move |ResponseBody { uri, .. }| (source, uri))
         })
         .filter_map(|(source, uri)| {
             if uri.is_data() || uri.is_mail() || uri.is_file() {
@@ -343,10 +616,10 @@ mod tests {
         let mut buf = Vec::new();
         let response = Response(
             InputSource::Stdin,
-            ResponseBody {
-                uri: Uri::try_from("http://127.0.0.1").unwrap(),
-                status: Status::Cached(CacheStatus::Ok(200)),
-            },
+            ResponseBody::new(
+                Uri::try_from("http://127.0.0.1").unwrap(),
+                Status::Cached(CacheStatus::Ok(200)),
+            ),
         );
         let formatter: Arc<Box<dyn ResponseFormatter>> =
             Arc::new(Box::new(formatters::response::Raw::new()));
@@ -368,10 +641,10 @@ mod tests {
         let mut buf = Vec::new();
         let response = Response(
             InputSource::Stdin,
-            ResponseBody {
-                uri: Uri::try_from("http://127.0.0.1").unwrap(),
-                status: Status::Cached(CacheStatus::Ok(200)),
-            },
+            ResponseBody::new(
+                Uri::try_from("http://127.0.0.1").unwrap(),
+                Status::Cached(CacheStatus::Ok(200)),
+            ),
         );
         let formatter: Arc<Box<dyn ResponseFormatter>> =
             Arc::new(Box::new(formatters::response::Raw::new()));
@@ -379,7 +652,7 @@ mod tests {
 
         assert!(!buf.is_empty());
         let buf = String::from_utf8_lossy(&buf);
-        assert_eq!(buf, "↻ [200] http://127.0.0.1/ | Cached: OK (cached)\n");
+        assert_eq!(buf, "↻ [200 OK] http://127.0.0.1/ | Cached: OK (cached)\n");
     }
 
     #[tokio::test]
@@ -394,4 +667,23 @@ mod tests {
             Status::Error(ErrorKind::InvalidURI(_))
         ));
     }
+
+    #[tokio::test]
+    async fn test_host_permits_are_removed_when_done() {
+        let permits = HostPermits::new(1);
+        let first = permits.acquire("example.com".to_string()).await;
+        let other = permits.acquire("example.org".to_string()).await;
+        assert_eq!(permits.semaphores().len(), 2);
+
+        drop(other);
+        assert_eq!(permits.semaphores().len(), 1);
+
+        // The host stays known while one of its requests is still in flight
+        let (waiting, ()) = tokio::join!(permits.acquire("example.com".to_string()), async {
+            drop(first);
+        });
+        assert_eq!(permits.semaphores().len(), 1);
+        drop(waiting);
+        assert!(permits.semaphores().is_empty());
+    }
 }
@@ -0,0 +1,75 @@
+use std::collections::HashSet;
+use std::io::{self, Write};
+
+use lychee_lib::{CheckPlan, Request, Result};
+use tokio_stream::StreamExt;
+
+use crate::ExitCode;
+
+use super::CommandParams;
+
+/// How the unique links of a run would be handled
+#[derive(Debug, Default)]
+struct RequestCount {
+    /// Number of unique links
+    total: usize,
+    /// Links which would be requested over the network
+    requests: usize,
+    /// Links which would be served from the cache
+    cached: usize,
+    /// Links which would be excluded
+    excluded: usize,
+    /// Links which would be handled without a network request, like local
+    /// files or unsupported schemes
+    other: usize,
+}
+
+/// Count how many network requests a run would make after deduplication,
+/// filtering and cache lookups, without making any of them
+pub(crate) async fn count<S>(params: CommandParams<S>) -> Result<ExitCode>
+where
+    S: futures::Stream<Item = Result<Request>>,
+{
+    let requests = params.requests;
+    tokio::pin!(requests);
+
+    let mut seen = HashSet::new();
+    let mut count = RequestCount::default();
+
+    while let Some(request) = requests.next().await {
+        let uri = request?.uri;
+        if !seen.insert(uri.clone()) {
+            continue;
+        }
+        count.total += 1;
+
+        // Same order as in the actual check: the cache is consulted before
+        // remapping the URI
+        if params.cache.contains_key(&uri) {
+            if params.client.is_excluded(&uri) {
+                count.excluded += 1;
+            } else {
+                count.cached += 1;
+            }
+            continue;
+        }
+
+        match params.client.plan(&uri)? {
+            CheckPlan::Excluded => count.excluded += 1,
+            CheckPlan::Request => count.requests += 1,
+            CheckPlan::NoRequest => count.other += 1,
+        }
+    }
+
+    writeln!(
+        io::stdout(),
+        "{} unique links: {} requests, {} cached, {} excluded, {} without request",
+        count.total,
+        count.requests,
+        count.cached,
+        count.excluded,
+        count.other
+    )?;
+
+    Ok(ExitCode::Success)
+}
@@ -0,0 +1,200 @@
+use std::env;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::Result;
+use tokio::net::{lookup_host, TcpStream};
+use tokio::time::timeout;
+use url::Url;
+
+use crate::options::{Config, LYCHEE_CACHE_FILE};
+use crate::ExitCode;
+
+/// Host which gets resolved to check that DNS works
+const DNS_PROBE_HOST: &str = "github.com:443";
+
+/// How long to wait for DNS resolution and for connecting to the proxy
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Environment variables from which the proxy gets picked up
+const PROXY_VARIABLES: [&str; 6] = [
+    "HTTPS_PROXY",
+    "https_proxy",
+    "HTTP_PROXY",
+    "http_proxy",
+    "ALL_PROXY",
+    "all_proxy",
+];
+
+/// Outcome of a single check of the doctor command
+#[derive(Debug)]
+struct Check {
+    passed: bool,
+    name: String,
+    message: String,
+}
+
+impl Check {
+    fn pass(name: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            passed: true,
+            name: name.into(),
+            message: message.into(),
+        }
+    }
+
+    fn fail(name: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            passed: false,
+            name: name.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Check the environment for common problems and print a checklist.
+///
+/// Returns [`ExitCode::UnexpectedFailure`] if any of the checks failed.
+pub(crate) async fn doctor(cfg: &Config) -> Result<ExitCode> {
+    let mut checks = vec![check_dns().await];
+    checks.extend(check_proxy().await);
+    checks.extend(cfg.process_ext.iter().map(|process_ext| {
+        check_command(
+            &format!("process-ext {}", process_ext.extension),
+            &process_ext.command,
+        )
+    }));
+    checks.push(check_cache(cfg.cache, Path::new(LYCHEE_CACHE_FILE)));
+
+    write_checks(&mut io::stdout(), &checks)?;
+    if checks.iter().all(|check| check.passed) {
+        Ok(ExitCode::Success)
+    } else {
+        Ok(ExitCode::UnexpectedFailure)
+    }
+}
+
+fn write_checks(writer: &mut dyn Write, checks: &[Check]) -> Result<()> {
+    for check in checks {
+        let verdict = if check.passed { "PASS" } else { "FAIL" };
+        writeln!(writer, "[{verdict}] {}: {}", check.name, check.message)?;
+    }
+    let failed = checks.iter().filter(|check| !check.passed).count();
+    if failed == 0 {
+        writeln!(writer, "No problems found")?;
+    } else {
+        writeln!(writer, "{failed} of {} checks failed", checks.len())?;
+    }
+    Ok(())
+}
+
+async fn check_dns() -> Check {
+    match timeout(PROBE_TIMEOUT, lookup_host(DNS_PROBE_HOST)).await {
+        Ok(Ok(addresses)) => match addresses.into_iter().next() {
+            Some(address) => {
+                Check::pass("dns", format!("resolved `{DNS_PROBE_HOST}` to {address}"))
+            }
+            None => Check::fail("dns", format!("no address for `{DNS_PROBE_HOST}`")),
+        },
+        Ok(Err(e)) => Check::fail("dns", format!("cannot resolve `{DNS_PROBE_HOST}`: {e}")),
+        Err(_) => Check::fail("dns", format!("resolving `{DNS_PROBE_HOST}` timed out")),
+    }
+}
+
+/// Check that the proxy configured in the environment accepts connections.
+/// Returns `None` if no proxy is configured.
+async fn check_proxy() -> Option<Check> {
+    let (variable, value) = PROXY_VARIABLES.iter().find_map(|variable| {
+        env::var(variable)
+            .ok()
+            .filter(|value| !value.is_empty())
+            .map(|value| (*variable, value))
+    })?;
+    let name = format!("proxy ({variable})");
+
+    let url = match Url::parse(&value) {
+        Ok(url) => url,
+        Err(e) => return Some(Check::fail(name, format!("invalid URL `{value}`: {e}"))),
+    };
+    let (Some(host), Some(port)) = (url.host_str(), url.port_or_known_default()) else {
+        return Some(Check::fail(name, format!("no host or port in `{value}`")));
+    };
+
+    let check = match timeout(PROBE_TIMEOUT, TcpStream::connect((host, port))).await {
+        Ok(Ok(_)) => Check::pass(name, format!("`{host}:{port}` is reachable")),
+        Ok(Err(e)) => Check::fail(name, format!("cannot connect to `{host}:{port}`: {e}")),
+        Err(_) => Check::fail(name, format!("connecting to `{host}:{port}` timed out")),
+    };
+    Some(check)
+}
+
+fn check_command(name: &str, command: &str) -> Check {
+    match find_command(command) {
+        Some(path) => Check::pass(name, format!("`{command}` found at {}", path.display())),
+        None => Check::fail(name, format!("`{command}` not found on PATH")),
+    }
+}
+
+/// Look up `command` like the OS would when spawning it: paths are used
+/// as-is, plain names are searched in the directories of `PATH`
+fn find_command(command: &str) -> Option<PathBuf> {
+    let path = Path::new(command);
+    if path.components().count() > 1 {
+        return path.is_file().then(|| path.to_path_buf());
+    }
+
+    let dirs = env::var_os("PATH")?;
+    env::split_paths(&dirs).find_map(|dir| {
+        let candidate = dir.join(command);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        if cfg!(windows) {
+            let candidate = candidate.with_extension("exe");
+            return candidate.is_file().then_some(candidate);
+        }
+        None
+    })
+}
+
+fn check_cache(enabled: bool, path: &Path) -> Check {
+    if !enabled {
+        return Check::pass("cache", "not used, enable it with `--cache`");
+    }
+
+    let existed = path.exists();
+    let result = OpenOptions::new().append(true).create(true).open(path);
+    if !existed && result.is_ok() {
+        // Don't leave an empty cache file behind
+        let _ = std::fs::remove_file(path);
+    }
+    match result {
+        Ok(_) => Check::pass("cache", format!("`{}` is writable", path.display())),
+        Err(e) => Check::fail("cache", format!("cannot write `{}`: {e}", path.display())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_find_command() {
+        assert!(find_command("sh").is_some());
+        assert!(find_command("lychee-no-such-command").is_none());
+        assert!(find_command("./lychee-no-such-command").is_none());
+    }
+
+    #[test]
+    fn test_check_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".lycheecache");
+
+        assert!(check_cache(true, &path).passed);
+        assert!(!path.exists());
+        assert!(!check_cache(true, &dir.path().join("missing/.lycheecache")).passed);
+    }
+}
@@ -1,11 +1,15 @@
 use log::error;
 use lychee_lib::Request;
 use lychee_lib::Result;
+use lychee_lib::{Base, FileType, Input, InputSource};
+use std::collections::BTreeSet;
+use std::fmt::{self, Display};
 use std::fs;
-use std::io::{self, Write};
-use std::path::PathBuf;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
 use tokio_stream::StreamExt;
 
+use crate::options::Config;
 use crate::verbosity::Verbosity;
 use crate::ExitCode;
 
@@ -29,7 +33,10 @@ fn create_writer(output: Option<PathBuf>) -> Result<Box<dyn Write>> {
     Ok(out)
 }
 
-/// Dump all detected links to stdout without checking them
+/// Dump all detected links to stdout without checking them.
+///
+/// With `--sort`, the links are printed sorted and without duplicates once
+/// all of them were collected, so that the output can be diffed.
 pub(crate) async fn dump<S>(params: CommandParams<S>) -> Result<ExitCode>
 where
     S: futures::Stream<Item = Result<Request>>,
@@ -42,6 +49,7 @@ where
     }
 
     let mut writer = create_writer(params.cfg.output)?;
+    let mut sorted = BTreeSet::new();
 
     while let Some(request) = requests.next().await {
         let mut request = request?;
@@ -49,21 +57,24 @@ where
         // Apply URI remappings (if any)
         params.client.remap(&mut request.uri)?;
 
-        // Avoid panic on broken pipe.
-        // See https://github.com/rust-lang/rust/issues/46016
-        // This can occur when piping the output of lychee
-        // to another program like `grep`.
-
         let excluded = params.client.is_excluded(&request.uri);
 
         if excluded && params.cfg.verbose.log_level() < log::Level::Info {
             continue;
         }
-        if let Err(e) = write(&mut writer, &request, &params.cfg.verbose, excluded) {
-            if e.kind() != io::ErrorKind::BrokenPipe {
-                error!("{e}");
-                return Ok(ExitCode::UnexpectedFailure);
-            }
+        let Some(line) = format_request(&request, &params.cfg.verbose, excluded) else {
+            continue;
+        };
+        if params.cfg.sort {
+            sorted.insert(line);
+        } else if !write_line(&mut writer, &line) {
+            return Ok(ExitCode::UnexpectedFailure);
+        }
+    }
+
+    for line in sorted {
+        if !write_line(&mut writer, &line) {
+            return Ok(ExitCode::UnexpectedFailure);
         }
     }
 
@@ -94,16 +105,135 @@ where
     Ok(ExitCode::Success)
 }
 
-/// Dump request to stdout
-fn write(
-    writer: &mut Box<dyn Write>,
-    request: &Request,
-    verbosity: &Verbosity,
-    excluded: bool,
-) -> io::Result<()> {
+/// The extractor which lychee uses for the links of an input source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Extractor {
+    Markdown,
+    Html,
+    Plaintext,
+    Pdf,
+    /// The file is converted to text by a `--process-ext` command first
+    Custom,
+    /// Directories are searched for Markdown and HTML files
+    Directory,
+    /// No links are extracted, e.g. from binary files
+    None,
+}
+
+impl Display for Extractor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Markdown => "markdown",
+            Self::Html => "html",
+            Self::Plaintext => "plaintext",
+            Self::Pdf => "pdf",
+            Self::Custom => "custom",
+            Self::Directory => "directory",
+            Self::None => "none",
+        };
+        f.write_str(name)
+    }
+}
+
+impl From<FileType> for Extractor {
+    fn from(file_type: FileType) -> Self {
+        match file_type {
+            FileType::Markdown => Self::Markdown,
+            FileType::Html => Self::Html,
+            FileType::Plaintext => Self::Plaintext,
+        }
+    }
+}
+
+/// Dump all input sources like [`dump_inputs`], each followed by a tab and
+/// the extractor which would be used for its links
+pub(crate) async fn dump_inputs_with_extractor(
+    inputs: Vec<Input>,
+    cfg: &Config,
+) -> Result<ExitCode> {
+    if let Some(out_file) = &cfg.output {
+        fs::File::create(out_file)?;
+    }
+
+    let mut writer = create_writer(cfg.output.clone())?;
+
+    for input in inputs {
+        let sources = input.clone().get_sources();
+        tokio::pin!(sources);
+        while let Some(source) = sources.next().await {
+            let source = source?;
+            let extractor = extractor(&input, &source, cfg);
+
+            writeln!(writer, "{source}\t{extractor}")?;
+        }
+    }
+
+    Ok(ExitCode::Success)
+}
+
+/// The extractor for `source`, which was found for `input`
+fn extractor(input: &Input, source: &str, cfg: &Config) -> Extractor {
+    match &input.source {
+        // Remote inputs without a path are assumed to be HTML
+        InputSource::RemoteUrl(url) if matches!(url.path(), "" | "/") => Extractor::Html,
+        InputSource::RemoteUrl(url) => FileType::from(url.as_str()).into(),
+        InputSource::Stdin => match &cfg.stdin_base {
+            Some(Base::Remote(url)) => FileType::from(url.path()).into(),
+            Some(Base::Local(path)) => FileType::from(path).into(),
+            None => input.file_type_hint.unwrap_or_default().into(),
+        },
+        InputSource::String(_) => input.file_type_hint.unwrap_or_default().into(),
+        // Only files given directly are converted by `--process-ext` and
+        // only directories given directly are searched
+        InputSource::FsPath(path) if path.is_dir() => Extractor::Directory,
+        InputSource::FsPath(path) if cfg.process_ext.iter().any(|ext| ext.matches(path)) => {
+            Extractor::Custom
+        }
+        InputSource::FsPath(_) | InputSource::FsGlob { .. } => file_extractor(Path::new(source)),
+        _ => Extractor::None,
+    }
+}
+
+/// The extractor for a file, based on its extension. Files which are not
+/// valid UTF-8 can't be read, so no links are extracted from them.
+fn file_extractor(path: &Path) -> Extractor {
+    if path.is_dir() {
+        return Extractor::None;
+    }
+    if path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"))
+    {
+        return if cfg!(feature = "pdf") {
+            Extractor::Pdf
+        } else {
+            Extractor::None
+        };
+    }
+    if !is_text(path) {
+        return Extractor::None;
+    }
+    FileType::from(path).into()
+}
+
+/// Whether the beginning of the file is valid UTF-8 without NUL bytes
+fn is_text(path: &Path) -> bool {
+    let mut buf = Vec::new();
+    let Ok(file) = fs::File::open(path) else {
+        return false;
+    };
+    if file.take(8192).read_to_end(&mut buf).is_err() || buf.contains(&0) {
+        return false;
+    }
+    // The last character might be cut off at the end of the buffer
+    std::str::from_utf8(&buf).map_or_else(|e| e.error_len().is_none(), |_| true)
+}
+
+/// The line to dump for a request, if any
+fn format_request(request: &Request, verbosity: &Verbosity, excluded: bool) -> Option<String> {
     // Only print `data:` URIs if verbose mode is at least `info`.
     if request.uri.is_data() && verbosity.log_level() < log::Level::Info {
-        return Ok(());
+        return None;
     }
 
     // Only print source if verbose mode is at least `info`. This way the normal
@@ -115,15 +245,24 @@ fn write(
     };
 
     // Mark excluded links
-    let out_str = if excluded {
-        format!("{request} [excluded]")
+    if excluded {
+        Some(format!("{request} [excluded]"))
     } else {
-        request
-    };
-
-    write_out(writer, &out_str)
+        Some(request)
+    }
 }
 
-fn write_out(writer: &mut Box<dyn Write>, out_str: &str) -> io::Result<()> {
-    writeln!(writer, "{out_str}")
+/// Write a line of the dump. Returns `false` if writing failed.
+fn write_line(writer: &mut Box<dyn Write>, line: &str) -> bool {
+    match writeln!(writer, "{line}") {
+        // Avoid panic on broken pipe.
+        // See https://github.com/rust-lang/rust/issues/46016
+        // This can occur when piping the output of lychee
+        // to another program like `grep`.
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => {
+            error!("{e}");
+            false
+        }
+        _ => true,
+    }
 }
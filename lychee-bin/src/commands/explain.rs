@@ -0,0 +1,71 @@
+use std::io::{self, Write};
+
+use anyhow::{Context, Result};
+use lychee_lib::{Client, Uri};
+
+use crate::cache::Cache;
+use crate::options::Config;
+use crate::{client, load_cache, ExitCode};
+
+/// Explain how a single URL would be handled, without checking it
+pub(crate) fn explain(url: &str, cfg: &Config) -> Result<ExitCode> {
+    let uri = Uri::try_from(url).with_context(|| format!("Cannot explain invalid URL `{url}`"))?;
+    let client = client::create(cfg, None)?;
+    let cache = load_cache(cfg).unwrap_or_default();
+
+    write_explanation(&mut io::stdout(), &uri, &client, &cache)?;
+    Ok(ExitCode::Success)
+}
+
+/// Write the verdict of every stage a URI passes before it gets checked,
+/// followed by the final disposition.
+///
+/// The stages are listed in the order in which they get applied: cached
+/// results are looked up for the original URI, while the filters apply to the
+/// remapped URI.
+fn write_explanation(
+    writer: &mut dyn Write,
+    uri: &Uri,
+    client: &Client,
+    cache: &Cache,
+) -> Result<()> {
+    writeln!(writer, "Explanation for {uri}")?;
+
+    let cached = cache.get(uri).map(|entry| entry.value().status);
+    match cached {
+        Some(status) => writeln!(writer, "  cache:   cached result '{status}'")?,
+        None => writeln!(writer, "  cache:   not cached")?,
+    }
+
+    let mut remapped = uri.clone();
+    client.remap(&mut remapped)?;
+    if remapped == *uri {
+        writeln!(writer, "  remap:   no rule matches")?;
+    } else {
+        writeln!(writer, "  remap:   remapped to {remapped}")?;
+    }
+
+    let decision = client.filter_decision(&remapped);
+    writeln!(writer, "  filter:  {decision}")?;
+
+    let rejects_private = client.rejects_private_address(&remapped);
+    if rejects_private {
+        writeln!(
+            writer,
+            "  private: private or local address, reported as error"
+        )?;
+    } else {
+        writeln!(writer, "  private: not rejected")?;
+    }
+
+    let disposition = match cached {
+        // Cached results are only used for links which are not excluded
+        Some(_) if client.is_excluded(uri) => "skipped, the link is excluded".to_string(),
+        Some(status) => format!("not requested, the cached result '{status}' is used"),
+        None if decision.is_excluded() => "skipped, the link is excluded".to_string(),
+        None if rejects_private => "reported as error without a request".to_string(),
+        None => format!("checked by requesting {remapped}"),
+    };
+    writeln!(writer, "Result: {disposition}")?;
+    Ok(())
+}
@@ -0,0 +1,238 @@
+//! Merge the JSON reports of several lychee runs into a single report.
+//!
+//! This is useful when a large check is sharded across parallel CI jobs.
+//! The reports are merged as plain JSON values, so that reports written by
+//! other lychee versions with additional fields can still be merged.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde_json::{Map, Value};
+
+use crate::ExitCode;
+
+/// Merge the given JSON reports and write the result to `output` or stdout.
+///
+/// Counters are summed up and the link maps are unioned, with entries for the
+/// same URL of an input source only being kept once. Each dropped duplicate is
+/// taken off the counters again, so that they match the merged maps. As the
+/// jobs usually run in parallel, the duration of the merged report is the
+/// longest of all durations.
+pub(crate) fn merge_reports(reports: &[PathBuf], output: Option<&PathBuf>) -> Result<ExitCode> {
+    let mut merged = Map::new();
+    for path in reports {
+        merge(&mut merged, read_report(path)?);
+    }
+
+    let formatted =
+        serde_json::to_string_pretty(&merged).context("Cannot format merged report as JSON")?;
+    if let Some(output) = output {
+        fs::write(output, formatted).context("Cannot write merged report to file")?;
+    } else {
+        writeln!(io::stdout(), "{formatted}")?;
+    }
+
+    let has_errors = merged.get("errors").and_then(Value::as_u64).unwrap_or(0) > 0;
+    Ok(if has_errors {
+        ExitCode::LinkCheckFailure
+    } else {
+        ExitCode::Success
+    })
+}
+
+fn read_report(path: &Path) -> Result<Map<String, Value>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Cannot read report `{}`", path.display()))?;
+    let report = serde_json::from_str(&content)
+        .with_context(|| format!("Cannot parse report `{}` as JSON", path.display()))?;
+    match report {
+        Value::Object(report) => Ok(report),
+        _ => bail!("Report `{}` is not a JSON object", path.display()),
+    }
+}
+
+/// Merge a single report into `merged`
+fn merge(merged: &mut Map<String, Value>, report: Map<String, Value>) {
+    let mut duplicates = Vec::new();
+    for (key, value) in report {
+        let Some(existing) = merged.get_mut(&key) else {
+            merged.insert(key, value);
+            continue;
+        };
+        match (existing, value) {
+            (Value::Number(a), Value::Number(b)) => {
+                let (a_value, b_value) = (a.as_u64().unwrap_or(0), b.as_u64().unwrap_or(0));
+                let merged_value = if key == "duration_secs" {
+                    a_value.max(b_value)
+                } else {
+                    a_value + b_value
+                };
+                *a = merged_value.into();
+            }
+            (Value::Bool(a), Value::Bool(b)) => *a |= b,
+            (Value::Object(a), Value::Object(b)) => {
+                for duplicate in merge_map(a, b) {
+                    duplicates.extend(counters(&key, &duplicate));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    for counter in duplicates {
+        if let Some(Value::Number(count)) = merged.get_mut(counter) {
+            *count = count.as_u64().unwrap_or(0).saturating_sub(1).into();
+        }
+    }
+}
+
+/// The counters which an entry of the link map `map` was counted in
+fn counters(map: &str, entry: &Value) -> Vec<&'static str> {
+    let status = entry
+        .pointer("/status/text")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let counter = match map {
+        "success_map" => "successful",
+        "warning_map" => "warnings",
+        "excluded_map" => "excludes",
+        "unsupported_map" => "unsupported",
+        "fail_map" if status.starts_with("Timeout") => "timeouts",
+        "fail_map" => "errors",
+        _ => return Vec::new(),
+    };
+    let mut counters = vec!["total", counter];
+    if status.starts_with("Cached") {
+        counters.push("cached");
+    }
+    counters
+}
+
+/// Union the entries of a link map like `fail_map`, which maps each input
+/// source to a list of links, and return the entries which were dropped as
+/// duplicates
+fn merge_map(merged: &mut Map<String, Value>, map: Map<String, Value>) -> Vec<Value> {
+    let mut duplicates = Vec::new();
+    for (source, entries) in map {
+        let Value::Array(entries) = entries else {
+            continue;
+        };
+        let existing = merged
+            .entry(source)
+            .or_insert_with(|| Value::Array(Vec::new()));
+        let Value::Array(existing) = existing else {
+            continue;
+        };
+
+        let mut seen: HashSet<String> = existing.iter().map(entry_key).collect();
+        for entry in entries {
+            if seen.insert(entry_key(&entry)) {
+                existing.push(entry);
+            } else {
+                duplicates.push(entry);
+            }
+        }
+    }
+    duplicates
+}
+
+/// The key by which duplicate entries are detected.
+///
+/// Most entries are identified by their URL. Suggestions don't have one and
+/// are compared as a whole instead.
+fn entry_key(entry: &Value) -> String {
+    entry
+        .get("url")
+        .and_then(Value::as_str)
+        .map_or_else(|| entry.to_string(), ToString::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn object(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(map) => map,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut merged = Map::new();
+        merge(
+            &mut merged,
+            object(json!({
+                "total": 2,
+                "errors": 1,
+                "duration_secs": 3,
+                "detailed_stats": false,
+                "fail_map": {
+                    "a.md": [{"url": "https://a.example/", "status": {"text": "404"}}]
+                }
+            })),
+        );
+        merge(
+            &mut merged,
+            object(json!({
+                "total": 3,
+                "errors": 2,
+                "duration_secs": 1,
+                "detailed_stats": true,
+                "fail_map": {
+                    "a.md": [
+                        {"url": "https://a.example/", "status": {"text": "404"}},
+                        {"url": "https://b.example/", "status": {"text": "500"}}
+                    ],
+                    "b.md": [{"url": "https://a.example/", "status": {"text": "404"}}]
+                }
+            })),
+        );
+
+        assert_eq!(
+            Value::Object(merged),
+            json!({
+                "total": 4,
+                "errors": 2,
+                "duration_secs": 3,
+                "detailed_stats": true,
+                "fail_map": {
+                    "a.md": [
+                        {"url": "https://a.example/", "status": {"text": "404"}},
+                        {"url": "https://b.example/", "status": {"text": "500"}}
+                    ],
+                    "b.md": [{"url": "https://a.example/", "status": {"text": "404"}}]
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_counters() {
+        let entry = |text| json!({"url": "https://a.example/", "status": {"text": text}});
+
+        assert_eq!(
+            counters("fail_map", &entry("404 Not Found")),
+            ["total", "errors"]
+        );
+        assert_eq!(
+            counters("fail_map", &entry("Timeout")),
+            ["total", "timeouts"]
+        );
+        assert_eq!(
+            counters("fail_map", &entry("Cached: Error (cached)")),
+            ["total", "errors", "cached"]
+        );
+        assert_eq!(
+            counters("success_map", &entry("200 OK")),
+            ["total", "successful"]
+        );
+        assert!(counters("suggestion_map", &entry("")).is_empty());
+    }
+}
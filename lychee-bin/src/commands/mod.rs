@@ -1,9 +1,24 @@
 pub(crate) mod check;
+pub(crate) mod count;
+pub(crate) mod doctor;
 pub(crate) mod dump;
+pub(crate) mod explain;
+pub(crate) mod merge;
+pub(crate) mod preview;
+pub(crate) mod schema;
+pub(crate) mod syntax;
 
 pub(crate) use check::check;
+pub(crate) use count::count;
+pub(crate) use doctor::doctor;
 pub(crate) use dump::dump;
 pub(crate) use dump::dump_inputs;
+pub(crate) use dump::dump_inputs_with_extractor;
+pub(crate) use explain::explain;
+pub(crate) use merge::merge_reports;
+pub(crate) use preview::preview;
+pub(crate) use schema::emit_json_schema;
+pub(crate) use syntax::syntax_only;
 
 use std::sync::Arc;
 
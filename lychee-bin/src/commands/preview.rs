@@ -0,0 +1,48 @@
+use std::io::{self, Write};
+
+use anyhow::Result;
+use lychee_lib::{extract::Extractor, Input, InputContent};
+use tokio_stream::StreamExt;
+
+use crate::options::Config;
+use crate::ExitCode;
+
+/// Print the first `limit` links extracted from the inputs together with the
+/// source line they appear in, without checking them
+pub(crate) async fn preview(inputs: Vec<Input>, limit: usize, cfg: &Config) -> Result<ExitCode> {
+    let extractor = Extractor::new(false, cfg.include_verbatim)
+        .code_annotations(cfg.include_code_annotation.clone())
+        .include_identifiers(cfg.include_identifiers);
+    let mut stdout = io::stdout();
+    let mut shown = 0;
+
+    for input in inputs {
+        let mut contents = Box::pin(input.get_contents(cfg.skip_missing));
+        while let Some(content) = contents.next().await {
+            let content = content?;
+            for raw_uri in extractor.extract(&content) {
+                if shown == limit {
+                    return Ok(ExitCode::Success);
+                }
+                shown += 1;
+
+                let Some(span) = raw_uri.span else {
+                    writeln!(stdout, "{}: {}", content.source, raw_uri.text)?;
+                    continue;
+                };
+                writeln!(stdout, "{}:{span}: {}", content.source, raw_uri.text)?;
+                if let Some(line) = source_line(&content, span.line) {
+                    writeln!(stdout, "    | {line}")?;
+                }
+            }
+        }
+    }
+
+    Ok(ExitCode::Success)
+}
+
+/// The trimmed line with the given 1-based number
+fn source_line(content: &InputContent, number: usize) -> Option<&str> {
+    let line = content.content.lines().nth(number.checked_sub(1)?)?;
+    Some(line.trim())
+}
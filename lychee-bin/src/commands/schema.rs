@@ -0,0 +1,83 @@
+//! Emit the JSON Schema of the report written with `--format json`.
+//!
+//! The schema is generated from [`ResponseStats`]. The link maps contain types
+//! of `lychee-lib` with hand-written `Serialize` implementations, so their
+//! schema is described by the proxy types below, which mirror the serialized
+//! form.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use schemars::{schema_for, JsonSchema};
+
+use crate::stats::{ResponseStats, UnsupportedLink};
+use crate::ExitCode;
+
+/// Write the JSON Schema of the JSON report to `path`
+pub(crate) fn emit_json_schema(path: &Path) -> Result<ExitCode> {
+    let schema = serde_json::to_string_pretty(&schema_for!(ResponseStats))
+        .context("Cannot format JSON schema")?;
+    fs::write(path, schema)
+        .with_context(|| format!("Cannot write JSON schema to `{}`", path.display()))?;
+    Ok(ExitCode::Success)
+}
+
+/// Responses grouped by the input source they were found in
+pub(crate) type ResponseMap = HashMap<String, Vec<ResponseBody>>;
+
+/// Suggestions grouped by the input source they were found in
+pub(crate) type SuggestionMap = HashMap<String, Vec<Suggestion>>;
+
+/// Links with unsupported schemes grouped by the input source they were
+/// found in
+pub(crate) type UnsupportedMap = HashMap<String, Vec<UnsupportedLink>>;
+
+/// A checked link
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+pub(crate) struct ResponseBody {
+    /// The URL which was checked
+    url: String,
+    /// The outcome of the check
+    status: Status,
+    /// Position of the link in its input, if known
+    span: Option<Span>,
+}
+
+/// The outcome of checking a link
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+pub(crate) struct Status {
+    /// Human-readable description, e.g. `200 OK`
+    text: String,
+    /// HTTP status code, if any. Refused connections have the pseudo-code `0`.
+    code: Option<u16>,
+    /// Canonical reason phrase of the HTTP status code, e.g. `Not Found`
+    reason: Option<String>,
+    /// Further details, e.g. the reason of an error
+    details: Option<String>,
+    /// Classification of the error, currently only `connection_refused`
+    error_kind: Option<String>,
+}
+
+/// Position of a link in its input
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+pub(crate) struct Span {
+    /// Line, starting at 1
+    line: usize,
+    /// Column in characters, starting at 1
+    column: usize,
+}
+
+/// An archived version of a broken link
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+pub(crate) struct Suggestion {
+    /// The broken URL
+    original: String,
+    /// The archived URL
+    suggestion: String,
+}
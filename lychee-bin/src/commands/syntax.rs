@@ -0,0 +1,165 @@
+use std::cell::RefCell;
+use std::io::{self, Write};
+
+use anyhow::Result;
+use lychee_lib::Input;
+use tokio_stream::StreamExt;
+use url::{SyntaxViolation, Url};
+
+use crate::options::Config;
+use crate::{client, create_collector, ExitCode};
+
+/// Schemes which are commonly misspelled in links
+const COMMON_SCHEMES: [&str; 2] = ["http", "https"];
+
+/// Extract the links of all inputs and validate that they are well-formed
+/// URLs, without checking any of them. Links are extracted and excluded with
+/// the same options as when checking them.
+pub(crate) async fn syntax_only(inputs: Vec<Input>, cfg: &Config) -> Result<ExitCode> {
+    let collector = create_collector(cfg)?;
+    let client = client::create(cfg, None)?;
+    let mut stdout = io::stdout();
+    let mut total = 0;
+    let mut malformed = 0;
+
+    for input in inputs {
+        let mut contents = Box::pin(input.get_contents(cfg.skip_missing));
+        while let Some(content) = contents.next().await {
+            let content = content?;
+            let source = content.source.clone();
+            for (raw_uri, uri) in collector.resolve_links(content)? {
+                if uri.is_some_and(|uri| client.is_excluded(&uri)) {
+                    continue;
+                }
+                total += 1;
+                let Some(error) = syntax_error(&raw_uri.text) else {
+                    continue;
+                };
+                malformed += 1;
+                let location = raw_uri
+                    .span
+                    .map_or_else(String::new, |span| format!(":{span}"));
+                writeln!(
+                    stdout,
+                    "[ERROR] {source}{location}: {} | {error}",
+                    raw_uri.text
+                )?;
+            }
+        }
+    }
+
+    writeln!(stdout, "{total} links, {malformed} malformed")?;
+    Ok(if malformed == 0 {
+        ExitCode::Success
+    } else {
+        ExitCode::LinkCheckFailure
+    })
+}
+
+/// Returns why `text` is not a well-formed URL, if it isn't.
+///
+/// Links without a scheme are relative links and always accepted. The URL
+/// parser is lenient and fixes some mistakes like a missing slash on its own,
+/// which are reported nevertheless, as other tools might not be as forgiving.
+fn syntax_error(text: &str) -> Option<String> {
+    let scheme = scheme(text)?;
+    if !COMMON_SCHEMES.contains(&scheme.as_str()) {
+        if let Some(common) = COMMON_SCHEMES.iter().find(|c| is_typo_of(&scheme, c)) {
+            return Some(format!(
+                "Unknown scheme `{scheme}`, did you mean `{common}`?"
+            ));
+        }
+    }
+
+    let violation = RefCell::new(None);
+    let url = Url::options()
+        .syntax_violation_callback(Some(&|v| {
+            if v != SyntaxViolation::EmbeddedCredentials {
+                violation.borrow_mut().get_or_insert(v);
+            }
+        }))
+        .parse(text);
+    let url = match url {
+        Ok(url) => url,
+        Err(e) => return Some(format!("Cannot parse URL: {e}")),
+    };
+    if let Some(violation) = violation.into_inner() {
+        return Some(format!("Malformed URL: {}", violation.description()));
+    }
+    if matches!(url.scheme(), "http" | "https")
+        && url.domain().is_some_and(|domain| domain.contains(".."))
+    {
+        return Some("Malformed URL: empty label in host".to_string());
+    }
+    None
+}
+
+/// The lowercase scheme of `text`, if it has one.
+///
+/// Single letters are not considered a scheme, as they are usually Windows
+/// drive letters like in `C:\`.
+fn scheme(text: &str) -> Option<String> {
+    let (scheme, _) = text.split_once(':')?;
+    let mut chars = scheme.chars();
+    let valid = scheme.len() > 1
+        && chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    valid.then(|| scheme.to_ascii_lowercase())
+}
+
+/// Whether `scheme` differs from `expected` by a single inserted, removed or
+/// replaced character
+fn is_typo_of(scheme: &str, expected: &str) -> bool {
+    let (a, b) = (scheme.as_bytes(), expected.as_bytes());
+    if a == b || a.len().abs_diff(b.len()) > 1 {
+        return false;
+    }
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let (a, b) = (&a[prefix..], &b[prefix..]);
+    // After the common prefix, skipping the first character of either or both
+    // must leave the same rest
+    a.get(1..) == b.get(1..) || a == b.get(1..).unwrap_or_default() || a.get(1..) == Some(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_syntax_error() {
+        assert_eq!(
+            syntax_error("https://example.com/path?query#fragment"),
+            None
+        );
+        assert_eq!(syntax_error("http://example.com"), None);
+        assert_eq!(syntax_error("mailto:user@example.com"), None);
+        assert_eq!(syntax_error("ftp://example.com"), None);
+        assert_eq!(syntax_error("../relative/path.md"), None);
+        assert_eq!(syntax_error("C:\\Users"), None);
+
+        assert!(syntax_error("htps://example.com")
+            .unwrap()
+            .contains("did you mean `https`"));
+        assert!(syntax_error("htp://example.com")
+            .unwrap()
+            .contains("did you mean `http`"));
+        assert!(syntax_error("https:/example.com")
+            .unwrap()
+            .starts_with("Malformed URL"));
+        assert!(syntax_error("https://example.com:99999/")
+            .unwrap()
+            .starts_with("Cannot parse URL"));
+        assert!(syntax_error("https://example..com/").is_some());
+    }
+
+    #[test]
+    fn test_is_typo_of() {
+        assert!(is_typo_of("htps", "https"));
+        assert!(is_typo_of("httpss", "https"));
+        assert!(is_typo_of("htpp", "http"));
+        assert!(is_typo_of("hhttp", "http"));
+        assert!(!is_typo_of("https", "https"));
+        assert!(!is_typo_of("ftp", "http"));
+        assert!(!is_typo_of("ftps", "https"));
+    }
+}
@@ -0,0 +1,107 @@
+//! Warn about links which got different statuses in different inputs.
+//!
+//! Every unique link is usually checked once and its result reused for all
+//! other occurrences. Links whose result doesn't get cached, e.g. warnings or
+//! links in inputs matching `--cache-exclude-path`, are checked again, as are
+//! occurrences checked concurrently. If these checks disagree, the result of
+//! a link depends on where it was found, which usually hints at a
+//! configuration problem or a flaky server.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use lychee_lib::Response;
+
+/// Status => inputs which got that status
+type StatusSources = BTreeMap<String, BTreeSet<String>>;
+
+/// Collects the statuses of all checked links together with the inputs they
+/// were found in.
+#[derive(Debug, Default)]
+pub(crate) struct ConsistencyReport {
+    /// Statuses of every link
+    statuses: BTreeMap<String, StatusSources>,
+}
+
+impl ConsistencyReport {
+    /// Record the status of a checked link. Excluded and unsupported links
+    /// are ignored, as they were never checked.
+    pub(crate) fn add(&mut self, response: &Response) {
+        let status = response.status();
+        if status.is_excluded() || status.is_unsupported() {
+            return;
+        }
+        self.statuses
+            .entry(response.1.uri.to_string())
+            .or_default()
+            .entry(status.code_as_string())
+            .or_default()
+            .insert(response.0.to_string());
+    }
+
+    /// Links which got more than one status, with the inputs per status
+    fn inconsistencies(&self) -> impl Iterator<Item = (&String, &StatusSources)> {
+        self.statuses
+            .iter()
+            .filter(|(_, statuses)| statuses.len() > 1)
+    }
+
+    /// Log a warning for every link which got different statuses
+    pub(crate) fn warn(&self) {
+        for (uri, statuses) in self.inconsistencies() {
+            let statuses: Vec<_> = statuses
+                .iter()
+                .map(|(status, sources)| {
+                    let sources: Vec<_> = sources.iter().map(String::as_str).collect();
+                    format!("{status} in {}", sources.join(", "))
+                })
+                .collect();
+            log::warn!(
+                "{uri} got different statuses across inputs: {}",
+                statuses.join("; ")
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use http::StatusCode;
+    use lychee_lib::{ErrorKind, InputSource, Status, Uri};
+
+    use super::*;
+
+    fn response(uri: &str, status: Status, source: &str) -> Response {
+        Response::new(
+            Uri::try_from(uri).unwrap(),
+            status,
+            InputSource::FsPath(PathBuf::from(source)),
+        )
+    }
+
+    #[test]
+    fn test_inconsistencies() {
+        let mut report = ConsistencyReport::default();
+        for (uri, status, source) in [
+            ("https://a.org", Status::Ok(StatusCode::OK), "a.md"),
+            ("https://a.org", Status::Ok(StatusCode::OK), "b.md"),
+            ("https://b.org", Status::Ok(StatusCode::OK), "a.md"),
+            (
+                "https://b.org",
+                Status::Error(ErrorKind::InvalidStatusCode(404)),
+                "b.md",
+            ),
+        ] {
+            report.add(&response(uri, status, source));
+        }
+        // Never checked, so not inconsistent
+        report.add(&response("https://a.org", Status::Excluded, "c.md"));
+
+        let inconsistencies: Vec<_> = report.inconsistencies().collect();
+        assert_eq!(inconsistencies.len(), 1);
+        let (uri, statuses) = inconsistencies[0];
+        assert_eq!(uri, "https://b.org/");
+        assert_eq!(statuses.keys().collect::<Vec<_>>(), vec!["200 OK", "ERR"]);
+    }
+}
@@ -0,0 +1,139 @@
+//! Report links which occur more than once within or across inputs.
+//!
+//! Every unique link is still checked only once. Repeated links are merely a
+//! documentation quality signal, so like the anchor report, this never
+//! influences the exit code.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    io::{self, Write},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+use futures::{future, Stream, StreamExt};
+use lychee_lib::Request;
+
+/// Positions of every link, keyed by the link
+type Occurrences = Arc<Mutex<BTreeMap<String, Vec<String>>>>;
+
+/// Collects the positions of all links while checking, so that links which
+/// occur more than once can be reported afterwards.
+#[derive(Debug, Default)]
+pub(crate) struct DuplicateReport {
+    occurrences: Occurrences,
+}
+
+impl DuplicateReport {
+    /// Write all links which occur more than once to stderr, together with
+    /// their positions
+    pub(crate) fn report(&self) -> Result<()> {
+        let duplicates = self.duplicates();
+        if duplicates.is_empty() {
+            return Ok(());
+        }
+
+        let mut handle = io::stderr();
+        writeln!(handle, "\nDuplicate links ({}):", duplicates.len())?;
+        for (uri, occurrences) in duplicates {
+            writeln!(handle, "  {uri} ({} occurrences)", occurrences.len())?;
+            for occurrence in occurrences {
+                writeln!(handle, "    {occurrence}")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the positions of all links which occur more than once, as
+    /// `source:line:column`, sorted by link
+    fn duplicates(&self) -> BTreeMap<String, Vec<String>> {
+        let mut duplicates = self
+            .occurrences
+            .lock()
+            .expect("duplicate links lock poisoned")
+            .clone();
+        duplicates.retain(|_, positions| positions.len() > 1);
+        duplicates
+    }
+}
+
+/// Record the positions of the links of `requests` in `report` (if any) and
+/// only pass on the first occurrence of a link within an input for checking.
+///
+/// This expects the collector to keep duplicates, see
+/// [`Collector::keep_duplicates`](lychee_lib::Collector::keep_duplicates).
+/// Links are compared after relative links were resolved, so two relative
+/// links only count as duplicates if they point to the same target.
+pub(crate) fn record_occurrences<S>(
+    requests: S,
+    report: Option<&DuplicateReport>,
+) -> impl Stream<Item = lychee_lib::Result<Request>>
+where
+    S: Stream<Item = lychee_lib::Result<Request>>,
+{
+    let occurrences = report.map(|report| report.occurrences.clone());
+    // Requests only differing in their span compare equal
+    let mut seen = HashSet::new();
+    requests.filter(move |request| {
+        let keep = match (&occurrences, request) {
+            (Some(occurrences), Ok(request)) => {
+                let position = match request.span {
+                    Some(span) => format!("{}:{span}", request.source),
+                    None => request.source.to_string(),
+                };
+                occurrences
+                    .lock()
+                    .expect("duplicate links lock poisoned")
+                    .entry(request.uri.to_string())
+                    .or_default()
+                    .push(position);
+                seen.insert(request.clone())
+            }
+            _ => true,
+        };
+        future::ready(keep)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use lychee_lib::{InputSource, RawUriSpan, Uri};
+
+    use super::*;
+
+    fn request(url: &str, line: usize) -> Request {
+        let mut request = Request::new(
+            Uri::try_from(url).unwrap(),
+            InputSource::Stdin,
+            None,
+            None,
+            None,
+        );
+        request.span = Some(RawUriSpan { line, column: 1 });
+        request
+    }
+
+    #[tokio::test]
+    async fn test_duplicates() {
+        let report = DuplicateReport::default();
+        let requests = futures::stream::iter([
+            request("https://example.com/a", 1),
+            request("https://example.com/b", 1),
+            request("https://example.com/a", 2),
+        ])
+        .map(Ok);
+
+        let passed_on = record_occurrences(requests, Some(&report))
+            .collect::<Vec<_>>()
+            .await;
+        // Every link is still checked once per input
+        assert_eq!(passed_on.len(), 2);
+
+        let duplicates = report.duplicates();
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(
+            duplicates["https://example.com/a"],
+            ["stdin:1:1", "stdin:2:1"]
+        );
+    }
+}
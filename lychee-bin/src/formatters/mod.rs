@@ -2,6 +2,8 @@ pub(crate) mod duration;
 pub(crate) mod response;
 pub(crate) mod stats;
 
+use std::path::Path;
+
 use lychee_lib::{CacheStatus, ResponseBody, Status};
 use supports_color::Stream;
 
@@ -11,6 +13,17 @@ use crate::{
 };
 
 use self::response::ResponseFormatter;
+use self::stats::StatsFormatter;
+
+/// The path relative to the working directory with forward slashes, as code
+/// scanning and check runs expect paths relative to the repository root
+pub(crate) fn relative_path(path: &Path) -> String {
+    let relative = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| path.strip_prefix(cwd).ok())
+        .unwrap_or(path);
+    relative.to_string_lossy().replace('\\', "/")
+}
 
 /// Detects whether a terminal supports color, and gives details about that
 /// support. It takes into account the `NO_COLOR` environment variable.
@@ -24,12 +37,15 @@ pub(crate) fn color_response(body: &ResponseBody) -> String {
         let out = match body.status {
             Status::Ok(_) | Status::Cached(CacheStatus::Ok(_)) => GREEN.apply_to(body),
             Status::Excluded
+            | Status::ExcludedWithReason(_)
             | Status::Unsupported(_)
             | Status::Cached(CacheStatus::Excluded | CacheStatus::Unsupported) => {
                 DIM.apply_to(body)
             }
             Status::Redirected(_) => NORMAL.apply_to(body),
-            Status::UnknownStatusCode(_) | Status::Timeout(_) => YELLOW.apply_to(body),
+            Status::UnknownStatusCode(_) | Status::Timeout(_) | Status::Warning(..) => {
+                YELLOW.apply_to(body)
+            }
             Status::Error(_) | Status::Cached(CacheStatus::Error(_)) => PINK.apply_to(body),
         };
         out.to_string()
@@ -45,3 +61,22 @@ pub(crate) fn get_formatter(format: &options::Format) -> Box<dyn ResponseFormatt
     }
     Box::new(response::Color::new())
 }
+
+/// Create a stats formatter for the final report based on the given format option.
+/// The compact and detailed formats list failed links by host if
+/// `collapse_by_host` is set.
+pub(crate) fn get_stats_formatter(
+    format: &options::Format,
+    collapse_by_host: Option<usize>,
+    status_breakdown: bool,
+) -> Box<dyn StatsFormatter> {
+    match format {
+        Format::Compact => Box::new(stats::Compact::new(collapse_by_host, status_breakdown)),
+        Format::Detailed => Box::new(stats::Detailed::new(collapse_by_host, status_breakdown)),
+        Format::Json => Box::new(stats::Json::new()),
+        Format::Markdown => Box::new(stats::Markdown::new()),
+        Format::Raw => Box::new(stats::Raw::new()),
+        Format::Sarif => Box::new(stats::Sarif::new()),
+        Format::Toml => Box::new(stats::Toml::new()),
+    }
+}
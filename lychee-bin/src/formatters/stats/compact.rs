@@ -9,30 +9,48 @@ use crate::{
     stats::ResponseStats,
 };
 
-use super::StatsFormatter;
+use super::{errors_by_host, status_breakdown, StatsFormatter};
 
 use anyhow::Result;
 
-struct CompactResponseStats(ResponseStats);
-
-impl Display for CompactResponseStats {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let stats = &self.0;
+struct CompactResponseStats {
+    stats: ResponseStats,
+    collapse_by_host: Option<usize>,
+    status_breakdown: bool,
+}
 
-        if !stats.fail_map.is_empty() {
-            let input = if stats.fail_map.len() == 1 {
-                "input"
+impl CompactResponseStats {
+    /// List the failed links by host, with at most `examples` links per host
+    fn write_collapsed(&self, f: &mut fmt::Formatter<'_>, examples: usize) -> fmt::Result {
+        for (host, responses) in errors_by_host(&self.stats) {
+            let errors = if responses.len() == 1 {
+                "error"
             } else {
-                "inputs"
+                "errors"
             };
+            color!(f, BOLD_YELLOW, "[{host}]: {} {errors}\n", responses.len())?;
+            for response in responses.iter().take(examples) {
+                writeln!(f, "{}", color_response(response))?;
+            }
+            if responses.len() > examples {
+                writeln!(f, "... and {} more", responses.len() - examples)?;
+            }
+            writeln!(f)?;
+        }
 
-            color!(
-                f,
-                BOLD_PINK,
-                "Issues found in {} {input}. Find details below.\n\n",
-                stats.fail_map.len()
-            )?;
+        if !self.stats.suggestion_map.is_empty() {
+            writeln!(f, "\u{2139} Suggestions")?;
+            for suggestion in self.stats.suggestion_map.values().flatten() {
+                writeln!(f, "{suggestion}")?;
+            }
+            writeln!(f)?;
         }
+        Ok(())
+    }
+
+    /// List the failed links of each input
+    fn write_per_input(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let stats = &self.stats;
         for (source, responses) in &stats.fail_map {
             color!(f, BOLD_YELLOW, "[{}]:\n", source)?;
             for response in responses {
@@ -48,6 +66,32 @@ impl Display for CompactResponseStats {
 
             writeln!(f)?;
         }
+        Ok(())
+    }
+}
+
+impl Display for CompactResponseStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let stats = &self.stats;
+
+        if !stats.fail_map.is_empty() {
+            let input = if stats.fail_map.len() == 1 {
+                "input"
+            } else {
+                "inputs"
+            };
+
+            color!(
+                f,
+                BOLD_PINK,
+                "Issues found in {} {input}. Find details below.\n\n",
+                stats.fail_map.len()
+            )?;
+        }
+        match self.collapse_by_host {
+            Some(examples) => self.write_collapsed(f, examples)?,
+            None => self.write_per_input(f)?,
+        }
 
         color!(f, NORMAL, "\u{1F50D} {} Total", stats.total)?;
 
@@ -61,6 +105,17 @@ impl Display for CompactResponseStats {
 
         let err_str = if total_errors == 1 { "Error" } else { "Errors" };
         color!(f, BOLD_PINK, " \u{1f6ab} {} {}", total_errors, err_str)?;
+        if self.status_breakdown && total_errors > 0 {
+            color!(f, BOLD_PINK, " ({})", status_breakdown(stats))?;
+        }
+        if stats.warnings > 0 {
+            let warn_str = if stats.warnings == 1 {
+                "Warning"
+            } else {
+                "Warnings"
+            };
+            color!(f, BOLD_YELLOW, " \u{26a0} {} {}", stats.warnings, warn_str)?;
+        }
         if stats.excludes > 0 {
             color!(f, BOLD_YELLOW, " \u{1F4A4} {} Excluded", stats.excludes)?;
         }
@@ -68,17 +123,30 @@ impl Display for CompactResponseStats {
     }
 }
 
-pub(crate) struct Compact;
+pub(crate) struct Compact {
+    collapse_by_host: Option<usize>,
+    status_breakdown: bool,
+}
 
 impl Compact {
-    pub(crate) const fn new() -> Self {
-        Self {}
+    /// List failed links by host instead of by input if `collapse_by_host`
+    /// is set, showing at most that many links per host. With
+    /// `status_breakdown`, the number of errors per status is shown as well
+    pub(crate) const fn new(collapse_by_host: Option<usize>, status_breakdown: bool) -> Self {
+        Self {
+            collapse_by_host,
+            status_breakdown,
+        }
     }
 }
 
 impl StatsFormatter for Compact {
     fn format_stats(&self, stats: ResponseStats) -> Result<Option<String>> {
-        let compact = CompactResponseStats(stats);
+        let compact = CompactResponseStats {
+            stats,
+            collapse_by_host: self.collapse_by_host,
+            status_breakdown: self.status_breakdown,
+        };
         Ok(Some(compact.to_string()))
     }
 }
@@ -1,4 +1,4 @@
-use super::StatsFormatter;
+use super::{errors_by_host, status_breakdown, StatsFormatter};
 use crate::{formatters::color_response, stats::ResponseStats};
 
 use anyhow::Result;
@@ -26,7 +26,7 @@ fn write_stat(f: &mut fmt::Formatter, title: &str, stat: usize, newline: bool) -
 
 impl Display for DetailedResponseStats {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let stats = &self.0;
+        let stats = &self.stats;
         let separator = "-".repeat(MAX_PADDING + 1);
 
         writeln!(f, "\u{1f4dd} Summary")?; // 📝
@@ -34,46 +34,93 @@ impl Display for DetailedResponseStats {
         write_stat(f, "\u{1f50d} Total", stats.total, true)?; // 🔍
         write_stat(f, "\u{2705} Successful", stats.successful, true)?; // ✅
         write_stat(f, "\u{23f3} Timeouts", stats.timeouts, true)?; // ⏳
+        write_stat(f, "\u{1f50c} Refused", stats.refused, true)?; // 🔌
         write_stat(f, "\u{1f500} Redirected", stats.redirects, true)?; // 🔀
         write_stat(f, "\u{1f47b} Excluded", stats.excludes, true)?; // 👻
         write_stat(f, "\u{2753} Unknown", stats.unknown, true)?; //❓
+        write_stat(f, "\u{26a0} Warnings", stats.warnings, true)?; // ⚠
         write_stat(f, "\u{1f6ab} Errors", stats.errors, false)?; // 🚫
+        if self.status_breakdown && stats.errors > 0 {
+            write!(f, " ({})", status_breakdown(stats))?;
+        }
 
-        for (source, responses) in &stats.fail_map {
-            // Using leading newlines over trailing ones (e.g. `writeln!`)
-            // lets us avoid extra newlines without any additional logic.
-            write!(f, "\n\nErrors in {source}")?;
-            for response in responses {
-                write!(f, "\n{}", color_response(response))?;
+        if let Some(examples) = self.collapse_by_host {
+            for (host, responses) in errors_by_host(stats) {
+                write!(f, "\n\nErrors on {host} ({})", responses.len())?;
+                for response in responses.iter().take(examples) {
+                    write!(f, "\n{}", color_response(response))?;
+                }
+                if responses.len() > examples {
+                    write!(f, "\n... and {} more", responses.len() - examples)?;
+                }
+            }
+            for (source, suggestions) in &stats.suggestion_map {
+                write!(f, "\n\nSuggestions in {source}")?;
+                for suggestion in suggestions {
+                    write!(f, "\n{suggestion}")?;
+                }
+            }
+        } else {
+            for (source, responses) in &stats.fail_map {
+                // Using leading newlines over trailing ones (e.g. `writeln!`)
+                // lets us avoid extra newlines without any additional logic.
+                write!(f, "\n\nErrors in {source}")?;
+                for response in responses {
+                    write!(f, "\n{}", color_response(response))?;
 
-                if let Some(suggestions) = &stats.suggestion_map.get(source) {
-                    writeln!(f, "\nSuggestions in {source}")?;
-                    for suggestion in *suggestions {
-                        writeln!(f, "{suggestion}")?;
+                    if let Some(suggestions) = &stats.suggestion_map.get(source) {
+                        writeln!(f, "\nSuggestions in {source}")?;
+                        for suggestion in *suggestions {
+                            writeln!(f, "{suggestion}")?;
+                        }
                     }
                 }
             }
         }
 
+        for (source, responses) in &stats.warning_map {
+            write!(f, "\n\nWarnings in {source}")?;
+            for response in responses {
+                write!(f, "\n{}", color_response(response))?;
+            }
+        }
+
         Ok(())
     }
 }
 
 /// Wrap as newtype because multiple `Display` implementations are not allowed
 /// for `ResponseStats`
-struct DetailedResponseStats(ResponseStats);
+struct DetailedResponseStats {
+    stats: ResponseStats,
+    collapse_by_host: Option<usize>,
+    status_breakdown: bool,
+}
 
-pub(crate) struct Detailed;
+pub(crate) struct Detailed {
+    collapse_by_host: Option<usize>,
+    status_breakdown: bool,
+}
 
 impl Detailed {
-    pub(crate) const fn new() -> Self {
-        Self
+    /// List failed links by host instead of by input if `collapse_by_host`
+    /// is set, showing at most that many links per host. With
+    /// `status_breakdown`, the number of errors per status is shown as well
+    pub(crate) const fn new(collapse_by_host: Option<usize>, status_breakdown: bool) -> Self {
+        Self {
+            collapse_by_host,
+            status_breakdown,
+        }
     }
 }
 
 impl StatsFormatter for Detailed {
     fn format_stats(&self, stats: ResponseStats) -> Result<Option<String>> {
-        let detailed = DetailedResponseStats(stats);
+        let detailed = DetailedResponseStats {
+            stats,
+            collapse_by_host: self.collapse_by_host,
+            status_breakdown: self.status_breakdown,
+        };
         Ok(Some(detailed.to_string()))
     }
 }
@@ -1,4 +1,7 @@
+use std::collections::HashSet;
+
 use anyhow::{Context, Result};
+use serde::Serialize;
 
 use super::StatsFormatter;
 use crate::stats::ResponseStats;
@@ -19,3 +22,41 @@ impl StatsFormatter for Json {
             .context("Cannot format stats as JSON")
     }
 }
+
+/// One-line summary of why lychee exited, written to stderr with
+/// `--json-errors` so that scripts don't have to parse the stats
+#[derive(Debug, Serialize, PartialEq)]
+pub(crate) struct ExitSummary {
+    exit_code: i32,
+    /// Number of failed links
+    error_count: usize,
+    /// The first failed link, sorted by input and URL
+    first_error: Option<String>,
+}
+
+impl ExitSummary {
+    pub(crate) fn new(stats: &ResponseStats, exit_code: i32) -> Self {
+        let first_error = stats
+            .fail_map
+            .iter()
+            .flat_map(|(source, responses)| {
+                responses
+                    .iter()
+                    .map(move |response| (source.to_string(), response))
+            })
+            .min_by_key(|(source, response)| (source.clone(), response.uri.to_string()))
+            .map(|(_, response)| {
+                format!("[{}] {}", response.status.code_as_string(), response.uri)
+            });
+        Self {
+            exit_code,
+            error_count: stats.fail_map.values().map(HashSet::len).sum(),
+            first_error,
+        }
+    }
+
+    /// Format the summary as compact, single-line JSON
+    pub(crate) fn format(&self) -> Result<String> {
+        serde_json::to_string(self).context("Cannot format exit summary as JSON")
+    }
+}
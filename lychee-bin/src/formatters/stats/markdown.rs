@@ -37,6 +37,10 @@ fn stats_table(stats: &ResponseStats) -> String {
             status: "\u{23f3} Timeouts",
             count: stats.timeouts,
         },
+        StatsTableEntry {
+            status: "\u{1f50c} Refused",
+            count: stats.refused,
+        },
         StatsTableEntry {
             status: "\u{1f500} Redirected",
             count: stats.redirects,
@@ -49,6 +53,10 @@ fn stats_table(stats: &ResponseStats) -> String {
             status: "\u{2753} Unknown",
             count: stats.unknown,
         },
+        StatsTableEntry {
+            status: "\u{26a0} Warnings",
+            count: stats.warnings,
+        },
         StatsTableEntry {
             status: "\u{1f6ab} Errors",
             count: stats.errors,
@@ -104,6 +112,10 @@ impl Display for MarkdownResponseStats {
             markdown_response(response).map_err(|_e| fmt::Error)
         })?;
 
+        write_stats_per_input(f, "Warnings", &stats.warning_map, |response| {
+            markdown_response(response).map_err(|_e| fmt::Error)
+        })?;
+
         write_stats_per_input(f, "Suggestions", &stats.suggestion_map, |suggestion| {
             Ok(format!(
                 "* {} --> {}",
@@ -166,40 +178,40 @@ mod tests {
 
     #[test]
     fn test_markdown_response_ok() {
-        let response = ResponseBody {
-            uri: Uri::try_from("http://example.com").unwrap(),
-            status: Status::Ok(StatusCode::OK),
-        };
+        let response = ResponseBody::new(
+            Uri::try_from("http://example.com").unwrap(),
+            Status::Ok(StatusCode::OK),
+        );
         let markdown = markdown_response(&response).unwrap();
         assert_eq!(
             markdown,
-            "* [200] [http://example.com/](http://example.com/)"
+            "* [200 OK] [http://example.com/](http://example.com/)"
         );
     }
 
     #[test]
     fn test_markdown_response_cached_ok() {
-        let response = ResponseBody {
-            uri: Uri::try_from("http://example.com").unwrap(),
-            status: Status::Cached(CacheStatus::Ok(200)),
-        };
+        let response = ResponseBody::new(
+            Uri::try_from("http://example.com").unwrap(),
+            Status::Cached(CacheStatus::Ok(200)),
+        );
         let markdown = markdown_response(&response).unwrap();
         assert_eq!(
             markdown,
-            "* [200] [http://example.com/](http://example.com/) | Cached: OK (cached)"
+            "* [200 OK] [http://example.com/](http://example.com/) | Cached: OK (cached)"
         );
     }
 
     #[test]
     fn test_markdown_response_cached_err() {
-        let response = ResponseBody {
-            uri: Uri::try_from("http://example.com").unwrap(),
-            status: Status::Cached(CacheStatus::Error(Some(400))),
-        };
+        let response = ResponseBody::new(
+            Uri::try_from("http://example.com").unwrap(),
+            Status::Cached(CacheStatus::Error(Some(400))),
+        );
         let markdown = markdown_response(&response).unwrap();
         assert_eq!(
             markdown,
-            "* [400] [http://example.com/](http://example.com/) | Cached: Error (cached)"
+            "* [400 Bad Request] [http://example.com/](http://example.com/) | Cached: Error (cached)"
         );
     }
 
@@ -212,9 +224,11 @@ mod tests {
 | 🔍 Total      | 0     |
 | ✅ Successful | 0     |
 | ⏳ Timeouts   | 0     |
+| 🔌 Refused    | 0     |
 | 🔀 Redirected | 0     |
 | 👻 Excluded   | 0     |
 | ❓ Unknown    | 0     |
+| ⚠ Warnings    | 0     |
 | 🚫 Errors     | 0     |";
         assert_eq!(table, expected.to_string());
     }
@@ -224,10 +238,10 @@ mod tests {
         let mut stats = ResponseStats::default();
         let response = Response(
             InputSource::Stdin,
-            ResponseBody {
-                uri: Uri::try_from("http://127.0.0.1").unwrap(),
-                status: Status::Cached(CacheStatus::Error(Some(404))),
-            },
+            ResponseBody::new(
+                Uri::try_from("http://127.0.0.1").unwrap(),
+                Status::Cached(CacheStatus::Error(Some(404))),
+            ),
         );
         stats.add(response);
         stats
@@ -246,16 +260,18 @@ mod tests {
 | 🔍 Total      | 1     |
 | ✅ Successful | 0     |
 | ⏳ Timeouts   | 0     |
+| 🔌 Refused    | 0     |
 | 🔀 Redirected | 0     |
 | 👻 Excluded   | 0     |
 | ❓ Unknown    | 0     |
+| ⚠ Warnings    | 0     |
 | 🚫 Errors     | 1     |
 
 ## Errors per input
 
 ### Errors in stdin
 
-* [404] [http://127.0.0.1/](http://127.0.0.1/) | Cached: Error (cached)
+* [404 Not Found] [http://127.0.0.1/](http://127.0.0.1/) | Cached: Error (cached)
 
 ## Suggestions per input
 
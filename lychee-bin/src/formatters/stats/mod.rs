@@ -3,17 +3,137 @@ mod detailed;
 mod json;
 mod markdown;
 mod raw;
+mod sarif;
+mod toml;
 
 pub(crate) use compact::Compact;
 pub(crate) use detailed::Detailed;
-pub(crate) use json::Json;
+pub(crate) use json::{ExitSummary, Json};
 pub(crate) use markdown::Markdown;
 pub(crate) use raw::Raw;
+pub(crate) use sarif::Sarif;
+pub(crate) use toml::Toml;
 
-use crate::stats::ResponseStats;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{hosts::external_host, stats::ResponseStats};
 use anyhow::Result;
+use lychee_lib::ResponseBody;
 
 pub(crate) trait StatsFormatter {
     /// Format the stats of all responses and write them to stdout
     fn format_stats(&self, stats: ResponseStats) -> Result<Option<String>>;
 }
+
+/// The failed links of all inputs grouped by host, the host with the most
+/// failed links first. Links which are not HTTP(S), like files, are grouped by
+/// their scheme.
+fn errors_by_host(stats: &ResponseStats) -> Vec<(String, Vec<&ResponseBody>)> {
+    let mut hosts: HashMap<String, Vec<&ResponseBody>> = HashMap::new();
+    for response in stats.fail_map.values().flatten() {
+        let host =
+            external_host(&response.uri).unwrap_or_else(|| response.uri.scheme().to_string());
+        hosts.entry(host).or_default().push(response);
+    }
+
+    let mut hosts: Vec<_> = hosts.into_iter().collect();
+    for (_, responses) in &mut hosts {
+        responses.sort_by(|a, b| a.uri.as_str().cmp(b.uri.as_str()));
+    }
+    hosts.sort_by(|(a_host, a), (b_host, b)| b.len().cmp(&a.len()).then(a_host.cmp(b_host)));
+    hosts
+}
+
+/// The number of failed links per status, like `404` or `timeout`, formatted
+/// as `404: 3, 500: 1, timeout: 2`. HTTP status codes come first.
+fn status_breakdown(stats: &ResponseStats) -> String {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for response in stats.fail_map.values().flatten() {
+        let label = if response.status.is_timeout() {
+            "timeout".to_string()
+        } else {
+            response
+                .status
+                .code()
+                .map_or_else(|| "error".to_string(), |code| code.as_str().to_string())
+        };
+        *counts.entry(label).or_default() += 1;
+    }
+    counts
+        .iter()
+        .map(|(label, count)| format!("{label}: {count}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use lychee_lib::{CacheStatus, InputSource, Response, Status, Uri};
+
+    use super::*;
+
+    #[test]
+    fn test_errors_by_host() {
+        let mut stats = ResponseStats::default();
+        for (source, uri) in [
+            ("a.md", "https://b.example/1"),
+            ("a.md", "https://a.example/2"),
+            ("b.md", "https://a.example/1"),
+            ("b.md", "file:///missing"),
+        ] {
+            stats.add(Response(
+                InputSource::String(source.to_string()),
+                ResponseBody::new(
+                    Uri::try_from(uri).unwrap(),
+                    Status::Cached(CacheStatus::Error(Some(404))),
+                ),
+            ));
+        }
+
+        let hosts: Vec<(String, Vec<&str>)> = errors_by_host(&stats)
+            .into_iter()
+            .map(|(host, responses)| {
+                let uris = responses.iter().map(|r| r.uri.as_str()).collect();
+                (host, uris)
+            })
+            .collect();
+        assert_eq!(
+            hosts,
+            [
+                (
+                    "a.example".to_string(),
+                    vec!["https://a.example/1", "https://a.example/2"]
+                ),
+                ("b.example".to_string(), vec!["https://b.example/1"]),
+                ("file".to_string(), vec!["file:///missing"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_status_breakdown() {
+        let mut stats = ResponseStats::default();
+        for (uri, status) in [
+            (
+                "https://example.com/1",
+                Status::Cached(CacheStatus::Error(Some(404))),
+            ),
+            (
+                "https://example.com/2",
+                Status::Cached(CacheStatus::Error(Some(500))),
+            ),
+            (
+                "https://example.com/3",
+                Status::Cached(CacheStatus::Error(Some(404))),
+            ),
+            ("https://example.com/4", Status::Timeout(None)),
+        ] {
+            stats.add(Response(
+                InputSource::String("a.md".to_string()),
+                ResponseBody::new(Uri::try_from(uri).unwrap(), status),
+            ));
+        }
+
+        assert_eq!(status_breakdown(&stats), "404: 2, 500: 1, timeout: 1");
+    }
+}
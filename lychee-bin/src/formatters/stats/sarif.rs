@@ -0,0 +1,155 @@
+use anyhow::{Context, Result};
+use lychee_lib::{InputSource, ResponseBody};
+use serde_json::{json, Value};
+
+use super::StatsFormatter;
+use crate::{formatters::relative_path, stats::ResponseStats};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const RULE_ID: &str = "broken-link";
+
+/// Format stats as a SARIF 2.1.0 document, e.g. for GitHub code scanning.
+/// Each failed link becomes a result located at its input and span.
+/// Links from stdin or a string input are located at `stdin` or `string`,
+/// as every result needs a location.
+pub(crate) struct Sarif;
+
+impl Sarif {
+    pub(crate) const fn new() -> Self {
+        Self {}
+    }
+}
+
+/// The location of a failed link
+fn sarif_location(source: &InputSource, response: &ResponseBody) -> Value {
+    let uri = match source {
+        InputSource::FsPath(path) => relative_path(path),
+        InputSource::RemoteUrl(url) => url.to_string(),
+        InputSource::Stdin => "stdin".to_string(),
+        InputSource::String(_) => "string".to_string(),
+        source => source.to_string(),
+    };
+    let mut location = json!({
+        "physicalLocation": {
+            "artifactLocation": { "uri": uri }
+        }
+    });
+    if let Some(span) = response.span {
+        location["physicalLocation"]["region"] = json!({
+            "startLine": span.line,
+            "startColumn": span.column,
+        });
+    }
+    location
+}
+
+fn sarif_result(source: &InputSource, response: &ResponseBody) -> Value {
+    let message = match response.status.details() {
+        Some(details) => format!("{}: {} ({details})", response.uri, response.status),
+        None => format!("{}: {}", response.uri, response.status),
+    };
+    json!({
+        "ruleId": RULE_ID,
+        "level": "error",
+        "message": { "text": message },
+        "locations": [sarif_location(source, response)],
+    })
+}
+
+fn sarif_document(stats: &ResponseStats) -> Value {
+    let mut failures: Vec<(&InputSource, &ResponseBody)> = stats
+        .fail_map
+        .iter()
+        .flat_map(|(source, responses)| responses.iter().map(move |response| (source, response)))
+        .collect();
+    failures.sort_by_key(|(source, response)| (source.to_string(), response.uri.to_string()));
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "lychee",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/lycheeverse/lychee",
+                    "rules": [{
+                        "id": RULE_ID,
+                        "shortDescription": { "text": "Broken link" },
+                    }],
+                }
+            },
+            "results": failures
+                .into_iter()
+                .map(|(source, response)| sarif_result(source, response))
+                .collect::<Vec<_>>(),
+        }]
+    })
+}
+
+impl StatsFormatter for Sarif {
+    fn format_stats(&self, stats: ResponseStats) -> Result<Option<String>> {
+        serde_json::to_string_pretty(&sarif_document(&stats))
+            .map(Some)
+            .context("Cannot format stats as SARIF")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use http::StatusCode;
+    use lychee_lib::{CacheStatus, RawUriSpan, Response, Status, Uri};
+
+    use super::*;
+
+    #[test]
+    fn test_sarif_results() {
+        let mut stats = ResponseStats::default();
+        stats.add(
+            Response::new(
+                Uri::try_from("https://example.com/404").unwrap(),
+                Status::Cached(CacheStatus::Error(Some(404))),
+                InputSource::FsPath(PathBuf::from("/docs/README.md")),
+            )
+            .with_span(Some(RawUriSpan { line: 3, column: 7 })),
+        );
+        stats.add(Response(
+            InputSource::Stdin,
+            ResponseBody::new(
+                Uri::try_from("https://example.com/ok").unwrap(),
+                Status::Ok(StatusCode::OK),
+            ),
+        ));
+
+        let sarif = sarif_document(&stats);
+        assert_eq!(sarif["version"], "2.1.0");
+        assert_eq!(sarif["runs"][0]["tool"]["driver"]["name"], "lychee");
+
+        let results = sarif["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["ruleId"], RULE_ID);
+        let location = &results[0]["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "/docs/README.md");
+        assert_eq!(location["region"]["startLine"], 3);
+        assert_eq!(location["region"]["startColumn"], 7);
+    }
+
+    #[test]
+    fn test_sarif_location_of_stdin() {
+        let mut stats = ResponseStats::default();
+        stats.add(Response(
+            InputSource::Stdin,
+            ResponseBody::new(
+                Uri::try_from("https://example.com/404").unwrap(),
+                Status::Cached(CacheStatus::Error(Some(404))),
+            ),
+        ));
+
+        let sarif = sarif_document(&stats);
+        let location = &sarif["runs"][0]["results"][0]["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "stdin");
+        assert!(location.get("region").is_none());
+    }
+}
@@ -0,0 +1,22 @@
+use anyhow::{Context, Result};
+
+use super::StatsFormatter;
+use crate::stats::ResponseStats;
+
+pub(crate) struct Toml;
+
+impl Toml {
+    pub(crate) const fn new() -> Self {
+        Self {}
+    }
+}
+
+impl StatsFormatter for Toml {
+    /// Format stats as TOML document, with the same structure as the JSON
+    /// output
+    fn format_stats(&self, stats: ResponseStats) -> Result<Option<String>> {
+        toml::to_string_pretty(&stats)
+            .map(Some)
+            .context("Cannot format stats as TOML")
+    }
+}
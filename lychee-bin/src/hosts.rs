@@ -0,0 +1,155 @@
+//! Report how many links point to each external host.
+//!
+//! This helps to spot an over-reliance on a single external domain. Like the
+//! anchor report, it is purely informational and never influences the exit
+//! code.
+
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+use anyhow::Result;
+use lychee_lib::Uri;
+use reqwest::Url;
+
+use crate::stats::ResponseStats;
+
+/// Number of links to a single host
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct HostCounts {
+    pub(crate) links: usize,
+    pub(crate) broken: usize,
+}
+
+/// Link counts per external host, sorted by the number of links
+#[derive(Debug)]
+pub(crate) struct HostReport {
+    hosts: Vec<(String, HostCounts)>,
+}
+
+impl HostReport {
+    /// Collect the hosts of all successful, failed and warning links.
+    ///
+    /// Requires `stats` to be extended, as successful links are only
+    /// recorded in that case.
+    pub(crate) fn new(stats: &ResponseStats) -> Self {
+        let mut hosts: HashMap<String, HostCounts> = HashMap::new();
+        let links = stats
+            .success_map
+            .values()
+            .chain(stats.warning_map.values())
+            .flatten()
+            .map(|body| (&body.uri, false))
+            .chain(
+                stats
+                    .fail_map
+                    .values()
+                    .flatten()
+                    .map(|body| (&body.uri, true)),
+            );
+        for (uri, broken) in links {
+            if let Some(host) = external_host(uri) {
+                let counts = hosts.entry(host).or_default();
+                counts.links += 1;
+                counts.broken += usize::from(broken);
+            }
+        }
+
+        let mut hosts: Vec<_> = hosts.into_iter().collect();
+        hosts.sort_by(|(a_host, a), (b_host, b)| {
+            b.links.cmp(&a.links).then_with(|| a_host.cmp(b_host))
+        });
+        Self { hosts }
+    }
+
+    /// Write the report to stderr
+    pub(crate) fn report(&self) -> Result<()> {
+        if self.hosts.is_empty() {
+            return Ok(());
+        }
+
+        let mut handle = io::stderr();
+        writeln!(handle, "\nLinks by host ({}):", self.hosts.len())?;
+        for (host, counts) in &self.hosts {
+            writeln!(
+                handle,
+                "  {host}: {} links, {} broken",
+                counts.links, counts.broken
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The host of a website link, including a non-default port
+pub(crate) fn external_host(uri: &Uri) -> Option<String> {
+    if !matches!(uri.scheme(), "http" | "https") {
+        return None;
+    }
+    let url = Url::parse(uri.as_str()).ok()?;
+    let host = url.host_str()?;
+    Some(match url.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+    use lychee_lib::{ErrorKind, InputSource, Response, ResponseBody, Status};
+
+    use super::*;
+
+    fn response(url: &str, status: Status) -> Response {
+        let uri = Uri::try_from(url).unwrap();
+        Response(InputSource::Stdin, ResponseBody::new(uri, status))
+    }
+
+    #[test]
+    fn test_host_report() {
+        let mut stats = ResponseStats::extended();
+        for url in [
+            "https://a.example.com/1",
+            "https://b.example.com/1",
+            "https://b.example.com/2",
+            "http://b.example.com:8080/",
+            "mailto:user@b.example.com",
+        ] {
+            stats.add(response(url, Status::Ok(StatusCode::OK)));
+        }
+        stats.add(response(
+            "https://b.example.com/broken",
+            Status::Error(ErrorKind::InvalidStatusCode(1000)),
+        ));
+
+        let report = HostReport::new(&stats);
+        assert_eq!(
+            report.hosts,
+            [
+                (
+                    "b.example.com".to_string(),
+                    HostCounts {
+                        links: 3,
+                        broken: 1
+                    }
+                ),
+                (
+                    "a.example.com".to_string(),
+                    HostCounts {
+                        links: 1,
+                        broken: 0
+                    }
+                ),
+                (
+                    "b.example.com:8080".to_string(),
+                    HostCounts {
+                        links: 1,
+                        broken: 0
+                    }
+                ),
+            ]
+        );
+    }
+}
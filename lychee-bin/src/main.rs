@@ -58,6 +58,7 @@
 #![deny(anonymous_parameters, macro_use_extern_crate, pointer_structural_match)]
 #![deny(missing_docs)]
 
+use std::collections::HashSet;
 use std::fs::{self, File};
 use std::io::{self, BufRead, BufReader, ErrorKind, Write};
 use std::path::PathBuf;
@@ -68,7 +69,9 @@ use clap::Parser;
 use color::YELLOW;
 use commands::CommandParams;
 use formatters::response::ResponseFormatter;
+use futures::{future, Stream, StreamExt};
 use log::{error, info, warn};
+use regex::RegexSet;
 
 #[cfg(feature = "native-tls")]
 use openssl_sys as _; // required for vendored-openssl feature
@@ -79,35 +82,48 @@ use ring as _; // required for apple silicon
 use lychee_lib::BasicAuthExtractor;
 use lychee_lib::Collector;
 use lychee_lib::CookieJar;
+use lychee_lib::{Input, InputSource, Request};
 
+mod anchors;
 mod archive;
 mod cache;
+mod check_run;
 mod client;
 mod color;
 mod commands;
+mod consistency;
+mod duplicates;
 mod formatters;
+mod hosts;
 mod options;
 mod parse;
+mod process_ext;
+mod retry;
 mod stats;
+mod stream;
 mod time;
+mod transform;
 mod verbosity;
 
 use crate::formatters::duration::Duration;
+use crate::formatters::stats::ExitSummary;
 use crate::{
+    anchors::AnchorReport,
     cache::{Cache, StoreExt},
     color::color,
-    formatters::stats::StatsFormatter,
-    options::{Config, Format, LycheeOptions, LYCHEE_CACHE_FILE, LYCHEE_IGNORE_FILE},
+    duplicates::DuplicateReport,
+    hosts::HostReport,
+    options::{Config, Format, InputFormat, LycheeOptions, LYCHEE_CACHE_FILE, LYCHEE_IGNORE_FILE},
 };
 
 /// A C-like enum that can be cast to `i32` and used as process exit code.
+#[derive(Clone, Copy)]
 enum ExitCode {
     Success = 0,
     // NOTE: exit code 1 is used for any `Result::Err` bubbled up to `main()`
     // using the `?` operator. For now, 1 acts as a catch-all for everything
     // non-link related (including config errors), until we find a way to
     // structure the error code handling better.
-    #[allow(unused)]
     UnexpectedFailure = 1,
     LinkCheckFailure = 2,
     ConfigFile = 3,
@@ -295,19 +311,220 @@ fn underlying_io_error_kind(error: &Error) -> Option<io::ErrorKind> {
     None
 }
 
+/// Write the formatted stats to the output file or to stdout
+fn write_stats(formatted: &str, is_empty: bool, cfg: &Config) -> Result<()> {
+    if let Some(output) = &cfg.output {
+        if cfg.output_append {
+            let mut file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(output)?;
+            // Keep the file valid JSON lines by writing the report as a
+            // single line after the results
+            if matches!(cfg.format, Format::Json) {
+                let report: serde_json::Value = serde_json::from_str(formatted)?;
+                writeln!(file, "{report}")
+            } else {
+                writeln!(file, "{formatted}")
+            }
+        } else {
+            fs::write(output, formatted)
+        }
+        .context("Cannot write status output to file")?;
+    } else {
+        if cfg.verbose.log_level() >= log::Level::Info && !is_empty {
+            // separate summary from the verbose list of links above
+            // with a newline
+            writeln!(io::stdout())?;
+        }
+        // we assume that the formatted stats don't have a final newline
+        writeln!(io::stdout(), "{formatted}")?;
+    }
+    Ok(())
+}
+
+/// Write the optional, informational reports which follow the stats
+async fn write_reports(
+    anchor_report: Option<AnchorReport>,
+    duplicate_report: Option<DuplicateReport>,
+    host_report: Option<HostReport>,
+    skip_missing: bool,
+) -> Result<()> {
+    if let Some(anchor_report) = anchor_report {
+        anchor_report.report(skip_missing).await?;
+    }
+    if let Some(duplicate_report) = duplicate_report {
+        duplicate_report.report()?;
+    }
+    if let Some(host_report) = host_report {
+        host_report.report()?;
+    }
+    Ok(())
+}
+
+/// Create the collector which extracts the links from the inputs
+fn create_collector(cfg: &Config) -> Result<Collector> {
+    let csv_url_column = match (cfg.input_format, &cfg.url_column) {
+        (Some(InputFormat::Csv), Some(column)) => Some(column.clone()),
+        (Some(InputFormat::Csv), None) => {
+            bail!(
+                "`--input-format csv` requires the column of the URLs; set it with `--url-column`"
+            )
+        }
+        (None, Some(_)) => bail!("`--url-column` requires `--input-format csv`"),
+        (None, None) => None,
+    };
+    Ok(Collector::new(cfg.base.clone())
+        .stdin_base(cfg.stdin_base.clone())
+        .changed_since(cfg.changed_since)
+        .skip_undated(cfg.skip_undated)
+        .skip_missing_inputs(cfg.skip_missing)
+        .extract_timeout(Some(std::time::Duration::from_secs(
+            cfg.extract_timeout.unwrap_or(cfg.timeout) as u64,
+        )))
+        .max_body_size(cfg.max_body_size)
+        .accept_encoding(cfg.accept_encoding.clone())
+        .robots_sitemaps(cfg.robots_sitemaps)
+        .include_verbatim(cfg.include_verbatim)
+        .include_embeds(cfg.include_embeds)
+        .code_annotations(cfg.include_code_annotation.clone())
+        .include_identifiers(cfg.include_identifiers)
+        .warn_unused_definitions(cfg.warn_unused_definitions)
+        .warn_duplicate_ids(cfg.warn_duplicate_ids)
+        .keep_duplicates(cfg.report_duplicates)
+        .csv_url_column(csv_url_column)
+        .strip_query_params(&cfg.strip_query_params)
+        .context("Invalid query parameter pattern in `--strip-query-params`")?
+        .canonicalize_mail(cfg.canonicalize_mail)
+        // File a bug if you rely on this envvar! It's going to go away eventually.
+        .use_html5ever(std::env::var("LYCHEE_USE_HTML5EVER").map_or(false, |x| x == "1")))
+}
+
+/// The links to check: either those extracted from the inputs, with files
+/// converted by `--process-ext` first, or the failed links of a previous
+/// report, passed through the transform command if any
+fn collect_links(
+    collector: Collector,
+    inputs: Vec<Input>,
+    cfg: &Config,
+) -> Result<impl Stream<Item = lychee_lib::Result<Request>>> {
+    let links = if let Some(report) = &cfg.retry_from {
+        futures::stream::iter(retry::failed_requests(report)?.into_iter().map(Ok)).left_stream()
+    } else {
+        let (inputs, converted) = process_ext::convert_inputs(inputs, &cfg.process_ext)?;
+        let converted = futures::stream::iter(converted.into_iter().map(Ok));
+        collector
+            .clone()
+            .collect_links(inputs)
+            .chain(collector.collect_links_from_contents(converted))
+            .right_stream()
+    };
+    let links = exclude_path_regex(links, &cfg.exclude_path_regex)?;
+    let links = match &cfg.transform_command {
+        Some(command) => {
+            transform::transform_requests(links, command.clone(), cfg.max_concurrency).left_stream()
+        }
+        None => links.right_stream(),
+    };
+    Ok(limit_urls(links, cfg.max_urls))
+}
+
+/// Drop requests for links found in files whose path matches one of the
+/// `--exclude-path-regex` patterns
+fn exclude_path_regex<S>(
+    requests: S,
+    patterns: &[String],
+) -> Result<impl Stream<Item = lychee_lib::Result<Request>>>
+where
+    S: Stream<Item = lychee_lib::Result<Request>>,
+{
+    let patterns = if patterns.is_empty() {
+        None
+    } else {
+        Some(RegexSet::new(patterns).context("Invalid `--exclude-path-regex` pattern")?)
+    };
+    Ok(requests.filter(move |request| {
+        let excluded = match (&patterns, request) {
+            (Some(patterns), Ok(request)) => match &request.source {
+                InputSource::FsPath(path) => patterns.is_match(&path.to_string_lossy()),
+                _ => false,
+            },
+            _ => false,
+        };
+        future::ready(!excluded)
+    }))
+}
+
+/// Stop reading `requests` once `max_urls` unique URLs have been passed on.
+/// Requests for URLs which were already passed on don't count towards the
+/// limit.
+fn limit_urls<S>(
+    requests: S,
+    max_urls: Option<usize>,
+) -> impl Stream<Item = lychee_lib::Result<Request>>
+where
+    S: Stream<Item = lychee_lib::Result<Request>>,
+{
+    let mut seen = HashSet::new();
+    requests.take_while(move |request| {
+        let keep = match (max_urls, request) {
+            (Some(max_urls), Ok(request)) if !seen.contains(&request.uri) => {
+                if seen.len() < max_urls {
+                    seen.insert(request.uri.clone());
+                    true
+                } else {
+                    warn!(
+                        "Reached the limit of {max_urls} unique URLs, skipping all remaining links"
+                    );
+                    false
+                }
+            }
+            _ => true,
+        };
+        future::ready(keep)
+    })
+}
+
 /// Run lychee on the given inputs
 async fn run(opts: &LycheeOptions) -> Result<i32> {
+    if opts.config.output_append && opts.config.output.is_none() {
+        bail!("`--output-append` requires an output file; set it with `--output`");
+    }
+    if opts.config.json_errors && !matches!(opts.config.format, Format::Json) {
+        bail!("`--json-errors` requires the JSON format; set it with `--format json`");
+    }
+
+    if let Some(url) = &opts.config.explain {
+        return Ok(commands::explain(url, &opts.config)? as i32);
+    }
+    if opts.config.doctor {
+        return Ok(commands::doctor(&opts.config).await? as i32);
+    }
+    if let Some(path) = &opts.config.emit_json_schema {
+        return Ok(commands::emit_json_schema(path)? as i32);
+    }
+    if !opts.config.merge_reports.is_empty() {
+        let reports = &opts.config.merge_reports;
+        return Ok(commands::merge_reports(reports, opts.config.output.as_ref())? as i32);
+    }
+
     let inputs = opts.inputs()?;
+    if opts.config.syntax_only {
+        return Ok(commands::syntax_only(inputs, &opts.config).await? as i32);
+    }
+    if let Some(limit) = opts.config.preview {
+        return Ok(commands::preview(inputs, limit, &opts.config).await? as i32);
+    }
 
-    let mut collector = Collector::new(opts.config.base.clone())
-        .skip_missing_inputs(opts.config.skip_missing)
-        .include_verbatim(opts.config.include_verbatim)
-        // File a bug if you rely on this envvar! It's going to go away eventually.
-        .use_html5ever(std::env::var("LYCHEE_USE_HTML5EVER").map_or(false, |x| x == "1"));
+    let mut collector = create_collector(&opts.config)?;
 
     if opts.config.dump_inputs {
-        let sources = collector.collect_sources(inputs);
-        let exit_code = commands::dump_inputs(sources, opts.config.output.as_ref()).await?;
+        let exit_code = if opts.config.with_extractor {
+            commands::dump_inputs_with_extractor(inputs, &opts.config).await?
+        } else {
+            let sources = collector.collect_sources(inputs);
+            commands::dump_inputs(sources, opts.config.output.as_ref()).await?
+        };
 
         return Ok(exit_code as i32);
     }
@@ -318,7 +535,17 @@ async fn run(opts: &LycheeOptions) -> Result<i32> {
         collector
     };
 
-    let requests = collector.collect_links(inputs);
+    // Unreferenced anchors are reported after checking, which requires
+    // reading the inputs a second time. Duplicate links are recorded while
+    // the requests are passed on for checking.
+    let anchor_report = opts
+        .config
+        .report_unreferenced_anchors
+        .then(|| AnchorReport::new(inputs.clone()));
+    let duplicate_report = opts.config.report_duplicates.then(DuplicateReport::default);
+    let links = collect_links(collector, inputs, &opts.config)?;
+    let requests = anchors::record_references(links, anchor_report.as_ref());
+    let requests = duplicates::record_occurrences(requests, duplicate_report.as_ref());
 
     let cache = load_cache(&opts.config).unwrap_or_default();
     let cache = Arc::new(cache);
@@ -348,55 +575,78 @@ async fn run(opts: &LycheeOptions) -> Result<i32> {
 
     let exit_code = if opts.config.dump {
         commands::dump(params).await?
+    } else if opts.config.count_only {
+        commands::count(params).await?
     } else {
-        let (stats, cache, exit_code) = commands::check(params).await?;
-
-        let github_issues = stats
-            .fail_map
-            .values()
-            .flatten()
-            .any(|body| body.uri.domain() == Some("github.com"));
-
-        let writer: Box<dyn StatsFormatter> = match opts.config.format {
-            Format::Compact => Box::new(formatters::stats::Compact::new()),
-            Format::Detailed => Box::new(formatters::stats::Detailed::new()),
-            Format::Json => Box::new(formatters::stats::Json::new()),
-            Format::Markdown => Box::new(formatters::stats::Markdown::new()),
-            Format::Raw => Box::new(formatters::stats::Raw::new()),
-        };
-        let is_empty = stats.is_empty();
-        let formatted = writer.format_stats(stats)?;
+        check_and_report(
+            params,
+            anchor_report,
+            duplicate_report,
+            cookie_jar,
+            &opts.config,
+        )
+        .await?
+    };
 
-        if let Some(formatted) = formatted {
-            if let Some(output) = &opts.config.output {
-                fs::write(output, formatted).context("Cannot write status output to file")?;
-            } else {
-                if opts.config.verbose.log_level() >= log::Level::Info && !is_empty {
-                    // separate summary from the verbose list of links above
-                    // with a newline
-                    writeln!(io::stdout())?;
-                }
-                // we assume that the formatted stats don't have a final newline
-                writeln!(io::stdout(), "{formatted}")?;
-            }
-        }
+    Ok(exit_code as i32)
+}
 
-        if github_issues && opts.config.github_token.is_none() {
-            let mut handle = io::stderr();
-            color!(handle, YELLOW, "\u{1f4a1} There were issues with GitHub URLs. You could try setting a GitHub token and running lychee again.",)?;
-        }
+/// Check the links, print the stats and write all reports, the cache and the
+/// cookie jar
+async fn check_and_report<S>(
+    params: CommandParams<S>,
+    anchor_report: Option<AnchorReport>,
+    duplicate_report: Option<DuplicateReport>,
+    cookie_jar: Option<CookieJar>,
+    cfg: &Config,
+) -> Result<ExitCode>
+where
+    S: Stream<Item = lychee_lib::Result<Request>>,
+{
+    let (stats, cache, exit_code) = Box::pin(commands::check(params)).await?;
+
+    let github_issues = stats
+        .fail_map
+        .values()
+        .flatten()
+        .any(|body| body.uri.domain() == Some("github.com"));
+
+    let host_report = cfg.host_report.then(|| HostReport::new(&stats));
+    if cfg.github_check_run {
+        let success = matches!(exit_code, ExitCode::Success);
+        check_run::report(&stats, success, cfg.github_token.as_ref()).await;
+    }
+    let writer =
+        formatters::get_stats_formatter(&cfg.format, cfg.collapse_by_host, cfg.status_breakdown);
+    let exit_summary = cfg
+        .json_errors
+        .then(|| ExitSummary::new(&stats, exit_code as i32));
+    let is_empty = stats.is_empty();
+    let formatted = writer.format_stats(stats)?;
+
+    if let Some(formatted) = formatted {
+        write_stats(&formatted, is_empty, cfg)?;
+    }
+    if let Some(exit_summary) = exit_summary {
+        writeln!(io::stderr(), "{}", exit_summary.format()?)?;
+    }
 
-        if opts.config.cache {
-            cache.store(LYCHEE_CACHE_FILE)?;
-        }
+    let skip_missing = cfg.skip_missing;
+    write_reports(anchor_report, duplicate_report, host_report, skip_missing).await?;
 
-        if let Some(cookie_jar) = cookie_jar.as_ref() {
-            info!("Saving cookie jar");
-            cookie_jar.save().context("Cannot save cookie jar")?;
-        }
+    if github_issues && cfg.github_token.is_none() {
+        let mut handle = io::stderr();
+        color!(handle, YELLOW, "\u{1f4a1} There were issues with GitHub URLs. You could try setting a GitHub token and running lychee again.",)?;
+    }
 
-        exit_code
-    };
+    if cfg.cache {
+        cache.store(LYCHEE_CACHE_FILE, cfg.max_cache_entries)?;
+    }
 
-    Ok(exit_code as i32)
+    if let Some(cookie_jar) = cookie_jar.as_ref() {
+        info!("Saving cookie jar");
+        cookie_jar.save().context("Cannot save cookie jar")?;
+    }
+
+    Ok(exit_code)
 }
@@ -5,8 +5,9 @@ use anyhow::{anyhow, Context, Error, Result};
 use clap::{arg, builder::TypedValueParser, Parser};
 use const_format::{concatcp, formatcp};
 use lychee_lib::{
-    AcceptSelector, Base, BasicAuthSelector, Input, DEFAULT_MAX_REDIRECTS, DEFAULT_MAX_RETRIES,
-    DEFAULT_RETRY_WAIT_TIME_SECS, DEFAULT_TIMEOUT_SECS, DEFAULT_USER_AGENT,
+    AcceptPatternSelector, AcceptSchemeSelector, AcceptSelector, Base, BasicAuthSelector, Date,
+    Input, ProcessExt, RequestMethods, UrlColumn, DEFAULT_FS_CONCURRENCY, DEFAULT_MAX_REDIRECTS,
+    DEFAULT_MAX_RETRIES, DEFAULT_RETRY_WAIT_TIME_SECS, DEFAULT_TIMEOUT_SECS, DEFAULT_USER_AGENT,
 };
 use secrecy::{ExposeSecret, SecretString};
 use serde::Deserialize;
@@ -26,6 +27,7 @@ const DEFAULT_MAX_CONCURRENCY: usize = 128;
 // whereas serde expects owned `String` types
 // (we can't use e.g. `TIMEOUT` or `timeout()` which gets created for serde)
 const MAX_CONCURRENCY_STR: &str = concatcp!(DEFAULT_MAX_CONCURRENCY);
+const FS_CONCURRENCY_STR: &str = concatcp!(DEFAULT_FS_CONCURRENCY);
 const MAX_CACHE_AGE_STR: &str = concatcp!(DEFAULT_MAX_CACHE_AGE);
 const MAX_REDIRECTS_STR: &str = concatcp!(DEFAULT_MAX_REDIRECTS);
 const MAX_RETRIES_STR: &str = concatcp!(DEFAULT_MAX_RETRIES);
@@ -52,6 +54,8 @@ pub(crate) enum Format {
     Json,
     Markdown,
     Raw,
+    Sarif,
+    Toml,
 }
 
 impl FromStr for Format {
@@ -63,11 +67,30 @@ impl FromStr for Format {
             "json" => Ok(Format::Json),
             "markdown" | "md" => Ok(Format::Markdown),
             "raw" => Ok(Format::Raw),
+            "sarif" => Ok(Format::Sarif),
+            "toml" => Ok(Format::Toml),
             _ => Err(anyhow!("Unknown format {}", format)),
         }
     }
 }
 
+/// Format of the inputs, overriding the detection by file extension
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum InputFormat {
+    Csv,
+}
+
+impl FromStr for InputFormat {
+    type Err = Error;
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format.to_lowercase().as_str() {
+            "csv" => Ok(InputFormat::Csv),
+            _ => Err(anyhow!("Unknown input format {}", format)),
+        }
+    }
+}
+
 // Macro for generating default functions to be used by serde
 macro_rules! default_function {
     ( $( $name:ident : $T:ty = $e:expr; )* ) => {
@@ -85,6 +108,7 @@ default_function! {
     max_redirects: usize = DEFAULT_MAX_REDIRECTS;
     max_retries: u64 = DEFAULT_MAX_RETRIES;
     max_concurrency: usize = DEFAULT_MAX_CONCURRENCY;
+    fs_concurrency: usize = DEFAULT_FS_CONCURRENCY;
     max_cache_age: Duration = humantime::parse_duration(DEFAULT_MAX_CACHE_AGE).unwrap();
     user_agent: String = DEFAULT_USER_AGENT.to_string();
     timeout: usize = DEFAULT_TIMEOUT_SECS;
@@ -99,7 +123,7 @@ macro_rules! fold_in {
     ( $cli:ident , $toml:ident ; $( $key:ident : $default:expr; )* ) => {
         $(
             if $cli.$key == $default && $toml.$key != $default {
-                $cli.$key = $toml.$key;
+                $cli.$key = $toml.$key.clone();
             }
         )*
     };
@@ -116,7 +140,7 @@ pub(crate) struct LycheeOptions {
     /// These can be: files (e.g. `README.md`), file globs (e.g. `"~/git/*/README.md"`),
     /// remote URLs (e.g. `https://example.com/README.md`) or standard input (`-`).
     /// NOTE: Use `--` to separate inputs from options that allow multiple arguments.
-    #[arg(name = "inputs", required = true)]
+    #[arg(name = "inputs", required_unless_present_any = ["explain", "merge_reports", "retry_from", "emit_json_schema", "doctor"])]
     raw_inputs: Vec<String>,
 
     /// Configuration file to use
@@ -141,7 +165,14 @@ impl LycheeOptions {
         };
         self.raw_inputs
             .iter()
-            .map(|s| Input::new(s, None, self.config.glob_ignore_case, excluded.clone()))
+            .map(|s| {
+                Input::new(s, None, self.config.glob_ignore_case, excluded.clone()).map(|input| {
+                    Input {
+                        follow_symlinks: self.config.follow_symlinks,
+                        ..input
+                    }
+                })
+            })
             .collect::<Result<_, _>>()
             .context("Cannot parse inputs from arguments")
     }
@@ -176,18 +207,109 @@ pub(crate) struct Config {
     #[serde(with = "humantime_serde")]
     pub(crate) max_cache_age: Duration,
 
+    /// Maximum number of entries to keep in the cache file.
+    /// When exceeded, the least recently used entries are evicted. Unlimited by default
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) max_cache_entries: Option<usize>,
+
+    /// Don't cache the results of links found in input files matching this
+    /// glob pattern, e.g. generated files whose links change between runs.
+    /// Can be given multiple times
+    #[arg(long, value_name = "GLOB", verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) cache_exclude_path: Vec<String>,
+
     /// Don't perform any link checking.
     /// Instead, dump all the links extracted from inputs that would be checked
     #[arg(long)]
     #[serde(default)]
     pub(crate) dump: bool,
 
+    /// Sort the links printed by `--dump` and remove duplicates, so that the
+    /// output can be committed and diffed
+    #[arg(long, requires = "dump", verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) sort: bool,
+
     /// Don't perform any link extraction and checking.
     /// Instead, dump all input sources from which links would be collected
     #[arg(long)]
     #[serde(default)]
     pub(crate) dump_inputs: bool,
 
+    /// With `--dump-inputs`, follow each input source by a tab and the
+    /// extractor used for its links: markdown, html, plaintext, pdf,
+    /// custom (converted by `--process-ext`), directory or none
+    #[arg(long, requires = "dump_inputs", verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) with_extractor: bool,
+
+    /// Don't perform any link checking.
+    /// Instead, print how many requests would be made after deduplication,
+    /// filtering and cache lookups, to estimate the cost of a run
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) count_only: bool,
+
+    /// Don't perform any link checking.
+    /// Instead, only validate that the extracted links are well-formed URLs
+    /// and report malformed ones as errors. No network requests are made for
+    /// the links
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) syntax_only: bool,
+
+    /// Don't perform any link checking.
+    /// Instead, print the first N extracted links (10 by default) with their
+    /// position and the line they appear in, e.g. `--preview=20`
+    #[arg(
+        long,
+        value_name = "N",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "10",
+        verbatim_doc_comment
+    )]
+    #[serde(default)]
+    pub(crate) preview: Option<usize>,
+
+    /// Don't perform any link extraction and checking.
+    /// Instead, explain how the given URL would be handled: print the verdict of
+    /// the cache, remapping and filtering stages, and whether it would be checked
+    #[arg(long, value_name = "URL", verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) explain: Option<String>,
+
+    /// Don't perform any link extraction and checking.
+    /// Instead, write the JSON Schema of the report written with
+    /// `--format json` to the given file
+    #[arg(long, value_name = "PATH", verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) emit_json_schema: Option<PathBuf>,
+
+    /// Don't perform any link extraction and checking.
+    /// Instead, merge the given JSON reports of previous runs into a single
+    /// report, e.g. to combine the results of sharded CI jobs
+    #[arg(long, value_name = "FILE", num_args = 1.., verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) merge_reports: Vec<PathBuf>,
+
+    /// Don't perform any link extraction and checking.
+    /// Instead, check the environment for common problems: DNS resolution,
+    /// the proxy from the environment, the commands of `--process-ext`, and
+    /// whether the cache file is writable
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) doctor: bool,
+
+    /// Don't extract links from inputs.
+    /// Instead, only check the links which failed in the given JSON report of
+    /// a previous run, e.g. to find out whether they got fixed
+    #[arg(long, value_name = "REPORT", verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) retry_from: Option<PathBuf>,
+
     /// Specify the use of a specific web archive.
     /// Can be used in combination with `--suggest`
     #[arg(long, value_parser = clap::builder::PossibleValuesParser::new(Archive::VARIANTS).map(|s| s.parse::<Archive>().unwrap()))]
@@ -205,8 +327,53 @@ pub(crate) struct Config {
     #[serde(default = "max_redirects")]
     pub(crate) max_redirects: usize,
 
-    /// Maximum number of retries per request
-    #[arg(long, default_value = &MAX_RETRIES_STR)]
+    /// Report working links which needed more than the given number of
+    /// redirects to resolve as warnings, showing the final URL
+    #[arg(long, value_name = "N", verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) warn_redirects_over: Option<usize>,
+
+    /// Fail working links whose redirects step down from HTTPS to HTTP
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) fail_on_https_downgrade: bool,
+
+    /// Fail links to images, like `<img src>` or Markdown images, whose
+    /// response doesn't have an `image/*` content type, e.g. an HTML error
+    /// page served with status 200
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) verify_image_content_type: bool,
+
+    /// Store the status and the headers needed to check links (like
+    /// `Location` or `Retry-After`, but not cookies) of all responses in
+    /// this directory, one file per request method and URL, to reproduce
+    /// the run offline with --replay
+    #[arg(long, value_name = "DIR", verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) record: Option<PathBuf>,
+
+    /// Answer requests with the responses stored by --record in this
+    /// directory instead of sending them. Links without a recorded response
+    /// fail
+    #[arg(
+        long,
+        value_name = "DIR",
+        conflicts_with = "record",
+        verbatim_doc_comment
+    )]
+    #[serde(default)]
+    pub(crate) replay: Option<PathBuf>,
+
+    /// Follow `<meta http-equiv="refresh">` redirects in HTML pages
+    /// and report the status of the refresh target instead
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) follow_meta_refresh: bool,
+
+    /// Maximum number of retries per request.
+    /// Requests are retried on timeouts and retryable statuses like 429 and 5xx
+    #[arg(long, default_value = &MAX_RETRIES_STR, verbatim_doc_comment)]
     #[serde(default = "max_retries")]
     pub(crate) max_retries: u64,
 
@@ -215,17 +382,44 @@ pub(crate) struct Config {
     #[serde(default = "max_concurrency")]
     pub(crate) max_concurrency: usize,
 
+    /// Maximum number of concurrent network requests to the same host.
+    /// Links waiting for their host don't take up any of the
+    /// `--max-concurrency` slots, so other hosts are still checked
+    #[arg(long, value_name = "N", verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) max_concurrency_per_host: Option<usize>,
+
+    /// Maximum number of concurrent filesystem checks of local links.
+    /// These run on separate threads and don't block network requests
+    #[arg(long, default_value = &FS_CONCURRENCY_STR, verbatim_doc_comment)]
+    #[serde(default = "fs_concurrency")]
+    pub(crate) fs_concurrency: usize,
+
     /// Number of threads to utilize.
     /// Defaults to number of cores available to the system
     #[arg(short = 'T', long)]
     #[serde(default)]
     pub(crate) threads: Option<usize>,
 
+    /// Stop extracting links once N unique URLs have been collected.
+    /// Guards against checking far more links than expected, e.g. from huge
+    /// inputs. A warning is printed if the limit is reached
+    #[arg(long, value_name = "N", verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) max_urls: Option<usize>,
+
     /// User agent
     #[arg(short, long, default_value = DEFAULT_USER_AGENT)]
     #[serde(default = "user_agent")]
     pub(crate) user_agent: String,
 
+    /// File with one user agent per line to cycle through, one per request,
+    /// instead of using a single user agent. Empty lines and lines starting
+    /// with `#` are ignored
+    #[arg(long, value_name = "FILE", verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) rotate_user_agents: Option<PathBuf>,
+
     /// Proceed for server connections considered insecure (invalid TLS)
     #[arg(short, long)]
     #[serde(default)]
@@ -243,13 +437,29 @@ pub(crate) struct Config {
     #[serde(default)]
     pub(crate) offline: bool,
 
+    /// Only check relative links to local files and fragments.
+    /// All absolute http(s) URLs are excluded, which is useful for offline docs validation
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) check_relative_only: bool,
+
     /// URLs to check (supports regex). Has preference over all excludes.
-    #[arg(long)]
+    /// Without `--exclude`, URLs which match no include pattern are excluded
+    #[arg(long, verbatim_doc_comment)]
     #[serde(default)]
     pub(crate) include: Vec<String>,
 
-    /// Exclude URLs and mail addresses from checking (supports regex)
-    #[arg(long)]
+    /// Treat `--include` as an allowlist: URLs which match no include
+    /// pattern are excluded even with `--exclude`, and `--exclude` takes
+    /// preference over `--include`
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) strict_include: bool,
+
+    /// Exclude URLs and mail addresses from checking (supports regex).
+    /// Patterns are case-sensitive unless prefixed with `(?i)` and match
+    /// anywhere in the URL unless anchored with `^` or `$`
+    #[arg(long, verbatim_doc_comment)]
     #[serde(default)]
     pub(crate) exclude: Vec<String>,
 
@@ -263,6 +473,19 @@ pub(crate) struct Config {
     #[serde(default)]
     pub(crate) exclude_path: Vec<PathBuf>,
 
+    /// Exclude links found in files whose path matches this regex.
+    /// Patterns are matched like `--exclude`, against paths in the form they
+    /// were found in, e.g. `./docs/index.md` for the input `.`
+    #[arg(long, value_name = "REGEX", verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) exclude_path_regex: Vec<String>,
+
+    /// Follow symlinks when traversing directories.
+    /// Symlinks which lead back into a parent directory are skipped
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) follow_symlinks: bool,
+
     /// Exclude all private IPs from checking.
     /// Equivalent to `--exclude-private --exclude-link-local --exclude-loopback`
     #[arg(short = 'E', long, verbatim_doc_comment)]
@@ -295,11 +518,72 @@ pub(crate) struct Config {
     #[serde(default)]
     pub(crate) include_mail: bool,
 
+    /// Check email addresses only by looking up the MX records of their domain,
+    /// instead of connecting to the mail server. Faster, but doesn't detect
+    /// missing mailboxes. Implies `--include-mail`. Requires lychee to be built
+    /// with the `verify-mail` feature
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) verify_mail: bool,
+
+    /// Also check `ftp://` links by connecting to the server and looking up the
+    /// file or directory. Requires lychee to be built with the `ftp` feature
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) include_ftp: bool,
+
+    /// Also check `gemini://` links by requesting the resource and reading
+    /// the status of the response. Requires lychee to be built with the
+    /// `gemini` feature
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) include_gemini: bool,
+
+    /// Also check `ws://` and `wss://` links by performing the WebSocket
+    /// handshake. The link is OK if the server switches protocols.
+    /// Requires lychee to be built with the `websocket` feature
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) include_websocket: bool,
+
     /// Remap URI matching pattern to different URI
     #[serde(default)]
     #[arg(long)]
     pub(crate) remap: Vec<String>,
 
+    /// File with remapping rules like --remap, one `<pattern> <uri>` per line.
+    /// Empty lines and lines starting with `#` are ignored. The rules apply in
+    /// order, after those given with --remap
+    #[arg(long, value_name = "PATH", verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) remap_file: Option<PathBuf>,
+
+    /// Shell command to rewrite links with before checking them, e.g. to sign
+    /// URLs. The command receives the links on stdin, one per line, and has
+    /// to print one line per link: the transformed link, or an empty line to
+    /// drop it. Links are passed in batches, so the command may run more than
+    /// once. If the command fails or prints an invalid URL, the affected links
+    /// are reported as errors. Applied before --remap
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) transform_command: Option<String>,
+
+    /// Remove query parameters whose name matches one of these glob patterns
+    /// from all links before checking them, e.g. `utm_*,fbclid`.
+    /// Links which only differ in these parameters are checked once.
+    /// Links are reported without the removed parameters
+    #[serde(default)]
+    #[arg(long, value_delimiter = ',', verbatim_doc_comment)]
+    pub(crate) strip_query_params: Vec<String>,
+
+    /// Reduce `mailto:` links to the canonical form of their address before
+    /// checking them: query parameters and the tag of plus-addressing
+    /// (`user+tag@`) are removed and the address is lowercased.
+    /// Spellings of the same mailbox are checked once
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) canonicalize_mail: bool,
+
     /// Automatically append file extensions to `file://` URIs as needed
     #[serde(default)]
     #[arg(
@@ -313,11 +597,43 @@ Example: --fallback-extensions html,htm,php,asp,aspx,jsp,cgi"
     )]
     pub(crate) fallback_extensions: Vec<String>,
 
+    /// Treat warnings as errors and exit with a non-zero status code
+    /// if any link produced a warning
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) fail_on_warnings: bool,
+
+    /// Don't fail the run because of links which failed with one of these
+    /// status codes, e.g. transient `429` errors. In contrast to `--accept`,
+    /// such links are still reported as errors.
+    /// Supports the same range syntax as `--accept`
+    #[arg(long, value_name = "CODES", verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) ignore_error_status: Option<AcceptSelector>,
+
     /// Custom request header
     #[arg(long)]
     #[serde(default)]
     pub(crate) header: Vec<String>,
 
+    /// Read bearer tokens of hosts from a `.env`-style file.
+    /// The token of a host is read from the variable `HOST_TOKEN_` followed by
+    /// the host, with every `.` replaced by `_` and every `-` by `__`, e.g.
+    /// `HOST_TOKEN_api_example_com=abc123` for `api.example.com`.
+    /// Tokens are never sent over HTTP, so hosts checked over HTTP, e.g. local
+    /// development servers, silently get no token. Other variables are ignored
+    #[arg(long, value_name = "PATH", verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) auth_env_file: Option<PathBuf>,
+
+    /// Content encodings to advertise in the `Accept-Encoding` header when
+    /// checking links and fetching remote inputs (e.g. "gzip, br").
+    /// Defaults to all supported encodings: gzip, br, deflate.
+    /// Compressed responses are decoded transparently
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) accept_encoding: Option<String>,
+
     /// A List of accepted status codes for valid links
     #[arg(
         short,
@@ -325,11 +641,12 @@ Example: --fallback-extensions html,htm,php,asp,aspx,jsp,cgi"
         default_value_t,
         long_help = "A List of accepted status codes for valid links
 
-The following accept range syntax is supported: [start]..[=]end|code. Some valid
-examples are:
+The following accept range syntax is supported: [start]..[=]end|start-end|code.
+Some valid examples are:
 
 - 200..=204
 - 200..204
+- 200-204
 - ..=204
 - ..204
 - 200
@@ -341,18 +658,102 @@ separated list of accepted status codes. This example will accept 200, 201,
     #[serde(default = "accept_selector")]
     pub(crate) accept: AcceptSelector,
 
+    /// Accept additional status codes only for URLs matching a regex.
+    /// E.g. `api.example.com:401,403`. Can be given multiple times;
+    /// the first matching pattern wins
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) accept_pattern: Vec<AcceptPatternSelector>,
+
+    /// Accept these status codes instead of --accept for URLs with a scheme.
+    /// E.g. `https:200,204`. Can be given multiple times
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) accept_scheme: Vec<AcceptSchemeSelector>,
+
     /// Enable the checking of fragments in links.
     #[arg(long)]
     #[serde(default)]
     pub(crate) include_fragments: bool,
 
-    /// Website timeout in seconds from connect to response finished
-    #[arg(short, long, default_value = &TIMEOUT_STR)]
+    /// List anchors of local inputs which no checked link points to.
+    /// This is informational only and doesn't affect the exit code
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) report_unreferenced_anchors: bool,
+
+    /// List links which occur more than once within or across inputs,
+    /// together with their positions. Each link is still checked only once.
+    /// This is informational only and doesn't affect the exit code
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) report_duplicates: bool,
+
+    /// After checking, list each external host with the number of links
+    /// pointing to it and how many of them are broken, sorted by link count.
+    /// This is informational only and doesn't affect the exit code
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) host_report: bool,
+
+    /// Website timeout in seconds from connect to response finished.
+    /// Applies to every retry separately, see --total-timeout.
+    /// Requests which take longer are counted as timeouts
+    #[arg(
+        short,
+        long,
+        visible_alias = "request-timeout",
+        default_value = &TIMEOUT_STR,
+        verbatim_doc_comment
+    )]
     #[serde(default = "timeout")]
     pub(crate) timeout: usize,
 
-    /// Minimum wait time in seconds between retries of failed requests
-    #[arg(short, long, default_value = &RETRY_WAIT_TIME_STR)]
+    /// Maximum time in seconds for checking a website across all retries.
+    /// Links which take longer are reported as timeouts
+    #[arg(long, value_name = "SECS", verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) total_timeout: Option<usize>,
+
+    /// Timeout in seconds for fetching remote inputs to extract links from,
+    /// from connect until the body is read. Links are still extracted from
+    /// the part of the body read until then. Defaults to --timeout
+    #[arg(long, value_name = "SECS", verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) extract_timeout: Option<usize>,
+
+    /// Maximum number of bytes read from the body of a remote input to
+    /// extract links from. The rest of the body is ignored
+    #[arg(long, value_name = "BYTES", verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) max_body_size: Option<usize>,
+
+    /// Treat remote URL inputs as site roots: read the site's /robots.txt,
+    /// fetch the sitemaps listed in its `Sitemap:` lines and check every page
+    /// in them instead of the input itself
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) robots_sitemaps: bool,
+
+    /// Convert input files with the given extension to text with an external
+    /// command before extracting links, e.g. `pdf:pdftotext {} -`.
+    /// `{}` is replaced with the path of the file and the output of the
+    /// command is read as plaintext. Only applies to files given as inputs
+    /// directly. Can be given multiple times
+    #[arg(long, value_name = "EXT:COMMAND", verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) process_ext: Vec<ProcessExt>,
+
+    /// Hard deadline in seconds for the whole run. Once reached, all in-flight
+    /// requests are cancelled immediately and the run fails
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) deadline: Option<usize>,
+
+    /// Minimum wait time in seconds between retries of failed requests.
+    /// The wait time doubles with every retry. A `Retry-After` header is
+    /// honored instead, but never waits longer than before the last retry
+    #[arg(short, long, default_value = &RETRY_WAIT_TIME_STR, verbatim_doc_comment)]
     #[serde(default = "retry_wait_time")]
     pub(crate) retry_wait_time: usize,
 
@@ -362,12 +763,46 @@ separated list of accepted status codes. This example will accept 200, 201,
     #[serde(default = "method")]
     pub(crate) method: String,
 
+    /// Request methods to try in order, e.g. `HEAD,GET`. The next method is
+    /// only tried if the server responds with 405 (Method Not Allowed) or
+    /// 501 (Not Implemented). Methods not listed in the `Allow` header of a
+    /// 405 response are skipped. Takes precedence over --method
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) request_methods: Option<RequestMethods>,
+
     /// Base URL or website root directory to check relative URLs
     /// e.g. <https://example.com> or `/path/to/public`
     #[arg(short, long, value_parser= parse_base)]
     #[serde(default)]
     pub(crate) base: Option<Base>,
 
+    /// Location of the document piped via stdin, used to resolve its relative
+    /// links, e.g. <https://example.com/docs/index.html> or `/path/to/docs`.
+    /// The extension determines how stdin gets parsed, e.g. `.html` as HTML
+    #[arg(long, value_parser = parse_base, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) stdin_base: Option<Base>,
+
+    /// Report links to local files outside of this directory as errors,
+    /// e.g. relative links like `../../etc/passwd`, instead of checking them
+    #[arg(long, value_name = "DIR", verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) root_dir: Option<PathBuf>,
+
+    /// Only check Markdown files whose frontmatter `lastmod` or `date` is on
+    /// or after this date, given as YYYY-MM-DD. Markdown files without a
+    /// frontmatter date are still checked, unless --skip-undated is set
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) changed_since: Option<Date>,
+
+    /// Skip Markdown files without a frontmatter date when using
+    /// --changed-since
+    #[arg(long, requires = "changed_since", verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) skip_undated: bool,
+
     /// Basic authentication support. E.g. `http://example.com username:password`
     #[arg(long)]
     #[serde(default)]
@@ -378,6 +813,14 @@ separated list of accepted status codes. This example will accept 200, 201,
     #[serde(default)]
     pub(crate) github_token: Option<SecretString>,
 
+    /// Create a GitHub check run for the commit in `GITHUB_SHA` of the
+    /// repository in `GITHUB_REPOSITORY`, with an annotation for each broken
+    /// link in a file. Requires a GitHub token. If the check run cannot be
+    /// created, a warning is logged
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) github_check_run: bool,
+
     /// Skip missing input files (default is to error if they don't exist)
     #[arg(long)]
     #[serde(default)]
@@ -388,6 +831,54 @@ separated list of accepted status codes. This example will accept 200, 201,
     #[serde(default)]
     pub(crate) include_verbatim: bool,
 
+    /// Find links to embedded content in `<iframe src>`, `<embed src>` and
+    /// `<object data>` of HTML inputs
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) include_embeds: bool,
+
+    /// Find links in fenced Markdown code blocks annotated with this info
+    /// string (e.g. `url`), even without `--include-verbatim`.
+    /// Can be given multiple times
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) include_code_annotation: Vec<String>,
+
+    /// Find identifiers like doi:10.1000/182, arXiv:2101.00001 or PMID:12345678
+    /// in Markdown and plaintext inputs. These and links to the DOI resolver
+    /// are checked through their canonical resolvers
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) include_identifiers: bool,
+
+    /// Warn about link reference definitions like `[label]: https://...` in
+    /// Markdown inputs which are never referenced. Their links are checked
+    /// either way
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) warn_unused_definitions: bool,
+
+    /// Warn about `id` attributes which occur more than once in an HTML
+    /// input, as links to such a fragment are ambiguous
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) warn_duplicate_ids: bool,
+
+    /// Parse all inputs in the given format instead of detecting it from the
+    /// file extension. Currently, only `csv` is supported, which requires
+    /// `--url-column`
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) input_format: Option<InputFormat>,
+
+    /// Column of CSV inputs which contains the URLs to check, given by its
+    /// number starting at 1 or by its name in the header row. Only the URLs
+    /// in this column are checked, with the row as position. Rows which
+    /// can't be parsed are skipped with a warning
+    #[arg(long, value_name = "N|NAME", verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) url_column: Option<UrlColumn>,
+
     /// Ignore case when expanding filesystem path glob inputs
     #[arg(long)]
     #[serde(default)]
@@ -398,16 +889,80 @@ separated list of accepted status codes. This example will accept 200, 201,
     #[serde(default)]
     pub(crate) output: Option<PathBuf>,
 
-    /// Output format of final status report (compact, detailed, json, markdown)
+    /// Write each result to the output file as soon as it is checked, so partial
+    /// results survive an interrupted run. The file is truncated when the run
+    /// starts and the final report is appended at the end.
+    /// Results are written as JSON lines for `--format json`, including the
+    /// final report, and as plain lines otherwise
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) output_append: bool,
+
+    /// Stream each result as a JSON line to the given Unix domain socket or named
+    /// pipe as soon as it is checked. If the target is not available, a warning
+    /// is printed and checking continues
+    #[arg(long, value_parser, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) stream_to: Option<PathBuf>,
+
+    /// List the failed links of the compact and detailed status report by
+    /// host instead of by input, with at most N example links per host
+    /// (3 by default) and the number of remaining ones, e.g.
+    /// `--collapse-by-host=5`
+    #[arg(
+        long,
+        value_name = "N",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "3",
+        verbatim_doc_comment
+    )]
+    #[serde(default)]
+    pub(crate) collapse_by_host: Option<usize>,
+
+    /// Break down the errors in the compact and detailed status report by
+    /// status code, e.g. `404: 3, 500: 1, timeout: 2`
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) status_breakdown: bool,
+
+    /// Output format of final status report (compact, detailed, json, markdown, sarif, toml)
     #[arg(short, long, default_value = "compact")]
     #[serde(default)]
     pub(crate) format: Format,
 
+    /// Also write a one-line JSON summary of the exit
+    /// code, the number of failed links and the first failed link to stderr, e.g.
+    /// `{"exit_code":2,"error_count":1,"first_error":"[404 Not Found] https://example.com/"}`.
+    /// Requires `--format json`
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) json_errors: bool,
+
     /// When HTTPS is available, treat HTTP links as errors
     #[arg(long)]
     #[serde(default)]
     pub(crate) require_https: bool,
 
+    /// Treat links to localhost, private, link-local or loopback addresses as errors.
+    /// Useful for catching development URLs in published documentation
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) fail_on_private: bool,
+
+    /// Treat links to this host as errors (can be repeated).
+    /// Unlike --exclude, which skips links, this fails them. If a --remap rule
+    /// applies to the link, the remapped URL is suggested as replacement.
+    #[arg(long, value_name = "HOST", verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) disallow_host: Vec<String>,
+
+    /// Warn about working links with target="_blank" which are missing
+    /// rel="noopener" (or rel="noreferrer"), as they allow tab-nabbing
+    #[arg(long, verbatim_doc_comment)]
+    #[serde(default)]
+    pub(crate) require_external_noopener: bool,
+
     /// Tell lychee to read cookies from the given file.
     /// Cookies will be stored in the cookie jar and sent with requests.
     /// New cookies will be stored in the cookie jar and existing cookies will be updated.
@@ -426,62 +981,138 @@ impl Config {
 
     /// Merge the configuration from TOML into the CLI configuration
     pub(crate) fn merge(&mut self, toml: Config) {
+        self.merge_link_selection(&toml);
+        self.merge_checking(&toml);
+
+        if self
+            .github_token
+            .as_ref()
+            .map(ExposeSecret::expose_secret)
+            .is_none()
+            && toml
+                .github_token
+                .as_ref()
+                .map(ExposeSecret::expose_secret)
+                .is_some()
+        {
+            self.github_token = toml.github_token;
+        }
+    }
+
+    /// Merge the keys which select the links to check
+    fn merge_link_selection(&mut self, toml: &Config) {
         fold_in! {
             // Destination and source configs
             self, toml;
 
             // Keys with defaults to assign
-            verbose: Verbosity::default();
-            cache: false;
-            no_progress: false;
-            max_redirects: DEFAULT_MAX_REDIRECTS;
-            max_retries: DEFAULT_MAX_RETRIES;
-            max_concurrency: DEFAULT_MAX_CONCURRENCY;
-            max_cache_age: humantime::parse_duration(DEFAULT_MAX_CACHE_AGE).unwrap();
-            threads: None;
-            user_agent: DEFAULT_USER_AGENT;
-            insecure: false;
+            max_urls: None;
+            check_relative_only: false;
             scheme: Vec::<String>::new();
             include: Vec::<String>::new();
+            strict_include: false;
             exclude: Vec::<String>::new();
             exclude_file: Vec::<String>::new(); // deprecated
             exclude_path: Vec::<PathBuf>::new();
+            exclude_path_regex: Vec::<String>::new();
+            follow_symlinks: false;
             exclude_all_private: false;
             exclude_private: false;
             exclude_link_local: false;
             exclude_loopback: false;
             exclude_mail: false;
             remap: Vec::<String>::new();
+            remap_file: None;
+            transform_command: None;
+            strip_query_params: Vec::<String>::new();
+            canonicalize_mail: false;
             fallback_extensions: Vec::<String>::new();
-            header: Vec::<String>::new();
-            timeout: DEFAULT_TIMEOUT_SECS;
-            retry_wait_time: DEFAULT_RETRY_WAIT_TIME_SECS;
-            method: DEFAULT_METHOD;
+            extract_timeout: None;
+            max_body_size: None;
+            robots_sitemaps: false;
+            process_ext: Vec::<ProcessExt>::new();
             base: None;
-            basic_auth: None;
+            stdin_base: None;
+            root_dir: None;
+            changed_since: None;
+            skip_undated: false;
             skip_missing: false;
             include_verbatim: false;
+            include_embeds: false;
+            include_code_annotation: Vec::<String>::new();
+            include_identifiers: false;
+            warn_unused_definitions: false;
+            warn_duplicate_ids: false;
+            input_format: None;
+            url_column: None;
             include_mail: false;
+            verify_mail: false;
+            include_ftp: false;
+            include_gemini: false;
+            include_websocket: false;
             glob_ignore_case: false;
+            include_fragments: false;
+        }
+    }
+
+    /// Merge the keys which control how links are checked and reported
+    fn merge_checking(&mut self, toml: &Config) {
+        fold_in! {
+            // Destination and source configs
+            self, toml;
+
+            // Keys with defaults to assign
+            verbose: Verbosity::default();
+            cache: false;
+            no_progress: false;
+            max_redirects: DEFAULT_MAX_REDIRECTS;
+            warn_redirects_over: None;
+            fail_on_https_downgrade: false;
+            verify_image_content_type: false;
+            record: None;
+            replay: None;
+            follow_meta_refresh: false;
+            max_retries: DEFAULT_MAX_RETRIES;
+            max_concurrency: DEFAULT_MAX_CONCURRENCY;
+            max_concurrency_per_host: None;
+            fs_concurrency: DEFAULT_FS_CONCURRENCY;
+            max_cache_age: humantime::parse_duration(DEFAULT_MAX_CACHE_AGE).unwrap();
+            max_cache_entries: None;
+            cache_exclude_path: Vec::<String>::new();
+            threads: None;
+            user_agent: DEFAULT_USER_AGENT;
+            rotate_user_agents: None;
+            insecure: false;
+            header: Vec::<String>::new();
+            auth_env_file: None;
+            accept_encoding: None;
+            timeout: DEFAULT_TIMEOUT_SECS;
+            total_timeout: None;
+            deadline: None;
+            retry_wait_time: DEFAULT_RETRY_WAIT_TIME_SECS;
+            method: DEFAULT_METHOD;
+            request_methods: None;
+            basic_auth: None;
+            github_check_run: false;
             output: None;
+            output_append: false;
+            stream_to: None;
+            collapse_by_host: None;
+            status_breakdown: false;
+            json_errors: false;
             require_https: false;
+            fail_on_private: false;
+            disallow_host: Vec::<String>::new();
+            require_external_noopener: false;
             cookie_jar: None;
-            include_fragments: false;
+            report_unreferenced_anchors: false;
+            report_duplicates: false;
+            host_report: false;
+            fail_on_warnings: false;
+            ignore_error_status: None;
             accept: AcceptSelector::default();
-        }
-
-        if self
-            .github_token
-            .as_ref()
-            .map(ExposeSecret::expose_secret)
-            .is_none()
-            && toml
-                .github_token
-                .as_ref()
-                .map(ExposeSecret::expose_secret)
-                .is_some()
-        {
-            self.github_token = toml.github_token;
+            accept_pattern: Vec::<AcceptPatternSelector>::new();
+            accept_scheme: Vec::<AcceptSchemeSelector>::new();
         }
     }
 }
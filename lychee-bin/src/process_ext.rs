@@ -0,0 +1,174 @@
+//! Convert input files to text with external commands before extracting
+//! links, configured with `--process-ext EXT:command {} args`.
+//!
+//! This allows checking links in file types lychee can't read itself, e.g.
+//! `pdf:pdftotext {} -` for PDF files. The `{}` placeholder is replaced with
+//! the path of the file and the output of the command is treated as
+//! plaintext.
+
+use std::ffi::OsString;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use lychee_lib::{FileType, Input, InputContent, InputSource, ProcessExt};
+
+/// Placeholder for the path of the file
+const PLACEHOLDER: &str = "{}";
+
+/// A single argument of a [`CommandTemplate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FormatTemplate {
+    /// Passed to the command unchanged
+    Literal(String),
+    /// Contains the `{}` placeholder, e.g. `{}` or `--input={}`
+    Placeholder { prefix: String, suffix: String },
+}
+
+impl FormatTemplate {
+    fn parse(arg: &str) -> Self {
+        match arg.split_once(PLACEHOLDER) {
+            Some((prefix, suffix)) => Self::Placeholder {
+                prefix: prefix.to_string(),
+                suffix: suffix.to_string(),
+            },
+            None => Self::Literal(arg.to_string()),
+        }
+    }
+
+    /// The argument for the file at `path`
+    fn generate(&self, path: &Path) -> OsString {
+        match self {
+            Self::Literal(arg) => arg.into(),
+            Self::Placeholder { prefix, suffix } => {
+                let mut arg = OsString::from(prefix);
+                arg.push(path);
+                arg.push(suffix);
+                arg
+            }
+        }
+    }
+}
+
+/// A command line with a placeholder for the path of the file to convert
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CommandTemplate {
+    program: String,
+    args: Vec<FormatTemplate>,
+}
+
+impl CommandTemplate {
+    pub(crate) fn new<S: AsRef<str>>(program: &str, args: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            program: program.to_string(),
+            args: args
+                .into_iter()
+                .map(|arg| FormatTemplate::parse(arg.as_ref()))
+                .collect(),
+        }
+    }
+
+    /// Run the command for the file at `path` and return what it printed to
+    /// stdout
+    pub(crate) fn generate_and_execute(&self, path: &Path) -> Result<String> {
+        let output = Command::new(&self.program)
+            .args(self.args.iter().map(|arg| arg.generate(path)))
+            .output()
+            .with_context(|| format!("Cannot run `{}`", self.program))?;
+
+        if !output.status.success() {
+            bail!(
+                "`{}` failed for `{}` ({}): {}",
+                self.program,
+                path.display(),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+impl From<&ProcessExt> for CommandTemplate {
+    fn from(process_ext: &ProcessExt) -> Self {
+        Self::new(&process_ext.command, &process_ext.args)
+    }
+}
+
+/// Convert the file inputs matching one of `process_exts` to text. Returns
+/// the remaining inputs and the contents of the converted files.
+///
+/// Only files given as inputs directly are converted, not files found in
+/// directories or by glob patterns.
+pub(crate) fn convert_inputs(
+    inputs: Vec<Input>,
+    process_exts: &[ProcessExt],
+) -> Result<(Vec<Input>, Vec<InputContent>)> {
+    let mut remaining = Vec::new();
+    let mut converted = Vec::new();
+    for input in inputs {
+        let file = match &input.source {
+            InputSource::FsPath(path) if path.is_file() => process_exts
+                .iter()
+                .find(|ext| ext.matches(path))
+                .map(|ext| (ext, path)),
+            _ => None,
+        };
+        match file {
+            Some((process_ext, path)) => converted.push(InputContent {
+                source: InputSource::FsPath(path.clone()),
+                file_type: FileType::Plaintext,
+                content: CommandTemplate::from(process_ext).generate_and_execute(path)?,
+            }),
+            None => remaining.push(input),
+        }
+    }
+    Ok((remaining, converted))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_format_template() {
+        let template = CommandTemplate::new("pdftotext", ["{}", "-", "--out={}.txt"]);
+        assert_eq!(
+            template.args,
+            vec![
+                FormatTemplate::Placeholder {
+                    prefix: String::new(),
+                    suffix: String::new()
+                },
+                FormatTemplate::Literal("-".to_string()),
+                FormatTemplate::Placeholder {
+                    prefix: "--out=".to_string(),
+                    suffix: ".txt".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generate_and_execute() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("links.txt");
+        std::fs::write(&path, "https://example.com\nhttps://example.org\n")?;
+
+        #[cfg(windows)]
+        let template = CommandTemplate::new("cmd", ["/C", "type", "{}"]);
+        #[cfg(not(windows))]
+        let template = CommandTemplate::new("cat", ["{}"]);
+
+        assert_eq!(
+            template.generate_and_execute(&path)?,
+            "https://example.com\nhttps://example.org\n"
+        );
+
+        let error = template
+            .generate_and_execute(&dir.path().join("missing.txt"))
+            .unwrap_err();
+        assert!(error.to_string().contains("missing.txt"), "{error}");
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+//! Re-check the links which failed in a previous run.
+//!
+//! Instead of extracting links from inputs, the links are read from the
+//! `fail_map` of a JSON report written with `--format json`. This allows a
+//! quick check whether previously broken links got fixed.
+
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result};
+use lychee_lib::{InputSource, Request, Uri};
+use reqwest::Url;
+use serde_json::Value;
+
+/// Requests for all failed links in the JSON report at `path`, attributed to
+/// the input they were found in
+pub(crate) fn failed_requests(path: &Path) -> Result<Vec<Request>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Cannot read report `{}`", path.display()))?;
+    let report: Value = serde_json::from_str(&content)
+        .with_context(|| format!("Cannot parse report `{}` as JSON", path.display()))?;
+    let Some(fail_map) = report.get("fail_map").and_then(Value::as_object) else {
+        return Ok(Vec::new());
+    };
+
+    let mut requests = Vec::new();
+    for (source, entries) in fail_map {
+        let urls = entries
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.get("url").and_then(Value::as_str));
+        for url in urls {
+            let uri = Uri::try_from(url)
+                .with_context(|| format!("Invalid URL `{url}` in report `{}`", path.display()))?;
+            requests.push(Request::new(uri, input_source(source), None, None, None));
+        }
+    }
+    Ok(requests)
+}
+
+/// Restore the input source from its representation in a report
+fn input_source(source: &str) -> InputSource {
+    match Url::parse(source) {
+        _ if source == "stdin" => InputSource::Stdin,
+        Ok(url) if matches!(url.scheme(), "http" | "https") => {
+            InputSource::RemoteUrl(Box::new(url))
+        }
+        _ => InputSource::FsPath(source.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn test_input_source() {
+        assert_eq!(input_source("stdin"), InputSource::Stdin);
+        assert_eq!(
+            input_source("https://example.com/docs"),
+            InputSource::RemoteUrl(Box::new(Url::parse("https://example.com/docs").unwrap()))
+        );
+        assert_eq!(
+            input_source("docs/README.md"),
+            InputSource::FsPath(PathBuf::from("docs/README.md"))
+        );
+    }
+}
@@ -4,24 +4,63 @@
 use std::collections::{HashMap, HashSet};
 
 use crate::archive::Suggestion;
-use lychee_lib::{CacheStatus, InputSource, Response, ResponseBody, Status};
+use crate::commands::schema;
+use lychee_lib::{
+    AcceptSelector, CacheStatus, InputSource, RawUriSpan, Response, ResponseBody, Status,
+};
+use schemars::JsonSchema;
 use serde::Serialize;
 
-#[derive(Default, Serialize, Debug)]
+/// A link with a scheme lychee can't check, e.g. `slack://`
+#[derive(Debug, Serialize, JsonSchema, Eq, Hash, PartialEq)]
+pub(crate) struct UnsupportedLink {
+    /// The unsupported URL
+    pub(crate) url: String,
+    /// The scheme of the URL, e.g. `slack`
+    pub(crate) scheme: String,
+    /// Position of the URL in its input, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<schema::Span>")]
+    pub(crate) span: Option<RawUriSpan>,
+}
+
+impl From<&ResponseBody> for UnsupportedLink {
+    fn from(body: &ResponseBody) -> Self {
+        Self {
+            url: body.uri.to_string(),
+            scheme: body.uri.scheme().to_string(),
+            span: body.span,
+        }
+    }
+}
+
+#[derive(Default, Serialize, Debug, JsonSchema)]
 pub(crate) struct ResponseStats {
     pub(crate) total: usize,
     pub(crate) successful: usize,
     pub(crate) unknown: usize,
     pub(crate) unsupported: usize,
     pub(crate) timeouts: usize,
+    /// Errors where the host refused the connection, also counted as `errors`
+    pub(crate) refused: usize,
     pub(crate) redirects: usize,
     pub(crate) excludes: usize,
     pub(crate) errors: usize,
+    pub(crate) warnings: usize,
     pub(crate) cached: usize,
+    #[schemars(with = "schema::ResponseMap")]
     pub(crate) success_map: HashMap<InputSource, HashSet<ResponseBody>>,
+    #[schemars(with = "schema::ResponseMap")]
     pub(crate) fail_map: HashMap<InputSource, HashSet<ResponseBody>>,
+    #[schemars(with = "schema::ResponseMap")]
+    pub(crate) warning_map: HashMap<InputSource, HashSet<ResponseBody>>,
+    #[schemars(with = "schema::SuggestionMap")]
     pub(crate) suggestion_map: HashMap<InputSource, HashSet<Suggestion>>,
+    #[schemars(with = "schema::ResponseMap")]
     pub(crate) excluded_map: HashMap<InputSource, HashSet<ResponseBody>>,
+    /// Unsupported links, which are listed even without detailed stats
+    #[schemars(with = "schema::UnsupportedMap")]
+    pub(crate) unsupported_map: HashMap<InputSource, HashSet<UnsupportedLink>>,
     pub(crate) duration_secs: u64,
     pub(crate) detailed_stats: bool,
 }
@@ -38,12 +77,18 @@ impl ResponseStats {
     pub(crate) fn increment_status_counters(&mut self, status: &Status) {
         match status {
             Status::Ok(_) => self.successful += 1,
-            Status::Error(_) => self.errors += 1,
+            Status::Error(_) => {
+                self.errors += 1;
+                if status.is_connection_refused() {
+                    self.refused += 1;
+                }
+            }
             Status::UnknownStatusCode(_) => self.unknown += 1,
             Status::Timeout(_) => self.timeouts += 1,
             Status::Redirected(_) => self.redirects += 1,
-            Status::Excluded => self.excludes += 1,
+            Status::Excluded | Status::ExcludedWithReason(_) => self.excludes += 1,
             Status::Unsupported(_) => self.unsupported += 1,
+            Status::Warning(..) => self.warnings += 1,
             Status::Cached(cache_status) => {
                 self.cached += 1;
                 match cache_status {
@@ -67,21 +112,47 @@ impl ResponseStats {
                 let fail = self.fail_map.entry(source).or_default();
                 fail.insert(response.1);
             }
+            Status::Warning(..) => {
+                let warning = self.warning_map.entry(source).or_default();
+                warning.insert(response.1);
+            }
             Status::Ok(_) if self.detailed_stats => {
                 let success = self.success_map.entry(source).or_default();
                 success.insert(response.1);
             }
-            Status::Excluded if self.detailed_stats => {
+            Status::Excluded | Status::ExcludedWithReason(_) if self.detailed_stats => {
                 let excluded = self.excluded_map.entry(source).or_default();
                 excluded.insert(response.1);
             }
+            _ if status.is_unsupported() => {
+                let unsupported = self.unsupported_map.entry(source).or_default();
+                unsupported.insert(UnsupportedLink::from(&response.1));
+            }
             _ => (),
         }
     }
 
+    /// Whether the run succeeded. Warnings only count as failures
+    /// if `fail_on_warnings` is set. `ignored_failures` is the number of
+    /// failed links which shouldn't fail the run.
     #[inline]
-    pub(crate) const fn is_success(&self) -> bool {
-        self.total == self.successful + self.excludes + self.unsupported
+    pub(crate) const fn is_success(&self, fail_on_warnings: bool, ignored_failures: usize) -> bool {
+        let warnings = if fail_on_warnings { 0 } else { self.warnings };
+        self.total
+            == self.successful + self.excludes + self.unsupported + warnings + ignored_failures
+    }
+
+    /// Number of failed links whose status code is contained in `codes`
+    pub(crate) fn failures_with_status(&self, codes: &AcceptSelector) -> usize {
+        self.fail_map
+            .values()
+            .flatten()
+            .filter(|body| {
+                body.status
+                    .code()
+                    .is_some_and(|code| codes.contains(code.as_u16()))
+            })
+            .count()
     }
 
     #[inline]
@@ -96,10 +167,10 @@ mod tests {
     use std::collections::{HashMap, HashSet};
 
     use http::StatusCode;
-    use lychee_lib::{ErrorKind, InputSource, Response, ResponseBody, Status, Uri};
+    use lychee_lib::{ErrorKind, InputSource, RawUriSpan, Response, ResponseBody, Status, Uri};
     use reqwest::Url;
 
-    use super::ResponseStats;
+    use super::{ResponseStats, UnsupportedLink};
 
     fn website(url: &str) -> Uri {
         Uri::from(Url::parse(url).expect("Expected valid Website URI"))
@@ -110,7 +181,7 @@ mod tests {
     // and it's a lot faster to just generate a fake response
     fn mock_response(status: Status) -> Response {
         let uri = website("https://some-url.com/ok");
-        let response_body = ResponseBody { uri, status };
+        let response_body = ResponseBody::new(uri, status);
         Response(InputSource::Stdin, response_body)
     }
 
@@ -126,6 +197,13 @@ mod tests {
         mock_response(Status::Excluded)
     }
 
+    fn dummy_warning() -> Response {
+        mock_response(Status::Warning(
+            StatusCode::OK,
+            "something looks off".to_string(),
+        ))
+    }
+
     #[tokio::test]
     async fn test_stats_is_empty() {
         let mut stats = ResponseStats::default();
@@ -182,4 +260,67 @@ mod tests {
         entry.insert(response_body);
         assert_eq!(stats.excluded_map, expected_excluded_map);
     }
+
+    #[test]
+    fn test_excluded_with_reason() {
+        let excluded = || {
+            mock_response(Status::ExcludedWithReason(
+                "blocked by company policy".to_string(),
+            ))
+        };
+        let mut stats = ResponseStats::extended();
+        stats.add(excluded());
+
+        assert_eq!(stats.excludes, 1);
+        assert!(stats.is_success(false, 0));
+
+        let Response(source, response_body) = excluded();
+        let expected_excluded_map: HashMap<InputSource, HashSet<ResponseBody>> =
+            HashMap::from_iter([(source, HashSet::from_iter([response_body]))]);
+        assert_eq!(stats.excluded_map, expected_excluded_map);
+    }
+
+    #[test]
+    fn test_unsupported_map() {
+        let mut stats = ResponseStats::default();
+        stats.add(
+            Response::new(
+                Uri::try_from("slack://channel?id=123").unwrap(),
+                Status::Unsupported(ErrorKind::InvalidUrlHost),
+                InputSource::Stdin,
+            )
+            .with_span(Some(RawUriSpan { line: 3, column: 7 })),
+        );
+
+        assert_eq!(stats.unsupported, 1);
+        let expected_unsupported_map = HashMap::from_iter([(
+            InputSource::Stdin,
+            HashSet::from_iter([UnsupportedLink {
+                url: "slack://channel?id=123".to_string(),
+                scheme: "slack".to_string(),
+                span: Some(RawUriSpan { line: 3, column: 7 }),
+            }]),
+        )]);
+        assert_eq!(stats.unsupported_map, expected_unsupported_map);
+    }
+
+    #[test]
+    fn test_warnings() {
+        let mut stats = ResponseStats::default();
+        stats.add(dummy_ok());
+        stats.add(dummy_warning());
+
+        assert_eq!(stats.warnings, 1);
+        assert!(stats.is_success(false, 0));
+        assert!(!stats.is_success(true, 0));
+
+        let Response(source, response_body) = dummy_warning();
+        let expected_warning_map: HashMap<InputSource, HashSet<ResponseBody>> =
+            HashMap::from_iter([(source, HashSet::from_iter([response_body]))]);
+        assert_eq!(stats.warning_map, expected_warning_map);
+
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["warnings"], 1);
+        assert_eq!(json["warning_map"]["stdin"].as_array().unwrap().len(), 1);
+    }
 }
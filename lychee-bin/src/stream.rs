@@ -0,0 +1,85 @@
+//! Stream results as NDJSON events to a Unix domain socket or named pipe.
+//!
+//! This allows long-running processes (e.g. a dashboard) to follow a run
+//! without parsing lychee's human-readable output. The stream is best-effort:
+//! if the target is unavailable, lychee warns and keeps checking.
+
+use std::path::{Path, PathBuf};
+
+use lychee_lib::Response;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// A connection to a Unix domain socket or named pipe which receives one JSON
+/// event per checked link
+pub(crate) struct EventStream {
+    path: PathBuf,
+    writer: Box<dyn AsyncWrite + Send + Unpin>,
+}
+
+impl EventStream {
+    /// Connect to the socket or named pipe at `path`.
+    ///
+    /// Returns `None` and logs a warning if the target is not available.
+    pub(crate) async fn connect(path: &Path) -> Option<Self> {
+        match open(path).await {
+            Ok(writer) => Some(Self {
+                path: path.to_path_buf(),
+                writer,
+            }),
+            Err(e) => {
+                log::warn!(
+                    "Cannot stream results to {}: {e}. Continuing without streaming",
+                    path.display()
+                );
+                None
+            }
+        }
+    }
+
+    /// Send the event for a single response.
+    ///
+    /// Returns `false` and logs a warning if the reader went away, in which
+    /// case the stream should not be used anymore.
+    pub(crate) async fn send(&mut self, response: &Response) -> bool {
+        let line = format!("{}\n", json_line(response));
+        match self.writer.write_all(line.as_bytes()).await {
+            Ok(()) => true,
+            Err(e) => {
+                log::warn!(
+                    "Cannot stream results to {}: {e}. Continuing without streaming",
+                    self.path.display()
+                );
+                false
+            }
+        }
+    }
+}
+
+/// Format a single response as a JSON line, including the input source
+pub(crate) fn json_line(response: &Response) -> String {
+    let mut line = serde_json::json!(response);
+    line["source"] = serde_json::json!(response.0);
+    line.to_string()
+}
+
+#[cfg(unix)]
+async fn open(path: &Path) -> std::io::Result<Box<dyn AsyncWrite + Send + Unpin>> {
+    use std::os::unix::fs::FileTypeExt;
+
+    if tokio::fs::metadata(path).await?.file_type().is_fifo() {
+        // Fails instead of blocking if there is no reader yet
+        let sender = tokio::net::unix::pipe::OpenOptions::new().open_sender(path)?;
+        Ok(Box::new(sender))
+    } else {
+        let socket = tokio::net::UnixStream::connect(path).await?;
+        Ok(Box::new(socket))
+    }
+}
+
+#[cfg(not(unix))]
+async fn open(_path: &Path) -> std::io::Result<Box<dyn AsyncWrite + Send + Unpin>> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "streaming is only supported on Unix",
+    ))
+}
@@ -0,0 +1,243 @@
+//! Rewrite extracted links with an external command before checking them.
+//!
+//! This allows arbitrary transformations which cannot be expressed as a
+//! `--remap` rule, like signing URLs. To avoid spawning a process per link,
+//! all links which are ready at the same time are passed to a single
+//! invocation of the command, one per line. The command has to print exactly
+//! one line per link, where an empty line drops the link. If the command
+//! fails, every link of the batch is reported as a failed link, just like a
+//! link for which the command printed an invalid URL.
+
+use std::convert::identity;
+use std::io;
+use std::process::Stdio;
+
+use futures::{stream, Stream, StreamExt};
+use lychee_lib::{ErrorKind, Request, Response, Result, Status, Uri};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Pass the URLs of the requests through `command`, in batches of at most
+/// `batch_size` requests
+pub(crate) fn transform_requests<S>(
+    requests: S,
+    command: String,
+    batch_size: usize,
+) -> impl Stream<Item = Result<Request>>
+where
+    S: Stream<Item = Result<Request>>,
+{
+    requests
+        .ready_chunks(batch_size.max(1))
+        .then(move |batch| {
+            let command = command.clone();
+            async move { transform_batch(&command, batch).await }
+        })
+        .flat_map(stream::iter)
+}
+
+/// Replace the URL of each request in the batch with the corresponding line
+/// of the command output. Errors of earlier stages are passed on unchanged.
+async fn transform_batch(command: &str, batch: Vec<Result<Request>>) -> Vec<Result<Request>> {
+    let mut input = String::new();
+    for request in batch.iter().flatten() {
+        input.push_str(request.uri.as_str());
+        input.push('\n');
+    }
+    if input.is_empty() {
+        return batch;
+    }
+    let expected = batch.iter().flatten().count();
+
+    let output = match run(command, input).await {
+        Ok(output) if output.lines().count() == expected => output,
+        Ok(output) => {
+            let lines = output.lines().count();
+            let reason = format!("expected {expected} lines of output, got {lines}");
+            return fail_batch(command, batch, &reason);
+        }
+        Err(e) => return fail_batch(command, batch, &e.to_string()),
+    };
+
+    let mut lines = output.lines();
+    let mut transformed = Vec::with_capacity(batch.len());
+    for request in batch {
+        let Ok(mut request) = request else {
+            transformed.push(request);
+            continue;
+        };
+        let line = lines.next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        match Uri::try_from(line) {
+            Ok(uri) => {
+                request.uri = uri;
+                transformed.push(Ok(request));
+            }
+            Err(e) => {
+                let reason = format!("invalid URL `{line}` in output: {e}");
+                transformed.push(Err(failure(command, request, &reason)));
+            }
+        }
+    }
+    transformed
+}
+
+/// Turn every request of a batch which the command failed on into an error
+fn fail_batch(command: &str, batch: Vec<Result<Request>>, reason: &str) -> Vec<Result<Request>> {
+    batch
+        .into_iter()
+        .map(|request| request.map_or_else(identity, |request| failure(command, request, reason)))
+        .map(Err)
+        .collect()
+}
+
+fn failure(command: &str, request: Request, reason: &str) -> ErrorKind {
+    ErrorKind::TransformCommand(command.to_string(), Box::new(request), reason.to_string())
+}
+
+/// Report a link which the transform command failed on as a failed link.
+///
+/// # Panics
+///
+/// Panics if `error` is not a transform error, as only those are passed on
+/// to the check.
+pub(crate) fn failed_response(error: ErrorKind) -> Response {
+    let ErrorKind::TransformCommand(_, request, _) = &error else {
+        unreachable!("only transform errors are reported per link: {error}");
+    };
+    let (uri, source, span) = (request.uri.clone(), request.source.clone(), request.span);
+    Response::new(uri, Status::Error(error), source).with_span(span)
+}
+
+/// Run `command` in a shell with `input` on stdin and return its stdout
+async fn run(command: &str, input: String) -> io::Result<String> {
+    let mut child = shell(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    // Write while reading the output, as the command might not consume all
+    // of its input before its output pipe is full
+    let write = async move {
+        let result = stdin.write_all(input.as_bytes()).await;
+        drop(stdin);
+        result
+    };
+    let (written, output) = tokio::join!(write, child.wait_with_output());
+    let output = output?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!("exited with {}", output.status)));
+    }
+    written?;
+    String::from_utf8(output.stdout).map_err(io::Error::other)
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use lychee_lib::InputSource;
+
+    use super::*;
+
+    fn request(url: &str) -> Request {
+        Request::new(
+            Uri::try_from(url).unwrap(),
+            InputSource::Stdin,
+            None,
+            None,
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_transform_batch() {
+        let batch = vec![
+            Ok(request("https://example.com/keep")),
+            Ok(request("https://example.com/drop")),
+        ];
+        let transformed = transform_batch("sed 's/.*drop$//; s/keep/kept/'", batch).await;
+
+        assert_eq!(transformed.len(), 1);
+        assert_eq!(
+            transformed[0].as_ref().unwrap().uri.as_str(),
+            "https://example.com/kept"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transform_batch_line_mismatch() {
+        let batch = vec![
+            Ok(request("https://example.com/a")),
+            Ok(request("https://example.com/b")),
+        ];
+        let transformed = transform_batch("head -n 1", batch).await;
+
+        let urls: Vec<_> = transformed
+            .iter()
+            .map(|result| match result {
+                Err(ErrorKind::TransformCommand(_, request, _)) => request.uri.as_str(),
+                _ => panic!("expected an error for every link, got {result:?}"),
+            })
+            .collect();
+        assert_eq!(urls, ["https://example.com/a", "https://example.com/b"]);
+    }
+
+    #[tokio::test]
+    async fn test_transform_batch_command_fails() {
+        let batch = vec![Ok(request("https://example.com/a"))];
+        let transformed = transform_batch("exit 1", batch).await;
+
+        assert!(matches!(
+            transformed.as_slice(),
+            [Err(ErrorKind::TransformCommand(_, _, reason))] if reason.contains("exit")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_transform_batch_invalid_url() {
+        let batch = vec![
+            Ok(request("https://example.com/a")),
+            Ok(request("https://example.com/b")),
+        ];
+        let transformed = transform_batch("sed 's/.*a$/not a url/'", batch).await;
+
+        assert!(matches!(
+            &transformed[0],
+            Err(ErrorKind::TransformCommand(_, request, reason))
+                if request.uri.as_str() == "https://example.com/a" && reason.contains("not a url")
+        ));
+        assert_eq!(
+            transformed[1].as_ref().unwrap().uri.as_str(),
+            "https://example.com/b"
+        );
+    }
+
+    #[test]
+    fn test_failed_response() {
+        let error = failure("false", request("https://example.com/a"), "exited with 1");
+        let response = failed_response(error);
+
+        assert_eq!(response.1.uri.as_str(), "https://example.com/a");
+        assert!(matches!(
+            response.status(),
+            Status::Error(ErrorKind::TransformCommand(..))
+        ));
+    }
+}
@@ -4,7 +4,7 @@ mod cli {
         collections::{HashMap, HashSet},
         error::Error,
         fs::{self, File},
-        io::Write,
+        io::{Read, Write},
         path::{Path, PathBuf},
     };
 
@@ -13,7 +13,7 @@ mod cli {
     use http::StatusCode;
     use lychee_lib::{InputSource, ResponseBody};
     use predicates::{
-        prelude::predicate,
+        prelude::{predicate, PredicateBooleanExt},
         str::{contains, is_empty},
     };
     use pretty_assertions::assert_eq;
@@ -84,9 +84,11 @@ mod cli {
         redirects: usize,
         excludes: usize,
         errors: usize,
+        warnings: usize,
         cached: usize,
         success_map: HashMap<InputSource, HashSet<ResponseBody>>,
         fail_map: HashMap<InputSource, HashSet<ResponseBody>>,
+        warning_map: HashMap<InputSource, HashSet<ResponseBody>>,
         suggestion_map: HashMap<InputSource, HashSet<ResponseBody>>,
         excluded_map: HashMap<InputSource, HashSet<ResponseBody>>,
     }
@@ -133,6 +135,55 @@ mod cli {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_json_errors() -> Result<()> {
+        let mock_server = wiremock::MockServer::start().await;
+        Mock::given(wiremock::matchers::path("/missing"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+        Mock::given(wiremock::matchers::method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+
+        let output = main_command()
+            .arg("--format")
+            .arg("json")
+            .arg("--json-errors")
+            .arg("-")
+            .write_stdin(format!("{0}/missing\n{0}/ok", mock_server.uri()))
+            .output()?;
+        assert_eq!(output.status.code(), Some(2));
+
+        // stdout contains only the stats
+        let stats: Value = serde_json::from_slice(&output.stdout)?;
+        assert_eq!(stats["errors"], 1);
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let summary: Value = serde_json::from_str(stderr.lines().last().unwrap())?;
+        assert_eq!(
+            summary,
+            serde_json::json!({
+                "exit_code": 2,
+                "error_count": 1,
+                "first_error": format!("[404 Not Found] {}/missing", mock_server.uri()),
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_errors_requires_json_format() {
+        main_command()
+            .arg("--json-errors")
+            .arg("-")
+            .write_stdin("https://example.com")
+            .assert()
+            .failure()
+            .stderr(contains("`--json-errors` requires the JSON format"));
+    }
+
     #[test]
     fn test_detailed_json_output_on_error() -> Result<()> {
         let test_path = fixtures_path().join("TEST_DETAILED_JSON_OUTPUT_ERROR.md");
@@ -161,6 +212,59 @@ mod cli {
         Ok(())
     }
 
+    #[test]
+    fn test_json_output_contains_span_of_excluded_link() -> Result<()> {
+        let test_path = fixtures_path().join("TEST_SPAN.md");
+
+        let mut cmd = main_command();
+        let output = cmd
+            .arg("--format")
+            .arg("json")
+            .arg("--verbose")
+            .arg("--exclude")
+            .arg("excluded.example.org")
+            .arg(&test_path)
+            .output()?;
+        assert!(output.status.success());
+
+        let output_json = serde_json::from_slice::<Value>(&output.stdout)?;
+        let excluded = &output_json["excluded_map"][&test_path.to_str().unwrap()][0];
+        assert_eq!(excluded["url"], "https://excluded.example.org/page");
+        assert_eq!(excluded["span"]["line"], 5);
+        assert_eq!(excluded["span"]["column"], 14);
+        Ok(())
+    }
+
+    #[test]
+    fn test_toml_output() -> Result<()> {
+        let test_path = fixtures_path().join("TEST_SPAN.md");
+
+        let mut cmd = main_command();
+        let output = cmd
+            .arg("--format")
+            .arg("toml")
+            .arg("--verbose")
+            .arg("--exclude")
+            .arg("excluded.example.org")
+            .arg(&test_path)
+            .output()?;
+        assert!(output.status.success());
+
+        let output_toml = toml::from_str::<toml::Table>(std::str::from_utf8(&output.stdout)?)?;
+        assert_eq!(output_toml["total"].as_integer(), Some(1));
+        assert_eq!(output_toml["excludes"].as_integer(), Some(1));
+        assert!(output_toml["fail_map"].as_table().unwrap().is_empty());
+
+        let excluded = &output_toml["excluded_map"][test_path.to_str().unwrap()][0];
+        assert_eq!(
+            excluded["url"].as_str(),
+            Some("https://excluded.example.org/page")
+        );
+        assert_eq!(excluded["span"]["line"].as_integer(), Some(5));
+        assert_eq!(excluded["span"]["column"].as_integer(), Some(14));
+        Ok(())
+    }
+
     #[test]
     fn test_exclude_all_private() -> Result<()> {
         test_json_output!(
@@ -174,6 +278,377 @@ mod cli {
         )
     }
 
+    #[test]
+    fn test_fail_on_private() {
+        let test_path = fixtures_path().join("TEST_ALL_PRIVATE.md");
+
+        main_command()
+            .arg("--fail-on-private")
+            .arg(test_path)
+            .assert()
+            .failure()
+            .code(2)
+            .stdout(contains("7 Total"))
+            .stdout(contains("7 Errors"))
+            .stdout(contains(
+                "http://127.0.0.1/ | Failed: This URI points to a private or local address",
+            ));
+    }
+
+    #[test]
+    fn test_disallow_host() {
+        main_command()
+            .arg("--disallow-host")
+            .arg("old.example.com")
+            .arg("--remap")
+            .arg("https://old.example.com https://new.example.com")
+            .arg("-")
+            .write_stdin("https://old.example.com/docs https://OLD.example.com/about")
+            .assert()
+            .failure()
+            .code(2)
+            .stdout(contains("2 Errors"))
+            .stdout(contains(
+                "Links to host 'old.example.com' are not allowed. Use 'https://new.example.com/docs' instead",
+            ));
+    }
+
+    #[tokio::test]
+    async fn test_host_report() -> Result<()> {
+        let mock_server = wiremock::MockServer::start().await;
+        Mock::given(wiremock::matchers::path("/broken"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+        Mock::given(wiremock::matchers::method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+        let host = mock_server.uri().replace("http://", "");
+
+        main_command()
+            .arg("--host-report")
+            .arg("--no-progress")
+            .arg("--base")
+            .arg(mock_server.uri())
+            .arg(fixtures_path().join("TEST_HOST_REPORT.md"))
+            .assert()
+            .failure()
+            .code(2)
+            .stderr(contains("Links by host (1):"))
+            .stderr(contains(format!("{host}: 3 links, 1 broken")));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_max_concurrency_per_host() -> Result<()> {
+        let slow = mock_server!(
+            StatusCode::OK,
+            set_delay(std::time::Duration::from_millis(500))
+        );
+        let fast = mock_server!(StatusCode::OK);
+        let slow_urls: Vec<_> = (1..=3).map(|i| format!("{}/{i}", slow.uri())).collect();
+        let fast_urls: Vec<_> = (1..=3).map(|i| format!("{}/{i}", fast.uri())).collect();
+
+        let output = main_command()
+            .arg("--max-concurrency")
+            .arg("2")
+            .arg("--max-concurrency-per-host")
+            .arg("1")
+            .arg("--verbose")
+            .arg("--no-progress")
+            .arg("-")
+            .write_stdin(format!("{} {}", slow_urls.join(" "), fast_urls.join(" ")))
+            .output()?;
+        assert!(output.status.success());
+
+        // Links to the slow host wait for each other without taking up the
+        // second slot, so the fast host is done before the second slow link
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let position = |url: &str| stderr.find(&format!("{url}\n")).unwrap();
+        for url in &fast_urls {
+            assert!(position(url) < position(&slow_urls[1]), "{stderr}");
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout_is_counted_as_timeout() -> Result<()> {
+        let slow = mock_server!(StatusCode::OK, set_delay(std::time::Duration::from_secs(3)));
+
+        let output = main_command()
+            .arg("--request-timeout")
+            .arg("1")
+            .arg("--retry-wait-time")
+            .arg("0")
+            .arg("--format")
+            .arg("json")
+            .arg("-")
+            .write_stdin(slow.uri())
+            .output()?;
+
+        let actual: Value = serde_json::from_slice(&output.stdout)?;
+        let expected = serde_json::to_value(MockResponseStats {
+            total: 1,
+            timeouts: 1,
+            ..MockResponseStats::default()
+        })?;
+        assert_json_include!(actual: actual, expected: expected);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_collapse_by_host() -> Result<()> {
+        let mock_server = wiremock::MockServer::start().await;
+        Mock::given(wiremock::matchers::path_regex("^/gone/"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+        Mock::given(wiremock::matchers::method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+        let host = mock_server.uri().replace("http://", "");
+
+        main_command()
+            .arg("--collapse-by-host=2")
+            .arg("--no-progress")
+            .arg("--base")
+            .arg(mock_server.uri())
+            .arg(fixtures_path().join("TEST_COLLAPSE_BY_HOST.md"))
+            .assert()
+            .failure()
+            .code(2)
+            .stdout(contains(format!("[{host}]: 5 errors")))
+            .stdout(contains(format!("{}/gone/1", mock_server.uri())))
+            .stdout(contains(format!("{}/gone/2", mock_server.uri())))
+            .stdout(contains(format!("{}/gone/3", mock_server.uri())).not())
+            .stdout(contains("... and 3 more"))
+            .stdout(contains("TEST_COLLAPSE_BY_HOST.md]").not());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_status_breakdown() -> Result<()> {
+        let mock_server = wiremock::MockServer::start().await;
+        Mock::given(wiremock::matchers::path_regex("^/missing/"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+        Mock::given(wiremock::matchers::path("/broken"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&mock_server)
+            .await;
+        Mock::given(wiremock::matchers::path("/slow"))
+            .respond_with(ResponseTemplate::new(200).set_delay(std::time::Duration::from_secs(3)))
+            .mount(&mock_server)
+            .await;
+        Mock::given(wiremock::matchers::method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+
+        main_command()
+            .arg("--status-breakdown")
+            .arg("--no-progress")
+            .arg("--max-retries")
+            .arg("0")
+            .arg("--timeout")
+            .arg("1")
+            .arg("--base")
+            .arg(mock_server.uri())
+            .arg(fixtures_path().join("TEST_STATUS_BREAKDOWN.md"))
+            .assert()
+            .failure()
+            .code(2)
+            .stdout(contains("4 Errors (404: 3, 500: 1, timeout: 1)"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_github_check_run() -> Result<()> {
+        let mock_server = wiremock::MockServer::start().await;
+        Mock::given(wiremock::matchers::path("/broken"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+        Mock::given(wiremock::matchers::method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+        Mock::given(wiremock::matchers::method("POST"))
+            .and(wiremock::matchers::path("/repos/owner/repo/check-runs"))
+            .and(wiremock::matchers::header("Authorization", "Bearer secret"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({ "id": 1 })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        main_command()
+            .current_dir(root_path())
+            .env("GITHUB_API_URL", mock_server.uri())
+            .env("GITHUB_REPOSITORY", "owner/repo")
+            .env("GITHUB_SHA", "0123abc")
+            .env("GITHUB_TOKEN", "secret")
+            .arg("--github-check-run")
+            .arg("--no-progress")
+            .arg("--base")
+            .arg(mock_server.uri())
+            .arg("fixtures/TEST_HOST_REPORT.md")
+            .assert()
+            .failure()
+            .code(2)
+            .stderr(contains("Cannot create GitHub check run").not());
+
+        let requests = mock_server.received_requests().await.unwrap();
+        let check_run = requests
+            .iter()
+            .find(|request| request.method == wiremock::http::Method::POST)
+            .unwrap();
+        let payload: Value = serde_json::from_slice(&check_run.body)?;
+        assert_eq!(payload["head_sha"], "0123abc");
+        assert_eq!(payload["conclusion"], "failure");
+        let annotations = payload["output"]["annotations"].as_array().unwrap();
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(annotations[0]["path"], "fixtures/TEST_HOST_REPORT.md");
+        assert_eq!(annotations[0]["start_line"], 5);
+        assert!(annotations[0]["message"]
+            .as_str()
+            .unwrap()
+            .starts_with(&format!("{}/broken", mock_server.uri())));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_github_check_run_failure_is_a_warning() -> Result<()> {
+        let mock_server = wiremock::MockServer::start().await;
+        Mock::given(wiremock::matchers::method("POST"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&mock_server)
+            .await;
+
+        main_command()
+            .env("GITHUB_API_URL", mock_server.uri())
+            .env("GITHUB_REPOSITORY", "owner/repo")
+            .env("GITHUB_SHA", "0123abc")
+            .env("GITHUB_TOKEN", "secret")
+            .arg("--github-check-run")
+            .arg(fixtures_path().join("TEST_REPETITION_1.txt"))
+            .arg("--exclude")
+            .arg(".*")
+            .assert()
+            .success()
+            .stderr(contains("Cannot create GitHub check run"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_report_duplicates() -> Result<()> {
+        let test_path = fixtures_path().join("TEST_DUPLICATES.md");
+
+        main_command()
+            .arg("--report-duplicates")
+            .arg("--exclude")
+            .arg("example.com")
+            .arg(&test_path)
+            .assert()
+            .success()
+            .stdout(contains("2 Total"))
+            .stderr(contains("Duplicate links (1):"))
+            .stderr(contains("https://example.com/docs (2 occurrences)"))
+            .stderr(contains(format!("{}:3:16", test_path.display())))
+            .stderr(contains(format!("{}:5:24", test_path.display())));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_require_external_noopener() -> Result<()> {
+        let mock_server = mock_server!(StatusCode::OK);
+        let test_path = fixtures_path().join("TEST_NOOPENER.html");
+
+        let output = main_command()
+            .arg("--require-external-noopener")
+            .arg("--no-progress")
+            .arg("--base")
+            .arg(mock_server.uri())
+            .arg(test_path)
+            .output()?;
+
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("4 Total"));
+        assert!(stdout.contains("1 Warning"));
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let warnings: Vec<_> = stderr.lines().filter(|l| l.contains("Warning:")).collect();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains(&format!("[200 OK] {}/tabnabbing", mock_server.uri())));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_warn_redirects_over() -> Result<()> {
+        let mock_server = wiremock::MockServer::start().await;
+        for (from, to) in [("/1", "/2"), ("/2", "/3"), ("/3", "/final")] {
+            Mock::given(wiremock::matchers::path(from))
+                .respond_with(
+                    ResponseTemplate::new(StatusCode::PERMANENT_REDIRECT)
+                        .insert_header("Location", format!("{}{to}", mock_server.uri())),
+                )
+                .mount(&mock_server)
+                .await;
+        }
+        Mock::given(wiremock::matchers::path("/final"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .mount(&mock_server)
+            .await;
+
+        let output = main_command()
+            .arg("--warn-redirects-over")
+            .arg("2")
+            .arg("--no-progress")
+            .arg("-")
+            .write_stdin(format!("{0}/1 {0}/2", mock_server.uri()))
+            .output()?;
+
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("2 Total"));
+        assert!(stdout.contains("1 OK"));
+        assert!(stdout.contains("1 Warning"));
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(&format!(
+            "Redirected 3 times, final URL: {}/final",
+            mock_server.uri()
+        )));
+        Ok(())
+    }
+
+    /// Requires network access to a public FTP server
+    #[cfg(feature = "ftp")]
+    #[test]
+    #[ignore]
+    fn test_include_ftp() {
+        main_command()
+            .arg("--include-ftp")
+            .arg("-")
+            .write_stdin("ftp://ftp.gnu.org/README\nftp://ftp.gnu.org/gnu/\nftp://ftp.gnu.org/does-not-exist\n")
+            .assert()
+            .failure()
+            .code(2)
+            .stdout(contains("2 OK"))
+            .stdout(contains("1 Error"))
+            .stderr(contains("Unreachable FTP resource"));
+    }
+
     #[test]
     fn test_email() -> Result<()> {
         test_json_output!(
@@ -201,6 +676,21 @@ mod cli {
         )
     }
 
+    #[cfg(feature = "verify-mail")]
+    #[test]
+    fn test_verify_mail() {
+        // `.invalid` is reserved and never has MX or address records
+        main_command()
+            .arg("--verify-mail")
+            .arg("-")
+            .write_stdin("mailto:hello@lychee.invalid")
+            .assert()
+            .failure()
+            .code(2)
+            .stdout(contains("1 Error"))
+            .stdout(contains("No MX or address records"));
+    }
+
     #[test]
     fn test_email_html_with_subject() -> Result<()> {
         let mut cmd = main_command();
@@ -264,6 +754,36 @@ mod cli {
             .stdout(contains("1 Excluded"));
     }
 
+    #[test]
+    fn test_json_output_lists_unsupported_uri_schemes() -> Result<()> {
+        let test_path = fixtures_path().join("TEST_SCHEMES.txt");
+
+        let mut cmd = main_command();
+        let output = cmd
+            .arg("--format")
+            .arg("json")
+            .arg("--exclude")
+            .arg("file://")
+            .arg("--exclude")
+            .arg("example.com")
+            .arg(&test_path)
+            .env_clear()
+            .output()?;
+        assert!(output.status.success());
+
+        let output_json = serde_json::from_slice::<Value>(&output.stdout)?;
+        assert_eq!(output_json["unsupported"], 1);
+        assert_eq!(
+            output_json["unsupported_map"][&test_path.to_str().unwrap()],
+            serde_json::json!([{
+                "url": "slack://channel?id=123",
+                "scheme": "slack",
+                "span": { "line": 1, "column": 1 }
+            }])
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_resolve_paths() {
         let mut cmd = main_command();
@@ -280,155 +800,526 @@ mod cli {
             .stdout(contains("4 OK"));
     }
 
-    #[test]
-    fn test_youtube_quirk() {
-        let url = "https://www.youtube.com/watch?v=NlKuICiT470&list=PLbWDhxwM_45mPVToqaIZNbZeIzFchsKKQ&index=7";
+    #[test]
+    fn test_root_dir() {
+        let root_dir = fixtures_path().join("root_dir");
+
+        main_command()
+            .arg("--root-dir")
+            .arg(&root_dir)
+            .arg(root_dir.join("index.md"))
+            .env_clear()
+            .assert()
+            .failure()
+            .code(2)
+            .stdout(contains("4 Total"))
+            .stdout(contains("2 OK"))
+            .stdout(contains("2 Errors"))
+            .stdout(contains(
+                "TEST.md | Failed: Link leads outside of the root directory",
+            ))
+            .stdout(contains(
+                "/etc/passwd | Failed: Link leads outside of the root directory",
+            ));
+    }
+
+    #[test]
+    fn test_check_relative_only() {
+        let mut cmd = main_command();
+        let dir = fixtures_path().join("relative_only");
+
+        cmd.arg("--check-relative-only")
+            .arg("--include-fragments")
+            .arg(dir.join("index.md"))
+            .env_clear()
+            .assert()
+            .success()
+            .stdout(contains("4 Total"))
+            .stdout(contains("2 OK"))
+            .stdout(contains("2 Excluded"));
+    }
+
+    #[test]
+    fn test_youtube_quirk() {
+        let url = "https://www.youtube.com/watch?v=NlKuICiT470&list=PLbWDhxwM_45mPVToqaIZNbZeIzFchsKKQ&index=7";
+
+        main_command()
+            .write_stdin(url)
+            .arg("--verbose")
+            .arg("--no-progress")
+            .arg("-")
+            .assert()
+            .success()
+            .stdout(contains("1 Total"))
+            .stdout(contains("1 OK"));
+    }
+
+    #[test]
+    fn test_crates_io_quirk() {
+        let url = "https://crates.io/crates/lychee";
+
+        main_command()
+            .write_stdin(url)
+            .arg("--verbose")
+            .arg("--no-progress")
+            .arg("-")
+            .assert()
+            .success()
+            .stdout(contains("1 Total"))
+            .stdout(contains("1 OK"));
+    }
+
+    #[test]
+    // Exclude Twitter links because they require login to view tweets.
+    // https://techcrunch.com/2023/06/30/twitter-now-requires-an-account-to-view-tweets/
+    // https://github.com/zedeus/nitter/issues/919
+    fn test_ignored_hosts() {
+        let url = "https://twitter.com/zarfeblong/status/1339742840142872577";
+
+        main_command()
+            .write_stdin(url)
+            .arg("--verbose")
+            .arg("--no-progress")
+            .arg("-")
+            .assert()
+            .success()
+            .stdout(contains("1 Total"))
+            .stdout(contains("1 Excluded"));
+    }
+
+    #[tokio::test]
+    async fn test_failure_404_link() -> Result<()> {
+        let mock_server = mock_server!(StatusCode::NOT_FOUND);
+        let dir = tempfile::tempdir()?;
+        let file_path = dir.path().join("test.txt");
+        let mut file = File::create(&file_path)?;
+        writeln!(file, "{}", mock_server.uri())?;
+
+        let mut cmd = main_command();
+        cmd.arg(file_path)
+            .write_stdin(mock_server.uri())
+            .assert()
+            .failure()
+            .code(2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_schemes() {
+        let mut cmd = main_command();
+        let test_schemes_path = fixtures_path().join("TEST_SCHEMES.md");
+
+        cmd.arg(test_schemes_path)
+            .arg("--scheme")
+            .arg("https")
+            .arg("--scheme")
+            .arg("http")
+            .env_clear()
+            .assert()
+            .success()
+            .stdout(contains("3 Total"))
+            .stdout(contains("2 OK"))
+            .stdout(contains("1 Excluded"));
+    }
+
+    #[test]
+    fn test_caching_single_file() {
+        let mut cmd = main_command();
+        // Repetitions in one file shall all be checked and counted only once.
+        let test_schemes_path_1 = fixtures_path().join("TEST_REPETITION_1.txt");
+
+        cmd.arg(&test_schemes_path_1)
+            .env_clear()
+            .assert()
+            .success()
+            .stdout(contains("1 Total"))
+            .stdout(contains("1 OK"));
+    }
+
+    #[test]
+    // Test that two identical requests don't get executed twice.
+    fn test_caching_across_files() -> Result<()> {
+        // Repetitions across multiple files shall all be checked only once.
+        let repeated_uris = fixtures_path().join("TEST_REPETITION_*.txt");
+
+        test_json_output!(
+            repeated_uris,
+            MockResponseStats {
+                total: 2,
+                cached: 1,
+                successful: 2,
+                excludes: 0,
+                ..MockResponseStats::default()
+            },
+            // Two requests to the same URI may be executed in parallel. As a
+            // result, the response might not be cached and the test would be
+            // flaky. Therefore limit the concurrency to one request at a time.
+            "--max-concurrency",
+            "1"
+        )
+    }
+
+    #[test]
+    fn test_failure_github_404_no_token() {
+        let mut cmd = main_command();
+        let test_github_404_path = fixtures_path().join("TEST_GITHUB_404.md");
+
+        cmd.arg(test_github_404_path)
+            .arg("--no-progress")
+            .env_clear()
+            .assert()
+            .failure()
+            .code(2)
+            .stdout(contains(
+                "✗ [404 Not Found] https://github.com/mre/idiomatic-rust-doesnt-exist-man | Failed: Network error: Not Found"
+            ))
+            .stderr(contains(
+                "There were issues with GitHub URLs. You could try setting a GitHub token and running lychee again.",
+            ));
+    }
+
+    #[tokio::test]
+    async fn test_stdin_input() {
+        let mut cmd = main_command();
+        let mock_server = mock_server!(StatusCode::OK);
+
+        cmd.arg("-")
+            .write_stdin(mock_server.uri())
+            .assert()
+            .success();
+    }
+
+    #[tokio::test]
+    async fn test_stdin_base() -> Result<()> {
+        let mock_server = wiremock::MockServer::start().await;
+        Mock::given(wiremock::matchers::path("/docs/guide.html"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        main_command()
+            .arg("--stdin-base")
+            .arg(format!("{}/docs/index.html", mock_server.uri()))
+            .arg("-")
+            .write_stdin(r#"<a href="guide.html">Guide</a>"#)
+            .assert()
+            .success()
+            .stdout(contains("1 Total"))
+            .stdout(contains("1 OK"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rotate_user_agents() -> Result<()> {
+        let mock_server = mock_server!(StatusCode::OK);
+        let dir = tempfile::tempdir()?;
+        let user_agents = dir.path().join("user-agents.txt");
+        fs::write(&user_agents, "# Browsers\nagent-a\n\nagent-b\n")?;
+
+        main_command()
+            .arg("--rotate-user-agents")
+            .arg(&user_agents)
+            .arg("-")
+            .write_stdin(format!("{0}/1 {0}/2 {0}/3 {0}/4", mock_server.uri()))
+            .assert()
+            .success()
+            .stdout(contains("4 OK"));
+
+        let used: HashSet<_> = mock_server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .filter_map(|request| request.headers.get("user-agent"))
+            .map(|user_agent| user_agent.to_str().unwrap().to_string())
+            .collect();
+        assert_eq!(
+            used,
+            HashSet::from(["agent-a".to_string(), "agent-b".to_string()])
+        );
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_transform_command() -> Result<()> {
+        let mock_server = wiremock::MockServer::start().await;
+        Mock::given(wiremock::matchers::path("/DOCS/PAGE"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+
+        // Uppercase the path, and drop links to `/skip`
+        let command = r#"awk -F/ '$4 == "skip" { print ""; next } { for (i = 4; i <= NF; i++) $i = toupper($i) } 1' OFS=/"#;
+        main_command()
+            .arg("--transform-command")
+            .arg(command)
+            .arg("--verbose")
+            .arg("-")
+            .write_stdin(format!("{0}/docs/page {0}/skip", mock_server.uri()))
+            .assert()
+            .success()
+            .stdout(contains("1 Total"))
+            .stdout(contains("1 OK"))
+            .stderr(contains(format!("{}/DOCS/PAGE", mock_server.uri())));
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_transform_command_failure_is_reported_per_link() -> Result<()> {
+        main_command()
+            .arg("--transform-command")
+            .arg("exit 1")
+            .arg("--no-progress")
+            .arg("-")
+            .write_stdin("https://example.com/a https://example.com/b")
+            .assert()
+            .failure()
+            .code(2)
+            .stdout(contains("2 Total"))
+            .stdout(contains("2 Errors"))
+            .stdout(contains(
+                "Transform command `exit 1` failed for `https://example.com/a`",
+            ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_changed_since() -> Result<()> {
+        let dir = fixtures_path().join("changed_since");
+        let dump = |args: &[&str]| -> Result<String> {
+            let output = main_command()
+                .arg("--dump")
+                .arg("--changed-since")
+                .arg("2024-01-01")
+                .args(args)
+                .arg(&dir)
+                .output()?;
+            assert!(output.status.success());
+            Ok(String::from_utf8(output.stdout)?)
+        };
+
+        let links = dump(&[])?;
+        assert!(links.contains("https://example.com/recent"));
+        assert!(links.contains("https://example.com/undated"));
+        assert!(!links.contains("https://example.com/old"));
+
+        let links = dump(&["--skip-undated"])?;
+        assert!(links.contains("https://example.com/recent"));
+        assert!(!links.contains("https://example.com/undated"));
+        assert!(!links.contains("https://example.com/old"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay() -> Result<()> {
+        let mock_server = wiremock::MockServer::start().await;
+        Mock::given(wiremock::matchers::path("/ok"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+        Mock::given(wiremock::matchers::path("/broken"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+        let links = format!("{0}/ok {0}/broken", mock_server.uri());
+        let dir = tempfile::tempdir()?;
+
+        let run = |mode: &str| -> Result<Value> {
+            let output = main_command()
+                .arg(mode)
+                .arg(dir.path())
+                .arg("--format")
+                .arg("json")
+                .arg("-")
+                .write_stdin(links.clone())
+                .output()?;
+            assert_eq!(output.status.code(), Some(2));
+            let mut report: Value = serde_json::from_slice(&output.stdout)?;
+            report["duration_secs"] = Value::Null;
+            Ok(report)
+        };
+
+        let recorded = run("--record")?;
+        assert_eq!(fs::read_dir(dir.path())?.count(), 2);
+        drop(mock_server);
+        let replayed = run("--replay")?;
+
+        assert_eq!(recorded["successful"], 1);
+        assert_eq!(recorded["errors"], 1);
+        assert_eq!(recorded, replayed);
+
+        Ok(())
+    }
+
+    /// Mount a mock which responds with `429 Too Many Requests` to the first
+    /// `throttled` requests and with `200 OK` afterwards
+    async fn throttling_mock_server(throttled: u64) -> wiremock::MockServer {
+        let mock_server = wiremock::MockServer::start().await;
+        Mock::given(wiremock::matchers::method("GET"))
+            // Retry-After is clamped to the backoff, which is zero below
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "60"))
+            .up_to_n_times(throttled)
+            .mount(&mock_server)
+            .await;
+        Mock::given(wiremock::matchers::method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+        mock_server
+    }
+
+    #[tokio::test]
+    async fn test_retry() -> Result<()> {
+        let mock_server = throttling_mock_server(2).await;
+        main_command()
+            .arg("--max-retries")
+            .arg("0")
+            .arg("--retry-wait-time")
+            .arg("0")
+            .arg("-")
+            .write_stdin(mock_server.uri())
+            .assert()
+            .failure()
+            .code(2)
+            .stdout(contains("[429 Too Many Requests]"));
 
+        let mock_server = throttling_mock_server(2).await;
         main_command()
-            .write_stdin(url)
-            .arg("--verbose")
-            .arg("--no-progress")
+            .arg("--max-retries")
+            .arg("2")
+            .arg("--retry-wait-time")
+            .arg("0")
             .arg("-")
+            .write_stdin(mock_server.uri())
+            .timeout(std::time::Duration::from_secs(10))
             .assert()
             .success()
-            .stdout(contains("1 Total"))
             .stdout(contains("1 OK"));
+
+        Ok(())
     }
 
-    #[test]
-    fn test_crates_io_quirk() {
-        let url = "https://crates.io/crates/lychee";
+    #[tokio::test]
+    async fn test_max_urls() -> Result<()> {
+        let mock_server = mock_server!(StatusCode::OK);
+        let urls: Vec<_> = (1..=5)
+            .map(|i| format!("{}/{i}", mock_server.uri()))
+            .collect();
 
         main_command()
-            .write_stdin(url)
-            .arg("--verbose")
-            .arg("--no-progress")
+            .arg("--max-urls")
+            .arg("3")
             .arg("-")
+            .write_stdin(urls.join("\n"))
             .assert()
             .success()
-            .stdout(contains("1 Total"))
-            .stdout(contains("1 OK"));
-    }
-
-    #[test]
-    // Exclude Twitter links because they require login to view tweets.
-    // https://techcrunch.com/2023/06/30/twitter-now-requires-an-account-to-view-tweets/
-    // https://github.com/zedeus/nitter/issues/919
-    fn test_ignored_hosts() {
-        let url = "https://twitter.com/zarfeblong/status/1339742840142872577";
+            .stdout(contains("3 Total"))
+            .stdout(contains("3 OK"))
+            .stderr(contains("Reached the limit of 3 unique URLs"));
 
         main_command()
-            .write_stdin(url)
-            .arg("--verbose")
-            .arg("--no-progress")
+            .arg("--max-urls")
+            .arg("5")
             .arg("-")
+            .write_stdin(urls.join("\n"))
             .assert()
             .success()
-            .stdout(contains("1 Total"))
-            .stdout(contains("1 Excluded"));
+            .stdout(contains("5 Total"))
+            .stderr(contains("Reached the limit").not());
+
+        Ok(())
     }
 
     #[tokio::test]
-    async fn test_failure_404_link() -> Result<()> {
-        let mock_server = mock_server!(StatusCode::NOT_FOUND);
-        let dir = tempfile::tempdir()?;
-        let file_path = dir.path().join("test.txt");
-        let mut file = File::create(&file_path)?;
-        writeln!(file, "{}", mock_server.uri())?;
+    async fn test_ignore_error_status() -> Result<()> {
+        let mock_server = mock_server!(StatusCode::TOO_MANY_REQUESTS);
 
-        let mut cmd = main_command();
-        cmd.arg(file_path)
+        main_command()
+            .arg("--ignore-error-status")
+            .arg("429")
+            .arg("--max-retries")
+            .arg("0")
+            .arg("-")
             .write_stdin(mock_server.uri())
             .assert()
-            .failure()
-            .code(2);
+            .success()
+            .stdout(contains("1 Error"))
+            .stdout(contains("[429 Too Many Requests]"));
 
         Ok(())
     }
 
-    #[test]
-    fn test_schemes() {
-        let mut cmd = main_command();
-        let test_schemes_path = fixtures_path().join("TEST_SCHEMES.md");
-
-        cmd.arg(test_schemes_path)
-            .arg("--scheme")
-            .arg("https")
-            .arg("--scheme")
-            .arg("http")
-            .env_clear()
-            .assert()
-            .success()
-            .stdout(contains("3 Total"))
-            .stdout(contains("2 OK"))
-            .stdout(contains("1 Excluded"));
-    }
-
-    #[test]
-    fn test_caching_single_file() {
-        let mut cmd = main_command();
-        // Repetitions in one file shall all be checked and counted only once.
-        let test_schemes_path_1 = fixtures_path().join("TEST_REPETITION_1.txt");
-
-        cmd.arg(&test_schemes_path_1)
-            .env_clear()
-            .assert()
-            .success()
-            .stdout(contains("1 Total"))
-            .stdout(contains("1 OK"));
-    }
+    #[tokio::test]
+    async fn test_reason_phrase_in_all_formats() -> Result<()> {
+        let mock_server = mock_server!(StatusCode::IM_A_TEAPOT);
+
+        for format in ["compact", "detailed", "markdown"] {
+            main_command()
+                .arg("--format")
+                .arg(format)
+                .arg("-")
+                .write_stdin(mock_server.uri())
+                .assert()
+                .failure()
+                .stdout(contains("418 I'm a teapot"));
+        }
 
-    #[test]
-    // Test that two identical requests don't get executed twice.
-    fn test_caching_across_files() -> Result<()> {
-        // Repetitions across multiple files shall all be checked only once.
-        let repeated_uris = fixtures_path().join("TEST_REPETITION_*.txt");
+        let output = main_command()
+            .arg("--format")
+            .arg("json")
+            .arg("-")
+            .write_stdin(mock_server.uri())
+            .output()?;
+        let report: Value = serde_json::from_slice(&output.stdout)?;
+        let status = &report["fail_map"]["stdin"][0]["status"];
+        assert_eq!(status["code"], 418);
+        assert_eq!(status["reason"], "I'm a teapot");
 
-        test_json_output!(
-            repeated_uris,
-            MockResponseStats {
-                total: 2,
-                cached: 1,
-                successful: 2,
-                excludes: 0,
-                ..MockResponseStats::default()
-            },
-            // Two requests to the same URI may be executed in parallel. As a
-            // result, the response might not be cached and the test would be
-            // flaky. Therefore limit the concurrency to one request at a time.
-            "--max-concurrency",
-            "1"
-        )
+        Ok(())
     }
 
     #[test]
-    fn test_failure_github_404_no_token() {
-        let mut cmd = main_command();
-        let test_github_404_path = fixtures_path().join("TEST_GITHUB_404.md");
+    fn test_connection_refused() -> Result<()> {
+        // Bind to a free port and close it again, so that nothing listens there
+        let port = std::net::TcpListener::bind("127.0.0.1:0")?
+            .local_addr()?
+            .port();
+        let url = format!("http://127.0.0.1:{port}");
+
+        let output = main_command()
+            .arg("--format")
+            .arg("json")
+            .arg("-")
+            .write_stdin(url.clone())
+            .output()?;
+        let report: Value = serde_json::from_slice(&output.stdout)?;
+        assert_eq!(report["refused"], 1);
+        assert_eq!(report["errors"], 1);
+        assert_eq!(report["timeouts"], 0);
+        let status = &report["fail_map"]["stdin"][0]["status"];
+        assert_eq!(status["code"], 0);
+        assert_eq!(status["error_kind"], "connection_refused");
 
-        cmd.arg(test_github_404_path)
-            .arg("--no-progress")
-            .env_clear()
+        main_command()
+            .arg("--format")
+            .arg("detailed")
+            .arg("-")
+            .write_stdin(url)
             .assert()
             .failure()
-            .code(2)
-            .stdout(contains(
-                "✗ [404] https://github.com/mre/idiomatic-rust-doesnt-exist-man | Failed: Network error: Not Found"
-            ))
-            .stderr(contains(
-                "There were issues with GitHub URLs. You could try setting a GitHub token and running lychee again.",
-            ));
-    }
-
-    #[tokio::test]
-    async fn test_stdin_input() {
-        let mut cmd = main_command();
-        let mock_server = mock_server!(StatusCode::OK);
+            .stdout(contains("000 Connection refused"))
+            .stdout(contains("Refused..........1"));
 
-        cmd.arg("-")
-            .write_stdin(mock_server.uri())
-            .assert()
-            .success();
+        Ok(())
     }
 
     #[tokio::test]
@@ -537,6 +1428,36 @@ mod cli {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_glob_brace_expansion() -> Result<()> {
+        let mut cmd = main_command();
+
+        let dir = tempfile::tempdir()?;
+        let mock_server_guide = mock_server!(StatusCode::OK);
+        let mock_server_api = mock_server!(StatusCode::OK);
+        let mock_server_blog = mock_server!(StatusCode::OK);
+        for (subdir, mock_server) in [
+            ("guide/basics", &mock_server_guide),
+            ("api", &mock_server_api),
+            ("blog", &mock_server_blog),
+        ] {
+            fs::create_dir_all(dir.path().join(subdir))?;
+            let mut file = File::create(dir.path().join(subdir).join("index.md"))?;
+            writeln!(file, "{}", mock_server.uri().as_str())?;
+        }
+
+        cmd.arg(dir.path().join("{guide,api}/**/*.md"))
+            .arg("--verbose")
+            .assert()
+            .success()
+            .stdout(contains("2 Total"))
+            .stderr(contains(mock_server_guide.uri()))
+            .stderr(contains(mock_server_api.uri()))
+            .stderr(contains(mock_server_blog.uri()).not());
+
+        Ok(())
+    }
+
     /// Test formatted file output
     #[test]
     fn test_formatted_file_output() -> Result<()> {
@@ -577,6 +1498,173 @@ mod cli {
         Ok(())
     }
 
+    /// Results written with `--output-append` must survive a killed run
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_output_append_keeps_partial_results() -> Result<()> {
+        let fast = mock_server!(StatusCode::OK);
+        let slow = mock_server!(
+            StatusCode::OK,
+            set_delay(std::time::Duration::from_secs(60))
+        );
+        let dir = tempfile::tempdir()?;
+        let outfile = dir.path().join("results.jsonl");
+
+        let mut child =
+            std::process::Command::new(assert_cmd::cargo::cargo_bin(env!("CARGO_PKG_NAME")))
+                .arg("--output")
+                .arg(&outfile)
+                .arg("--output-append")
+                .arg("--format")
+                .arg("json")
+                .arg("--timeout")
+                .arg("120")
+                .arg("-")
+                .stdin(std::process::Stdio::piped())
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .spawn()?;
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(format!("{}\n{}\n", fast.uri(), slow.uri()).as_bytes())?;
+
+        // Wait for the fast link to be reported, then kill the run
+        for _ in 0..100 {
+            if !fs::read_to_string(&outfile).unwrap_or_default().is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        child.kill()?;
+        child.wait()?;
+
+        let output = fs::read_to_string(&outfile)?;
+        let lines = output
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<std::result::Result<Vec<Value>, _>>()?;
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0]["url"], format!("{}/", fast.uri()));
+        assert_eq!(lines[0]["source"], "stdin");
+
+        // A run which finishes normally replaces the previous results and
+        // ends with the final report on a single line
+        main_command()
+            .arg("--output")
+            .arg(&outfile)
+            .arg("--output-append")
+            .arg("--format")
+            .arg("json")
+            .arg("-")
+            .write_stdin(fast.uri())
+            .assert()
+            .success();
+
+        let output = fs::read_to_string(&outfile)?;
+        let lines = output
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<std::result::Result<Vec<Value>, _>>()?;
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["url"], format!("{}/", fast.uri()));
+        assert_eq!(lines[1]["total"], 1);
+        assert_eq!(lines[1]["successful"], 1);
+        Ok(())
+    }
+
+    /// Every result must be sent to the `--stream-to` socket as a JSON line
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stream_to_unix_socket() -> Result<()> {
+        use std::io::Read;
+        use std::os::unix::net::UnixListener;
+
+        let ok = mock_server!(StatusCode::OK);
+        let not_found = mock_server!(StatusCode::NOT_FOUND);
+        let dir = tempfile::tempdir()?;
+        let socket = dir.path().join("lychee.sock");
+
+        let listener = UnixListener::bind(&socket)?;
+        let reader = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut events = String::new();
+            stream.read_to_string(&mut events).unwrap();
+            events
+        });
+
+        main_command()
+            .arg("--stream-to")
+            .arg(&socket)
+            .arg("-")
+            .write_stdin(format!("{}\n{}\n", ok.uri(), not_found.uri()))
+            .assert()
+            .failure()
+            .code(2);
+
+        let events = reader.join().unwrap();
+        let events = events
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<std::result::Result<Vec<Value>, _>>()?;
+        assert_eq!(events.len(), 2);
+
+        let mut urls: Vec<_> = events.iter().map(|e| e["url"].clone()).collect();
+        urls.sort_by_key(ToString::to_string);
+        let mut expected = vec![
+            Value::from(format!("{}/", ok.uri())),
+            Value::from(format!("{}/", not_found.uri())),
+        ];
+        expected.sort_by_key(ToString::to_string);
+        assert_eq!(urls, expected);
+        assert!(events.iter().all(|e| e["source"] == "stdin"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stream_to_unavailable_socket() -> Result<()> {
+        let mock_server = mock_server!(StatusCode::OK);
+        let dir = tempfile::tempdir()?;
+
+        main_command()
+            .arg("--stream-to")
+            .arg(dir.path().join("missing.sock"))
+            .arg("-")
+            .write_stdin(mock_server.uri())
+            .assert()
+            .success()
+            .stderr(contains("Continuing without streaming"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_deadline_cancels_in_flight_requests() -> Result<()> {
+        let fast = mock_server!(StatusCode::OK);
+        let hanging = mock_server!(
+            StatusCode::OK,
+            set_delay(std::time::Duration::from_secs(60))
+        );
+
+        let start = std::time::Instant::now();
+        main_command()
+            .arg("--deadline")
+            .arg("2")
+            .arg("--timeout")
+            .arg("120")
+            .arg("-")
+            .write_stdin(format!("{}\n{}", fast.uri(), hanging.uri()))
+            .assert()
+            .failure()
+            .code(2)
+            .stdout(contains("1 OK"))
+            .stderr(contains("Deadline of 2s reached"));
+
+        // The hanging request is dropped instead of waiting for the timeout
+        assert!(start.elapsed() < std::time::Duration::from_secs(20));
+        Ok(())
+    }
+
     /// Test excludes
     #[test]
     fn test_exclude_wildcard() -> Result<()> {
@@ -593,19 +1681,67 @@ mod cli {
         Ok(())
     }
 
-    #[test]
-    fn test_exclude_multiple_urls() -> Result<()> {
+    #[test]
+    fn test_exclude_multiple_urls() -> Result<()> {
+        let mut cmd = main_command();
+        let test_path = fixtures_path().join("TEST.md");
+
+        cmd.arg(test_path)
+            .arg("--exclude")
+            .arg("https://en.wikipedia.org/*")
+            .arg("--exclude")
+            .arg("https://ldra.com/")
+            .assert()
+            .success()
+            .stdout(contains("4 Excluded"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_include_allowlist() -> Result<()> {
+        let mock_server = mock_server!(StatusCode::OK);
+        let server = mock_server.uri();
+        let mut cmd = main_command();
+
+        // Only links matching an include pattern get checked
+        cmd.arg("-")
+            .arg("--include")
+            .arg("/docs/")
+            .write_stdin(format!(
+                "{server}/docs/guide {server}/blog/post {server}/about"
+            ))
+            .assert()
+            .success()
+            .stdout(contains("3 Total"))
+            .stdout(contains("1 OK"))
+            .stdout(contains("2 Excluded"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_strict_include() -> Result<()> {
+        let mock_server = mock_server!(StatusCode::OK);
+        let server = mock_server.uri();
         let mut cmd = main_command();
-        let test_path = fixtures_path().join("TEST.md");
 
-        cmd.arg(test_path)
-            .arg("--exclude")
-            .arg("https://en.wikipedia.org/*")
+        // Excludes win over includes, and links matching no include are
+        // excluded even though an exclude is given
+        cmd.arg("-")
+            .arg("--strict-include")
+            .arg("--include")
+            .arg("/docs/")
             .arg("--exclude")
-            .arg("https://ldra.com/")
+            .arg("/drafts/")
+            .write_stdin(format!(
+                "{server}/docs/guide {server}/docs/drafts/new {server}/about"
+            ))
             .assert()
             .success()
-            .stdout(contains("4 Excluded"));
+            .stdout(contains("3 Total"))
+            .stdout(contains("1 OK"))
+            .stdout(contains("2 Excluded"));
 
         Ok(())
     }
@@ -752,6 +1888,57 @@ mod cli {
             .success();
     }
 
+    #[tokio::test]
+    async fn test_accept_dash_range() {
+        let mock_server = mock_server!(StatusCode::FORBIDDEN);
+        main_command()
+            .arg("--accept")
+            .arg("200-299,403")
+            .arg("-")
+            .write_stdin(mock_server.uri())
+            .env_clear()
+            .assert()
+            .success()
+            .stdout(contains("1 OK"));
+
+        main_command()
+            .arg("--accept")
+            .arg("204-200")
+            .arg("-")
+            .write_stdin(mock_server.uri())
+            .env_clear()
+            .assert()
+            .failure()
+            .stderr(contains(
+                "invalid range indices, the start must not be larger than the end",
+            ));
+    }
+
+    #[tokio::test]
+    async fn test_accept_pattern() {
+        let api_server = mock_server!(StatusCode::FORBIDDEN);
+        let other_server = mock_server!(StatusCode::FORBIDDEN);
+
+        // Both servers run on localhost, so the port tells them apart.
+        // The trailing slash prevents matching ports with the same prefix.
+        main_command()
+            .arg("--accept-pattern")
+            .arg(format!("{}/:401,403", api_server.uri()))
+            .arg("-")
+            .write_stdin(format!("{}\n{}", api_server.uri(), other_server.uri()))
+            .env_clear()
+            .assert()
+            .failure()
+            .code(2)
+            .stdout(contains("2 Total"))
+            .stdout(contains("1 OK"))
+            .stdout(contains("1 Error"))
+            .stdout(contains(format!(
+                "{}/ | Failed: Network error: Forbidden",
+                other_server.uri()
+            )));
+    }
+
     #[test]
     fn test_lycheeignore_file() -> Result<()> {
         let mut cmd = main_command();
@@ -828,9 +2015,9 @@ mod cli {
         // run first without cache to generate the cache file
         test_cmd
             .assert()
-            .stderr(contains(format!("[200] {}/\n", mock_server_ok.uri())))
+            .stderr(contains(format!("[200 OK] {}/\n", mock_server_ok.uri())))
             .stderr(contains(format!(
-                "[404] {}/ | Failed: Network error: Not Found\n",
+                "[404 Not Found] {}/ | Failed: Network error: Not Found\n",
                 mock_server_err.uri()
             )));
 
@@ -843,11 +2030,11 @@ mod cli {
         test_cmd
             .assert()
             .stderr(contains(format!(
-                "[200] {}/ | Cached: OK (cached)\n",
+                "[200 OK] {}/ | Cached: OK (cached)\n",
                 mock_server_ok.uri()
             )))
             .stderr(contains(format!(
-                "[404] {}/ | Cached: Error (cached)\n",
+                "[404 Not Found] {}/ | Cached: Error (cached)\n",
                 mock_server_err.uri()
             )));
 
@@ -876,58 +2063,472 @@ mod cli {
         writeln!(file, "{}", mock_server_teapot.uri().as_str())?;
         writeln!(file, "{}", mock_server_server_error.uri().as_str())?;
 
-        let mut cmd = main_command();
-        let test_cmd = cmd
-            .current_dir(&base_path)
-            .arg(dir.path().join("c.md"))
-            .arg("--verbose")
-            .arg("--cache");
+        let mut cmd = main_command();
+        let test_cmd = cmd
+            .current_dir(&base_path)
+            .arg(dir.path().join("c.md"))
+            .arg("--verbose")
+            .arg("--cache");
+
+        assert!(
+            !cache_file.exists(),
+            "cache file should not exist before this test"
+        );
+
+        // run first without cache to generate the cache file
+        // ignore exit code
+        test_cmd
+            .assert()
+            .failure()
+            .code(2)
+            .stdout(contains(format!(
+                "[418 I'm a teapot] {}/ | Failed: Network error: I\'m a teapot",
+                mock_server_teapot.uri()
+            )))
+            .stdout(contains(format!(
+                "[500 Internal Server Error] {}/ | Failed: Network error: Internal Server Error",
+                mock_server_server_error.uri()
+            )));
+
+        // check content of cache file
+        let data = fs::read_to_string(&cache_file)?;
+        assert!(data.contains(&format!("{}/,200", mock_server_ok.uri())));
+        assert!(data.contains(&format!("{}/,418", mock_server_teapot.uri())));
+        assert!(data.contains(&format!("{}/,500", mock_server_server_error.uri())));
+
+        // run again to verify cache behavior
+        // this time accept 418 and 500 as valid status codes
+        test_cmd
+            .arg("--no-progress")
+            .arg("--accept")
+            .arg("418,500")
+            .assert()
+            .success()
+            .stderr(contains(format!(
+                "[418 I'm a teapot] {}/ | Cached: OK (cached)",
+                mock_server_teapot.uri()
+            )))
+            .stderr(contains(format!(
+                "[500 Internal Server Error] {}/ | Cached: OK (cached)",
+                mock_server_server_error.uri()
+            )));
+
+        // clear the cache file
+        fs::remove_file(&cache_file)?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_inconsistent_statuses() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mock_server = wiremock::MockServer::start().await;
+        // Only the first request succeeds
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .respond_with(wiremock::ResponseTemplate::new(StatusCode::OK))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .respond_with(wiremock::ResponseTemplate::new(StatusCode::NOT_FOUND))
+            .mount(&mock_server)
+            .await;
+
+        fs::create_dir(dir.path().join("generated"))?;
+        fs::write(dir.path().join("generated/a.md"), mock_server.uri())?;
+        fs::write(dir.path().join("generated/b.md"), mock_server.uri())?;
+
+        // Results of excluded paths are not cached, so the link gets checked
+        // for each input
+        main_command()
+            .current_dir(dir.path())
+            .arg("generated/a.md")
+            .arg("generated/b.md")
+            .arg("--cache-exclude-path")
+            .arg("generated/*")
+            .assert()
+            .failure()
+            .stderr(contains(format!(
+                "{}/ got different statuses across inputs: 200 OK in generated/",
+                mock_server.uri()
+            )))
+            .stderr(contains("; 404 Not Found in generated/"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_inconsistent_statuses_of_concurrent_checks() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mock_server = wiremock::MockServer::start().await;
+        // Only the first request succeeds. It is answered slowly, so that the
+        // link is checked again for the other input before its result is
+        // cached.
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .respond_with(
+                wiremock::ResponseTemplate::new(StatusCode::OK)
+                    .set_delay(std::time::Duration::from_millis(500)),
+            )
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .respond_with(wiremock::ResponseTemplate::new(StatusCode::NOT_FOUND))
+            .mount(&mock_server)
+            .await;
+
+        fs::write(dir.path().join("a.md"), mock_server.uri())?;
+        fs::write(dir.path().join("b.md"), mock_server.uri())?;
+
+        main_command()
+            .current_dir(dir.path())
+            .arg("a.md")
+            .arg("b.md")
+            .assert()
+            .failure()
+            .stderr(contains(format!(
+                "{}/ got different statuses across inputs: 200 OK in ",
+                mock_server.uri()
+            )))
+            .stderr(contains("; 404 Not Found in "));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lycheecache_exclude_path() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let cache_file = dir.path().join(LYCHEE_CACHE_FILE);
+
+        let mock_server_docs = mock_server!(StatusCode::OK);
+        let mock_server_generated = mock_server!(StatusCode::OK);
+
+        fs::create_dir(dir.path().join("docs"))?;
+        fs::create_dir(dir.path().join("generated"))?;
+        fs::write(dir.path().join("docs/index.md"), mock_server_docs.uri())?;
+        fs::write(
+            dir.path().join("generated/links.md"),
+            mock_server_generated.uri(),
+        )?;
+
+        main_command()
+            .current_dir(dir.path())
+            .arg("docs/index.md")
+            .arg("generated/links.md")
+            .arg("--verbose")
+            .arg("--cache")
+            .arg("--cache-exclude-path")
+            .arg("generated/*")
+            .assert()
+            .success()
+            .stdout(contains("2 OK"));
+
+        // check content of cache file
+        let data = fs::read_to_string(&cache_file)?;
+        assert!(data.contains(&format!("{}/,200", mock_server_docs.uri())));
+        assert!(!data.contains(&mock_server_generated.uri()));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lycheecache_skips_warnings() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let cache_file = dir.path().join(LYCHEE_CACHE_FILE);
+        let mock_server = mock_server!(StatusCode::OK);
+
+        // Warnings must be reported again instead of being served from the
+        // cache as a plain success
+        for _ in 0..2 {
+            main_command()
+                .current_dir(dir.path())
+                .arg("--cache")
+                .arg("--require-external-noopener")
+                .arg("--fail-on-warnings")
+                .arg("--base")
+                .arg(mock_server.uri())
+                .arg(fixtures_path().join("TEST_NOOPENER.html"))
+                .assert()
+                .failure()
+                .stdout(contains("3 OK"))
+                .stdout(contains("1 Warning"));
+        }
+
+        let data = fs::read_to_string(&cache_file)?;
+        assert!(data.contains(&format!("{}/same-tab,200", mock_server.uri())));
+        assert!(!data.contains("tabnabbing"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lycheecache_max_age() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let cache_file = dir.path().join(LYCHEE_CACHE_FILE);
+
+        let mock_server_stale = mock_server!(StatusCode::OK);
+        let mock_server_legacy = mock_server!(StatusCode::OK);
+        let mock_server_fresh = mock_server!(StatusCode::OK);
+
+        // Seed the cache with a stale entry, an entry in the old format
+        // without a timestamp and a fresh entry, all recorded as broken
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs();
+        let mut file = File::create(&cache_file)?;
+        writeln!(file, "{}/,404,{}", mock_server_stale.uri(), now - 2 * 86400)?;
+        writeln!(file, "{}/,404", mock_server_legacy.uri())?;
+        writeln!(file, "{}/,404,{now}", mock_server_fresh.uri())?;
+
+        main_command()
+            .current_dir(dir.path())
+            .arg("--cache")
+            .arg("--max-cache-age")
+            .arg("1d")
+            .arg("--verbose")
+            .arg("-")
+            .write_stdin(format!(
+                "{}\n{}\n{}",
+                mock_server_stale.uri(),
+                mock_server_legacy.uri(),
+                mock_server_fresh.uri()
+            ))
+            .assert()
+            .failure()
+            .stderr(contains(format!("[200 OK] {}/\n", mock_server_stale.uri())))
+            .stderr(contains(format!(
+                "[200 OK] {}/\n",
+                mock_server_legacy.uri()
+            )))
+            .stderr(contains(format!(
+                "[404 Not Found] {}/ | Cached: Error (cached)\n",
+                mock_server_fresh.uri()
+            )));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lycheecache_max_entries() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let cache_file = dir.path().join(LYCHEE_CACHE_FILE);
+
+        // Seed the cache with entries of different age. The last one was
+        // written by an older version without the time of the last access.
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs();
+        let mut file = File::create(&cache_file)?;
+        writeln!(
+            file,
+            "https://oldest.example/,200,{},{}",
+            now - 30,
+            now - 30
+        )?;
+        writeln!(file, "https://older.example/,200,{},{}", now - 20, now - 20)?;
+        writeln!(file, "https://old.example/,200,{}", now - 10)?;
+
+        let mock_server = mock_server!(StatusCode::OK);
+
+        // Serving the oldest entry from the cache makes it the most recently
+        // used one, so that it is kept
+        main_command()
+            .current_dir(dir.path())
+            .arg("--cache")
+            .arg("--max-cache-entries")
+            .arg("3")
+            .arg("-")
+            .write_stdin(format!("{} https://oldest.example/", mock_server.uri()))
+            .assert()
+            .success();
+
+        let data = fs::read_to_string(&cache_file)?;
+        assert_eq!(data.lines().count(), 3);
+        assert!(data.contains(&format!("{}/,200", mock_server.uri())));
+        assert!(data.contains("https://oldest.example/,200"));
+        assert!(data.contains("https://old.example/,200"));
+        assert!(!data.contains("https://older.example/"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_only() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs();
+        let mut file = File::create(dir.path().join(LYCHEE_CACHE_FILE))?;
+        writeln!(file, "https://cached.example/,200,{now}")?;
+
+        main_command()
+            .current_dir(dir.path())
+            .arg("--count-only")
+            .arg("--cache")
+            .arg("--exclude")
+            .arg("excluded")
+            .arg(fixtures_path().join("TEST_COUNT_ONLY.md"))
+            .assert()
+            .success()
+            .stdout(contains(
+                "5 unique links: 2 requests, 1 cached, 1 excluded, 1 without request",
+            ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retry_from() -> Result<()> {
+        let mock_server = wiremock::MockServer::start().await;
+        for path in ["/broken-1", "/broken-2"] {
+            Mock::given(wiremock::matchers::path(path))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+        Mock::given(wiremock::matchers::path("/ok"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let dir = tempfile::tempdir()?;
+        let report = dir.path().join("report.json");
+        let uri = mock_server.uri();
+        fs::write(
+            &report,
+            format!(
+                r#"{{
+                    "total": 3,
+                    "errors": 2,
+                    "success_map": {{"README.md": [{{"url": "{uri}/ok", "status": {{"text": "200 OK"}}}}]}},
+                    "fail_map": {{
+                        "README.md": [{{"url": "{uri}/broken-1", "status": {{"text": "404 Not Found"}}}}],
+                        "docs/guide.md": [{{"url": "{uri}/broken-2", "status": {{"text": "404 Not Found"}}}}]
+                    }}
+                }}"#
+            ),
+        )?;
+
+        main_command()
+            .arg("--retry-from")
+            .arg(&report)
+            .arg("--verbose")
+            .assert()
+            .success()
+            .stdout(contains("2 Total"))
+            .stdout(contains("2 OK"))
+            .stderr(contains(format!("{uri}/broken-1")))
+            .stderr(contains(format!("{uri}/broken-2")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_emit_json_schema() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("schema.json");
+
+        main_command()
+            .arg("--emit-json-schema")
+            .arg(&path)
+            .assert()
+            .success()
+            .stdout(is_empty());
+
+        let schema: Value = serde_json::from_str(&fs::read_to_string(&path)?)?;
+        let properties = &schema["properties"];
+        assert_eq!(properties["total"]["type"], "integer");
+        // Broken links are reported in the `fail_map`
+        assert_eq!(properties["fail_map"]["type"], "object");
+        assert_eq!(properties["warning_map"]["type"], "object");
+        assert!(schema["definitions"]["ResponseBody"]["properties"]["url"].is_object());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_reports() -> Result<()> {
+        let reports = fixtures_path().join("merge_reports");
+
+        let output = main_command()
+            .arg("--merge-reports")
+            .arg(reports.join("shard1.json"))
+            .arg(reports.join("shard2.json"))
+            .output()?;
+        assert_eq!(output.status.code(), Some(2));
+
+        let merged = serde_json::from_slice::<Value>(&output.stdout)?;
+        // The broken link of README.md is part of both reports
+        assert_eq!(merged["total"], 6);
+        assert_eq!(merged["successful"], 4);
+        assert_eq!(merged["errors"], 2);
+        assert_eq!(merged["duration_secs"], 7);
+        assert_eq!(merged["fail_map"]["README.md"].as_array().unwrap().len(), 1);
+        assert_eq!(
+            merged["fail_map"]["docs/guide.md"][0]["url"],
+            "https://example.org/gone"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_preview() -> Result<()> {
+        let test_path = fixtures_path().join("TEST_PREVIEW.md");
+
+        let output = main_command().arg("--preview=2").arg(&test_path).output()?;
+
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains(&format!(
+            "{}:3:31: https://example.com/install\n    | Read the [installation guide](https://example.com/install) first.",
+            test_path.display()
+        )));
+        assert!(stdout.contains("https://example.com/usage"));
+        assert!(!stdout.contains("https://example.com/faq"));
 
-        assert!(
-            !cache_file.exists(),
-            "cache file should not exist before this test"
-        );
+        Ok(())
+    }
 
-        // run first without cache to generate the cache file
-        // ignore exit code
-        test_cmd
+    #[tokio::test]
+    async fn test_syntax_only() -> Result<()> {
+        let mock_server = wiremock::MockServer::start().await;
+        Mock::given(wiremock::matchers::any())
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        main_command()
+            .arg("--syntax-only")
+            .arg(fixtures_path().join("TEST_SYNTAX.md"))
+            .arg("-")
+            .write_stdin(mock_server.uri())
             .assert()
             .failure()
             .code(2)
-            .stdout(contains(format!(
-                "[418] {}/ | Failed: Network error: I\'m a teapot",
-                mock_server_teapot.uri()
-            )))
-            .stdout(contains(format!(
-                "[500] {}/ | Failed: Network error: Internal Server Error",
-                mock_server_server_error.uri()
-            )));
-
-        // check content of cache file
-        let data = fs::read_to_string(&cache_file)?;
-        assert!(data.contains(&format!("{}/,200", mock_server_ok.uri())));
-        assert!(data.contains(&format!("{}/,418", mock_server_teapot.uri())));
-        assert!(data.contains(&format!("{}/,500", mock_server_server_error.uri())));
+            .stdout(contains(
+                "htps://example.com/docs | Unknown scheme `htps`, did you mean `https`?",
+            ))
+            .stdout(contains("https:/example.com/page | Malformed URL"))
+            .stdout(contains(
+                "TEST_SYNTAX.md:7:23: https://example.com:99999/ | Cannot parse URL",
+            ))
+            .stdout(contains("6 links, 3 malformed"));
 
-        // run again to verify cache behavior
-        // this time accept 418 and 500 as valid status codes
-        test_cmd
-            .arg("--no-progress")
-            .arg("--accept")
-            .arg("418,500")
+        // Excluded links are not validated
+        main_command()
+            .arg("--syntax-only")
+            .arg("--exclude")
+            .arg("example.com/page")
+            .arg(fixtures_path().join("TEST_SYNTAX.md"))
             .assert()
-            .success()
-            .stderr(contains(format!(
-                "[418] {}/ | Cached: OK (cached)",
-                mock_server_teapot.uri()
-            )))
-            .stderr(contains(format!(
-                "[500] {}/ | Cached: OK (cached)",
-                mock_server_server_error.uri()
-            )));
-
-        // clear the cache file
-        fs::remove_file(&cache_file)?;
+            .failure()
+            .code(2)
+            .stdout(contains("https:/example.com/page").not())
+            .stdout(contains("4 links, 2 malformed"));
 
         Ok(())
     }
@@ -1056,6 +2657,28 @@ mod cli {
 
         Ok(())
     }
+
+    #[test]
+    fn test_include_code_annotation() -> Result<()> {
+        let mut cmd = main_command();
+        let input = fixtures_path().join("TEST_ANNOTATED_CODE_BLOCKS.md");
+
+        let cmd = cmd
+            .arg("--include-code-annotation")
+            .arg("url")
+            .arg(input)
+            .arg("--dump")
+            .assert()
+            .success()
+            .stdout(contains("http://127.0.0.1/annotated"));
+
+        // Only the annotated code block is included
+        let output = cmd.get_output();
+        let output = std::str::from_utf8(&output.stdout).unwrap();
+        assert_eq!(output.lines().count(), 1);
+
+        Ok(())
+    }
     #[tokio::test]
     async fn test_verbatim_skipped_by_default_via_file() -> Result<()> {
         let file = fixtures_path().join("TEST_VERBATIM.html");
@@ -1077,78 +2700,408 @@ mod cli {
         let body = fs::read_to_string(file)?;
         let mock_server = mock_response!(body);
 
-        cmd.arg("--dump")
-            .arg(mock_server.uri())
+        cmd.arg("--dump")
+            .arg(mock_server.uri())
+            .assert()
+            .success()
+            .stdout(is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_include_verbatim_via_remote_url() -> Result<()> {
+        let mut cmd = main_command();
+        let file = fixtures_path().join("TEST_VERBATIM.html");
+        let body = fs::read_to_string(file)?;
+        let mock_server = mock_response!(body);
+
+        cmd.arg("--include-verbatim")
+            .arg("--dump")
+            .arg(mock_server.uri())
+            .assert()
+            .success()
+            .stdout(contains("http://www.example.com/pre"))
+            .stdout(contains("http://www.example.com/code"))
+            .stdout(contains("http://www.example.com/samp"))
+            .stdout(contains("http://www.example.com/kbd"))
+            .stdout(contains("http://www.example.com/var"))
+            .stdout(contains("http://www.example.com/script"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_max_body_size_via_remote_url() -> Result<()> {
+        let body = format!(
+            r#"<a href="https://example.com/first">first</a>{}<a href="https://example.com/last">last</a>"#,
+            "x".repeat(100_000)
+        );
+        let mock_server = mock_response!(body);
+
+        main_command()
+            .arg("--max-body-size")
+            .arg("1000")
+            .arg("--dump")
+            .arg(mock_server.uri())
+            .assert()
+            .success()
+            .stdout(contains("https://example.com/first"))
+            .stdout(contains("https://example.com/last").not())
+            .stderr(contains("responded with 200 OK"))
+            .stderr(contains("exceeded the maximum body size"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_robots_sitemaps() -> Result<()> {
+        let mock_server = wiremock::MockServer::start().await;
+        let uri = mock_server.uri();
+        Mock::given(wiremock::matchers::path("/robots.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(format!(
+                "User-agent: *\nDisallow:\nSitemap: {uri}/sitemap.xml\n"
+            )))
+            .mount(&mock_server)
+            .await;
+        Mock::given(wiremock::matchers::path("/sitemap.xml"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url><loc>{uri}/first</loc></url>
+  <url><loc>{uri}/second</loc></url>
+</urlset>"#
+            )))
+            .mount(&mock_server)
+            .await;
+        for page in ["/first", "/second"] {
+            Mock::given(wiremock::matchers::path(page))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+
+        main_command()
+            .arg("--robots-sitemaps")
+            .arg(&uri)
+            .assert()
+            .success()
+            .stdout(contains("2 Total"));
+
+        // Both pages were requested exactly once
+        mock_server.verify().await;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_extract_timeout_via_remote_url() -> Result<()> {
+        // Send the headers and the start of a large body, then stall. The
+        // server runs on its own thread, as the test runtime is blocked while
+        // lychee runs.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let port = listener.local_addr()?.port();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let _ = stream.read(&mut [0; 1024]);
+                let _ = stream.write_all(
+                    b"HTTP/1.1 200 OK\r\n\
+                      Content-Type: text/html\r\n\
+                      Content-Length: 1000000\r\n\r\n\
+                      <a href=\"https://example.com/first\">first</a>",
+                );
+                std::thread::sleep(std::time::Duration::from_secs(30));
+            }
+        });
+
+        main_command()
+            .arg("--timeout")
+            .arg("60")
+            .arg("--extract-timeout")
+            .arg("1")
+            .arg("--dump")
+            .arg(format!("http://127.0.0.1:{port}/"))
+            .timeout(std::time::Duration::from_secs(10))
+            .assert()
+            .success()
+            .stdout(contains("https://example.com/first"))
+            .stderr(contains("responded with 200 OK"))
+            .stderr(contains("timed out"));
+
+        // Without a response, the input can't be read at all
+        let mock_server = mock_server!(
+            StatusCode::OK,
+            set_delay(std::time::Duration::from_secs(30))
+        );
+        main_command()
+            .arg("--extract-timeout")
+            .arg("1")
+            .arg("--dump")
+            .arg(mock_server.uri())
+            .timeout(std::time::Duration::from_secs(10))
+            .assert()
+            .failure()
+            .stderr(contains("Network error"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_require_https() -> Result<()> {
+        let mut cmd = main_command();
+        let test_path = fixtures_path().join("TEST_HTTP.html");
+        cmd.arg(&test_path).assert().success();
+
+        let mut cmd = main_command();
+        cmd.arg("--require-https").arg(test_path).assert().failure();
+
+        Ok(())
+    }
+
+    /// If base-dir is not set, don't throw an error in case we encounter
+    /// an absolute local link within a file (e.g. `/about`).
+    #[test]
+    fn test_ignore_absolute_local_links_without_base() -> Result<()> {
+        let mut cmd = main_command();
+
+        let offline_dir = fixtures_path().join("offline");
+
+        cmd.arg("--offline")
+            .arg(&offline_dir.join("index.html"))
+            .env_clear()
+            .assert()
+            .success()
+            .stdout(contains("0 Total"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_inputs_without_scheme() -> Result<()> {
+        let test_path = fixtures_path().join("TEST_HTTP.html");
+        let mut cmd = main_command();
+
+        cmd.arg("--dump")
+            .arg("example.com")
+            .arg(&test_path)
+            .arg("https://example.org")
+            .assert()
+            .success();
+        Ok(())
+    }
+
+    #[test]
+    fn test_dump_sort() -> Result<()> {
+        let input = fixtures_path().join("dump_sort");
+
+        let output = main_command()
+            .arg("--dump")
+            .arg("--sort")
+            .arg(input)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+
+        assert_eq!(
+            std::str::from_utf8(&output)?,
+            "https://crates.io/crates/lychee
+https://docs.rs/lychee-lib
+https://github.com/lycheeverse/lychee
+https://www.rust-lang.org/
+"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dump_embeds() -> Result<()> {
+        let test_path = fixtures_path().join("TEST_EMBEDS.html");
+
+        main_command()
+            .arg("--dump")
+            .arg(&test_path)
+            .assert()
+            .success()
+            .stdout(contains("https://example.com/page"))
+            .stdout(contains("https://example.com/iframe").not());
+
+        main_command()
+            .arg("--dump")
+            .arg("--include-embeds")
+            .arg(&test_path)
+            .assert()
+            .success()
+            .stdout(contains("https://example.com/iframe"))
+            .stdout(contains("https://example.com/embed.swf"))
+            .stdout(contains("https://example.com/object.pdf"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dump_meta_refresh_target() -> Result<()> {
+        let test_path = fixtures_path().join("TEST_META_REFRESH.html");
+        let mut cmd = main_command();
+
+        cmd.arg("--dump")
+            .arg(&test_path)
+            .assert()
+            .success()
+            .stdout(contains("https://example.com/new-home"));
+
+        Ok(())
+    }
+
+    /// Files matching `--process-ext` are read through the given command
+    #[cfg(unix)]
+    #[test]
+    fn test_process_ext() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let test_path = dir.path().join("links.rev");
+        fs::write(&test_path, "detrevnoc/gro.elpmaxe//:sptth\n")?;
+
+        main_command()
+            .arg("--dump")
+            .arg("--process-ext")
+            .arg("rev:rev {}")
+            .arg(&test_path)
+            .assert()
+            .success()
+            .stdout(contains("https://example.org/converted"));
+
+        main_command()
+            .arg("--dump")
+            .arg("--process-ext")
+            .arg("rev:false {}")
+            .arg(&test_path)
+            .assert()
+            .failure()
+            .stderr(contains("`false` failed"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dump_reference_links() -> Result<()> {
+        let test_path = fixtures_path().join("TEST_REFERENCE_LINKS.md");
+
+        let mut assert = main_command()
+            .arg("--dump")
+            .arg("--warn-unused-definitions")
+            .arg(&test_path)
             .assert()
             .success()
-            .stdout(is_empty());
+            .stderr(contains("11:11: Unused reference definition `[unused]`"));
+        for url in [
+            "https://example.com/docs",
+            "https://example.com/collapsed",
+            "https://example.com/shortcut",
+            "https://example.com/logo.png",
+            "https://example.com/unused",
+            "https://example.com/footnote",
+            "https://example.com/paper",
+        ] {
+            assert = assert.stdout(contains(url));
+        }
 
         Ok(())
     }
 
-    #[tokio::test]
-    async fn test_include_verbatim_via_remote_url() -> Result<()> {
-        let mut cmd = main_command();
-        let file = fixtures_path().join("TEST_VERBATIM.html");
-        let body = fs::read_to_string(file)?;
-        let mock_server = mock_response!(body);
+    #[test]
+    fn test_warn_duplicate_ids() -> Result<()> {
+        let test_path = fixtures_path().join("TEST_DUPLICATE_IDS.html");
 
-        cmd.arg("--include-verbatim")
+        main_command()
             .arg("--dump")
-            .arg(mock_server.uri())
+            .arg("--warn-duplicate-ids")
+            .arg(&test_path)
             .assert()
             .success()
-            .stdout(contains("http://www.example.com/pre"))
-            .stdout(contains("http://www.example.com/code"))
-            .stdout(contains("http://www.example.com/samp"))
-            .stdout(contains("http://www.example.com/kbd"))
-            .stdout(contains("http://www.example.com/var"))
-            .stdout(contains("http://www.example.com/script"));
+            .stderr(contains(
+                "TEST_DUPLICATE_IDS.html:8:9: Duplicate id `install`, also defined at 11:9",
+            ))
+            .stderr(contains("`usage`").not());
+
         Ok(())
     }
 
     #[test]
-    fn test_require_https() -> Result<()> {
-        let mut cmd = main_command();
-        let test_path = fixtures_path().join("TEST_HTTP.html");
-        cmd.arg(&test_path).assert().success();
+    fn test_csv_url_column() -> Result<()> {
+        let test_path = fixtures_path().join("TEST_URL_COLUMN.csv");
+
+        for column in ["2", "url"] {
+            main_command()
+                .arg("--dump")
+                .arg("--input-format")
+                .arg("csv")
+                .arg("--url-column")
+                .arg(column)
+                .arg(&test_path)
+                .assert()
+                .success()
+                .stdout(contains("https://lychee.cli.rs/"))
+                .stdout(contains("https://example.org/"))
+                .stdout(contains("https://example.com/").not())
+                .stdout(contains("https://example.net/").not())
+                .stderr(contains(
+                    "TEST_URL_COLUMN.csv:3:1: Skipping malformed CSV row: Found 2 fields instead of 3",
+                ));
+        }
 
-        let mut cmd = main_command();
-        cmd.arg("--require-https").arg(test_path).assert().failure();
+        main_command()
+            .arg("--input-format")
+            .arg("csv")
+            .arg(&test_path)
+            .assert()
+            .failure()
+            .stderr(contains(
+                "`--input-format csv` requires the column of the URLs",
+            ));
 
         Ok(())
     }
 
-    /// If base-dir is not set, don't throw an error in case we encounter
-    /// an absolute local link within a file (e.g. `/about`).
     #[test]
-    fn test_ignore_absolute_local_links_without_base() -> Result<()> {
-        let mut cmd = main_command();
+    fn test_dump_identifiers() -> Result<()> {
+        let test_path = fixtures_path().join("TEST_IDENTIFIERS.md");
 
-        let offline_dir = fixtures_path().join("offline");
+        main_command()
+            .arg("--dump")
+            .arg(&test_path)
+            .assert()
+            .success()
+            .stdout(is_empty());
 
-        cmd.arg("--offline")
-            .arg(&offline_dir.join("index.html"))
-            .env_clear()
+        main_command()
+            .arg("--dump")
+            .arg("--include-identifiers")
+            .arg(&test_path)
             .assert()
             .success()
-            .stdout(contains("0 Total"));
+            .stdout(contains("doi:10.1000/182"))
+            .stdout(contains("arxiv:1706.03762"));
 
         Ok(())
     }
 
+    /// Requires network access to the DOI and arXiv resolvers
     #[test]
-    fn test_inputs_without_scheme() -> Result<()> {
-        let test_path = fixtures_path().join("TEST_HTTP.html");
-        let mut cmd = main_command();
+    #[ignore]
+    fn test_include_identifiers() {
+        let test_path = fixtures_path().join("TEST_IDENTIFIERS.md");
 
-        cmd.arg("--dump")
-            .arg("example.com")
-            .arg(&test_path)
-            .arg("https://example.org")
+        main_command()
+            .arg("--include-identifiers")
+            .arg(test_path)
             .assert()
-            .success();
-        Ok(())
+            .failure()
+            .code(2)
+            .stdout(contains("2 OK"))
+            .stdout(contains("1 Error"))
+            .stdout(contains("https://doi.org/10.1000/this-doi-does-not-exist"));
     }
 
     #[test]
@@ -1201,6 +3154,31 @@ mod cli {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_strip_query_params() -> Result<()> {
+        let mock_server = wiremock::MockServer::start().await;
+        Mock::given(wiremock::matchers::method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        main_command()
+            .arg("--strip-query-params")
+            .arg("utm_*,fbclid")
+            .arg("-")
+            .write_stdin(format!(
+                "{0}/?utm_source=newsletter {0}/?utm_source=twitter&fbclid=123",
+                mock_server.uri()
+            ))
+            .assert()
+            .success()
+            .stdout(contains("1 Total"))
+            .stdout(contains("1 OK"));
+
+        Ok(())
+    }
+
     #[test]
     #[ignore = "Skipping test until https://github.com/robinst/linkify/pull/58 is merged"]
     fn test_remap_path() -> Result<()> {
@@ -1256,6 +3234,64 @@ mod cli {
         Ok(())
     }
 
+    #[test]
+    fn test_remap_file() -> Result<()> {
+        main_command()
+            .arg("--dump")
+            .arg("--remap")
+            .arg("https://github.com/lycheeverse/lychee$ https://codeberg.org/lycheeverse/lychee")
+            .arg("--remap-file")
+            .arg(fixtures_path().join("remaps.txt"))
+            .arg("--")
+            .arg("-")
+            .write_stdin(
+                "https://github.com/lycheeverse/lychee\n\
+                 https://github.com/lycheeverse/lychee-action\n\
+                 https://docs.rs/lychee\n",
+            )
+            .env_clear()
+            .assert()
+            .success()
+            // The rule given with --remap applies first
+            .stdout(contains("https://codeberg.org/lycheeverse/lychee\n"))
+            .stdout(contains("https://gitlab.com/lycheeverse/lychee-action"))
+            .stdout(contains("https://lychee.cli.rs/"))
+            .stdout(contains("github.com").not());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_remap_file_invalid_regex() -> Result<()> {
+        let remap_file = NamedTempFile::new()?;
+        fs::write(
+            remap_file.path(),
+            "# Broken rule\nhttps://(github.com https://gitlab.com\n",
+        )?;
+
+        let inline = main_command()
+            .arg("--dump")
+            .arg("--remap")
+            .arg("https://(github.com https://gitlab.com")
+            .arg("-")
+            .write_stdin("https://github.com")
+            .env_clear()
+            .output()?;
+        main_command()
+            .arg("--dump")
+            .arg("--remap-file")
+            .arg(remap_file.path())
+            .arg("-")
+            .write_stdin("https://github.com")
+            .env_clear()
+            .assert()
+            .failure()
+            .stderr(contains("Remaps must be of the form"))
+            .stderr(String::from_utf8(inline.stderr)?);
+
+        Ok(())
+    }
+
     #[test]
     fn test_excluded_paths() -> Result<()> {
         let test_path = fixtures_path().join("exclude-path");
@@ -1280,6 +3316,64 @@ mod cli {
         Ok(())
     }
 
+    #[test]
+    fn test_exclude_path_regex_matches_like_exclude() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::create_dir(dir.path().join("Docs"))?;
+        fs::write(
+            dir.path().join("Docs/guide.md"),
+            "https://example.com/Docs/guide.md",
+        )?;
+        fs::write(dir.path().join("index.md"), "https://example.com/index.md")?;
+
+        // The same pattern excludes the link with `--exclude` and the file
+        // containing it with `--exclude-path-regex`
+        for flag in ["--exclude", "--exclude-path-regex"] {
+            // Case-sensitive and unanchored
+            main_command()
+                .current_dir(dir.path())
+                .arg("--dump")
+                .arg(flag)
+                .arg("/docs/guide")
+                .arg(".")
+                .assert()
+                .success()
+                .stdout(contains("https://example.com/Docs/guide.md"));
+
+            main_command()
+                .current_dir(dir.path())
+                .arg("--dump")
+                .arg(flag)
+                .arg("(?i)/docs/guide")
+                .arg(".")
+                .assert()
+                .success()
+                .stdout("https://example.com/index.md\n");
+
+            // Anchored patterns must match from the start
+            main_command()
+                .current_dir(dir.path())
+                .arg("--dump")
+                .arg(flag)
+                .arg("^/Docs/guide")
+                .arg(".")
+                .assert()
+                .success()
+                .stdout(contains("https://example.com/Docs/guide.md"));
+        }
+
+        main_command()
+            .current_dir(dir.path())
+            .arg("--exclude-path-regex")
+            .arg("[")
+            .arg(".")
+            .assert()
+            .failure()
+            .stderr(contains("Invalid `--exclude-path-regex` pattern"));
+
+        Ok(())
+    }
+
     #[test]
     fn test_handle_relative_paths_as_input() -> Result<()> {
         let test_path = fixtures_path();
@@ -1427,6 +3521,39 @@ mod cli {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_auth_env_file() -> Result<()> {
+        let mock_server = wiremock::MockServer::start().await;
+        Mock::given(wiremock::matchers::method("GET"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&mock_server)
+            .await;
+
+        let port = mock_server.address().port();
+        let urls = format!("http://127.0.0.1:{port}/ip\nhttp://localhost:{port}/name");
+
+        main_command()
+            .arg("-")
+            .write_stdin(urls.clone())
+            .assert()
+            .failure()
+            .stdout(contains("2 Errors"));
+
+        // Tokens are never sent in plain text, so the HTTP mock server gets
+        // none. Which token each host gets is tested in the library.
+        main_command()
+            .arg("--auth-env-file")
+            .arg(fixtures_path().join("TEST_AUTH.env"))
+            .arg("-")
+            .write_stdin(urls)
+            .assert()
+            .failure()
+            .stdout(contains("2 Total"))
+            .stdout(contains("2 Errors"));
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_cookie_jar() -> Result<()> {
         // Create a random cookie jar file
@@ -1453,6 +3580,83 @@ mod cli {
         Ok(())
     }
 
+    #[test]
+    fn test_explain_exclude() {
+        main_command()
+            .arg("--explain")
+            .arg("https://github.com/lycheeverse/lychee")
+            .arg("--exclude")
+            .arg("^https://gitlab")
+            .arg("--exclude")
+            .arg("github\\.com/lycheeverse")
+            .assert()
+            .success()
+            .stdout(contains(
+                r"filter:  excluded by exclude pattern `github\.com/lycheeverse`",
+            ))
+            .stdout(contains("Result: skipped, the link is excluded"));
+    }
+
+    #[test]
+    fn test_doctor_reports_missing_process_ext_command() {
+        main_command()
+            .arg("--doctor")
+            .arg("--process-ext")
+            .arg("pdf:lychee-no-such-command {} -")
+            .assert()
+            .failure()
+            .code(1)
+            .stdout(contains(
+                "[FAIL] process-ext pdf: `lychee-no-such-command` not found on PATH",
+            ))
+            .stdout(contains("[PASS] cache: not used"));
+    }
+
+    #[cfg(unix)] // The fixture contains symlinks, which git creates on Unix only
+    #[test]
+    fn test_follow_symlinks() {
+        let docs = fixtures_path().join("symlinks").join("docs");
+
+        main_command()
+            .arg("--dump")
+            .arg(&docs)
+            .assert()
+            .success()
+            .stdout(contains("https://lychee.cli.rs/docs"))
+            .stdout(contains("https://lychee.cli.rs/shared").not());
+
+        main_command()
+            .arg("--dump")
+            .arg("--follow-symlinks")
+            .arg(&docs)
+            .assert()
+            .success()
+            .stdout(contains("https://lychee.cli.rs/docs"))
+            .stdout(contains("https://lychee.cli.rs/shared"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_follow_symlinks_skips_loops() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let docs = dir.path().join("docs");
+        fs::create_dir(&docs)?;
+        fs::write(docs.join("index.md"), "https://lychee.cli.rs/docs")?;
+        std::os::unix::fs::symlink("..", docs.join("parent"))?;
+
+        main_command()
+            .arg("--dump")
+            .arg("--follow-symlinks")
+            .arg(&docs)
+            .timeout(std::time::Duration::from_secs(10))
+            .assert()
+            .success()
+            .stdout(contains("https://lychee.cli.rs/docs").count(1))
+            .stderr(contains("Skipping symlink"));
+
+        Ok(())
+    }
+
     #[test]
     fn test_dump_inputs_glob_md() -> Result<()> {
         let pattern = fixtures_path().join("**/*.md");
@@ -1487,6 +3691,34 @@ mod cli {
         Ok(())
     }
 
+    #[test]
+    fn test_dump_inputs_with_extractor() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("binary.bin"),
+            [0x7f, b'E', b'L', b'F', 0, 0xff],
+        )?;
+        fs::write(dir.path().join("notes.txt"), "https://example.com")?;
+        let dump_inputs = fixtures_path().join("dump_inputs");
+
+        main_command()
+            .arg("--dump-inputs")
+            .arg("--with-extractor")
+            .arg(dump_inputs.join("markdown.md"))
+            .arg(dump_inputs.join("subfolder/test.html"))
+            .arg(dump_inputs.join("subfolder"))
+            .arg(dir.path().join("*"))
+            .assert()
+            .success()
+            .stdout(contains("dump_inputs/markdown.md\tmarkdown\n"))
+            .stdout(contains("dump_inputs/subfolder/test.html\thtml\n"))
+            .stdout(contains("dump_inputs/subfolder\tdirectory\n"))
+            .stdout(contains("binary.bin\tnone\n"))
+            .stdout(contains("notes.txt\tplaintext\n"));
+
+        Ok(())
+    }
+
     #[test]
     fn test_dump_inputs_url() -> Result<()> {
         let mut cmd = main_command();
@@ -1557,6 +3789,87 @@ mod cli {
             .stdout(contains("3 Errors"));
     }
 
+    #[test]
+    fn test_percent_encoded_fragments() {
+        let mut cmd = main_command();
+        let input = fixtures_path().join("fragments_encoding");
+
+        cmd.arg("--verbose")
+            .arg("--include-fragments")
+            .arg(input)
+            .assert()
+            .failure()
+            .stderr(contains(
+                "fixtures/fragments_encoding/index.html#%C3%9Cber | Failed: Cannot find fragment",
+            ))
+            // Plain and encoded links to the same fragment are the same URL
+            .stdout(contains("3 Total"))
+            .stdout(contains("2 OK"))
+            .stdout(contains("1 Error"));
+    }
+
+    #[test]
+    fn test_rustdoc_fragments() {
+        let mut cmd = main_command();
+        let input = fixtures_path().join("rustdoc");
+
+        cmd.arg("--verbose")
+            .arg("--offline")
+            .arg("--include-fragments")
+            .arg(input)
+            .assert()
+            .failure()
+            // Successful links are printed without an error message
+            .stderr(contains("fixtures/rustdoc/struct.Foo.html#method.foo\n"))
+            .stderr(contains(
+                "fixtures/rustdoc/struct.Foo.html#structfield.bar\n",
+            ))
+            .stderr(contains("fixtures/rustdoc/src/demo/lib.rs.html#3-5\n"))
+            .stderr(contains(
+                "fixtures/rustdoc/struct.Foo.html#method.removed | Failed: Cannot find fragment",
+            ))
+            .stdout(contains("19 OK"))
+            .stdout(contains("1 Error"));
+    }
+
+    #[test]
+    fn test_self_referential_fragments() {
+        let mut cmd = main_command();
+        let input = fixtures_path().join("self_fragments").join("index.html");
+
+        cmd.arg("--verbose")
+            .arg("--include-fragments")
+            .arg(input)
+            .assert()
+            .failure()
+            .stderr(contains("fixtures/self_fragments/index.html#good"))
+            .stderr(contains(
+                "fixtures/self_fragments/index.html#bad | Failed: Cannot find fragment",
+            ))
+            .stdout(contains("4 Total"))
+            .stdout(contains("3 OK"))
+            .stdout(contains("1 Error"));
+    }
+
+    #[test]
+    fn test_report_unreferenced_anchors() {
+        let mut cmd = main_command();
+        let input = fixtures_path().join("unreferenced_anchors");
+
+        let assert = cmd
+            .arg("--report-unreferenced-anchors")
+            .arg(input)
+            .assert()
+            .success()
+            .stdout(contains("2 Total"))
+            .stderr(contains("Unreferenced anchors"))
+            .stderr(contains("other.md#unreferenced-section"));
+
+        let stderr = std::str::from_utf8(&assert.get_output().stderr).unwrap();
+        assert!(!stderr.contains("other.md#referenced-section"));
+        assert!(!stderr.contains("index.md#index"));
+    }
+
     #[test]
     fn test_fallback_extensions() {
         let mut cmd = main_command();
@@ -8,7 +8,8 @@
 //! [`Handler::chain`] on each handler in the chain consecutively.
 //!
 //! To add external handlers, you can implement the [`Handler`] trait and add
-//! the handler to the chain.
+//! the handler to the chain. Reusable handlers like [`RetryHandler`] can be
+//! added to a chain as well.
 //!
 //! [pattern]: https://github.com/lpxxn/rust-design-pattern/blob/master/behavioral/chain_of_responsibility.rs
 use crate::Status;
@@ -17,6 +18,11 @@ use core::fmt::Debug;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+mod retry;
+
+pub use retry::RetryHandler;
+pub(crate) use retry::{max_backoff, retry_after};
+
 /// Result of a handler.
 ///
 /// This is used to decide if the chain should continue to the next handler or
@@ -53,9 +59,21 @@ pub(crate) type InnerChain<T, R> = Vec<Box<dyn Handler<T, R> + Send>>;
 ///
 /// This is a wrapper around the inner chain type and allows for
 /// concurrent access to the chain.
-#[derive(Debug)]
 pub struct Chain<T, R>(Arc<Mutex<InnerChain<T, R>>>);
 
+impl<T, R> Debug for Chain<T, R> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut debug = f.debug_struct("Chain");
+        // Debug output can't wait for the chain to be unlocked, e.g. while
+        // it is being traversed
+        match self.0.try_lock() {
+            Ok(chain) => debug.field("len", &chain.len()),
+            Err(_) => debug.field("len", &format_args!("<locked>")),
+        };
+        debug.finish()
+    }
+}
+
 impl<T, R> Default for Chain<T, R> {
     fn default() -> Self {
         Self(Arc::new(Mutex::new(InnerChain::default())))
@@ -77,6 +95,16 @@ impl<T, R> Chain<T, R> {
         Self(Arc::new(Mutex::new(values)))
     }
 
+    /// Returns the number of handlers in the chain
+    pub async fn len(&self) -> usize {
+        self.0.lock().await.len()
+    }
+
+    /// Returns `true` if the chain has no handlers
+    pub async fn is_empty(&self) -> bool {
+        self.0.lock().await.is_empty()
+    }
+
     /// Traverse the chain with the given input.
     ///
     /// This will call `chain` on each handler in the chain and return
@@ -223,4 +251,17 @@ mod test {
         let result = chain.traverse(Result(0)).await;
         assert_eq!(result, Done(Result(80)));
     }
+
+    #[tokio::test]
+    async fn chain_len() {
+        use super::Chain;
+        let chain: Chain<Result, Result> = Chain::new(vec![Box::new(Add(7)), Box::new(Add(3))]);
+        assert_eq!(chain.len().await, 2);
+        assert!(!chain.is_empty().await);
+        assert_eq!(format!("{chain:?}"), "Chain { len: 2 }");
+
+        let empty: Chain<Result, Result> = Chain::default();
+        assert!(empty.is_empty().await);
+        assert_eq!(format!("{empty:?}"), "Chain { len: 0 }");
+    }
 }
@@ -0,0 +1,219 @@
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+use http::{header::RETRY_AFTER, HeaderMap, StatusCode};
+use reqwest::Request;
+
+use super::{ChainResult, Handler};
+use crate::Status;
+
+/// A [`Handler`] which retries requests that are throttled by the server.
+///
+/// The request is sent with the given `reqwest` client. If the server
+/// responds with `429 Too Many Requests` or `503 Service Unavailable`, the
+/// handler waits and tries again, up to `max_retries` times. The wait time
+/// starts at `base_delay` and doubles with every attempt, unless the server
+/// sends a `Retry-After` header, which is honored instead. The requested wait
+/// is capped at the backoff before the last retry, so that a server can't
+/// stall the check.
+///
+/// Any other response ends the chain with its status. Once all retries are
+/// used up, the request is passed on with [`ChainResult::Next`], so that the
+/// final attempt is made by the next handler in the chain.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use lychee_lib::{chain::RequestChain, ClientBuilder, RetryHandler};
+///
+/// let retry = RetryHandler::new(reqwest::Client::new(), 3, Duration::from_secs(1));
+/// let client = ClientBuilder::builder()
+///     .plugin_request_chain(RequestChain::new(vec![Box::new(retry)]))
+///     .build()
+///     .client();
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryHandler {
+    reqwest_client: reqwest::Client,
+    max_retries: u64,
+    base_delay: Duration,
+}
+
+impl RetryHandler {
+    /// Creates a handler which sends requests with `reqwest_client` and
+    /// retries throttled requests up to `max_retries` times, starting with a
+    /// wait time of `base_delay`.
+    #[must_use]
+    pub const fn new(
+        reqwest_client: reqwest::Client,
+        max_retries: u64,
+        base_delay: Duration,
+    ) -> Self {
+        Self {
+            reqwest_client,
+            max_retries,
+            base_delay,
+        }
+    }
+}
+
+#[async_trait]
+impl Handler<Request, Status> for RetryHandler {
+    async fn handle(&mut self, request: Request) -> ChainResult<Request, Status> {
+        let mut delay = self.base_delay;
+        let max_delay = max_backoff(self.base_delay, self.max_retries);
+        for _ in 0..self.max_retries {
+            // Requests with streaming bodies can't be sent more than once
+            let Some(attempt) = request.try_clone() else {
+                break;
+            };
+            let response = match self.reqwest_client.execute(attempt).await {
+                Ok(response) => response,
+                Err(e) => return ChainResult::Done(e.into()),
+            };
+            if !is_throttled(response.status()) {
+                return ChainResult::Done(Status::new(&response, None));
+            }
+            let requested = retry_after(response.headers());
+            tokio::time::sleep(requested.map_or(delay, |wait| wait.min(max_delay))).await;
+            delay = delay.saturating_mul(2);
+        }
+        ChainResult::Next(request)
+    }
+}
+
+/// Whether the server asks the client to slow down
+fn is_throttled(code: StatusCode) -> bool {
+    code == StatusCode::TOO_MANY_REQUESTS || code == StatusCode::SERVICE_UNAVAILABLE
+}
+
+/// The wait time of an exponential backoff starting at `base_delay` before
+/// the last of `max_retries` retries
+pub(crate) fn max_backoff(base_delay: Duration, max_retries: u64) -> Duration {
+    let doublings = u32::try_from(max_retries.saturating_sub(1)).unwrap_or(u32::MAX);
+    base_delay.saturating_mul(2_u32.checked_pow(doublings).unwrap_or(u32::MAX))
+}
+
+/// The wait time requested by a `Retry-After` header, given either in
+/// seconds or as an HTTP date
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    fn request(server: &MockServer) -> Request {
+        reqwest::Client::new()
+            .get(format!("{}/", server.uri()))
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_retry_throttled_request() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut handler = RetryHandler::new(reqwest::Client::new(), 3, Duration::from_secs(60));
+        let result = handler.handle(request(&server)).await;
+        assert!(matches!(
+            result,
+            ChainResult::Done(Status::Ok(StatusCode::OK))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_final_attempt_is_passed_on() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let mut handler = RetryHandler::new(reqwest::Client::new(), 2, Duration::from_millis(1));
+        let result = handler.handle(request(&server)).await;
+        assert!(matches!(result, ChainResult::Next(_)));
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_is_capped() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "86400"))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let mut handler = RetryHandler::new(reqwest::Client::new(), 2, Duration::from_millis(10));
+        let result = tokio::time::timeout(Duration::from_secs(5), handler.handle(request(&server)))
+            .await
+            .expect("the wait time is capped");
+        assert!(matches!(
+            result,
+            ChainResult::Done(Status::Ok(StatusCode::OK))
+        ));
+    }
+
+    #[test]
+    fn test_max_backoff() {
+        let base = Duration::from_secs(1);
+        assert_eq!(max_backoff(base, 0), base);
+        assert_eq!(max_backoff(base, 1), base);
+        assert_eq!(max_backoff(base, 3), Duration::from_secs(4));
+        assert_eq!(max_backoff(base, u64::MAX), base * u32::MAX);
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("120"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(120)));
+
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("soon"));
+        assert_eq!(retry_after(&headers), None);
+    }
+}
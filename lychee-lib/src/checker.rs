@@ -1,12 +1,21 @@
 use crate::{
-    chain::{ChainResult, Handler},
+    chain::{max_backoff, retry_after, ChainResult, Handler},
+    meta_refresh,
+    recording::{self, Recording},
+    redirect::{self, https_downgrade},
     retry::RetryExt,
-    Status,
+    types::methods::allowed_methods,
+    ErrorKind, RequestMethods, Status,
 };
 use async_trait::async_trait;
-use http::StatusCode;
-use reqwest::Request;
-use std::{collections::HashSet, time::Duration};
+use http::{header::CONTENT_TYPE, StatusCode};
+use log::debug;
+use regex::Regex;
+use reqwest::{Method, Request, Url};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
 
 #[derive(Debug, Clone)]
 pub(crate) struct Checker {
@@ -14,47 +23,321 @@ pub(crate) struct Checker {
     max_retries: u64,
     reqwest_client: reqwest::Client,
     accepted: Option<HashSet<StatusCode>>,
+    accepted_patterns: Vec<(Regex, HashSet<StatusCode>)>,
+    accepted_schemes: HashMap<String, HashSet<StatusCode>>,
+    warn_redirects_over: Option<usize>,
+    methods: RequestMethods,
+    fail_on_https_downgrade: bool,
+    expect_image: bool,
+    recording: Option<Recording>,
+    record_meta_refresh: bool,
 }
 
 impl Checker {
-    pub(crate) const fn new(
+    pub(crate) fn new(
         retry_wait_time: Duration,
         max_retries: u64,
         reqwest_client: reqwest::Client,
         accepted: Option<HashSet<StatusCode>>,
+        accepted_patterns: Vec<(Regex, HashSet<StatusCode>)>,
+        warn_redirects_over: Option<usize>,
     ) -> Self {
         Self {
             retry_wait_time,
             max_retries,
             reqwest_client,
             accepted,
+            accepted_patterns,
+            accepted_schemes: HashMap::new(),
+            warn_redirects_over,
+            methods: RequestMethods::default(),
+            fail_on_https_downgrade: false,
+            expect_image: false,
+            recording: None,
+            record_meta_refresh: false,
+        }
+    }
+
+    /// Accept these status codes instead of `accepted` for URLs with the
+    /// given schemes
+    pub(crate) fn accepted_schemes(
+        mut self,
+        accepted_schemes: HashMap<String, HashSet<StatusCode>>,
+    ) -> Self {
+        self.accepted_schemes = accepted_schemes;
+        self
+    }
+
+    /// Fall back to the methods after the one of a request in `methods` if
+    /// the server doesn't support it
+    pub(crate) fn methods(mut self, methods: RequestMethods) -> Self {
+        self.methods = methods;
+        self
+    }
+
+    /// Fail requests which were redirected from HTTPS to HTTP
+    pub(crate) const fn fail_on_https_downgrade(mut self, fail_on_https_downgrade: bool) -> Self {
+        self.fail_on_https_downgrade = fail_on_https_downgrade;
+        self
+    }
+
+    /// Fail successful responses whose `Content-Type` is not an image, as
+    /// the request is for the source of an image
+    pub(crate) const fn expect_image(mut self, expect_image: bool) -> Self {
+        self.expect_image = expect_image;
+        self
+    }
+
+    /// Record responses to or replay them from a directory
+    pub(crate) fn recording(mut self, recording: Option<Recording>) -> Self {
+        self.recording = recording;
+        self
+    }
+
+    /// Record the meta refreshes of successful HTML responses for the client
+    /// to follow
+    pub(crate) const fn record_meta_refresh(mut self, record_meta_refresh: bool) -> Self {
+        self.record_meta_refresh = record_meta_refresh;
+        self
+    }
+
+    /// Check `request`, falling back to another method if the server doesn't
+    /// support the method of the request.
+    ///
+    /// Only the configured methods after the one of `request` are tried. If
+    /// the server lists the methods it supports in an `Allow` header, the
+    /// first of them is tried next, otherwise simply the next method.
+    async fn check_methods(&self, mut request: Request) -> Status {
+        let mut methods: Vec<&Method> = self
+            .methods
+            .iter()
+            .skip_while(|method| *method != request.method())
+            .skip(1)
+            .collect();
+        loop {
+            let Checked {
+                status,
+                allowed_methods,
+                ..
+            } = self
+                .retry_request(clone_unwrap(&request), !methods.is_empty())
+                .await;
+
+            let Some(code) = status
+                .code()
+                .filter(|code| RequestMethods::is_unsupported_method(*code))
+            else {
+                return status;
+            };
+            let next = match allowed_methods {
+                Some(allowed) => methods.iter().position(|next| allowed.contains(next)),
+                None => (!methods.is_empty()).then_some(0),
+            };
+            let Some(next) = next else {
+                return status;
+            };
+            let next = methods.remove(next);
+            debug!(
+                "{} doesn't support {} ({code}), trying {next}",
+                request.url(),
+                request.method()
+            );
+            *request.method_mut() = next.clone();
         }
     }
 
     /// Retry requests up to `max_retries` times
     /// with an exponential backoff.
-    pub(crate) async fn retry_request(&self, request: Request) -> Status {
+    ///
+    /// A `Retry-After` header of the response is honored instead of the
+    /// backoff, but never waits longer than the backoff before the last retry.
+    /// A `501 Not Implemented` response is not retried if the request has
+    /// another method to fall back to.
+    async fn retry_request(&self, request: Request, has_fallback: bool) -> Checked {
         let mut retries: u64 = 0;
         let mut wait_time = self.retry_wait_time;
 
-        let mut status = self.check_default(clone_unwrap(&request)).await;
+        let mut checked = self.check_default(clone_unwrap(&request)).await;
         while retries < self.max_retries {
-            if status.is_success() || !status.should_retry() {
-                return status;
+            let status = &checked.status;
+            let falls_back = has_fallback && status.code() == Some(StatusCode::NOT_IMPLEMENTED);
+            if status.is_success() || !status.should_retry() || falls_back {
+                return checked;
             }
             retries += 1;
-            tokio::time::sleep(wait_time).await;
+            let max_wait_time = max_backoff(self.retry_wait_time, self.max_retries);
+            tokio::time::sleep(
+                checked
+                    .requested_wait
+                    .map_or(wait_time, |wait| wait.min(max_wait_time)),
+            )
+            .await;
             wait_time = wait_time.saturating_mul(2);
-            status = self.check_default(clone_unwrap(&request)).await;
+            checked = self.check_default(clone_unwrap(&request)).await;
         }
-        status
+        checked
     }
 
     /// Check a URI using [reqwest](https://github.com/seanmonstar/reqwest).
-    async fn check_default(&self, request: Request) -> Status {
-        match self.reqwest_client.execute(request).await {
-            Ok(ref response) => Status::new(response, self.accepted.clone()),
-            Err(e) => e.into(),
+    async fn check_default(&self, request: Request) -> Checked {
+        let url = request.url().clone();
+        let method = request.method().clone();
+        let accepted = self.accepted_for(&url);
+        let (checked, redirects) = match &self.recording {
+            Some(Recording::Replay(dir)) => match recording::replay(dir, &method, &url).await {
+                Ok(response) => (
+                    self.handle_response(&method, &url, response, accepted)
+                        .await,
+                    Vec::new(),
+                ),
+                Err(e) => (Checked::from(Status::from(e)), Vec::new()),
+            },
+            recording => {
+                let (response, redirects) =
+                    redirect::track(self.reqwest_client.execute(request)).await;
+                let checked = match response {
+                    Ok(response) => {
+                        if let Some(Recording::Record(dir)) = recording {
+                            recording::record(dir, &method, &url, &response).await;
+                        }
+                        self.handle_response(&method, &url, response, accepted)
+                            .await
+                    }
+                    Err(e) => Checked::from(Status::from(e)),
+                };
+                (checked, redirects)
+            }
+        };
+        match self.redirect_status(&redirects, &checked.status) {
+            Some(redirect_status) => Checked::from(redirect_status),
+            None => checked,
+        }
+    }
+
+    /// The status of the `response` to the request `method url` together with
+    /// what the retries and method fallbacks need to know about it.
+    ///
+    /// Also records what later steps need to know about the response.
+    async fn handle_response(
+        &self,
+        method: &Method,
+        url: &Url,
+        response: reqwest::Response,
+        accepted: Option<HashSet<StatusCode>>,
+    ) -> Checked {
+        let status = self.response_status(url, &response, accepted);
+        let requested_wait = retry_after(response.headers());
+        let allowed_methods = allowed_methods(response.status(), response.headers());
+        if status.is_success() && self.record_meta_refresh {
+            meta_refresh::record(method, response).await;
+        }
+        Checked {
+            status,
+            requested_wait,
+            allowed_methods,
+        }
+    }
+
+    /// The status of `response`, which is an error if an image was expected
+    /// but the response has a different content type
+    fn response_status(
+        &self,
+        url: &Url,
+        response: &reqwest::Response,
+        accepted: Option<HashSet<StatusCode>>,
+    ) -> Status {
+        let status = Status::new(response, accepted);
+        if !self.expect_image || !status.is_success() {
+            return status;
+        }
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        if content_type
+            .trim_start()
+            .to_ascii_lowercase()
+            .starts_with("image/")
+        {
+            status
+        } else {
+            let content_type = if content_type.is_empty() {
+                "none"
+            } else {
+                content_type
+            };
+            Status::Error(ErrorKind::NotAnImage(
+                url.clone().into(),
+                content_type.to_string(),
+            ))
+        }
+    }
+
+    /// An error if the successful request which took the redirect `chain` was
+    /// redirected from HTTPS to HTTP and `fail_on_https_downgrade` is set, or
+    /// a warning if it took more redirects than `warn_redirects_over`
+    fn redirect_status(&self, chain: &[Url], status: &Status) -> Option<Status> {
+        if chain.is_empty() {
+            return None;
+        }
+        let code = status.code().filter(|_| status.is_success())?;
+        if self.fail_on_https_downgrade {
+            if let Some(insecure) = https_downgrade(chain) {
+                return Some(Status::Error(ErrorKind::HttpsDowngrade(
+                    insecure.clone().into(),
+                )));
+            }
+        }
+        let hops = chain.len() - 1;
+        if hops <= self.warn_redirects_over? {
+            return None;
+        }
+        let target = chain.last()?;
+        Some(Status::Warning(
+            code,
+            format!("Redirected {hops} times, final URL: {target}"),
+        ))
+    }
+
+    /// Status codes accepted for `url`.
+    ///
+    /// The codes for the scheme of `url` replace the globally accepted ones.
+    /// The codes of the first matching pattern are accepted in addition.
+    fn accepted_for(&self, url: &Url) -> Option<HashSet<StatusCode>> {
+        let accepted = match self.accepted_schemes.get(url.scheme()) {
+            Some(codes) => Some(codes.clone()),
+            None => self.accepted.clone(),
+        };
+        let Some((_, codes)) = self
+            .accepted_patterns
+            .iter()
+            .find(|(regex, _)| regex.is_match(url.as_str()))
+        else {
+            return accepted;
+        };
+
+        let mut accepted = accepted.unwrap_or_default();
+        accepted.extend(codes);
+        Some(accepted)
+    }
+}
+
+/// The outcome of a single request
+struct Checked {
+    status: Status,
+    /// Wait time requested by a `Retry-After` header
+    requested_wait: Option<Duration>,
+    /// Methods listed in the `Allow` header of a `405` response
+    allowed_methods: Option<Vec<Method>>,
+}
+
+impl From<Status> for Checked {
+    fn from(status: Status) -> Self {
+        Self {
+            status,
+            requested_wait: None,
+            allowed_methods: None,
         }
     }
 }
@@ -75,6 +358,52 @@ fn clone_unwrap(request: &Request) -> Request {
 #[async_trait]
 impl Handler<Request, Status> for Checker {
     async fn handle(&mut self, input: Request) -> ChainResult<Request, Status> {
-        ChainResult::Done(self.retry_request(input).await)
+        ChainResult::Done(self.check_methods(input).await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redirect_status() {
+        let checker = Checker::new(
+            Duration::ZERO,
+            0,
+            reqwest::Client::new(),
+            None,
+            Vec::new(),
+            Some(1),
+        )
+        .fail_on_https_downgrade(true);
+        let ok = Status::Ok(StatusCode::OK);
+        let urls: Vec<Url> = [
+            "http://a.example/",
+            "https://b.example/",
+            "http://c.example/",
+        ]
+        .iter()
+        .map(|url| Url::parse(url).unwrap())
+        .collect();
+
+        assert_eq!(checker.redirect_status(&[], &ok), None);
+        assert_eq!(checker.redirect_status(&urls[..2], &ok), None);
+        assert_eq!(
+            checker.redirect_status(&urls[1..], &ok),
+            Some(Status::Error(ErrorKind::HttpsDowngrade(
+                urls[2].clone().into()
+            )))
+        );
+        let chain = [urls[0].clone(), urls[0].clone(), urls[2].clone()];
+        assert_eq!(
+            checker.redirect_status(&chain, &ok),
+            Some(Status::Warning(
+                StatusCode::OK,
+                "Redirected 2 times, final URL: http://c.example/".to_string()
+            ))
+        );
+        let error = Status::Error(ErrorKind::InvalidStatusCode(1000));
+        assert_eq!(checker.redirect_status(&urls, &error), None);
     }
 }
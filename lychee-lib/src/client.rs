@@ -13,7 +13,12 @@
     clippy::default_trait_access,
     clippy::used_underscore_binding
 )]
-use std::{collections::HashSet, path::Path, sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
 
 #[cfg(all(feature = "email-check", feature = "native-tls"))]
 use check_if_email_exists::{check_email, CheckEmailInput, Reachable};
@@ -23,26 +28,45 @@ use http::{
 };
 use log::{debug, warn};
 use octocrab::Octocrab;
-use regex::RegexSet;
-use reqwest::{header, redirect, Url};
+use regex::{Regex, RegexSet};
+use reqwest::{header, Url};
 use reqwest_cookie_store::CookieStoreMutex;
 use secrecy::{ExposeSecret, SecretString};
+use tokio::sync::Semaphore;
 use typed_builder::TypedBuilder;
 
 use crate::{
-    chain::{Chain, ClientRequestChains, RequestChain},
+    chain::{Chain, ClientRequestChains, Handler, RequestChain},
     checker::Checker,
-    filter::{Excludes, Filter, Includes},
+    filter::{ExcludeFn, ExcludePredicate, Excludes, Filter, FilterDecision, Includes},
+    meta_refresh::{self, MetaRefresh},
     quirks::Quirks,
+    recording::Recording,
+    redirect,
     remap::Remaps,
-    types::uri::github::GithubUri,
-    utils::fragment_checker::FragmentChecker,
-    ErrorKind, Request, Response, Result, Status, Uri,
+    types::uri::{
+        github::GithubUri,
+        identifier::Identifier,
+        raw::{is_image_source, is_missing_noopener},
+    },
+    user_agents::UserAgentRotation,
+    utils::{self, fragment_checker::FragmentChecker},
+    AcceptPatternSelector, AcceptSchemeSelector, AcceptSelector, BasicAuthCredentials, ErrorKind,
+    HostTokens, Request, RequestMethods, Response, Result, Status, Uri,
 };
 
 #[cfg(all(feature = "email-check", feature = "native-tls"))]
 use crate::types::mail;
 
+#[cfg(feature = "ftp")]
+use crate::ftp;
+#[cfg(feature = "gemini")]
+use crate::gemini;
+#[cfg(feature = "verify-mail")]
+use crate::mx::MxResolver;
+#[cfg(feature = "websocket")]
+use crate::websocket;
+
 /// Default number of redirects before a request is deemed as failed, 5.
 pub const DEFAULT_MAX_REDIRECTS: usize = 5;
 /// Default number of retries before a request is deemed as failed, 3.
@@ -51,6 +75,8 @@ pub const DEFAULT_MAX_RETRIES: u64 = 3;
 pub const DEFAULT_RETRY_WAIT_TIME_SECS: usize = 1;
 /// Default timeout in seconds before a request is deemed as failed, 20.
 pub const DEFAULT_TIMEOUT_SECS: usize = 20;
+/// Default number of concurrent filesystem checks of `file://` URIs, 64.
+pub const DEFAULT_FS_CONCURRENCY: usize = 64;
 /// Default user agent, `lychee-<PKG_VERSION>`.
 pub const DEFAULT_USER_AGENT: &str = concat!("lychee/", env!("CARGO_PKG_VERSION"));
 
@@ -63,6 +89,33 @@ const CONNECT_TIMEOUT: u64 = 10;
 /// information.
 const TCP_KEEPALIVE: u64 = 60;
 
+/// A function which is called with every result of [`Client::check`]
+pub type OnResultFn = dyn FnMut(&Response) + Send;
+
+/// Shareable wrapper of an [`OnResultFn`]
+#[derive(Clone)]
+pub struct ResultCallback(Arc<Mutex<Box<OnResultFn>>>);
+
+impl ResultCallback {
+    fn call(&self, response: &Response) {
+        // A panicking callback must not stop other results from being reported
+        let mut on_result = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        on_result(response);
+    }
+}
+
+impl From<Box<OnResultFn>> for ResultCallback {
+    fn from(on_result: Box<OnResultFn>) -> Self {
+        Self(Arc::new(Mutex::new(on_result)))
+    }
+}
+
+impl std::fmt::Debug for ResultCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ResultCallback(..)")
+    }
+}
+
 /// Builder for [`Client`].
 ///
 /// See crate-level documentation for usage example.
@@ -98,6 +151,19 @@ pub struct ClientBuilder {
     /// Automatically append file extensions to `file://` URIs as needed
     fallback_extensions: Vec<String>,
 
+    /// Directory which local files must lie within. Links to files outside
+    /// of it, e.g. `../../etc/passwd`, are reported as errors instead of being
+    /// checked.
+    root_dir: Option<PathBuf>,
+
+    /// Maximum number of `file://` URIs whose existence and fragments are
+    /// checked at the same time. The checks run on the blocking thread pool,
+    /// independently of the network requests.
+    ///
+    /// Defaults to [`DEFAULT_FS_CONCURRENCY`].
+    #[builder(default = DEFAULT_FS_CONCURRENCY)]
+    fs_concurrency: usize,
+
     /// Links matching this set of regular expressions are **always** checked.
     ///
     /// This has higher precedence over [`ClientBuilder::excludes`], **but**
@@ -105,10 +171,24 @@ pub struct ClientBuilder {
     /// [`ClientBuilder::schemes`] below.
     includes: Option<RegexSet>,
 
+    /// Treat [`ClientBuilder::includes`] as an allowlist: links matching none
+    /// of its regular expressions are ignored, even if
+    /// [`ClientBuilder::excludes`] are given, and links matching
+    /// [`ClientBuilder::excludes`] or [`ClientBuilder::exclude_fn`] are
+    /// ignored even if they match [`ClientBuilder::includes`].
+    strict_includes: bool,
+
     /// Links matching this set of regular expressions are ignored, **except**
     /// when a link also matches against [`ClientBuilder::includes`].
     excludes: Option<RegexSet>,
 
+    /// Custom logic to ignore links, e.g. based on a database lookup. Links
+    /// for which the function returns `true` are ignored like links matching
+    /// [`ClientBuilder::excludes`], **except** when a link also matches
+    /// against [`ClientBuilder::includes`].
+    #[builder(setter(transform = |predicate: Box<ExcludeFn>| Some(predicate.into())))]
+    exclude_fn: Option<ExcludePredicate>,
+
     /// When `true`, exclude all private network addresses.
     ///
     /// This effectively turns on the following fields:
@@ -186,12 +266,81 @@ pub struct ClientBuilder {
     /// When `true`, check mail addresses.
     include_mail: bool,
 
+    /// When `true`, mail addresses are only checked for MX records of their
+    /// domain, instead of asking the mail server whether the mailbox exists.
+    /// An address is valid if its domain has at least one MX record or, if it
+    /// has none, an A or AAAA record.
+    ///
+    /// This is much faster and works without the `email-check` feature, but
+    /// doesn't detect missing mailboxes. Mail addresses still need to be
+    /// included with `include_mail`.
+    ///
+    /// This requires the `verify-mail` feature. Otherwise, mail addresses are
+    /// unsupported when this is `true`.
+    verify_mail: bool,
+
+    /// When `true`, check `ftp://` URIs by connecting to the server and
+    /// looking up the file or directory.
+    ///
+    /// This requires the `ftp` feature. Otherwise, or when `false`, FTP URIs
+    /// are unsupported.
+    include_ftp: bool,
+
+    /// When `true`, check `gemini://` URIs by requesting the resource from
+    /// the server and reading the status of the response header.
+    ///
+    /// This requires the `gemini` feature. Otherwise, or when `false`, Gemini
+    /// URIs are unsupported.
+    include_gemini: bool,
+
+    /// When `true`, check `ws://` and `wss://` URIs by performing the
+    /// WebSocket opening handshake. The endpoint is reachable if the server
+    /// switches protocols.
+    ///
+    /// This requires the `websocket` feature. Otherwise, or when `false`,
+    /// WebSocket URIs are unsupported.
+    include_websocket: bool,
+
+    /// When `true`, identifiers of scholarly works like `doi:10.1000/182`,
+    /// `arxiv:2101.00001` or `pmid:12345678` as well as links to the DOI
+    /// resolver are checked through their canonical resolvers.
+    ///
+    /// DOIs are looked up without following the redirect to the publisher,
+    /// as many publishers block automated requests.
+    include_identifiers: bool,
+
     /// Maximum number of redirects per request before returning an error.
     ///
     /// Defaults to [`DEFAULT_MAX_REDIRECTS`].
     #[builder(default = DEFAULT_MAX_REDIRECTS)]
     max_redirects: usize,
 
+    /// Report working links which took more than this number of redirects to
+    /// resolve as warnings.
+    ///
+    /// Long redirect chains often indicate link rot, even if they stay below
+    /// [`ClientBuilder::max_redirects`].
+    warn_redirects_over: Option<usize>,
+
+    /// Fail working links whose redirect chain steps down from HTTPS to
+    /// HTTP, as the content could be tampered with on the way.
+    fail_on_https_downgrade: bool,
+
+    /// Fail links to images, like the `src` of an `<img>` or a Markdown
+    /// image, whose response has a `Content-Type` other than `image/*`, e.g.
+    /// an HTML error page served with status 200.
+    verify_image_content_type: bool,
+
+    /// Store the status and the headers needed to check links of all
+    /// responses in this directory, so that the run can be reproduced with
+    /// [`ClientBuilder::replay`].
+    record: Option<PathBuf>,
+
+    /// Answer requests with the responses recorded in this directory by
+    /// [`ClientBuilder::record`] instead of sending them. Requests without a
+    /// recorded response fail. Takes precedence over recording.
+    replay: Option<PathBuf>,
+
     /// Maximum number of retries per request before returning an error.
     ///
     /// Defaults to [`DEFAULT_MAX_RETRIES`].
@@ -210,6 +359,12 @@ pub struct ClientBuilder {
     #[builder(default_code = "String::from(DEFAULT_USER_AGENT)")]
     user_agent: String,
 
+    /// User agents to cycle through, one per request.
+    ///
+    /// When not empty, these replace `user_agent`. This helps with hosts
+    /// which block many requests with the same user agent.
+    user_agents: Vec<String>,
+
     /// When `true`, accept invalid SSL certificates.
     ///
     /// # Warning
@@ -236,18 +391,62 @@ pub struct ClientBuilder {
     /// [here]: https://docs.rs/reqwest/latest/reqwest/struct.ClientBuilder.html#method.default_headers
     custom_headers: HeaderMap,
 
+    /// Bearer tokens which are sent to the hosts they belong to, see
+    /// [`HostTokens`].
+    host_tokens: HostTokens,
+
+    /// Value of the `Accept-Encoding` header sent with every request.
+    ///
+    /// When `None`, all supported encodings (gzip, brotli and deflate) are
+    /// advertised. Compressed responses are decoded transparently either way,
+    /// so this only controls what the server is asked to send.
+    accept_encoding: Option<String>,
+
     /// HTTP method used for requests, e.g. `GET` or `HEAD`.
     #[builder(default = reqwest::Method::GET)]
     method: reqwest::Method,
 
+    /// HTTP methods to try in order, e.g. `HEAD` and then `GET`. The next
+    /// method is only tried if the server responds with `405 Method Not
+    /// Allowed` or `501 Not Implemented`. Methods not listed in the `Allow`
+    /// header of a `405` response are skipped.
+    ///
+    /// Takes precedence over [`method`](Self::method) if set.
+    request_methods: Option<RequestMethods>,
+
     /// Set of accepted return codes / status codes.
     ///
     /// Unmatched return codes/ status codes are deemed as errors.
     accepted: Option<HashSet<StatusCode>>,
 
+    /// Additional accepted status codes for URLs matching a pattern.
+    ///
+    /// The codes of the first matching [`AcceptPatternSelector`] are accepted
+    /// on top of `accepted`, e.g. to allow `403` for an auth-gated host only.
+    accepted_patterns: Vec<AcceptPatternSelector>,
+
+    /// Accepted status codes for URLs with a given scheme.
+    ///
+    /// The codes of a matching [`AcceptSchemeSelector`] are used instead of
+    /// `accepted`, e.g. to accept different codes for `http` and `https`
+    /// links. Codes of a matching pattern in `accepted_patterns` are still
+    /// accepted on top.
+    accepted_schemes: Vec<AcceptSchemeSelector>,
+
     /// Response timeout per request in seconds.
+    ///
+    /// This applies to every attempt separately. See
+    /// [`ClientBuilder::total_timeout`] to limit the time across retries.
     timeout: Option<Duration>,
 
+    /// Maximum time for checking a website, across all retries and request
+    /// methods. Checks which take longer are reported as timeouts.
+    ///
+    /// Without it, a link which is retried `max_retries` times can take
+    /// several times [`ClientBuilder::timeout`] plus the wait time between
+    /// the retries.
+    total_timeout: Option<Duration>,
+
     /// Initial time between retries of failed requests.
     ///
     /// Defaults to [`DEFAULT_RETRY_WAIT_TIME_SECS`].
@@ -270,6 +469,30 @@ pub struct ClientBuilder {
     /// HTTPS.
     require_https: bool,
 
+    /// When `true`, links to localhost, loopback, private or link-local
+    /// addresses are reported as errors instead of being checked.
+    ///
+    /// This catches development URLs which leaked into published content.
+    /// Links excluded through one of the `exclude_` options are still
+    /// excluded.
+    fail_on_private: bool,
+
+    /// Links to these hosts are reported as errors instead of being checked.
+    ///
+    /// In contrast to `excludes`, which skips links, this enforces the use of
+    /// canonical domains. If a remapping rule applies to a disallowed link,
+    /// the remapped URI is suggested as replacement. Hosts are compared
+    /// case-insensitively and must match exactly.
+    disallowed_hosts: Vec<String>,
+
+    /// When `true`, working links which open in a new tab (`target="_blank"`)
+    /// without `rel="noopener"` or `rel="noreferrer"` are reported as
+    /// warnings.
+    ///
+    /// Such links allow the linked page to navigate the opener through
+    /// `window.opener`, which is known as tab-nabbing.
+    require_external_noopener: bool,
+
     /// Cookie store used for requests.
     ///
     /// See <https://docs.rs/reqwest/latest/reqwest/struct.ClientBuilder.html#method.cookie_store>
@@ -278,11 +501,28 @@ pub struct ClientBuilder {
     /// Enable the checking of fragments in links.
     include_fragments: bool,
 
+    /// When `true`, follow `<meta http-equiv="refresh">` redirects of HTML
+    /// pages and report the status of the final target.
+    ///
+    /// At most [`ClientBuilder::max_redirects`] meta refreshes are followed
+    /// per link.
+    follow_meta_refresh: bool,
+
     /// Requests run through this chain where each item in the chain
     /// can modify the request. A chained item can also decide to exit
     /// early and return a status, so that subsequent chain items are
     /// skipped and the lychee-internal request chain is not activated.
     plugin_request_chain: RequestChain,
+
+    /// Function which is called with every result as soon as its check
+    /// completes, no matter whether the link is OK, broken or excluded.
+    /// This allows custom progress reporting or logging without collecting
+    /// all results first.
+    ///
+    /// The function is shared by all clones of the [`Client`] and calls are
+    /// serialized, so it should return quickly.
+    #[builder(setter(transform = |on_result: Box<OnResultFn>| Some(ResultCallback::from(on_result))))]
+    on_result: Option<ResultCallback>,
 }
 
 impl Default for ClientBuilder {
@@ -306,101 +546,173 @@ impl ClientBuilder {
     /// - The GitHub client cannot be created. Since the implementation also
     ///   uses reqwest under the hood, this errors in the same circumstances as
     ///   the last one.
+    /// - One of the accepted URL patterns is not a valid regex.
     ///
     /// [here]: https://docs.rs/reqwest/latest/reqwest/struct.ClientBuilder.html#errors
     pub fn client(self) -> Result<Client> {
-        let Self {
-            user_agent,
-            custom_headers: mut headers,
-            ..
-        } = self;
+        let reqwest_client = self.reqwest_client()?;
+        let github_client = self.github_client()?;
+        #[cfg(feature = "verify-mail")]
+        let mx_resolver = self.mx_resolver()?;
+
+        let accepted_patterns = compile_accepted_patterns(self.accepted_patterns)?;
+        let accepted_schemes = self
+            .accepted_schemes
+            .into_iter()
+            .map(|scheme| (scheme.scheme, status_codes(scheme.selector)))
+            .collect();
+        let user_agents = self.user_agents.iter().map(HeaderValue::try_from);
+        let user_agent_rotation =
+            UserAgentRotation::new(user_agents.collect::<std::result::Result<_, _>>()?);
+
+        let filter = Filter {
+            includes: self.includes.map(|regex| Includes { regex }),
+            strict_includes: self.strict_includes,
+            excludes: self.excludes.map(|regex| Excludes { regex }),
+            exclude_predicate: self.exclude_fn,
+            schemes: self.schemes,
+            // exclude_all_private option turns on all "private" excludes,
+            // including private IPs, link-local IPs and loopback IPs
+            exclude_private_ips: self.exclude_all_private || self.exclude_private_ips,
+            exclude_link_local_ips: self.exclude_all_private || self.exclude_link_local_ips,
+            exclude_loopback_ips: self.exclude_all_private || self.exclude_loopback_ips,
+            include_mail: self.include_mail,
+        };
+
+        Ok(Client {
+            reqwest_client,
+            github_client,
+            remaps: self.remaps,
+            fallback_extensions: self.fallback_extensions,
+            root_dir: self.root_dir,
+            fs_permits: Arc::new(Semaphore::new(self.fs_concurrency.max(1))),
+            filter,
+            max_retries: self.max_retries,
+            retry_wait_time: self.retry_wait_time,
+            methods: self
+                .request_methods
+                .unwrap_or_else(|| RequestMethods::from(self.method)),
+            accepted: self.accepted,
+            accepted_patterns,
+            accepted_schemes,
+            require_https: self.require_https,
+            fail_on_private: self.fail_on_private,
+            disallowed_hosts: self.disallowed_hosts,
+            verify_mail: self.verify_mail,
+            #[cfg(feature = "verify-mail")]
+            mx_resolver,
+            include_ftp: self.include_ftp,
+            include_gemini: self.include_gemini,
+            include_websocket: self.include_websocket,
+            include_identifiers: self.include_identifiers,
+            timeout: self.timeout,
+            total_timeout: self.total_timeout,
+            require_external_noopener: self.require_external_noopener,
+            include_fragments: self.include_fragments,
+            follow_meta_refresh: self.follow_meta_refresh,
+            max_redirects: self.max_redirects,
+            warn_redirects_over: self.warn_redirects_over,
+            fail_on_https_downgrade: self.fail_on_https_downgrade,
+            verify_image_content_type: self.verify_image_content_type,
+            recording: self
+                .replay
+                .map(Recording::Replay)
+                .or(self.record.map(Recording::Record)),
+            user_agent_rotation,
+            host_tokens: self.host_tokens,
+            fragment_checker: FragmentChecker::new(),
+            plugin_request_chain: self.plugin_request_chain,
+            on_result: self.on_result,
+        })
+    }
 
+    /// Build the `reqwest` client for HTTP requests, with the default headers,
+    /// the transport settings and the redirect policy applied
+    fn reqwest_client(&self) -> Result<reqwest::Client> {
+        let mut headers = self.custom_headers.clone();
         if let Some(prev_user_agent) =
-            headers.insert(header::USER_AGENT, HeaderValue::try_from(&user_agent)?)
+            headers.insert(header::USER_AGENT, HeaderValue::try_from(&self.user_agent)?)
         {
             debug!(
-                "Found user-agent in headers: {}. Overriding it with {user_agent}.",
+                "Found user-agent in headers: {}. Overriding it with {}.",
                 prev_user_agent.to_str().unwrap_or("�"),
+                self.user_agent,
             );
-        };
+        }
 
         headers.insert(
             header::TRANSFER_ENCODING,
             HeaderValue::from_static("chunked"),
         );
 
-        // Custom redirect policy to enable logging of redirects.
-        let max_redirects = self.max_redirects;
-        let redirect_policy = redirect::Policy::custom(move |attempt| {
-            if attempt.previous().len() > max_redirects {
-                attempt.error("too many redirects")
-            } else {
-                debug!("Redirecting to {}", attempt.url());
-                attempt.follow()
-            }
-        });
+        if let Some(accept_encoding) = &self.accept_encoding {
+            headers.insert(
+                header::ACCEPT_ENCODING,
+                HeaderValue::try_from(accept_encoding)?,
+            );
+        }
+
+        let redirect_policy = redirect::policy(self.max_redirects);
 
         let mut builder = reqwest::ClientBuilder::new()
             .gzip(true)
+            .brotli(true)
+            .deflate(true)
             .default_headers(headers)
             .danger_accept_invalid_certs(self.allow_insecure)
             .connect_timeout(Duration::from_secs(CONNECT_TIMEOUT))
             .tcp_keepalive(Duration::from_secs(TCP_KEEPALIVE))
             .redirect(redirect_policy);
 
-        if let Some(cookie_jar) = self.cookie_jar {
-            builder = builder.cookie_provider(cookie_jar);
+        if let Some(cookie_jar) = &self.cookie_jar {
+            builder = builder.cookie_provider(cookie_jar.clone());
         }
 
-        let reqwest_client = match self.timeout {
+        match self.timeout {
             Some(t) => builder.timeout(t),
             None => builder,
         }
         .build()
-        .map_err(ErrorKind::NetworkRequest)?;
-
-        let github_client = match self.github_token.as_ref().map(ExposeSecret::expose_secret) {
-            Some(token) if !token.is_empty() => Some(
-                Octocrab::builder()
-                    .personal_token(token.clone())
-                    .build()
-                    // this is essentially the same reqwest::ClientBuilder::build error
-                    // see https://docs.rs/octocrab/0.18.1/src/octocrab/lib.rs.html#360-364
-                    .map_err(ErrorKind::BuildGithubClient)?,
-            ),
-            _ => None,
-        };
+        .map_err(ErrorKind::NetworkRequest)
+    }
 
-        let filter = Filter {
-            includes: self.includes.map(|regex| Includes { regex }),
-            excludes: self.excludes.map(|regex| Excludes { regex }),
-            schemes: self.schemes,
-            // exclude_all_private option turns on all "private" excludes,
-            // including private IPs, link-local IPs and loopback IPs
-            exclude_private_ips: self.exclude_all_private || self.exclude_private_ips,
-            exclude_link_local_ips: self.exclude_all_private || self.exclude_link_local_ips,
-            exclude_loopback_ips: self.exclude_all_private || self.exclude_loopback_ips,
-            include_mail: self.include_mail,
-        };
+    /// Build the GitHub client if a non-empty token is set
+    fn github_client(&self) -> Result<Option<Octocrab>> {
+        match self.github_token.as_ref().map(ExposeSecret::expose_secret) {
+            Some(token) if !token.is_empty() => Octocrab::builder()
+                .personal_token(token.clone())
+                .build()
+                .map(Some)
+                // this is essentially the same reqwest::ClientBuilder::build error
+                // see https://docs.rs/octocrab/0.18.1/src/octocrab/lib.rs.html#360-364
+                .map_err(ErrorKind::BuildGithubClient),
+            _ => Ok(None),
+        }
+    }
 
-        Ok(Client {
-            reqwest_client,
-            github_client,
-            remaps: self.remaps,
-            fallback_extensions: self.fallback_extensions,
-            filter,
-            max_retries: self.max_retries,
-            retry_wait_time: self.retry_wait_time,
-            method: self.method,
-            accepted: self.accepted,
-            require_https: self.require_https,
-            include_fragments: self.include_fragments,
-            fragment_checker: FragmentChecker::new(),
-            plugin_request_chain: self.plugin_request_chain,
-        })
+    /// Create the resolver for MX lookups if `verify_mail` is set
+    #[cfg(feature = "verify-mail")]
+    fn mx_resolver(&self) -> Result<Option<Arc<MxResolver>>> {
+        if !self.verify_mail {
+            return Ok(None);
+        }
+        Ok(Some(Arc::new(MxResolver::from_system_conf()?)))
     }
 }
 
+/// How [`Client::check`] would handle a URI, as predicted by [`Client::plan`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckPlan {
+    /// The URI is excluded without being checked
+    Excluded,
+    /// Checking the URI goes over the network, e.g. an HTTP request, an FTP
+    /// login or the MX lookup of a mail domain
+    Request,
+    /// The URI is checked without going over the network, e.g. a local file,
+    /// or it is reported as an error or as unsupported right away
+    NoRequest,
+}
+
 /// Handles incoming requests and returns responses.
 ///
 /// See [`ClientBuilder`] which contains sane defaults for all configuration
@@ -419,6 +731,12 @@ pub struct Client {
     /// Automatically append file extensions to `file://` URIs as needed
     fallback_extensions: Vec<String>,
 
+    /// Report links to local files outside of this directory as errors.
+    root_dir: Option<PathBuf>,
+
+    /// Limits the number of concurrent filesystem checks
+    fs_permits: Arc<Semaphore>,
+
     /// Rules to decided whether each link should be checked or ignored.
     filter: Filter,
 
@@ -429,28 +747,98 @@ pub struct Client {
     /// each failure.
     retry_wait_time: Duration,
 
-    /// HTTP method used for requests, e.g. `GET` or `HEAD`.
+    /// HTTP methods used for requests, tried in order until the server
+    /// supports one.
     ///
-    /// The same method will be used for all links.
-    method: reqwest::Method,
+    /// The same methods will be used for all links.
+    methods: RequestMethods,
 
     /// Set of accepted return codes / status codes.
     ///
     /// Unmatched return codes/ status codes are deemed as errors.
     accepted: Option<HashSet<StatusCode>>,
 
+    /// Additional accepted status codes for URLs matching a regex.
+    accepted_patterns: Vec<(Regex, HashSet<StatusCode>)>,
+
+    /// Accepted status codes by URL scheme, replacing `accepted`.
+    accepted_schemes: HashMap<String, HashSet<StatusCode>>,
+
     /// Requires using HTTPS when it's available.
     ///
     /// This would treat unencrypted links as errors when HTTPS is available.
     require_https: bool,
 
+    /// Report links to local or private addresses as errors.
+    fail_on_private: bool,
+
+    /// Report links to these hosts as errors.
+    disallowed_hosts: Vec<String>,
+
+    /// Check mail addresses only for MX records of their domain.
+    verify_mail: bool,
+
+    /// Resolver for the MX records of mail domains, if mail addresses are
+    /// only checked for MX records.
+    #[cfg(feature = "verify-mail")]
+    mx_resolver: Option<Arc<MxResolver>>,
+
+    /// Check `ftp://` URIs.
+    include_ftp: bool,
+
+    /// Check `gemini://` URIs.
+    include_gemini: bool,
+
+    /// Check `ws://` and `wss://` URIs.
+    include_websocket: bool,
+
+    /// Check DOI, arXiv and `PubMed` identifiers through their resolvers.
+    include_identifiers: bool,
+
+    /// Response timeout per request, for checks not handled by `reqwest`.
+    #[cfg_attr(not(any(feature = "ftp", feature = "gemini")), allow(dead_code))]
+    timeout: Option<Duration>,
+
+    /// Maximum time for checking a website, across all retries.
+    total_timeout: Option<Duration>,
+
+    /// Warn about `target="_blank"` links without `rel="noopener"`.
+    require_external_noopener: bool,
+
     /// Enable the checking of fragments in links.
     include_fragments: bool,
 
+    /// Follow `<meta http-equiv="refresh">` redirects of HTML pages.
+    follow_meta_refresh: bool,
+
+    /// Maximum number of meta refreshes followed per link.
+    max_redirects: usize,
+
+    /// Warn about working links which took more redirects than this.
+    warn_redirects_over: Option<usize>,
+
+    /// Fail working links which were redirected from HTTPS to HTTP.
+    fail_on_https_downgrade: bool,
+
+    /// Fail links to images whose response is not an image.
+    verify_image_content_type: bool,
+
+    /// Record responses to or replay them from a directory, if configured.
+    recording: Option<Recording>,
+
+    /// Cycles through the user agents of requests, if configured.
+    user_agent_rotation: UserAgentRotation,
+
+    /// Sends bearer tokens to the hosts they belong to.
+    host_tokens: HostTokens,
+
     /// Caches Fragments
     fragment_checker: FragmentChecker,
 
     plugin_request_chain: RequestChain,
+
+    /// Called with every result of [`Client::check`].
+    on_result: Option<ResultCallback>,
 }
 
 impl Client {
@@ -465,18 +853,31 @@ impl Client {
     /// - `request` does not represent a valid URI.
     /// - Encrypted connection for a HTTP URL is available but unused.  (Only
     ///   checked when `Client::require_https` is `true`.)
-    #[allow(clippy::missing_panics_doc)]
     pub async fn check<T, E>(&self, request: T) -> Result<Response>
     where
         Request: TryFrom<T, Error = E>,
         ErrorKind: From<E>,
     {
+        let response = self.check_request(request.try_into()?).await?;
+        if let Some(on_result) = &self.on_result {
+            on_result.call(&response);
+        }
+        Ok(response)
+    }
+
+    async fn check_request(&self, request: Request) -> Result<Response> {
         let Request {
-            ref mut uri,
+            mut uri,
             credentials,
             source,
+            target,
+            rel,
+            span,
+            element,
+            attribute,
             ..
-        } = request.try_into()?;
+        } = request;
+        let uri = &mut uri;
 
         // Allow filtering based on element and attribute
         // if !self.filter.is_allowed(uri) {
@@ -487,31 +888,65 @@ impl Client {
         //     ));
         // }
 
+        if let Some(status) = self.disallowed_host_status(uri) {
+            return Ok(Response::new(uri.clone(), status, source).with_span(span));
+        }
+
         self.remap(uri)?;
 
+        // Identifiers are reported with their canonical URL, but may be
+        // looked up through a different one
+        let mut lookup = None;
+        if let Some(identifier) = self.identifier(uri) {
+            *uri = Uri::try_from(identifier.resolver_url())?;
+            lookup = Some(Uri::try_from(identifier.lookup_url())?);
+        }
+
         if self.is_excluded(uri) {
-            return Ok(Response::new(uri.clone(), Status::Excluded, source));
+            return Ok(Response::new(uri.clone(), Status::Excluded, source).with_span(span));
         }
 
-        let default_chain: RequestChain = Chain::new(vec![
-            Box::<Quirks>::default(),
-            Box::new(credentials),
-            Box::new(Checker::new(
-                self.retry_wait_time,
-                self.max_retries,
-                self.reqwest_client.clone(),
-                self.accepted.clone(),
-            )),
-        ]);
+        if self.rejects_private_address(uri) {
+            let status = Status::Error(ErrorKind::PrivateAddress(uri.clone()));
+            return Ok(Response::new(uri.clone(), status, source).with_span(span));
+        }
 
         let status = match uri.scheme() {
             _ if uri.is_file() => self.check_file(uri).await,
+            _ if uri.is_mail() && self.verify_mail => self.check_mail_mx(uri).await,
             _ if uri.is_mail() => self.check_mail(uri).await,
             _ if uri.is_tel() => self.check_tel(uri).await,
-            _ => self.check_website(uri, default_chain).await?,
+            "ftp" if self.include_ftp => self.check_ftp(uri).await,
+            "gemini" if self.include_gemini => self.check_gemini(uri).await,
+            _ if uri.is_websocket() && self.include_websocket => {
+                self.check_websocket(uri, credentials).await
+            }
+            _ => {
+                let checker =
+                    self.checker(is_image_source(element.as_deref(), attribute.as_deref()));
+                let default_chain = self.request_chain(credentials, Box::new(checker));
+                let uri = lookup.as_ref().unwrap_or(uri);
+                self.check_website(uri, default_chain).await?
+            }
+        };
+
+        let status = match status.code() {
+            Some(code)
+                if self.require_external_noopener
+                    && status.is_success()
+                    && matches!(uri.scheme(), "http" | "https")
+                    && is_missing_noopener(target.as_deref(), rel.as_deref()) =>
+            {
+                Status::Warning(
+                    code,
+                    "Link opens in a new tab without rel=\"noopener\" (tab-nabbing risk)"
+                        .to_string(),
+                )
+            }
+            _ => status,
         };
 
-        Ok(Response::new(uri.clone(), status, source))
+        Ok(Response::new(uri.clone(), status, source).with_span(span))
     }
 
     /// Remap `uri` using the client-defined remapping rules.
@@ -526,12 +961,125 @@ impl Client {
         Ok(())
     }
 
+    /// Returns the scholarly identifier `uri` refers to, if identifiers get
+    /// checked through their resolvers.
+    fn identifier(&self, uri: &Uri) -> Option<Identifier> {
+        if self.include_identifiers {
+            Identifier::from_uri(uri)
+        } else {
+            None
+        }
+    }
+
     /// Returns whether the given `uri` should be ignored from checking.
     #[must_use]
     pub fn is_excluded(&self, uri: &Uri) -> bool {
         self.filter.is_excluded(uri)
     }
 
+    /// Returns whether the given `uri` gets checked or ignored and why.
+    #[must_use]
+    pub fn filter_decision(&self, uri: &Uri) -> FilterDecision {
+        self.filter.decide(uri)
+    }
+
+    /// Returns whether the given `uri` gets reported as an error without
+    /// checking it, because it points to a private or local address and
+    /// `fail_on_private` is set.
+    #[must_use]
+    pub fn rejects_private_address(&self, uri: &Uri) -> bool {
+        self.fail_on_private && is_private_address(uri)
+    }
+
+    /// Returns how [`Client::check`] would handle `uri`, without checking it.
+    /// This goes through the same steps as the check: disallowed hosts,
+    /// remapping, identifiers, exclusions and private addresses, followed by
+    /// the checks of the schemes which are enabled and compiled in.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if remapping `uri` or resolving an identifier fails.
+    pub fn plan(&self, uri: &Uri) -> Result<CheckPlan> {
+        if self.disallowed_host_status(uri).is_some() {
+            return Ok(CheckPlan::NoRequest);
+        }
+        let mut uri = uri.clone();
+        self.remap(&mut uri)?;
+        if let Some(identifier) = self.identifier(&uri) {
+            uri = Uri::try_from(identifier.resolver_url())?;
+        }
+        if self.is_excluded(&uri) {
+            return Ok(CheckPlan::Excluded);
+        }
+        if self.rejects_private_address(&uri) {
+            return Ok(CheckPlan::NoRequest);
+        }
+
+        let request = match uri.scheme() {
+            _ if uri.is_file() => false,
+            _ if uri.is_mail() && self.verify_mail => return Ok(self.plan_mail_mx()),
+            _ if uri.is_mail() => return Ok(Self::plan_mail()),
+            _ if uri.is_tel() => return Ok(CheckPlan::Excluded),
+            "ftp" if self.include_ftp => cfg!(feature = "ftp"),
+            "gemini" if self.include_gemini => cfg!(feature = "gemini"),
+            _ if uri.is_websocket() && self.include_websocket => cfg!(feature = "websocket"),
+            scheme => matches!(scheme, "http" | "https"),
+        };
+        Ok(if request {
+            CheckPlan::Request
+        } else {
+            CheckPlan::NoRequest
+        })
+    }
+
+    /// How [`Client::check_mail`] would handle a mail address
+    const fn plan_mail() -> CheckPlan {
+        if cfg!(all(feature = "email-check", feature = "native-tls")) {
+            CheckPlan::Request
+        } else {
+            CheckPlan::Excluded
+        }
+    }
+
+    /// How [`Client::check_mail_mx`] would handle a mail address
+    #[cfg(feature = "verify-mail")]
+    const fn plan_mail_mx(&self) -> CheckPlan {
+        if self.mx_resolver.is_some() {
+            CheckPlan::Request
+        } else {
+            Self::plan_mail()
+        }
+    }
+
+    /// How [`Client::check_mail_mx`] would handle a mail address
+    #[cfg(not(feature = "verify-mail"))]
+    #[allow(clippy::unused_self)]
+    const fn plan_mail_mx(&self) -> CheckPlan {
+        CheckPlan::NoRequest
+    }
+
+    /// Returns an error status if the host of `uri` is disallowed, suggesting
+    /// the remapped URI as replacement if a remapping rule applies.
+    fn disallowed_host_status(&self, uri: &Uri) -> Option<Status> {
+        let host = uri.url.host_str()?;
+        if !self
+            .disallowed_hosts
+            .iter()
+            .any(|disallowed| disallowed.eq_ignore_ascii_case(host))
+        {
+            return None;
+        }
+        let mut replacement = uri.clone();
+        let replacement = match self.remap(&mut replacement) {
+            Ok(()) if replacement != *uri => Some(replacement),
+            _ => None,
+        };
+        Some(Status::Error(ErrorKind::DisallowedHost(
+            host.to_string(),
+            replacement,
+        )))
+    }
+
     /// Checks the given URI of a website.
     ///
     /// # Errors
@@ -542,7 +1090,9 @@ impl Client {
     /// - The response status code is not accepted.
     /// - The URI cannot be converted to HTTPS.
     pub async fn check_website(&self, uri: &Uri, default_chain: RequestChain) -> Result<Status> {
-        match self.check_website_inner(uri, &default_chain).await {
+        let (status, refresh) =
+            meta_refresh::track(self.check_website_inner(uri, &default_chain)).await;
+        match status {
             Status::Ok(code) if self.require_https && uri.scheme() == "http" => {
                 if self
                     .check_website_inner(&uri.to_https()?, &default_chain)
@@ -556,10 +1106,69 @@ impl Client {
                     Ok(Status::Ok(code))
                 }
             }
+            s if s.is_success() && self.follow_meta_refresh => Ok(self
+                .follow_meta_refresh(uri, s, refresh, &default_chain)
+                .await),
             s => Ok(s),
         }
     }
 
+    /// Follow `<meta http-equiv="refresh">` redirects starting at `uri`.
+    ///
+    /// Returns the status of the final target, so that a page which refreshes
+    /// to a broken link is reported as an error. `status` and `refresh` are
+    /// what the check of `uri` found out; `status` is returned if the page
+    /// does not refresh.
+    async fn follow_meta_refresh(
+        &self,
+        uri: &Uri,
+        mut status: Status,
+        mut refresh: Option<MetaRefresh>,
+        default_chain: &RequestChain,
+    ) -> Status {
+        let mut current = uri.clone();
+        for _ in 0..self.max_redirects {
+            let Some(target) = self
+                .meta_refresh_target(&current, refresh, default_chain)
+                .await
+            else {
+                break;
+            };
+            debug!("Following meta refresh from {current} to {target}");
+            (status, refresh) =
+                meta_refresh::track(self.check_website_inner(&target, default_chain)).await;
+            if !status.is_success() {
+                break;
+            }
+            current = target;
+        }
+        status
+    }
+
+    /// The target of the meta `refresh` of the page at `uri`, which was just
+    /// checked.
+    ///
+    /// The checker records the target from the response to the check, so the
+    /// page is only fetched again if it was checked without a body, e.g. with
+    /// `HEAD`. That request goes through the request chains as well.
+    async fn meta_refresh_target(
+        &self,
+        uri: &Uri,
+        refresh: Option<MetaRefresh>,
+        default_chain: &RequestChain,
+    ) -> Option<Uri> {
+        let mut refresh = refresh?;
+        if refresh == MetaRefresh::Unknown {
+            let request = self.reqwest_client.get(uri.as_str()).build().ok()?;
+            let chains = ClientRequestChains::new(vec![&self.plugin_request_chain, default_chain]);
+            refresh = meta_refresh::track(chains.traverse(request)).await.1?;
+        }
+        match refresh {
+            MetaRefresh::Target(target) => Some(target.into()),
+            MetaRefresh::Unknown => None,
+        }
+    }
+
     /// Checks the given URI of a website.
     ///
     /// Unsupported schemes will be ignored
@@ -585,23 +1194,33 @@ impl Client {
             return Status::Unsupported(ErrorKind::InvalidURI(uri.clone()));
         }
 
-        let request = self
-            .reqwest_client
-            .request(self.method.clone(), uri.as_str())
-            .build();
-
-        let request = match request {
-            Ok(r) => r,
-            Err(e) => return e.into(),
+        let status = match self.total_timeout {
+            Some(total_timeout) => {
+                tokio::time::timeout(total_timeout, self.send_request(uri, default_chain))
+                    .await
+                    .unwrap_or(Status::Timeout(None))
+            }
+            None => self.send_request(uri, default_chain).await,
         };
-
-        let status = ClientRequestChains::new(vec![&self.plugin_request_chain, default_chain])
-            .traverse(request)
-            .await;
-
         self.handle_github(status, uri).await
     }
 
+    /// Send a request to `uri` through the request chains, with the first
+    /// configured method. The checker falls back to the other methods if the
+    /// server doesn't support it.
+    async fn send_request(&self, uri: &Uri, default_chain: &RequestChain) -> Status {
+        // `RequestMethods` is never empty
+        let method = self.methods.iter().next().cloned().unwrap_or_default();
+        match self.reqwest_client.request(method, uri.as_str()).build() {
+            Ok(request) => {
+                ClientRequestChains::new(vec![&self.plugin_request_chain, default_chain])
+                    .traverse(request)
+                    .await
+            }
+            Err(e) => e.into(),
+        }
+    }
+
     // Pull out the heavy machinery in case of a failed normal request.
     // This could be a GitHub URL and we ran into the rate limiter.
     // TODO: We should first try to parse the URI as GitHub URI first (Lucius, Jan 2023)
@@ -662,30 +1281,22 @@ impl Client {
         let Ok(path) = uri.url.to_file_path() else {
             return ErrorKind::InvalidFilePath(uri.clone()).into();
         };
-
-        if path.exists() {
-            if self.include_fragments {
-                return self.check_fragment(&path, uri).await;
+        if let Some(root_dir) = &self.root_dir {
+            if !utils::path::is_within(root_dir, &path) {
+                return ErrorKind::OutsideRootDir(uri.clone(), root_dir.clone()).into();
             }
-            return Status::Ok(StatusCode::OK);
-        }
-
-        if path.extension().is_some() {
-            return ErrorKind::InvalidFilePath(uri.clone()).into();
         }
 
-        // if the path has no file extension, try to append some
-        let mut path_buf = path.clone();
-        for ext in &self.fallback_extensions {
-            path_buf.set_extension(ext);
-            if path_buf.exists() {
-                if self.include_fragments {
-                    return self.check_fragment(&path_buf, uri).await;
-                }
-                return Status::Ok(StatusCode::OK);
-            }
+        // The semaphore is never closed, so acquiring a permit can't fail
+        let _permit = self.fs_permits.acquire().await;
+        let fallback_extensions = self.fallback_extensions.clone();
+        let resolved =
+            tokio::task::spawn_blocking(move || resolve_file(path, &fallback_extensions)).await;
+        match resolved {
+            Ok(Some(path)) if self.include_fragments => self.check_fragment(&path, uri).await,
+            Ok(Some(_)) => Status::Ok(StatusCode::OK),
+            Ok(None) | Err(_) => ErrorKind::InvalidFilePath(uri.clone()).into(),
         }
-        ErrorKind::InvalidFilePath(uri.clone()).into()
     }
 
     /// Checks a `file` URI's fragment.
@@ -704,9 +1315,17 @@ impl Client {
     ///
     /// URIs may contain query parameters (e.g. `contact@example.com?subject="Hello"`),
     /// which are ignored by this check. The are not part of the mail address
-    /// and instead passed to a mail client.
+    /// and instead passed to a mail client. Only the addresses in `to`, `cc`
+    /// and `bcc` parameters are validated, but not checked.
     #[cfg(all(feature = "email-check", feature = "native-tls"))]
     pub async fn check_mail(&self, uri: &Uri) -> Status {
+        if !utils::mail::is_valid(uri.as_str()) {
+            return ErrorKind::UnreachableEmailAddress(
+                uri.clone(),
+                "Invalid: The address is syntactically incorrect".to_string(),
+            )
+            .into();
+        }
         let address = uri.url.path().to_string();
         let input = CheckEmailInput::new(address);
         let result = &(check_email(&input).await);
@@ -727,25 +1346,233 @@ impl Client {
         Status::Excluded
     }
 
-    /// Check a tel
+    /// Check a mail address by looking up the MX records of its domain.
+    ///
+    /// The address is valid if it is syntactically correct and its domain has
+    /// at least one MX record. A domain without MX records is its own mail
+    /// server if it has an address (RFC 5321, section 5.1), so the address is
+    /// valid as well if the domain has an A or AAAA record. Whether the
+    /// mailbox exists is not checked.
+    #[cfg(feature = "verify-mail")]
+    pub async fn check_mail_mx(&self, uri: &Uri) -> Status {
+        let Some(resolver) = &self.mx_resolver else {
+            return self.check_mail(uri).await;
+        };
+        let domain = utils::mail::domain(uri.as_str());
+        let (true, Some(domain)) = (utils::mail::is_valid(uri.as_str()), domain) else {
+            return ErrorKind::UnreachableEmailAddress(
+                uri.clone(),
+                "Invalid: The address is syntactically incorrect".to_string(),
+            )
+            .into();
+        };
+        match resolver.check(domain).await {
+            Ok(()) => Status::Ok(StatusCode::OK),
+            Err(reason) => ErrorKind::UnreachableEmailAddress(uri.clone(), reason).into(),
+        }
+    }
+
+    /// Check a mail address by looking up the MX records of its domain.
     ///
-    /// This implementation simply excludes all tel.
+    /// This implementation treats all mail addresses as unsupported, as
+    /// lychee was built without the `verify-mail` feature.
+    #[cfg(not(feature = "verify-mail"))]
     #[allow(clippy::unused_async)]
-    pub async fn check_tel(&self, _uri: &Uri) -> Status {
-        Status::Excluded
+    pub async fn check_mail_mx(&self, uri: &Uri) -> Status {
+        Status::Unsupported(ErrorKind::InvalidURI(uri.clone()))
     }
-}
 
-// Check if the given `Url` would cause `reqwest` to panic.
-// This is a workaround for https://github.com/lycheeverse/lychee/issues/539
-// and can be removed once https://github.com/seanmonstar/reqwest/pull/1399
-// got merged.
-// It is exactly the same check that reqwest runs internally, but unfortunately
-// it `unwrap`s (and panics!) instead of returning an error, which we could handle.
+    /// Check an `ftp://` URI by logging into the server and looking up the
+    /// file or directory. No data connection is opened.
+    #[cfg(feature = "ftp")]
+    pub async fn check_ftp(&self, uri: &Uri) -> Status {
+        let target = match ftp::FtpTarget::from_url(&uri.url) {
+            Ok(target) => target,
+            Err(e) => return Status::Error(e),
+        };
+        let timeout = self
+            .timeout
+            .unwrap_or(Duration::from_secs(DEFAULT_TIMEOUT_SECS as u64));
+        match ftp::check(&target, timeout).await {
+            Some(Ok(())) => Status::Ok(StatusCode::OK),
+            Some(Err(reason)) => ErrorKind::UnreachableFtpResource(uri.clone(), reason).into(),
+            None => Status::Timeout(None),
+        }
+    }
+
+    /// Check an `ftp://` URI.
+    ///
+    /// This implementation treats all FTP URIs as unsupported, as lychee was
+    /// built without the `ftp` feature.
+    #[cfg(not(feature = "ftp"))]
+    #[allow(clippy::unused_async)]
+    pub async fn check_ftp(&self, uri: &Uri) -> Status {
+        Status::Unsupported(ErrorKind::InvalidURI(uri.clone()))
+    }
+
+    /// Check a `gemini://` URI by requesting the resource and mapping the
+    /// status of the response header. The body is never read.
+    #[cfg(feature = "gemini")]
+    pub async fn check_gemini(&self, uri: &Uri) -> Status {
+        let timeout = self
+            .timeout
+            .unwrap_or(Duration::from_secs(DEFAULT_TIMEOUT_SECS as u64));
+        match gemini::request(&uri.url, timeout).await {
+            Some(Ok(header)) => gemini::status_from_header(uri, &header),
+            Some(Err(reason)) => ErrorKind::UnreachableGeminiResource(uri.clone(), reason).into(),
+            None => Status::Timeout(None),
+        }
+    }
+
+    /// Check a `gemini://` URI.
+    ///
+    /// This implementation treats all Gemini URIs as unsupported, as lychee
+    /// was built without the `gemini` feature.
+    #[cfg(not(feature = "gemini"))]
+    #[allow(clippy::unused_async)]
+    pub async fn check_gemini(&self, uri: &Uri) -> Status {
+        Status::Unsupported(ErrorKind::InvalidURI(uri.clone()))
+    }
+
+    /// Check a `ws://` or `wss://` URI by performing the WebSocket opening
+    /// handshake. The connection is closed as soon as the server agrees to
+    /// switch protocols.
+    ///
+    /// The handshake goes through the same request chains as HTTP requests,
+    /// so custom headers, credentials and host tokens are applied to it.
+    #[cfg(feature = "websocket")]
+    pub async fn check_websocket(
+        &self,
+        uri: &Uri,
+        credentials: Option<BasicAuthCredentials>,
+    ) -> Status {
+        let Some(request) = websocket::upgrade_request(&uri.url) else {
+            return Status::Error(ErrorKind::InvalidURI(uri.clone()));
+        };
+        let handshake = websocket::Handshake::new(self.reqwest_client.clone(), uri.clone());
+        let default_chain = self.request_chain(credentials, Box::new(handshake));
+        ClientRequestChains::new(vec![&self.plugin_request_chain, &default_chain])
+            .traverse(request)
+            .await
+    }
+
+    /// Check a `ws://` or `wss://` URI.
+    ///
+    /// This implementation treats all WebSocket URIs as unsupported, as lychee
+    /// was built without the `websocket` feature.
+    #[cfg(not(feature = "websocket"))]
+    #[allow(clippy::unused_async)]
+    pub async fn check_websocket(
+        &self,
+        uri: &Uri,
+        _credentials: Option<BasicAuthCredentials>,
+    ) -> Status {
+        Status::Unsupported(ErrorKind::InvalidURI(uri.clone()))
+    }
+
+    /// The default request chain: sets the user agent, applies quirks,
+    /// credentials and host tokens, and hands the request to `sender`
+    fn request_chain(
+        &self,
+        credentials: Option<BasicAuthCredentials>,
+        sender: Box<dyn Handler<reqwest::Request, Status> + Send>,
+    ) -> RequestChain {
+        Chain::new(vec![
+            Box::new(self.user_agent_rotation.clone()),
+            Box::<Quirks>::default(),
+            Box::new(credentials),
+            Box::new(self.host_tokens.clone()),
+            sender,
+        ])
+    }
+
+    /// The checker which sends HTTP requests at the end of the default
+    /// request chain
+    fn checker(&self, image_source: bool) -> Checker {
+        Checker::new(
+            self.retry_wait_time,
+            self.max_retries,
+            self.reqwest_client.clone(),
+            self.accepted.clone(),
+            self.accepted_patterns.clone(),
+            self.warn_redirects_over,
+        )
+        .accepted_schemes(self.accepted_schemes.clone())
+        .fail_on_https_downgrade(self.fail_on_https_downgrade)
+        .methods(self.methods.clone())
+        .record_meta_refresh(self.follow_meta_refresh)
+        .expect_image(self.verify_image_content_type && image_source)
+        .recording(self.recording.clone())
+    }
+
+    /// Check a tel
+    ///
+    /// This implementation simply excludes all tel.
+    #[allow(clippy::unused_async)]
+    pub async fn check_tel(&self, _uri: &Uri) -> Status {
+        Status::Excluded
+    }
+}
+
+// Check if the given `Url` would cause `reqwest` to panic.
+// This is a workaround for https://github.com/lycheeverse/lychee/issues/539
+// and can be removed once https://github.com/seanmonstar/reqwest/pull/1399
+// got merged.
+// It is exactly the same check that reqwest runs internally, but unfortunately
+// it `unwrap`s (and panics!) instead of returning an error, which we could handle.
 fn validate_url(url: &Url) -> bool {
     http::Uri::try_from(url.as_str()).is_err()
 }
 
+/// Compile the regexes of the accepted patterns.
+fn compile_accepted_patterns(
+    patterns: Vec<AcceptPatternSelector>,
+) -> Result<Vec<(Regex, HashSet<StatusCode>)>> {
+    let mut accepted_patterns = Vec::with_capacity(patterns.len());
+    for pattern in patterns {
+        let codes = status_codes(pattern.selector);
+        accepted_patterns.push((Regex::new(&pattern.raw_uri_regex)?, codes));
+    }
+    Ok(accepted_patterns)
+}
+
+/// The valid status codes of `selector`.
+///
+/// Codes outside of the valid range can never be returned by a server,
+/// so they can safely be dropped.
+fn status_codes(selector: AcceptSelector) -> HashSet<StatusCode> {
+    selector
+        .into_set()
+        .into_iter()
+        .filter_map(|code| StatusCode::from_u16(code).ok())
+        .collect()
+}
+
+/// Whether `uri` points to localhost or a loopback, private or link-local
+/// address. Uses the same detection as the `exclude_` options of the filter.
+fn is_private_address(uri: &Uri) -> bool {
+    uri.is_loopback()
+        || uri.is_private()
+        || uri.is_link_local()
+        || uri.domain() == Some("localhost")
+}
+
+/// The existing file `path` points to. If `path` doesn't exist and has no
+/// file extension, the `fallback_extensions` are tried in order.
+fn resolve_file(path: PathBuf, fallback_extensions: &[String]) -> Option<PathBuf> {
+    if path.exists() {
+        return Some(path);
+    }
+    if path.extension().is_some() {
+        return None;
+    }
+    let mut path = path;
+    fallback_extensions.iter().find_map(|ext| {
+        path.set_extension(ext);
+        path.exists().then(|| path.clone())
+    })
+}
+
 /// A shorthand function to check a single URI.
 ///
 /// This provides the simplest link check utility without having to create a
@@ -771,21 +1598,23 @@ where
 mod tests {
     use std::{
         fs::File,
+        sync::{Arc, Mutex},
         time::{Duration, Instant},
     };
 
     use async_trait::async_trait;
     use http::{header::HeaderMap, StatusCode};
-    use reqwest::header;
+    use regex::RegexSet;
+    use reqwest::{header, Url};
     use tempfile::tempdir;
     use wiremock::matchers::path;
 
-    use super::ClientBuilder;
+    use super::{CheckPlan, Client, ClientBuilder};
     use crate::{
         chain::{ChainResult, Handler, RequestChain},
         mock_server,
         test_utils::get_mock_client_response,
-        Request, Status, Uri,
+        ErrorKind, FilterDecision, HostTokens, Request, Response, Status, Uri,
     };
 
     #[tokio::test]
@@ -888,6 +1717,42 @@ mod tests {
         assert!(res.status().is_success());
     }
 
+    #[tokio::test]
+    async fn test_fs_concurrency() {
+        let dir = tempdir().unwrap();
+        let uris: Vec<_> = (0..500)
+            .map(|i| {
+                let path = dir.path().join(format!("{i}.html"));
+                // Every other link points to a missing file
+                if i % 2 == 0 {
+                    File::create(&path).unwrap();
+                }
+                format!("file://{}", path.to_str().unwrap())
+            })
+            .collect();
+
+        let client = ClientBuilder::builder()
+            .fs_concurrency(4_usize)
+            .build()
+            .client()
+            .unwrap();
+        let responses =
+            futures::future::join_all(uris.iter().map(|uri| client.check(uri.as_str()))).await;
+
+        let successes = responses
+            .iter()
+            .filter(|response| response.as_ref().unwrap().status().is_success())
+            .count();
+        assert_eq!(successes, 250);
+        assert_eq!(client.fs_permits.available_permits(), 4);
+
+        // Checks wait while all permits are taken
+        let permits = client.fs_permits.clone().acquire_many_owned(4).await;
+        let check = tokio::time::timeout(Duration::from_millis(100), client.check(&*uris[0]));
+        assert!(check.await.is_err());
+        drop(permits);
+    }
+
     #[tokio::test]
     async fn test_custom_headers() {
         // See https://github.com/rust-lang/crates.io/issues/788
@@ -904,6 +1769,48 @@ mod tests {
         assert!(res.status().is_success());
     }
 
+    #[tokio::test]
+    async fn test_host_tokens() {
+        let mock_server = wiremock::MockServer::start().await;
+        for (token, status) in [("abc123", 200), ("xyz789", 202)] {
+            wiremock::Mock::given(wiremock::matchers::header(
+                "Authorization",
+                format!("Bearer {token}").as_str(),
+            ))
+            .respond_with(wiremock::ResponseTemplate::new(status))
+            .mount(&mock_server)
+            .await;
+        }
+        wiremock::Mock::given(wiremock::matchers::any())
+            .respond_with(wiremock::ResponseTemplate::new(401))
+            .mount(&mock_server)
+            .await;
+
+        // The mock server only speaks HTTP, so allow sending the tokens in
+        // plain text
+        let host_tokens: HostTokens = "HOST_TOKEN_127_0_0_1=abc123\nHOST_TOKEN_localhost=xyz789"
+            .parse()
+            .unwrap();
+        let client = ClientBuilder::builder()
+            .host_tokens(host_tokens.plain_text())
+            .build()
+            .client()
+            .unwrap();
+
+        let port = mock_server.address().port();
+        for (host, expected) in [("127.0.0.1", 200), ("localhost", 202)] {
+            let res = client
+                .check(format!("http://{host}:{port}/"))
+                .await
+                .unwrap();
+            assert_eq!(
+                res.status().code(),
+                Some(StatusCode::from_u16(expected).unwrap()),
+                "{host}"
+            );
+        }
+    }
+
     #[tokio::test]
     async fn test_exclude_mail_by_default() {
         let client = ClientBuilder::builder()
@@ -939,6 +1846,244 @@ mod tests {
         }));
     }
 
+    #[tokio::test]
+    async fn test_identifier_reported_with_resolver_url() {
+        let client = ClientBuilder::builder()
+            .include_identifiers(true)
+            .excludes(RegexSet::new([r"^https://doi\.org/"]).unwrap())
+            .build()
+            .client()
+            .unwrap();
+
+        let res = client.check("doi:10.1000/182").await.unwrap();
+        assert_eq!(res.1.uri.as_str(), "https://doi.org/10.1000/182");
+        assert!(res.status().is_excluded());
+    }
+
+    #[tokio::test]
+    async fn test_exclude_fn() {
+        let client = ClientBuilder::builder()
+            .exclude_fn(Box::new(|url: &Url| {
+                url.host_str() == Some("skipped.example")
+            }))
+            .excludes(RegexSet::new([r"^https://excluded\.example/"]).unwrap())
+            .includes(RegexSet::new([r"^https://skipped\.example/included"]).unwrap())
+            .build()
+            .client()
+            .unwrap();
+
+        assert_eq!(
+            client.filter_decision(&Uri::try_from("https://skipped.example/page").unwrap()),
+            FilterDecision::ExcludedByPredicate
+        );
+        let res = client.check("https://skipped.example/page").await.unwrap();
+        assert!(res.status().is_excluded());
+
+        // Composes with exclude patterns, and include patterns take precedence
+        assert!(client.is_excluded(&Uri::try_from("https://excluded.example/").unwrap()));
+        assert!(!client.is_excluded(&Uri::try_from("https://skipped.example/included").unwrap()));
+        assert!(!client.is_excluded(&Uri::try_from("https://checked.example/").unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_on_result() {
+        let ok = mock_server!(StatusCode::OK);
+        let broken = mock_server!(StatusCode::NOT_FOUND);
+        let results = Arc::new(Mutex::new(Vec::new()));
+
+        let reported = results.clone();
+        let client = ClientBuilder::builder()
+            .excludes(RegexSet::new([r"^https://excluded\.example/"]).unwrap())
+            .max_retries(0_u64)
+            .on_result(Box::new(move |response: &Response| {
+                reported.lock().unwrap().push((
+                    response.1.uri.to_string(),
+                    response.status().code_as_string(),
+                ));
+            }))
+            .build()
+            .client()
+            .unwrap();
+
+        for uri in [
+            ok.uri(),
+            broken.uri(),
+            "https://excluded.example/".to_string(),
+        ] {
+            client.check(uri).await.unwrap();
+        }
+
+        assert_eq!(
+            *results.lock().unwrap(),
+            vec![
+                (format!("{}/", ok.uri()), "200 OK".to_string()),
+                (format!("{}/", broken.uri()), "404 Not Found".to_string()),
+                (
+                    "https://excluded.example/".to_string(),
+                    "EXCLUDED".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ftp_unsupported_by_default() {
+        let client = ClientBuilder::builder().build().client().unwrap();
+        let res = client.check("ftp://127.0.0.1/README").await.unwrap();
+        assert!(res.status().is_unsupported());
+    }
+
+    #[tokio::test]
+    async fn test_gemini_unsupported_by_default() {
+        let client = ClientBuilder::builder().build().client().unwrap();
+        let res = client.check("gemini://127.0.0.1/").await.unwrap();
+        assert!(res.status().is_unsupported());
+    }
+
+    #[cfg(feature = "gemini")]
+    #[tokio::test]
+    #[ignore = "requires network access"]
+    async fn test_gemini_public_capsule() {
+        let client = ClientBuilder::builder()
+            .include_gemini(true)
+            .build()
+            .client()
+            .unwrap();
+        let res = client.check("gemini://geminiprotocol.net/").await.unwrap();
+        assert!(res.status().is_success());
+    }
+
+    #[tokio::test]
+    async fn test_websocket_unsupported_by_default() {
+        let client = ClientBuilder::builder().build().client().unwrap();
+        let res = client.check("wss://127.0.0.1/socket").await.unwrap();
+        assert!(res.status().is_unsupported());
+    }
+
+    /// A server which agrees to every WebSocket handshake containing
+    /// `required_header` and closes the connection right after
+    #[cfg(feature = "websocket")]
+    async fn mock_websocket_server(required_header: &'static str) -> u16 {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buf = [0; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    match stream.read(&mut buf).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => request.extend_from_slice(&buf[..n]),
+                    }
+                }
+                let request = String::from_utf8_lossy(&request).to_lowercase();
+                let response: &[u8] = if request.contains(&required_header.to_lowercase()) {
+                    b"HTTP/1.1 101 Switching Protocols\r\n\
+                      Upgrade: websocket\r\n\
+                      Connection: Upgrade\r\n\r\n"
+                } else {
+                    b"HTTP/1.1 401 Unauthorized\r\nContent-Length: 0\r\n\r\n"
+                };
+                let _ = stream.write_all(response).await;
+            }
+        });
+        port
+    }
+
+    #[cfg(feature = "websocket")]
+    #[tokio::test]
+    async fn test_websocket() {
+        let client = ClientBuilder::builder()
+            .include_websocket(true)
+            .build()
+            .client()
+            .unwrap();
+
+        let port = mock_websocket_server("").await;
+        let res = client
+            .check(format!("ws://127.0.0.1:{port}/socket"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), &Status::Ok(StatusCode::SWITCHING_PROTOCOLS));
+
+        // A plain HTTP server doesn't switch protocols
+        let mock_server = mock_server!(StatusCode::OK);
+        let url = mock_server.uri().replacen("http", "ws", 1);
+        let res = client.check(url).await.unwrap();
+        assert!(res.status().is_error());
+    }
+
+    #[cfg(feature = "websocket")]
+    #[tokio::test]
+    async fn test_websocket_request_chain() {
+        let port = mock_websocket_server("x-token: secret\r\n").await;
+        let url = format!("ws://127.0.0.1:{port}/socket");
+
+        let client = ClientBuilder::builder()
+            .include_websocket(true)
+            .build()
+            .client()
+            .unwrap();
+        let res = client.check(url.as_str()).await.unwrap();
+        assert_eq!(
+            res.status(),
+            &Status::Error(ErrorKind::RejectedWebSocketHandshake(
+                url.as_str().try_into().unwrap(),
+                StatusCode::UNAUTHORIZED
+            ))
+        );
+
+        let client = ClientBuilder::builder()
+            .include_websocket(true)
+            .custom_headers(HeaderMap::from_iter([(
+                header::HeaderName::from_static("x-token"),
+                header::HeaderValue::from_static("secret"),
+            )]))
+            .build()
+            .client()
+            .unwrap();
+        let res = client.check(url.as_str()).await.unwrap();
+        assert_eq!(res.status(), &Status::Ok(StatusCode::SWITCHING_PROTOCOLS));
+    }
+
+    #[cfg(feature = "websocket")]
+    #[tokio::test]
+    async fn test_websocket_basic_auth() {
+        // `user:pass`, base64-encoded
+        let port = mock_websocket_server("authorization: basic dxnlcjpwyxnz\r\n").await;
+        let client = ClientBuilder::builder()
+            .include_websocket(true)
+            .build()
+            .client()
+            .unwrap();
+
+        let mut request: Request = format!("ws://127.0.0.1:{port}/socket")
+            .as_str()
+            .try_into()
+            .unwrap();
+        request.credentials = Some(crate::BasicAuthCredentials {
+            username: "user".into(),
+            password: "pass".into(),
+        });
+        let res = client.check(request).await.unwrap();
+        assert_eq!(res.status(), &Status::Ok(StatusCode::SWITCHING_PROTOCOLS));
+    }
+
+    #[cfg(feature = "websocket")]
+    #[tokio::test]
+    #[ignore = "requires network access"]
+    async fn test_websocket_public_endpoint() {
+        let client = ClientBuilder::builder()
+            .include_websocket(true)
+            .build()
+            .client()
+            .unwrap();
+        let res = client.check("wss://ws.postman-echo.com/raw").await.unwrap();
+        assert!(res.status().is_success());
+    }
+
     #[tokio::test]
     async fn test_include_tel() {
         let client = ClientBuilder::builder().build().client().unwrap();
@@ -947,6 +2092,145 @@ mod tests {
         }));
     }
 
+    #[tokio::test]
+    async fn test_disallowed_host() {
+        let client = ClientBuilder::builder()
+            .disallowed_hosts(vec!["Example.com".to_string()])
+            .build()
+            .client()
+            .unwrap();
+
+        let res = client.check("https://example.com/foo").await.unwrap();
+        assert!(res.status().is_error());
+        assert_eq!(
+            res.status().to_string(),
+            "Failed: Links to host 'example.com' are not allowed"
+        );
+
+        let res = client.check("https://sub.example.com/foo").await.unwrap();
+        assert!(!matches!(
+            res.status(),
+            Status::Error(ErrorKind::DisallowedHost(..))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_plan() {
+        let plan = |client: &Client, uri: &str| client.plan(&Uri::try_from(uri).unwrap()).unwrap();
+
+        let client = ClientBuilder::builder()
+            .disallowed_hosts(vec!["disallowed.lychee.cli.rs".to_string()])
+            .excludes(RegexSet::new([r"^https://arxiv\.org/"]).unwrap())
+            .include_identifiers(true)
+            .build()
+            .client()
+            .unwrap();
+        assert_eq!(plan(&client, "https://lychee.cli.rs"), CheckPlan::Request);
+        assert_eq!(
+            plan(&client, "https://disallowed.lychee.cli.rs"),
+            CheckPlan::NoRequest
+        );
+        assert_eq!(plan(&client, "doi:10.1000/182"), CheckPlan::Request);
+        assert_eq!(plan(&client, "arxiv:2101.00001"), CheckPlan::Excluded);
+        assert_eq!(
+            plan(&client, "file:///tmp/index.html"),
+            CheckPlan::NoRequest
+        );
+        assert_eq!(
+            plan(&client, "slack://channel?id=123"),
+            CheckPlan::NoRequest
+        );
+        assert_eq!(plan(&client, "tel:1234567890"), CheckPlan::Excluded);
+        assert_eq!(
+            plan(&client, "mailto:mail@lychee.cli.rs"),
+            CheckPlan::Excluded
+        );
+
+        // Without identifiers, `doi:` is an unsupported scheme
+        let client = ClientBuilder::builder().build().client().unwrap();
+        assert_eq!(plan(&client, "doi:10.1000/182"), CheckPlan::NoRequest);
+    }
+
+    #[tokio::test]
+    async fn test_plan_mail() {
+        let plan = |client: &Client| {
+            client
+                .plan(&Uri::try_from("mailto:mail@lychee.cli.rs").unwrap())
+                .unwrap()
+        };
+
+        let client = ClientBuilder::builder()
+            .include_mail(true)
+            .build()
+            .client()
+            .unwrap();
+        let expected = if cfg!(all(feature = "email-check", feature = "native-tls")) {
+            CheckPlan::Request
+        } else {
+            CheckPlan::Excluded
+        };
+        assert_eq!(plan(&client), expected);
+
+        let client = ClientBuilder::builder()
+            .include_mail(true)
+            .verify_mail(true)
+            .build()
+            .client()
+            .unwrap();
+        let expected = if cfg!(feature = "verify-mail") {
+            CheckPlan::Request
+        } else {
+            CheckPlan::NoRequest
+        };
+        assert_eq!(plan(&client), expected);
+    }
+
+    #[tokio::test]
+    async fn test_plan_schemes() {
+        let plan = |client: &Client, uri: &str| client.plan(&Uri::try_from(uri).unwrap()).unwrap();
+        let request_if = |compiled_in| {
+            if compiled_in {
+                CheckPlan::Request
+            } else {
+                CheckPlan::NoRequest
+            }
+        };
+
+        let client = ClientBuilder::builder().build().client().unwrap();
+        assert_eq!(
+            plan(&client, "ftp://lychee.cli.rs/file"),
+            CheckPlan::NoRequest
+        );
+        assert_eq!(
+            plan(&client, "gemini://lychee.cli.rs/"),
+            CheckPlan::NoRequest
+        );
+        assert_eq!(
+            plan(&client, "wss://lychee.cli.rs/socket"),
+            CheckPlan::NoRequest
+        );
+
+        let client = ClientBuilder::builder()
+            .include_ftp(true)
+            .include_gemini(true)
+            .include_websocket(true)
+            .build()
+            .client()
+            .unwrap();
+        assert_eq!(
+            plan(&client, "ftp://lychee.cli.rs/file"),
+            request_if(cfg!(feature = "ftp"))
+        );
+        assert_eq!(
+            plan(&client, "gemini://lychee.cli.rs/"),
+            request_if(cfg!(feature = "gemini"))
+        );
+        assert_eq!(
+            plan(&client, "wss://lychee.cli.rs/socket"),
+            request_if(cfg!(feature = "websocket"))
+        );
+    }
+
     #[tokio::test]
     async fn test_require_https() {
         let client = ClientBuilder::builder().build().client().unwrap();
@@ -963,6 +2247,142 @@ mod tests {
         assert!(res.status().is_error());
     }
 
+    #[tokio::test]
+    async fn test_request_methods_fallback() {
+        for code in [StatusCode::METHOD_NOT_ALLOWED, StatusCode::NOT_IMPLEMENTED] {
+            let mock_server = wiremock::MockServer::start().await;
+            wiremock::Mock::given(wiremock::matchers::method("HEAD"))
+                .respond_with(wiremock::ResponseTemplate::new(code))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+            wiremock::Mock::given(wiremock::matchers::method("GET"))
+                .respond_with(wiremock::ResponseTemplate::new(StatusCode::OK))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+
+            let client = ClientBuilder::builder()
+                .request_methods(Some("HEAD,GET".parse().unwrap()))
+                .build()
+                .client()
+                .unwrap();
+
+            let res = client.check(mock_server.uri()).await.unwrap();
+            assert!(res.status().is_success(), "{code}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_methods_retry_last_method() {
+        let mock_server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::method("HEAD"))
+            .respond_with(wiremock::ResponseTemplate::new(StatusCode::NOT_IMPLEMENTED))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .respond_with(wiremock::ResponseTemplate::new(StatusCode::NOT_IMPLEMENTED))
+            .expect(3)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::builder()
+            .request_methods(Some("HEAD,GET".parse().unwrap()))
+            .max_retries(2_u64)
+            .retry_wait_time(Duration::from_millis(10))
+            .build()
+            .client()
+            .unwrap();
+
+        let res = client.check(mock_server.uri()).await.unwrap();
+        assert_eq!(res.status().code(), Some(StatusCode::NOT_IMPLEMENTED));
+    }
+
+    #[tokio::test]
+    async fn test_request_methods_no_fallback_on_other_errors() {
+        let mock_server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::method("HEAD"))
+            .respond_with(wiremock::ResponseTemplate::new(StatusCode::NOT_FOUND))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .respond_with(wiremock::ResponseTemplate::new(StatusCode::OK))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::builder()
+            .request_methods(Some("HEAD,GET".parse().unwrap()))
+            .build()
+            .client()
+            .unwrap();
+
+        let res = client.check(mock_server.uri()).await.unwrap();
+        assert!(res.status().is_error());
+    }
+
+    #[tokio::test]
+    async fn test_request_methods_allow_header() {
+        let mock_server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::method("HEAD"))
+            .respond_with(
+                wiremock::ResponseTemplate::new(StatusCode::METHOD_NOT_ALLOWED)
+                    .insert_header("Allow", "GET, OPTIONS"),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        // Skipped, as the server doesn't allow it
+        wiremock::Mock::given(wiremock::matchers::method("POST"))
+            .respond_with(wiremock::ResponseTemplate::new(StatusCode::OK))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .respond_with(wiremock::ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::builder()
+            .request_methods(Some("HEAD,POST,GET".parse().unwrap()))
+            .build()
+            .client()
+            .unwrap();
+
+        let res = client.check(mock_server.uri()).await.unwrap();
+        assert!(res.status().is_success());
+    }
+
+    #[tokio::test]
+    async fn test_request_methods_allow_header_without_configured_method() {
+        let mock_server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::method("HEAD"))
+            .respond_with(
+                wiremock::ResponseTemplate::new(StatusCode::METHOD_NOT_ALLOWED)
+                    .insert_header("Allow", "POST"),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .respond_with(wiremock::ResponseTemplate::new(StatusCode::OK))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::builder()
+            .request_methods(Some("HEAD,GET".parse().unwrap()))
+            .build()
+            .client()
+            .unwrap();
+
+        let res = client.check(mock_server.uri()).await.unwrap();
+        assert!(res.status().is_error());
+    }
+
     #[tokio::test]
     async fn test_timeout() {
         // Note: this checks response timeout, not connect timeout.
@@ -984,6 +2404,47 @@ mod tests {
         assert!(res.status().is_timeout());
     }
 
+    #[tokio::test]
+    async fn test_total_timeout() {
+        let mock_server = mock_server!(StatusCode::TOO_MANY_REQUESTS);
+
+        // Without a total timeout, the retries would take 100ms + 200ms + ...
+        let retry =
+            crate::RetryHandler::new(reqwest::Client::new(), 10, Duration::from_millis(100));
+        let client = ClientBuilder::builder()
+            .plugin_request_chain(RequestChain::new(vec![Box::new(retry)]))
+            .total_timeout(Duration::from_millis(250))
+            .build()
+            .client()
+            .unwrap();
+
+        let start = Instant::now();
+        let res = client.check(mock_server.uri()).await.unwrap();
+        assert!(res.status().is_timeout());
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(mock_server.received_requests().await.unwrap().len() > 1);
+    }
+
+    #[tokio::test]
+    async fn test_connection_refused() {
+        // Bind to a free port and close it again, so that nothing listens there
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        let client = ClientBuilder::builder().build().client().unwrap();
+        let res = client
+            .check(format!("http://127.0.0.1:{port}"))
+            .await
+            .unwrap();
+
+        assert!(res.status().is_connection_refused());
+        assert!(!res.status().is_timeout());
+        assert_eq!(res.status().code_as_string(), "000 Connection refused");
+    }
+
     #[tokio::test]
     async fn test_exponential_backoff() {
         let mock_delay = Duration::from_millis(20);
@@ -1039,6 +2500,112 @@ mod tests {
         assert!(res.status().is_error());
     }
 
+    #[tokio::test]
+    async fn test_accepted_schemes() {
+        let mock_server = mock_server!(StatusCode::FORBIDDEN);
+
+        let client = ClientBuilder::builder()
+            .accepted_schemes(vec!["http:403".parse().unwrap()])
+            .build()
+            .client()
+            .unwrap();
+        let res = client.check(mock_server.uri()).await.unwrap();
+        assert!(res.status().is_success());
+
+        let client = ClientBuilder::builder()
+            .accepted_schemes(vec!["https:403".parse().unwrap()])
+            .build()
+            .client()
+            .unwrap();
+        let res = client.check(mock_server.uri()).await.unwrap();
+        assert!(res.status().is_error());
+    }
+
+    #[tokio::test]
+    async fn test_fail_on_https_downgrade() {
+        let mock_server = wiremock::MockServer::start().await;
+
+        let ok_uri = format!("{}/ok", &mock_server.uri());
+        let redirect_uri = format!("{}/redirect", &mock_server.uri());
+
+        let redirect = wiremock::ResponseTemplate::new(StatusCode::PERMANENT_REDIRECT)
+            .insert_header("Location", ok_uri.as_str());
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(path("/redirect"))
+            .respond_with(redirect)
+            .mount(&mock_server)
+            .await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(path("/ok"))
+            .respond_with(wiremock::ResponseTemplate::new(StatusCode::OK))
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::builder()
+            .fail_on_https_downgrade(true)
+            .build()
+            .client()
+            .unwrap();
+
+        // Redirects between HTTP URLs are fine
+        let res = client.check(redirect_uri).await.unwrap();
+        assert!(res.status().is_success());
+    }
+
+    #[tokio::test]
+    async fn test_verify_image_content_type() {
+        let mock_server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(path("/logo.png"))
+            .respond_with(
+                wiremock::ResponseTemplate::new(StatusCode::OK)
+                    .insert_header("Content-Type", "text/html; charset=utf-8"),
+            )
+            .mount(&mock_server)
+            .await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(path("/banner.png"))
+            .respond_with(
+                wiremock::ResponseTemplate::new(StatusCode::OK)
+                    .insert_header("Content-Type", "image/png"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::builder()
+            .verify_image_content_type(true)
+            .build()
+            .client()
+            .unwrap();
+        let image_request = |uri: &str| {
+            Request::new(
+                Uri::try_from(uri).unwrap(),
+                crate::InputSource::Stdin,
+                Some("img".to_string()),
+                Some("src".to_string()),
+                None,
+            )
+        };
+
+        let logo = format!("{}/logo.png", mock_server.uri());
+        let res = client.check(image_request(&logo)).await.unwrap();
+        assert_eq!(
+            res.status(),
+            &Status::Error(ErrorKind::NotAnImage(
+                Uri::try_from(logo.as_str()).unwrap(),
+                "text/html; charset=utf-8".to_string()
+            ))
+        );
+
+        let banner = format!("{}/banner.png", mock_server.uri());
+        let res = client.check(image_request(&banner)).await.unwrap();
+        assert!(res.status().is_success());
+
+        // Links which are not the source of an image are not affected
+        let res = client.check(logo.as_str()).await.unwrap();
+        assert!(res.status().is_success());
+    }
+
     #[tokio::test]
     async fn test_max_redirects() {
         let mock_server = wiremock::MockServer::start().await;
@@ -1103,6 +2670,90 @@ mod tests {
         assert!(res.status().is_error());
     }
 
+    #[tokio::test]
+    async fn test_follow_meta_refresh() {
+        let mock_server = wiremock::MockServer::start().await;
+
+        let refresh = wiremock::ResponseTemplate::new(StatusCode::OK).set_body_raw(
+            r#"<html><head><meta http-equiv="refresh" content="0; url=/gone"></head></html>"#,
+            "text/html",
+        );
+        // Once per check, as the target is taken from the response to the
+        // check itself, and once to fetch the page checked with `HEAD`
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(path("/refresh"))
+            .respond_with(refresh.clone())
+            .expect(3)
+            .mount(&mock_server)
+            .await;
+        wiremock::Mock::given(wiremock::matchers::method("HEAD"))
+            .and(path("/refresh"))
+            .respond_with(refresh)
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let gone = wiremock::ResponseTemplate::new(StatusCode::NOT_FOUND);
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(path("/gone"))
+            .respond_with(gone)
+            .mount(&mock_server)
+            .await;
+
+        let refresh_uri = format!("{}/refresh", &mock_server.uri());
+
+        let client = ClientBuilder::builder().build().client().unwrap();
+        let res = client.check(refresh_uri.clone()).await.unwrap();
+        assert!(res.status().is_success());
+
+        let client = ClientBuilder::builder()
+            .follow_meta_refresh(true)
+            .build()
+            .client()
+            .unwrap();
+        let res = client.check(refresh_uri.clone()).await.unwrap();
+        assert!(res.status().is_error());
+
+        // Pages checked without a body are fetched to find their target
+        let client = ClientBuilder::builder()
+            .follow_meta_refresh(true)
+            .request_methods(Some("HEAD".parse().unwrap()))
+            .build()
+            .client()
+            .unwrap();
+        let res = client.check(refresh_uri).await.unwrap();
+        assert!(res.status().is_error());
+    }
+
+    #[tokio::test]
+    async fn test_follow_meta_refresh_ignores_other_content() {
+        let mock_server = wiremock::MockServer::start().await;
+
+        // Not HTML, so the refresh is not followed
+        let text = wiremock::ResponseTemplate::new(StatusCode::OK).set_body_raw(
+            r#"<meta http-equiv="refresh" content="0; url=/gone">"#,
+            "text/plain",
+        );
+        wiremock::Mock::given(wiremock::matchers::any())
+            .and(path("/text"))
+            .respond_with(text)
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::builder()
+            .follow_meta_refresh(true)
+            .request_methods(Some("HEAD".parse().unwrap()))
+            .build()
+            .client()
+            .unwrap();
+        let res = client
+            .check(format!("{}/text", &mock_server.uri()))
+            .await
+            .unwrap();
+        assert!(res.status().is_success());
+    }
+
     #[tokio::test]
     async fn test_unsupported_scheme() {
         let examples = vec![
@@ -1144,4 +2795,36 @@ mod tests {
         let res = result.await.unwrap();
         assert_eq!(res.status(), &Status::Excluded);
     }
+
+    #[tokio::test]
+    async fn test_chain_excluded_with_reason() {
+        use reqwest::Request;
+
+        #[derive(Debug)]
+        struct ExampleHandler();
+
+        #[async_trait]
+        impl Handler<Request, Status> for ExampleHandler {
+            async fn handle(&mut self, _: Request) -> ChainResult<Request, Status> {
+                ChainResult::Done(Status::ExcludedWithReason(
+                    "blocked by company policy".to_string(),
+                ))
+            }
+        }
+
+        let chain = RequestChain::new(vec![Box::new(ExampleHandler {})]);
+
+        let client = ClientBuilder::builder()
+            .plugin_request_chain(chain)
+            .build()
+            .client()
+            .unwrap();
+
+        let res = client.check("http://example.com").await.unwrap();
+        assert!(res.status().is_excluded());
+        assert_eq!(
+            res.to_string(),
+            "? [EXCLUDED] http://example.com/ | Excluded: blocked by company policy"
+        );
+    }
 }
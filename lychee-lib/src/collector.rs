@@ -1,23 +1,48 @@
 use crate::{
-    basic_auth::BasicAuthExtractor, extract::Extractor, types::uri::raw::RawUri, utils::request,
-    Base, Input, Request, Result,
+    basic_auth::BasicAuthExtractor,
+    extract::{
+        csv::extract_csv, html::html5gum::duplicate_html_ids,
+        markdown::unused_reference_definitions, Extractor,
+    },
+    types::{uri::raw::RawUri, FileType, InputSource},
+    utils::{frontmatter, mail, request, url},
+    Base, Date, FetchLimits, Input, InputContent, Request, Result, Uri, UrlColumn,
 };
 use futures::TryStreamExt;
 use futures::{
     stream::{self, Stream},
     StreamExt,
 };
+use glob::Pattern;
+use log::warn;
 use par_stream::ParStreamExt;
+use reqwest::Url;
+use std::{collections::HashSet, time::Duration};
 
 /// Collector keeps the state of link collection
 /// It drives the link extraction from inputs
 #[derive(Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Collector {
     basic_auth_extractor: Option<BasicAuthExtractor>,
     skip_missing_inputs: bool,
     include_verbatim: bool,
+    code_annotations: Vec<String>,
+    include_identifiers: bool,
+    include_embeds: bool,
+    strip_query_params: Vec<Pattern>,
+    canonicalize_mail: bool,
     use_html5ever: bool,
     base: Option<Base>,
+    stdin_base: Option<Url>,
+    changed_since: Option<Date>,
+    skip_undated: bool,
+    fetch_limits: FetchLimits,
+    robots_sitemaps: bool,
+    warn_unused_definitions: bool,
+    warn_duplicate_ids: bool,
+    keep_duplicates: bool,
+    csv_url_column: Option<UrlColumn>,
 }
 
 impl Collector {
@@ -28,8 +53,26 @@ impl Collector {
             basic_auth_extractor: None,
             skip_missing_inputs: false,
             include_verbatim: false,
+            code_annotations: Vec::new(),
+            include_identifiers: false,
+            include_embeds: false,
+            strip_query_params: Vec::new(),
+            canonicalize_mail: false,
             use_html5ever: false,
             base,
+            stdin_base: None,
+            changed_since: None,
+            skip_undated: false,
+            fetch_limits: FetchLimits {
+                timeout: None,
+                max_body_size: None,
+                accept_encoding: None,
+            },
+            robots_sitemaps: false,
+            warn_unused_definitions: false,
+            warn_duplicate_ids: false,
+            keep_duplicates: false,
+            csv_url_column: None,
         }
     }
 
@@ -54,6 +97,169 @@ impl Collector {
         self
     }
 
+    /// Include links from Markdown code blocks annotated with one of the given
+    /// info strings, e.g. `url` for a block starting with ```` ```url ````
+    #[must_use]
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn code_annotations(mut self, annotations: Vec<String>) -> Self {
+        self.code_annotations = annotations;
+        self
+    }
+
+    /// Include identifiers of scholarly works like `doi:10.1000/182` found in
+    /// the text of Markdown and plaintext inputs
+    #[must_use]
+    pub const fn include_identifiers(mut self, yes: bool) -> Self {
+        self.include_identifiers = yes;
+        self
+    }
+
+    /// Include the URLs of embedded content like `<iframe src>`,
+    /// `<embed src>` and `<object data>` in HTML
+    #[must_use]
+    pub const fn include_embeds(mut self, yes: bool) -> Self {
+        self.include_embeds = yes;
+        self
+    }
+
+    /// Log a warning for each link reference definition like
+    /// `[label]: https://example.com` in Markdown inputs which is never
+    /// referenced
+    #[must_use]
+    pub const fn warn_unused_definitions(mut self, yes: bool) -> Self {
+        self.warn_unused_definitions = yes;
+        self
+    }
+
+    /// Warn about `id` attributes which occur more than once in an HTML
+    /// input, as they make links to the fragment ambiguous
+    #[must_use]
+    pub const fn warn_duplicate_ids(mut self, yes: bool) -> Self {
+        self.warn_duplicate_ids = yes;
+        self
+    }
+
+    /// Pass on every occurrence of a link within an input instead of only the
+    /// first one, e.g. to report links which occur more than once. As
+    /// requests only differing in their span compare equal, callers can still
+    /// deduplicate them.
+    #[must_use]
+    pub const fn keep_duplicates(mut self, yes: bool) -> Self {
+        self.keep_duplicates = yes;
+        self
+    }
+
+    /// Parse all inputs as CSV with a header row and only extract the URLs in
+    /// the given column, one per row. Rows which can't be parsed are skipped
+    /// with a warning.
+    #[must_use]
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn csv_url_column(mut self, column: Option<UrlColumn>) -> Self {
+        self.csv_url_column = column;
+        self
+    }
+
+    /// Remove query parameters whose name matches one of the given glob
+    /// patterns (e.g. `utm_*`) from all links. Links which only differ in
+    /// these parameters are deduplicated and checked once. The links are
+    /// reported without the removed parameters.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if one of the patterns is not a valid glob pattern
+    pub fn strip_query_params<S: AsRef<str>>(mut self, patterns: &[S]) -> Result<Self> {
+        self.strip_query_params = patterns
+            .iter()
+            .map(|pattern| Pattern::new(pattern.as_ref()))
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(self)
+    }
+
+    /// Reduce `mailto:` links to the canonical form of their address: without
+    /// query parameters, without the tag of plus-addressing (`user+tag@`) and
+    /// lowercased. Spellings of the same mailbox are checked once.
+    #[must_use]
+    pub const fn canonicalize_mail(mut self, yes: bool) -> Self {
+        self.canonicalize_mail = yes;
+        self
+    }
+
+    /// Resolve relative links of stdin inputs against `base`, the location of
+    /// the piped document, e.g. `https://example.com/docs/index.html` or a
+    /// local directory. Without it, relative links from stdin are skipped.
+    ///
+    /// As stdin has no file name, its file type is derived from the extension
+    /// of `base` instead, so that `index.html` makes stdin get parsed as HTML.
+    #[must_use]
+    pub fn stdin_base(mut self, base: Option<Base>) -> Self {
+        self.stdin_base = base.and_then(|base| match base {
+            Base::Remote(url) => Some(url),
+            Base::Local(path) => {
+                let path = std::env::current_dir().ok()?.join(path);
+                if path.is_dir() {
+                    Url::from_directory_path(path).ok()
+                } else {
+                    Url::from_file_path(path).ok()
+                }
+            }
+        });
+        self
+    }
+
+    /// Only extract links from Markdown inputs whose frontmatter `lastmod` or
+    /// `date` field is on or after `date`, given as `YYYY-MM-DD`. Markdown
+    /// inputs without such a field are still checked, unless
+    /// [`skip_undated`](Self::skip_undated) is set. Other inputs are
+    /// unaffected.
+    #[must_use]
+    pub const fn changed_since(mut self, date: Option<Date>) -> Self {
+        self.changed_since = date;
+        self
+    }
+
+    /// Skip Markdown inputs without a frontmatter date when filtering them
+    /// with [`changed_since`](Self::changed_since)
+    #[must_use]
+    pub const fn skip_undated(mut self, yes: bool) -> Self {
+        self.skip_undated = yes;
+        self
+    }
+
+    /// Stop fetching a remote input to extract links from after `timeout`.
+    /// Links are still extracted from the part of the body read until then.
+    #[must_use]
+    pub const fn extract_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.fetch_limits.timeout = timeout;
+        self
+    }
+
+    /// Stop reading the body of a remote input to extract links from after
+    /// `max_body_size` bytes
+    #[must_use]
+    pub const fn max_body_size(mut self, max_body_size: Option<usize>) -> Self {
+        self.fetch_limits.max_body_size = max_body_size;
+        self
+    }
+
+    /// Send `accept_encoding` as the `Accept-Encoding` header when fetching
+    /// remote inputs, instead of the encodings `reqwest` can decode
+    #[must_use]
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn accept_encoding(mut self, accept_encoding: Option<String>) -> Self {
+        self.fetch_limits.accept_encoding = accept_encoding;
+        self
+    }
+
+    /// Treat remote URL inputs as site roots: instead of the input itself,
+    /// check the pages listed in the sitemaps referenced by the `Sitemap:`
+    /// lines of the site's `/robots.txt`.
+    /// See [`Input::get_sitemap_contents`].
+    #[must_use]
+    pub const fn robots_sitemaps(mut self, yes: bool) -> Self {
+        self.robots_sitemaps = yes;
+        self
+    }
+
     /// Pass a [`BasicAuthExtractor`] which is capable to match found
     /// URIs to basic auth credentials. These credentials get passed to the
     /// request in question.
@@ -81,28 +287,197 @@ impl Collector {
     /// Will return `Err` if links cannot be extracted from an input
     pub fn collect_links(self, inputs: Vec<Input>) -> impl Stream<Item = Result<Request>> {
         let skip_missing_inputs = self.skip_missing_inputs;
-        let base = self.base;
-        stream::iter(inputs)
-            .par_then_unordered(None, move |input| async move {
-                input.get_contents(skip_missing_inputs)
+        let fetch_limits = self.fetch_limits.clone();
+        let robots_sitemaps = self.robots_sitemaps;
+        let contents = stream::iter(inputs)
+            .par_then_unordered(None, move |input| {
+                let fetch_limits = fetch_limits.clone();
+                async move {
+                    if robots_sitemaps {
+                        input
+                            .get_sitemap_contents(skip_missing_inputs, fetch_limits)
+                            .left_stream()
+                    } else {
+                        input
+                            .get_contents_with_limits(skip_missing_inputs, fetch_limits)
+                            .right_stream()
+                    }
+                }
             })
-            .flatten()
+            .flatten();
+        self.collect_links_from_contents(contents)
+    }
+
+    /// Fetch all unique links from contents which were retrieved by the
+    /// caller, e.g. files converted to text by an external program.
+    /// Otherwise, this is the same as [`collect_links`](Self::collect_links).
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if links cannot be extracted from a content
+    pub fn collect_links_from_contents(
+        self,
+        contents: impl Stream<Item = Result<InputContent>> + Send + 'static,
+    ) -> impl Stream<Item = Result<Request>> {
+        let extractor = self.extractor();
+        let base = self.base;
+        let stdin_base = self.stdin_base;
+        let skip_undated = self.skip_undated;
+        let warn_unused_definitions = self.warn_unused_definitions;
+        let warn_duplicate_ids = self.warn_duplicate_ids;
+        let keep_duplicates = self.keep_duplicates;
+        contents
             .par_then_unordered(None, move |content| {
                 // send to parallel worker
                 let base = base.clone();
+                let stdin_base = stdin_base.clone();
+                let changed_since = self.changed_since;
                 let basic_auth_extractor = self.basic_auth_extractor.clone();
+                let strip_query_params = self.strip_query_params.clone();
+                let canonicalize_mail = self.canonicalize_mail;
+                let csv_url_column = self.csv_url_column.clone();
+                let extractor = extractor.clone();
                 async move {
-                    let content = content?;
-
-                    let extractor = Extractor::new(self.use_html5ever, self.include_verbatim);
-                    let uris: Vec<RawUri> = extractor.extract(&content);
-
-                    let requests = request::create(uris, &content, &base, &basic_auth_extractor)?;
+                    let mut content = content?;
+                    let is_unchanged = is_unchanged(&content, changed_since, skip_undated);
+                    let base = content_base(&mut content, base, stdin_base);
+
+                    let uris: Vec<RawUri> = if is_unchanged {
+                        Vec::new()
+                    } else if let Some(column) = &csv_url_column {
+                        let (uris, malformed) = extract_csv(&content.content, column);
+                        for (span, reason) in malformed {
+                            warn!(
+                                "{}:{span}: Skipping malformed CSV row: {reason}",
+                                content.source
+                            );
+                        }
+                        uris
+                    } else {
+                        extractor.extract(&content)
+                    };
+                    if warn_unused_definitions
+                        && !is_unchanged
+                        && content.file_type == FileType::Markdown
+                    {
+                        for (label, span) in unused_reference_definitions(&content.content) {
+                            warn!(
+                                "{}:{span}: Unused reference definition `[{label}]`",
+                                content.source
+                            );
+                        }
+                    }
+                    if warn_duplicate_ids && !is_unchanged && content.file_type == FileType::Html {
+                        for (id, spans) in duplicate_html_ids(&content.content) {
+                            let spans: Vec<String> = spans
+                                .iter()
+                                .map(|span| span.map_or("?".to_string(), |span| span.to_string()))
+                                .collect();
+                            warn!(
+                                "{}:{}: Duplicate id `{id}`, also defined at {}",
+                                content.source,
+                                spans[0],
+                                spans[1..].join(", ")
+                            );
+                        }
+                    }
+
+                    let mut requests =
+                        request::create(uris, &content, &base, &basic_auth_extractor)?;
+                    for request in &mut requests {
+                        normalize(&mut request.uri.url, &strip_query_params, canonicalize_mail);
+                    }
+                    if !keep_duplicates {
+                        let unique: HashSet<_> = requests.into_iter().collect();
+                        requests = unique.into_iter().collect();
+                    }
                     Result::Ok(stream::iter(requests.into_iter().map(Ok)))
                 }
             })
             .try_flatten()
     }
+
+    /// Extract the links of `content` like [`collect_links`](Self::collect_links)
+    /// does, each together with the URI it resolves to. The URI is `None` for
+    /// links which would not be checked, e.g. relative links without a base.
+    ///
+    /// Other than when collecting links, every link is returned, whether it is
+    /// a valid URI or not, so that the links can be validated.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a link of a file cannot be resolved to a path
+    pub fn resolve_links(&self, mut content: InputContent) -> Result<Vec<(RawUri, Option<Uri>)>> {
+        let base = content_base(&mut content, self.base.clone(), self.stdin_base.clone());
+        self.extractor()
+            .extract(&content)
+            .into_iter()
+            .map(|raw_uri| {
+                let uri = request::create(vec![raw_uri.clone()], &content, &base, &None)?
+                    .pop()
+                    .map(|request| {
+                        let mut uri = request.uri;
+                        normalize(
+                            &mut uri.url,
+                            &self.strip_query_params,
+                            self.canonicalize_mail,
+                        );
+                        uri
+                    });
+                Ok((raw_uri, uri))
+            })
+            .collect()
+    }
+
+    /// The extractor for the links of the inputs
+    fn extractor(&self) -> Extractor {
+        Extractor::new(self.use_html5ever, self.include_verbatim)
+            .code_annotations(self.code_annotations.clone())
+            .include_identifiers(self.include_identifiers)
+            .include_embeds(self.include_embeds)
+    }
+}
+
+/// The base to resolve the relative links of `content` against. Input from
+/// stdin uses `stdin_base` if set, which also determines its file type.
+fn content_base(
+    content: &mut InputContent,
+    base: Option<Base>,
+    stdin_base: Option<Url>,
+) -> Option<Base> {
+    match (&content.source, stdin_base) {
+        (InputSource::Stdin, Some(stdin_base)) => {
+            if content.file_type == FileType::Plaintext {
+                content.file_type = FileType::from(stdin_base.path());
+            }
+            Some(Base::Remote(stdin_base))
+        }
+        _ => base,
+    }
+}
+
+/// Remove the query parameters matching `strip_query_params` from `url` and
+/// canonicalize it if it is a `mailto:` link and `canonicalize_mail` is set
+fn normalize(url: &mut Url, strip_query_params: &[Pattern], canonicalize_mail: bool) {
+    url::strip_query_params(url, strip_query_params);
+    if canonicalize_mail {
+        mail::canonicalize(url);
+    }
+}
+
+/// Whether the links of `content` are skipped, as it is a Markdown document
+/// which was not changed since the date `since`
+fn is_unchanged(content: &InputContent, since: Option<Date>, skip_undated: bool) -> bool {
+    let Some(since) = since else {
+        return false;
+    };
+    if content.file_type != FileType::Markdown {
+        return false;
+    }
+    match frontmatter::last_modified(&content.content) {
+        Some(date) => date < since,
+        None => skip_undated,
+    }
 }
 
 #[cfg(test)]
@@ -180,6 +555,7 @@ mod tests {
                 source: InputSource::String(TEST_STRING.to_owned()),
                 file_type_hint: None,
                 excluded_paths: None,
+                follow_symlinks: false,
             },
             Input {
                 source: InputSource::RemoteUrl(Box::new(
@@ -189,11 +565,13 @@ mod tests {
                 )),
                 file_type_hint: None,
                 excluded_paths: None,
+                follow_symlinks: false,
             },
             Input {
                 source: InputSource::FsPath(file_path),
                 file_type_hint: None,
                 excluded_paths: None,
+                follow_symlinks: false,
             },
             Input {
                 source: InputSource::FsGlob {
@@ -202,6 +580,7 @@ mod tests {
                 },
                 file_type_hint: None,
                 excluded_paths: None,
+                follow_symlinks: false,
             },
         ];
 
@@ -220,6 +599,32 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_collect_with_stripped_query_params() {
+        let input = Input {
+            source: InputSource::String(
+                "https://example.com/?utm_source=a https://example.com/?utm_source=b&id=1"
+                    .to_string(),
+            ),
+            file_type_hint: Some(FileType::Plaintext),
+            excluded_paths: None,
+            follow_symlinks: false,
+        };
+        let links: HashSet<Uri> = Collector::new(None)
+            .strip_query_params(&["utm_*"])
+            .unwrap()
+            .collect_links(vec![input])
+            .map(|r| r.unwrap().uri)
+            .collect()
+            .await;
+
+        let expected_links = HashSet::from_iter([
+            website("https://example.com/"),
+            website("https://example.com/?id=1"),
+        ]);
+        assert_eq!(links, expected_links);
+    }
+
     #[tokio::test]
     async fn test_collect_markdown_links() {
         let base = Base::try_from("https://github.com/hello-rust/lychee/").unwrap();
@@ -227,6 +632,7 @@ mod tests {
             source: InputSource::String("This is [a test](https://endler.dev). This is a relative link test [Relative Link Test](relative_link)".to_string()),
             file_type_hint: Some(FileType::Markdown),
                 excluded_paths: None,
+                follow_symlinks: false,
         };
         let links = collect(vec![input], Some(base)).await;
 
@@ -253,6 +659,7 @@ mod tests {
             ),
             file_type_hint: Some(FileType::Html),
             excluded_paths: None,
+            follow_symlinks: false,
         };
         let links = collect(vec![input], Some(base)).await;
 
@@ -282,6 +689,7 @@ mod tests {
             ),
             file_type_hint: Some(FileType::Html),
             excluded_paths: None,
+            follow_symlinks: false,
         };
         let links = collect(vec![input], Some(base)).await;
 
@@ -308,6 +716,7 @@ mod tests {
             ),
             file_type_hint: Some(FileType::Markdown),
             excluded_paths: None,
+            follow_symlinks: false,
         };
 
         let links = collect(vec![input], Some(base)).await;
@@ -331,6 +740,7 @@ mod tests {
             source: InputSource::String(input),
             file_type_hint: Some(FileType::Html),
             excluded_paths: None,
+            follow_symlinks: false,
         };
         let links = collect(vec![input], Some(base)).await;
 
@@ -363,6 +773,7 @@ mod tests {
             source: InputSource::RemoteUrl(Box::new(server_uri.clone())),
             file_type_hint: None,
             excluded_paths: None,
+            follow_symlinks: false,
         };
 
         let links = collect(vec![input], None).await;
@@ -375,6 +786,70 @@ mod tests {
         assert_eq!(links, expected_urls);
     }
 
+    #[tokio::test]
+    async fn test_extract_links_from_gzip_encoded_html() {
+        use flate2::{write::GzEncoder, Compression};
+
+        let contents = r#"<html><a href="https://example.com/compressed">Link</a></html>"#;
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(contents.as_bytes()).unwrap();
+        let body = encoder.finish().unwrap();
+
+        let mock_server = mock_server!(
+            StatusCode::OK,
+            set_body_raw(body, "text/html"),
+            insert_header("Content-Encoding", "gzip")
+        );
+
+        let input = Input {
+            source: InputSource::RemoteUrl(Box::new(Url::parse(&mock_server.uri()).unwrap())),
+            file_type_hint: None,
+            excluded_paths: None,
+            follow_symlinks: false,
+        };
+
+        let links = collect(vec![input], None).await;
+
+        let expected_urls = HashSet::from_iter([website("https://example.com/compressed")]);
+
+        assert_eq!(links, expected_urls);
+    }
+
+    #[tokio::test]
+    async fn test_accept_encoding_of_remote_input() {
+        use wiremock::{
+            matchers::{header, method},
+            Mock, MockServer, ResponseTemplate,
+        };
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("Accept-Encoding", "identity"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                r#"<a href="https://example.com/identity">Link</a>"#,
+                "text/html",
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let input = Input {
+            source: InputSource::RemoteUrl(Box::new(Url::parse(&mock_server.uri()).unwrap())),
+            file_type_hint: None,
+            excluded_paths: None,
+            follow_symlinks: false,
+        };
+        let links: HashSet<Uri> = Collector::new(None)
+            .accept_encoding(Some("identity".to_string()))
+            .collect_links(vec![input])
+            .map(|r| r.unwrap().uri)
+            .collect()
+            .await;
+
+        let expected_urls = HashSet::from_iter([website("https://example.com/identity")]);
+        assert_eq!(links, expected_urls);
+    }
+
     #[tokio::test]
     async fn test_email_with_query_params() {
         let input = Input {
@@ -383,6 +858,7 @@ mod tests {
             ),
             file_type_hint: None,
             excluded_paths: None,
+            follow_symlinks: false,
         };
         let links = collect(vec![input], None).await;
 
@@ -390,4 +866,26 @@ mod tests {
 
         assert_eq!(links, expected_links);
     }
+
+    #[tokio::test]
+    async fn test_canonicalize_mail() {
+        let input = Input {
+            source: InputSource::String(
+                "<a href='mailto:User+News@Example.com?subject=Hi'>a</a>
+                 <a href='mailto:user@example.com'>b</a>"
+                    .to_string(),
+            ),
+            file_type_hint: Some(FileType::Html),
+            excluded_paths: None,
+            follow_symlinks: false,
+        };
+        let links: HashSet<Uri> = Collector::new(None)
+            .canonicalize_mail(true)
+            .collect_links(vec![input])
+            .map(|request| request.unwrap().uri)
+            .collect()
+            .await;
+
+        assert_eq!(links, HashSet::from_iter([mail("user@example.com")]));
+    }
 }
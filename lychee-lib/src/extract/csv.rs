@@ -0,0 +1,135 @@
+use ::csv::{ErrorKind, Position, ReaderBuilder, StringRecord};
+
+use crate::types::{
+    uri::raw::{RawUri, RawUriSpan},
+    UrlColumn,
+};
+
+/// Extract the URLs in `column` of the CSV `input`, one per row, with the
+/// position of the cell as span. The first row is the header.
+///
+/// Rows which cannot be parsed or which don't have as many fields as the
+/// header are skipped. They are returned along with the reason, so that the
+/// caller can warn about them.
+pub(crate) fn extract_csv(
+    input: &str,
+    column: &UrlColumn,
+) -> (Vec<RawUri>, Vec<(RawUriSpan, String)>) {
+    let mut reader = ReaderBuilder::new().from_reader(input.as_bytes());
+    let mut uris = Vec::new();
+    let mut malformed = Vec::new();
+
+    let index = match column {
+        UrlColumn::Index(index) => index - 1,
+        UrlColumn::Name(name) => {
+            let position = reader
+                .headers()
+                .ok()
+                .and_then(|headers| headers.iter().position(|header| header.trim() == name));
+            let Some(index) = position else {
+                let span = RawUriSpan { line: 1, column: 1 };
+                malformed.push((span, format!("No column `{name}` in the header row")));
+                return (uris, malformed);
+            };
+            index
+        }
+    };
+
+    let mut record = StringRecord::new();
+    loop {
+        match reader.read_record(&mut record) {
+            Ok(false) => break,
+            Ok(true) => {
+                let Some(field) = record.get(index).map(str::trim) else {
+                    malformed.push((row_span(record.position()), format!("No column {column}")));
+                    continue;
+                };
+                if field.is_empty() {
+                    continue;
+                }
+                let start = record.position().map_or(0, |position| {
+                    usize::try_from(position.byte()).unwrap_or_default()
+                });
+                let offset = input[start..].find(field).map_or(start, |i| start + i);
+                uris.push(RawUri {
+                    span: Some(RawUriSpan::at_offset(input, offset)),
+                    ..RawUri::from(field)
+                });
+            }
+            Err(err) => {
+                let span = row_span(err.position());
+                let reason = match err.kind() {
+                    ErrorKind::UnequalLengths {
+                        expected_len, len, ..
+                    } => format!("Found {len} fields instead of {expected_len}"),
+                    _ => err.to_string(),
+                };
+                malformed.push((span, reason));
+            }
+        }
+    }
+    (uris, malformed)
+}
+
+/// The span of the start of the row at `position`
+fn row_span(position: Option<&Position>) -> RawUriSpan {
+    let line = position.map_or(0, Position::line);
+    RawUriSpan {
+        line: usize::try_from(line).unwrap_or_default(),
+        column: 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "name,url,homepage
+lychee,https://lychee.cli.rs,https://example.com/lychee
+broken,https://example.com/broken
+\"quoted, name\",\"https://example.org\",
+";
+
+    #[test]
+    fn test_extract_csv_column() {
+        for column in [UrlColumn::Index(2), UrlColumn::Name("url".to_string())] {
+            let (uris, malformed) = extract_csv(INPUT, &column);
+            assert_eq!(
+                uris,
+                [
+                    RawUri {
+                        span: Some(RawUriSpan { line: 2, column: 8 }),
+                        ..RawUri::from("https://lychee.cli.rs")
+                    },
+                    RawUri {
+                        span: Some(RawUriSpan {
+                            line: 4,
+                            column: 17
+                        }),
+                        ..RawUri::from("https://example.org")
+                    },
+                ]
+            );
+            assert_eq!(
+                malformed,
+                [(
+                    RawUriSpan { line: 3, column: 1 },
+                    "Found 2 fields instead of 3".to_string()
+                )]
+            );
+        }
+    }
+
+    #[test]
+    fn test_extract_csv_unknown_column() {
+        let (uris, malformed) = extract_csv(INPUT, &UrlColumn::Name("link".to_string()));
+        assert!(uris.is_empty());
+        assert_eq!(
+            malformed,
+            [(
+                RawUriSpan { line: 1, column: 1 },
+                "No column `link` in the header row".to_string()
+            )]
+        );
+    }
+}
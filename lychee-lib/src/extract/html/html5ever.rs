@@ -4,27 +4,37 @@ use html5ever::{
     tokenizer::{Tag, TagKind, Token, TokenSink, TokenSinkResult, Tokenizer, TokenizerOpts},
 };
 
-use super::{super::plaintext::extract_plaintext, is_email_link, is_verbatim_elem, srcset};
-use crate::types::uri::raw::RawUri;
-
-#[derive(Clone, Default)]
-struct LinkExtractor {
+use super::{
+    super::plaintext::extract_plaintext, is_email_link, is_verbatim_elem, parse_meta_refresh,
+    srcset,
+};
+use crate::types::uri::raw::{RawUri, RawUriSpan, SpanIndex};
+
+#[derive(Clone)]
+struct LinkExtractor<'buf> {
+    buf: &'buf str,
+    spans: SpanIndex<'buf>,
+    // byte offset after the last link which was found in `buf`
+    cursor: usize,
     links: Vec<RawUri>,
     include_verbatim: bool,
     current_verbatim_element_name: Option<String>,
 }
 
-impl TokenSink for LinkExtractor {
+impl TokenSink for LinkExtractor<'_> {
     type Handle = ();
 
     #[allow(clippy::match_same_arms)]
-    fn process_token(&mut self, token: Token, _line_number: u64) -> TokenSinkResult<()> {
+    fn process_token(&mut self, token: Token, line_number: u64) -> TokenSinkResult<()> {
+        let line = usize::try_from(line_number).unwrap_or(usize::MAX);
         match token {
             Token::CharacterTokens(raw) => {
                 if self.current_verbatim_element_name.is_some() {
                     return TokenSinkResult::Continue;
                 }
-                self.links.extend(extract_plaintext(&raw));
+                for uri in extract_plaintext(&raw) {
+                    self.push(uri, line);
+                }
             }
             Token::TagToken(tag) => {
                 let Tag {
@@ -69,6 +79,15 @@ impl TokenSink for LinkExtractor {
                     }
                 }
 
+                let find_attr = |attr_name: &str| {
+                    attrs
+                        .iter()
+                        .find(|attr| &attr.name.local == attr_name)
+                        .map(|attr| attr.value.to_string())
+                };
+                let target = find_attr("target");
+                let rel = find_attr("rel");
+
                 for attr in attrs {
                     let urls = LinkExtractor::extract_urls_from_elem_attr(
                         &attr.name.local,
@@ -98,10 +117,15 @@ impl TokenSink for LinkExtractor {
                                 text: url.to_string(),
                                 element: Some(name.to_string()),
                                 attribute: Some(attr.name.local.to_string()),
+                                target: target.clone(),
+                                rel: rel.clone(),
+                                span: None,
                             })
                             .collect::<Vec<_>>(),
                     };
-                    self.links.extend(new_urls);
+                    for uri in new_urls {
+                        self.push(uri, line);
+                    }
                 }
             }
             Token::ParseError(_err) => {
@@ -116,15 +140,38 @@ impl TokenSink for LinkExtractor {
     }
 }
 
-impl LinkExtractor {
-    pub(crate) const fn new(include_verbatim: bool) -> Self {
+impl<'buf> LinkExtractor<'buf> {
+    pub(crate) fn new(buf: &'buf str, include_verbatim: bool) -> Self {
         Self {
+            buf,
+            spans: SpanIndex::new(buf),
+            cursor: 0,
             links: vec![],
             include_verbatim,
             current_verbatim_element_name: None,
         }
     }
 
+    /// Add `uri`, which was found in a token ending on `line`, with its
+    /// position.
+    ///
+    /// html5ever only reports lines, so the URI is searched for from the end
+    /// of the previous link up to the end of `line`. If it doesn't appear
+    /// verbatim there, e.g. because it contains HTML entities, it gets the
+    /// position of the start of `line`.
+    fn push(&mut self, mut uri: RawUri, line: usize) {
+        let end = self.spans.line_end(line);
+        let start = self.cursor.min(end);
+        uri.span = Some(match self.buf[start..end].find(&uri.text) {
+            Some(offset) => {
+                self.cursor = start + offset + uri.text.len();
+                self.spans.span(start + offset)
+            }
+            None => RawUriSpan { line, column: 1 },
+        });
+        self.links.push(uri);
+    }
+
     /// Extract all semantically known links from a given HTML attribute.
     #[allow(clippy::unnested_or_patterns)]
     pub(crate) fn extract_urls_from_elem_attr<'a>(
@@ -160,18 +207,22 @@ impl LinkExtractor {
             (_, "srcset") => {
                 Some(srcset::parse(attr_value).into_iter())
             }
+            ("meta", "content") => {
+                parse_meta_refresh(attr_value).map(|url| vec![url].into_iter())
+            }
             _ => None,
         }
     }
 }
 
-/// Extract unparsed URL strings from an HTML string.
+/// Extract unparsed URL strings from an HTML string, with their position in
+/// `buf`.
 pub(crate) fn extract_html(buf: &str, include_verbatim: bool) -> Vec<RawUri> {
     let mut input = BufferQueue::default();
     input.push_back(StrTendril::from(buf));
 
     let mut tokenizer = Tokenizer::new(
-        LinkExtractor::new(include_verbatim),
+        LinkExtractor::new(buf, include_verbatim),
         TokenizerOpts::default(),
     );
     let _handle = tokenizer.feed(&mut input);
@@ -204,6 +255,9 @@ mod tests {
             text: "https://example.org".to_string(),
             element: Some("a".to_string()),
             attribute: Some("href".to_string()),
+            target: None,
+            rel: None,
+            span: None,
         }];
 
         let uris = extract_html(HTML_INPUT, false);
@@ -217,26 +271,41 @@ mod tests {
                 text: "https://example.com".to_string(),
                 element: None,
                 attribute: None,
+                target: None,
+                rel: None,
+                span: None,
             },
             RawUri {
                 text: "https://example.org".to_string(),
                 element: Some("a".to_string()),
                 attribute: Some("href".to_string()),
+                target: None,
+                rel: None,
+                span: None,
             },
             RawUri {
                 text: "https://foo.com".to_string(),
                 element: None,
                 attribute: None,
+                target: None,
+                rel: None,
+                span: None,
             },
             RawUri {
                 text: "http://bar.com/some/path".to_string(),
                 element: None,
                 attribute: None,
+                target: None,
+                rel: None,
+                span: None,
             },
             RawUri {
                 text: "https://baz.org".to_string(),
                 element: Some("a".to_string()),
                 attribute: Some("href".to_string()),
+                target: None,
+                rel: None,
+                span: None,
             },
         ];
 
@@ -259,6 +328,9 @@ mod tests {
             text: "https://example.com/".to_string(),
             element: Some("a".to_string()),
             attribute: Some("href".to_string()),
+            target: None,
+            rel: None,
+            span: None,
         }];
 
         let uris = extract_html(HTML_INPUT, false);
@@ -276,11 +348,42 @@ mod tests {
             text: "https://example.org".to_string(),
             element: Some("a".to_string()),
             attribute: Some("href".to_string()),
+            target: None,
+            rel: None,
+            span: None,
         }];
         let uris = extract_html(input, false);
         assert_eq!(uris, expected);
     }
 
+    #[test]
+    fn test_extract_target_and_rel() {
+        let input = r#"
+        <a href="https://example.com" target="_blank" rel="noopener">new tab</a>
+        <a target="_blank" href="https://example.org">new tab</a>
+        "#;
+        let expected = vec![
+            RawUri {
+                text: "https://example.com".to_string(),
+                element: Some("a".to_string()),
+                attribute: Some("href".to_string()),
+                target: Some("_blank".to_string()),
+                rel: Some("noopener".to_string()),
+                span: None,
+            },
+            RawUri {
+                text: "https://example.org".to_string(),
+                element: Some("a".to_string()),
+                attribute: Some("href".to_string()),
+                target: Some("_blank".to_string()),
+                rel: None,
+                span: None,
+            },
+        ];
+        let uris = extract_html(input, false);
+        assert_eq!(uris, expected);
+    }
+
     #[test]
     fn test_exclude_script_tags() {
         let input = r#"
@@ -293,6 +396,9 @@ mod tests {
             text: "https://example.org".to_string(),
             element: Some("a".to_string()),
             attribute: Some("href".to_string()),
+            target: None,
+            rel: None,
+            span: None,
         }];
         let uris = extract_html(input, false);
         assert_eq!(uris, expected);
@@ -315,6 +421,9 @@ mod tests {
             text: "mailto:foo@bar.com".to_string(),
             element: Some("a".to_string()),
             attribute: Some("href".to_string()),
+            target: None,
+            rel: None,
+            span: None,
         }];
         let uris = extract_html(input, false);
         assert_eq!(uris, expected);
@@ -337,6 +446,9 @@ mod tests {
             text: "tel:1234567890".to_string(),
             element: Some("a".to_string()),
             attribute: Some("href".to_string()),
+            target: None,
+            rel: None,
+            span: None,
         }];
         let uris = extract_html(input, false);
         assert_eq!(uris, expected);
@@ -1,21 +1,94 @@
-use std::collections::HashSet;
+use std::{
+    cell::Cell,
+    collections::{BTreeMap, HashSet},
+    convert::Infallible,
+    rc::Rc,
+};
+
+use html5gum::{Emitter, Error, Reader, State, Tokenizer};
+
+use super::{is_email_link, is_verbatim_elem, parse_meta_refresh, srcset};
+use crate::{
+    extract::plaintext::extract_plaintext,
+    types::uri::raw::{RawUri, RawUriSpan, SpanIndex},
+};
+
+/// Reads a string like the reader of `html5gum`, but shares the byte offset
+/// it read up to with the [`LinkExtractor`], so that links get the position
+/// at which the tokenizer found them
+struct OffsetReader<'a> {
+    input: &'a [u8],
+    offset: Rc<Cell<usize>>,
+}
+
+impl Reader for OffsetReader<'_> {
+    type Error = Infallible;
 
-use html5gum::{Emitter, Error, State, Tokenizer};
+    fn read_byte(&mut self) -> Result<Option<u8>, Self::Error> {
+        let offset = self.offset.get();
+        let byte = self.input.get(offset).copied();
+        if byte.is_some() {
+            self.offset.set(offset + 1);
+        }
+        Ok(byte)
+    }
 
-use super::{is_email_link, is_verbatim_elem, srcset};
-use crate::{extract::plaintext::extract_plaintext, types::uri::raw::RawUri};
+    fn try_read_string(&mut self, s: &[u8], case_sensitive: bool) -> Result<bool, Self::Error> {
+        let offset = self.offset.get();
+        let Some(next) = self.input.get(offset..offset + s.len()) else {
+            return Ok(false);
+        };
+        let matches = next == s || (!case_sensitive && next.eq_ignore_ascii_case(s));
+        if matches {
+            self.offset.set(offset + s.len());
+        }
+        Ok(matches)
+    }
+
+    fn read_until<'b>(
+        &'b mut self,
+        needle: &[u8],
+        _: &'b mut [u8; 4],
+    ) -> Result<Option<&'b [u8]>, Self::Error> {
+        let offset = self.offset.get();
+        let rest = &self.input[offset..];
+        if rest.is_empty() {
+            return Ok(None);
+        }
+        let len = match rest.iter().position(|byte| needle.contains(byte)) {
+            Some(0) => 1,
+            Some(position) => position,
+            None => rest.len(),
+        };
+        self.offset.set(offset + len);
+        Ok(Some(&rest[..len]))
+    }
+}
 
 #[derive(Clone)]
-struct LinkExtractor {
+struct LinkExtractor<'buf> {
+    // looks up the positions of links by the byte offsets of `offset`
+    spans: SpanIndex<'buf>,
+    // byte offset the tokenizer read up to
+    offset: Rc<Cell<usize>>,
     // note: what html5gum calls a tag, lychee calls an element
     links: Vec<RawUri>,
-    fragments: HashSet<String>,
+    // values of all id attributes in document order
+    ids: Vec<String>,
     current_string: Vec<u8>,
+    // byte offset at which `current_string` starts
+    current_string_start: usize,
     current_element_name: Vec<u8>,
     current_element_is_closing: bool,
     current_element_nofollow: bool,
+    // index of the first link extracted from the current element's attributes
+    current_element_links_start: usize,
+    current_element_target: Option<String>,
+    current_element_rel: Option<String>,
     current_attribute_name: Vec<u8>,
     current_attribute_value: Vec<u8>,
+    // byte offset at which `current_attribute_value` starts
+    current_attribute_value_start: usize,
     last_start_element: Vec<u8>,
     include_verbatim: bool,
     current_verbatim_element_name: Option<Vec<u8>>,
@@ -28,17 +101,24 @@ unsafe fn from_utf8_unchecked(s: &[u8]) -> &str {
     std::str::from_utf8_unchecked(s)
 }
 
-impl LinkExtractor {
-    pub(crate) fn new(include_verbatim: bool) -> Self {
+impl<'buf> LinkExtractor<'buf> {
+    pub(crate) fn new(buf: &'buf str, include_verbatim: bool) -> Self {
         LinkExtractor {
+            spans: SpanIndex::new(buf),
+            offset: Rc::new(Cell::new(0)),
             links: Vec::new(),
-            fragments: HashSet::new(),
+            ids: Vec::new(),
             current_string: Vec::new(),
+            current_string_start: 0,
             current_element_name: Vec::new(),
             current_element_is_closing: false,
             current_element_nofollow: false,
+            current_element_links_start: 0,
+            current_element_target: None,
+            current_element_rel: None,
             current_attribute_name: Vec::new(),
             current_attribute_value: Vec::new(),
+            current_attribute_value_start: 0,
             last_start_element: Vec::new(),
             include_verbatim,
             current_verbatim_element_name: None,
@@ -80,6 +160,9 @@ impl LinkExtractor {
             (_, "srcset") => {
                 Some(srcset::parse(attr_value).into_iter())
             }
+            ("meta", "content") => {
+                parse_meta_refresh(attr_value).map(|url| vec![url].into_iter())
+            }
             _ => None,
         }
     }
@@ -95,7 +178,12 @@ impl LinkExtractor {
         }
 
         let raw = unsafe { from_utf8_unchecked(&self.current_string) };
-        self.links.extend(extract_plaintext(raw));
+        let start = self.spans.span(self.current_string_start);
+        self.links
+            .extend(extract_plaintext(raw).into_iter().map(|uri| RawUri {
+                span: uri.span.map(|span| span.shifted(start)),
+                ..uri
+            }));
         self.current_string.clear();
     }
 
@@ -151,6 +239,14 @@ impl LinkExtractor {
             if attr == "rel" && value.contains("nofollow") {
                 self.current_element_nofollow = true;
             }
+
+            // Attributes may come in any order, so these get applied to the
+            // links of the element once the whole tag was read
+            match attr {
+                "target" => self.current_element_target = Some(value.to_string()),
+                "rel" => self.current_element_rel = Some(value.to_string()),
+                _ => {}
+            }
             if self.current_element_nofollow {
                 self.current_attribute_name.clear();
                 self.current_attribute_value.clear();
@@ -159,45 +255,75 @@ impl LinkExtractor {
 
             let urls = LinkExtractor::extract_urls_from_elem_attr(attr, name, value);
 
+            let value_start = self.current_attribute_value_start;
             let new_urls = match urls {
-                None => extract_plaintext(value),
-                Some(urls) => urls
-                    .into_iter()
-                    .filter(|url| {
-                        // Only accept email addresses, which occur in `href` attributes
-                        // and start with `mailto:`. Technically, email addresses could
-                        // also occur in plain text, but we don't want to extract those
-                        // because of the high false positive rate.
-                        //
-                        // This ignores links like `<img srcset="v2@1.5x.png">`
-                        let is_email = is_email_link(url);
-                        let is_mailto = url.starts_with("mailto:");
-                        let is_phone = url.starts_with("tel:");
-                        let is_href = attr == "href";
-
-                        !is_email || (is_mailto && is_href) || (is_phone && is_href)
-                    })
-                    .map(|url| RawUri {
-                        text: url.to_string(),
-                        element: Some(name.to_string()),
-                        attribute: Some(attr.to_string()),
-                    })
-                    .collect::<Vec<_>>(),
+                None => {
+                    let start = self.spans.span(value_start);
+                    extract_plaintext(value)
+                        .into_iter()
+                        .map(|uri| RawUri {
+                            span: uri.span.map(|span| span.shifted(start)),
+                            ..uri
+                        })
+                        .collect()
+                }
+                Some(urls) => {
+                    urls.into_iter()
+                        .filter(|url| {
+                            // Only accept email addresses, which occur in `href` attributes
+                            // and start with `mailto:`. Technically, email addresses could
+                            // also occur in plain text, but we don't want to extract those
+                            // because of the high false positive rate.
+                            //
+                            // This ignores links like `<img srcset="v2@1.5x.png">`
+                            let is_email = is_email_link(url);
+                            let is_mailto = url.starts_with("mailto:");
+                            let is_phone = url.starts_with("tel:");
+                            let is_href = attr == "href";
+
+                            !is_email || (is_mailto && is_href) || (is_phone && is_href)
+                        })
+                        .map(|url| RawUri {
+                            text: url.to_string(),
+                            element: Some(name.to_string()),
+                            attribute: Some(attr.to_string()),
+                            target: None,
+                            rel: None,
+                            // URLs are parts of the attribute value, e.g. of a
+                            // `srcset`
+                            span: Some(self.spans.span(
+                                value_start + (url.as_ptr() as usize - value.as_ptr() as usize),
+                            )),
+                        })
+                        .collect::<Vec<_>>()
+                }
             };
 
             self.links.extend(new_urls);
 
             if attr == "id" {
-                self.fragments.insert(value.to_string());
+                self.ids.push(value.to_string());
             }
         }
 
         self.current_attribute_name.clear();
         self.current_attribute_value.clear();
     }
+
+    /// Attach the `target` and `rel` attributes of the current element to all
+    /// links extracted from it
+    fn flush_link_attributes(&mut self) {
+        if self.current_element_target.is_none() && self.current_element_rel.is_none() {
+            return;
+        }
+        for link in &mut self.links[self.current_element_links_start..] {
+            link.target.clone_from(&self.current_element_target);
+            link.rel.clone_from(&self.current_element_rel);
+        }
+    }
 }
 
-impl Emitter for &mut LinkExtractor {
+impl Emitter for &mut LinkExtractor<'_> {
     type Token = ();
 
     fn set_last_start_tag(&mut self, last_start_tag: Option<&[u8]>) {
@@ -220,6 +346,9 @@ impl Emitter for &mut LinkExtractor {
     }
 
     fn emit_string(&mut self, c: &[u8]) {
+        if self.current_string.is_empty() {
+            self.current_string_start = self.offset.get().saturating_sub(c.len());
+        }
         self.current_string.extend(c);
     }
 
@@ -227,6 +356,9 @@ impl Emitter for &mut LinkExtractor {
         self.flush_current_characters();
         self.current_element_name.clear();
         self.current_element_nofollow = false;
+        self.current_element_links_start = self.links.len();
+        self.current_element_target = None;
+        self.current_element_rel = None;
         self.current_element_is_closing = false;
     }
 
@@ -249,6 +381,7 @@ impl Emitter for &mut LinkExtractor {
         };
 
         self.flush_old_attribute();
+        self.flush_link_attributes();
         next_state
     }
 
@@ -274,6 +407,9 @@ impl Emitter for &mut LinkExtractor {
         self.current_attribute_name.extend(s);
     }
     fn push_attribute_value(&mut self, s: &[u8]) {
+        if self.current_attribute_value.is_empty() {
+            self.current_attribute_value_start = self.offset.get().saturating_sub(s.len());
+        }
         self.current_attribute_value.extend(s);
     }
 
@@ -290,20 +426,73 @@ impl Emitter for &mut LinkExtractor {
     fn emit_current_comment(&mut self) {}
 }
 
-/// Extract unparsed URL strings from an HTML string.
-pub(crate) fn extract_html(buf: &str, include_verbatim: bool) -> Vec<RawUri> {
-    let mut extractor = LinkExtractor::new(include_verbatim);
-    let mut tokenizer = Tokenizer::new_with_emitter(buf, &mut extractor).infallible();
+/// Run the tokenizer over `buf`, feeding the tokens to `extractor`
+fn tokenize(buf: &str, extractor: &mut LinkExtractor) {
+    let reader = OffsetReader {
+        input: buf.as_bytes(),
+        offset: extractor.offset.clone(),
+    };
+    let mut tokenizer = Tokenizer::new_with_emitter(reader, extractor).infallible();
     assert!(tokenizer.next().is_none());
+}
+
+/// Extract unparsed URL strings from an HTML string, with their position in
+/// `buf`.
+pub(crate) fn extract_html(buf: &str, include_verbatim: bool) -> Vec<RawUri> {
+    let mut extractor = LinkExtractor::new(buf, include_verbatim);
+    tokenize(buf, &mut extractor);
     extractor.links
 }
 
 /// Extract fragments from id attributes within a HTML string.
 pub(crate) fn extract_html_fragments(buf: &str) -> HashSet<String> {
-    let mut extractor = LinkExtractor::new(true);
-    let mut tokenizer = Tokenizer::new_with_emitter(buf, &mut extractor).infallible();
-    assert!(tokenizer.next().is_none());
-    extractor.fragments
+    let mut extractor = LinkExtractor::new(buf, true);
+    tokenize(buf, &mut extractor);
+    extractor.ids.into_iter().collect()
+}
+
+/// The id attributes which occur more than once within a HTML string, sorted
+/// by id, with the positions of all their occurrences.
+///
+/// Positions are found by searching for the attributes in document order, so
+/// an occurrence gets no position if its attribute isn't written verbatim as
+/// `id="value"`, `id='value'` or `id=value`.
+pub(crate) fn duplicate_html_ids(buf: &str) -> Vec<(String, Vec<Option<RawUriSpan>>)> {
+    let mut extractor = LinkExtractor::new(buf, true);
+    tokenize(buf, &mut extractor);
+
+    let mut occurrences: BTreeMap<String, Vec<Option<RawUriSpan>>> = BTreeMap::new();
+    let mut offset = 0;
+    for id in extractor.ids {
+        let start = find_id_attribute(buf, offset, &id);
+        if let Some(start) = start {
+            offset = start + 1;
+        }
+        occurrences
+            .entry(id)
+            .or_default()
+            .push(start.map(|start| RawUriSpan::at_offset(buf, start)));
+    }
+    occurrences
+        .into_iter()
+        .filter(|(_, spans)| spans.len() > 1)
+        .collect()
+}
+
+/// Byte offset of the first attribute `id` with the given value in
+/// `buf[offset..]`, with any kind of quoting
+fn find_id_attribute(buf: &str, offset: usize, id: &str) -> Option<usize> {
+    ["\"", "'", ""]
+        .iter()
+        .filter_map(|quote| {
+            let attribute = format!("id={quote}{id}");
+            buf[offset..]
+                .match_indices(&attribute)
+                .map(|(i, _)| offset + i)
+                // Skip attributes like `data-id`
+                .find(|&start| buf[..start].ends_with(|c: char| c.is_ascii_whitespace()))
+        })
+        .min()
 }
 
 #[cfg(test)]
@@ -335,12 +524,35 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_duplicate_html_ids() {
+        let input = r#"<h1 id="intro" data-id="intro">Intro</h1>
+<p id='unique'>Text</p>
+<section
+  id=intro>Again</section>"#;
+        let duplicates = duplicate_html_ids(input);
+        assert_eq!(
+            duplicates,
+            vec![(
+                "intro".to_string(),
+                vec![
+                    Some(RawUriSpan { line: 1, column: 5 }),
+                    Some(RawUriSpan { line: 4, column: 3 }),
+                ]
+            )]
+        );
+        assert!(duplicate_html_ids(HTML_INPUT).is_empty());
+    }
+
     #[test]
     fn test_skip_verbatim() {
         let expected = vec![RawUri {
             text: "https://example.org".to_string(),
             element: Some("a".to_string()),
             attribute: Some("href".to_string()),
+            target: None,
+            rel: None,
+            span: None,
         }];
 
         let uris = extract_html(HTML_INPUT, false);
@@ -354,26 +566,41 @@ mod tests {
                 text: "https://example.com".to_string(),
                 element: None,
                 attribute: None,
+                target: None,
+                rel: None,
+                span: None,
             },
             RawUri {
                 text: "https://example.org".to_string(),
                 element: Some("a".to_string()),
                 attribute: Some("href".to_string()),
+                target: None,
+                rel: None,
+                span: None,
             },
             RawUri {
                 text: "https://foo.com".to_string(),
                 element: None,
                 attribute: None,
+                target: None,
+                rel: None,
+                span: None,
             },
             RawUri {
                 text: "http://bar.com/some/path".to_string(),
                 element: None,
                 attribute: None,
+                target: None,
+                rel: None,
+                span: None,
             },
             RawUri {
                 text: "https://baz.org".to_string(),
                 element: Some("a".to_string()),
                 attribute: Some("href".to_string()),
+                target: None,
+                rel: None,
+                span: None,
             },
         ];
 
@@ -396,6 +623,9 @@ mod tests {
             text: "https://example.com/".to_string(),
             element: Some("a".to_string()),
             attribute: Some("href".to_string()),
+            target: None,
+            rel: None,
+            span: None,
         }];
 
         let uris = extract_html(HTML_INPUT, false);
@@ -432,11 +662,42 @@ mod tests {
             text: "https://example.org".to_string(),
             element: Some("a".to_string()),
             attribute: Some("href".to_string()),
+            target: None,
+            rel: None,
+            span: None,
         }];
         let uris = extract_html(input, false);
         assert_eq!(uris, expected);
     }
 
+    #[test]
+    fn test_extract_target_and_rel() {
+        let input = r#"
+        <a href="https://example.com" target="_blank" rel="noopener">new tab</a>
+        <a target="_blank" href="https://example.org">new tab</a>
+        "#;
+        let expected = vec![
+            RawUri {
+                text: "https://example.com".to_string(),
+                element: Some("a".to_string()),
+                attribute: Some("href".to_string()),
+                target: Some("_blank".to_string()),
+                rel: Some("noopener".to_string()),
+                span: None,
+            },
+            RawUri {
+                text: "https://example.org".to_string(),
+                element: Some("a".to_string()),
+                attribute: Some("href".to_string()),
+                target: Some("_blank".to_string()),
+                rel: None,
+                span: None,
+            },
+        ];
+        let uris = extract_html(input, false);
+        assert_eq!(uris, expected);
+    }
+
     #[test]
     fn test_exclude_script_tags() {
         let input = r#"
@@ -449,6 +710,27 @@ mod tests {
             text: "https://example.org".to_string(),
             element: Some("a".to_string()),
             attribute: Some("href".to_string()),
+            target: None,
+            rel: None,
+            span: None,
+        }];
+        let uris = extract_html(input, false);
+        assert_eq!(uris, expected);
+    }
+
+    #[test]
+    fn test_extract_meta_refresh() {
+        let input = r#"
+        <meta name="viewport" content="width=device-width, initial-scale=1">
+        <meta http-equiv="refresh" content="0; url=/new-page">
+        "#;
+        let expected = vec![RawUri {
+            text: "/new-page".to_string(),
+            element: Some("meta".to_string()),
+            attribute: Some("content".to_string()),
+            target: None,
+            rel: None,
+            span: None,
         }];
         let uris = extract_html(input, false);
         assert_eq!(uris, expected);
@@ -471,6 +753,9 @@ mod tests {
             text: "tel:1234567890".to_string(),
             element: Some("a".to_string()),
             attribute: Some("href".to_string()),
+            target: None,
+            rel: None,
+            span: None,
         }];
         let uris = extract_html(input, false);
         assert_eq!(uris, expected);
@@ -493,6 +778,9 @@ mod tests {
             text: "mailto:foo@bar.com".to_string(),
             element: Some("a".to_string()),
             attribute: Some("href".to_string()),
+            target: None,
+            rel: None,
+            span: None,
         }];
         let uris = extract_html(input, false);
         assert_eq!(uris, expected);
@@ -542,16 +830,25 @@ mod tests {
             text: "/cdn-cgi/image/format=webp,width=640/https://img.youtube.com/vi/hVBl8_pgQf0/maxresdefault.jpg".to_string(),
             element: Some("img".to_string()),
             attribute: Some("srcset".to_string()),
+            target: None,
+            rel: None,
+            span: None,
         },
         RawUri {
             text: "/cdn-cgi/image/format=webp,width=750/https://img.youtube.com/vi/hVBl8_pgQf0/maxresdefault.jpg".to_string(),
             element: Some("img".to_string()),
             attribute: Some("srcset".to_string()),
+            target: None,
+            rel: None,
+            span: None,
         },
         RawUri {
             text: "/cdn-cgi/image/format=webp,width=3840/https://img.youtube.com/vi/hVBl8_pgQf0/maxresdefault.jpg".to_string(),
             element: Some("img".to_string()),
             attribute: Some("src".to_string()),
+            target: None,
+            rel: None,
+            span: None,
         }
 
         ];
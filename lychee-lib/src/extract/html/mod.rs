@@ -48,6 +48,27 @@ pub(crate) fn is_verbatim_elem(name: &str) -> bool {
     )
 }
 
+/// Extract the target URL from the `content` attribute of a
+/// `<meta http-equiv="refresh">` element, e.g. `0; url=https://example.com`.
+///
+/// Returns `None` if the value does not follow the refresh syntax, so other
+/// `<meta content="...">` values are left alone.
+pub(crate) fn parse_meta_refresh(content: &str) -> Option<&str> {
+    let (delay, target) = content.split_once([';', ','])?;
+    let delay = delay.trim();
+    if delay.is_empty() || !delay.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+
+    let (key, url) = target.split_once('=')?;
+    if !key.trim().eq_ignore_ascii_case("url") {
+        return None;
+    }
+
+    let url = url.trim().trim_matches(|c| c == '\'' || c == '"').trim();
+    (!url.is_empty()).then_some(url)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -69,4 +90,20 @@ mod tests {
         assert!(is_verbatim_elem("listing"));
         assert!(is_verbatim_elem("script"));
     }
+
+    #[test]
+    fn test_parse_meta_refresh() {
+        assert_eq!(
+            parse_meta_refresh("0; url=https://example.com"),
+            Some("https://example.com")
+        );
+        assert_eq!(parse_meta_refresh("5;URL='/new-page'"), Some("/new-page"));
+        assert_eq!(parse_meta_refresh("0, url=\"foo.html\""), Some("foo.html"));
+        assert_eq!(parse_meta_refresh("30"), None);
+        assert_eq!(parse_meta_refresh("0; url="), None);
+        assert_eq!(
+            parse_meta_refresh("width=device-width, initial-scale=1"),
+            None
+        );
+    }
 }
@@ -1,23 +1,49 @@
 //! Extract links and fragments from markdown documents
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
 
-use pulldown_cmark::{Event, Options, Parser, Tag};
+use pulldown_cmark::{CodeBlockKind, Event, LinkType, Options, Parser, Tag};
 
-use crate::{extract::plaintext::extract_plaintext, types::uri::raw::RawUri};
+use crate::{
+    extract::plaintext::{extract_identifiers, extract_plaintext},
+    types::uri::raw::{RawUri, RawUriSpan, SpanIndex},
+};
 
 use super::html::html5gum::{extract_html, extract_html_fragments};
 
 /// Extract unparsed URL strings from a Markdown string.
-pub(crate) fn extract_markdown(input: &str, include_verbatim: bool) -> Vec<RawUri> {
+///
+/// Links inside fenced code blocks are only extracted if `include_verbatim` is
+/// set or if the info string of the block is one of `code_annotations`
+/// (e.g. ```` ```url ````). Identifiers like `doi:10.1000/182` are extracted
+/// from text if `include_identifiers` is set.
+///
+/// Reference-style links like `[text][label]` get the span of the URL in
+/// their definition `[label]: https://...`. Definitions which are never
+/// referenced are extracted as well, after all other links. Other links get
+/// the span of their destination, or of the start of the link if the
+/// destination isn't written verbatim.
+pub(crate) fn extract_markdown(
+    input: &str,
+    include_verbatim: bool,
+    code_annotations: &[String],
+    include_identifiers: bool,
+) -> Vec<RawUri> {
     // In some cases it is undesirable to extract links from within code blocks,
     // which is why we keep track of entries and exits while traversing the input.
     let mut inside_code_block = false;
-
-    let parser = Parser::new(input);
-    parser
-        .filter_map(|event| match event {
+    let mut include_code_block = false;
+
+    let spans = SpanIndex::new(input);
+    let parser = Parser::new_ext(input, Options::ENABLE_FOOTNOTES);
+    let mut definitions = ReferenceDefinitions::new(&parser, input);
+    let mut uris: Vec<RawUri> = parser
+        .into_offset_iter()
+        .filter_map(|(event, range)| match event {
             // A link. The first field is the link type, the second the destination URL and the third is a title.
-            Event::Start(Tag::Link(_, uri, _)) => {
+            Event::Start(Tag::Link(link_type, uri, _)) => {
                 Some(vec![RawUri {
                     text: uri.to_string(),
                     // Emulate `<a href="...">` tag here to be compatible with
@@ -25,10 +51,15 @@ pub(crate) fn extract_markdown(input: &str, include_verbatim: bool) -> Vec<RawUr
                     // `LinkType` for better granularity in the future
                     element: Some("a".to_string()),
                     attribute: Some("href".to_string()),
+                    target: None,
+                    rel: None,
+                    span: definitions
+                        .resolve(link_type, &input[range.clone()])
+                        .or_else(|| Some(spans.span(destination_offset(input, range, &uri)))),
                 }])
             }
             // An image. The first field is the link type, the second the destination URL and the third is a title.
-            Event::Start(Tag::Image(_, uri, _)) => {
+            Event::Start(Tag::Image(link_type, uri, _)) => {
                 Some(vec![RawUri {
                     text: uri.to_string(),
                     // Emulate `<img src="...">` tag here to be compatible with
@@ -36,24 +67,35 @@ pub(crate) fn extract_markdown(input: &str, include_verbatim: bool) -> Vec<RawUr
                     // `LinkType` for better granularity in the future
                     element: Some("img".to_string()),
                     attribute: Some("src".to_string()),
+                    target: None,
+                    rel: None,
+                    span: definitions
+                        .resolve(link_type, &input[range.clone()])
+                        .or_else(|| Some(spans.span(destination_offset(input, range, &uri)))),
                 }])
             }
             // A code block (inline or fenced).
-            Event::Start(Tag::CodeBlock(_)) => {
+            Event::Start(Tag::CodeBlock(kind)) => {
                 inside_code_block = true;
+                include_code_block = include_verbatim || is_annotated(&kind, code_annotations);
                 None
             }
             Event::End(Tag::CodeBlock(_)) => {
                 inside_code_block = false;
+                include_code_block = false;
                 None
             }
 
             // A text node.
             Event::Text(txt) => {
-                if inside_code_block && !include_verbatim {
+                if inside_code_block && !include_code_block {
                     None
+                } else if include_identifiers {
+                    let mut uris = extract_plaintext(&txt);
+                    uris.extend(extract_identifiers(&txt));
+                    Some(place(uris, &txt, input, range, &spans))
                 } else {
-                    Some(extract_plaintext(&txt))
+                    Some(place(extract_plaintext(&txt), &txt, input, range, &spans))
                 }
             }
 
@@ -61,13 +103,14 @@ pub(crate) fn extract_markdown(input: &str, include_verbatim: bool) -> Vec<RawUr
             Event::Html(html) => {
                 // This won't exclude verbatim links right now, because HTML gets passed in chunks
                 // by pulldown_cmark. So excluding `<pre>` and `<code>` is not handled right now.
-                Some(extract_html(&html, include_verbatim))
+                let uris = extract_html(&html, include_verbatim);
+                Some(place(uris, &html, input, range, &spans))
             }
 
             // An inline code node.
             Event::Code(code) => {
                 if include_verbatim {
-                    Some(extract_plaintext(&code))
+                    Some(place(extract_plaintext(&code), &code, input, range, &spans))
                 } else {
                     None
                 }
@@ -77,9 +120,187 @@ pub(crate) fn extract_markdown(input: &str, include_verbatim: bool) -> Vec<RawUr
             _ => None,
         })
         .flatten()
+        .collect();
+
+    uris.extend(definitions.unused().map(|definition| RawUri {
+        text: definition.dest.clone(),
+        element: Some("a".to_string()),
+        attribute: Some("href".to_string()),
+        target: None,
+        rel: None,
+        span: Some(definition.span),
+    }));
+    uris
+}
+
+/// Byte offset of the destination `dest` of the inline link, image or
+/// autolink whose source is `input[range]`, or of the start of the link if
+/// the destination isn't written verbatim, e.g. because it contains escapes
+fn destination_offset(input: &str, range: Range<usize>, dest: &str) -> usize {
+    let source = &input[range.clone()];
+    source
+        .match_indices(dest)
+        .map(|(offset, _)| offset)
+        // The destination follows the opening parenthesis of an inline link or
+        // the angle bracket of an autolink, possibly with whitespace and an
+        // angle bracket in between
+        .find(|&offset| {
+            let before = source[..offset].trim_end_matches('<').trim_end();
+            before.is_empty() || before.ends_with('(')
+        })
+        .map_or(range.start, |offset| range.start + offset)
+}
+
+/// Make the spans of `uris`, which are relative to the `text` of an event,
+/// relative to the whole document, given the source `input[range]` of the
+/// event. If the source doesn't contain the text verbatim, e.g. because it
+/// contains escapes, all URIs get the position of the start of the event.
+fn place(
+    uris: Vec<RawUri>,
+    text: &str,
+    input: &str,
+    range: Range<usize>,
+    spans: &SpanIndex,
+) -> Vec<RawUri> {
+    let source = &input[range.clone()];
+    let text_start = source
+        .find(text)
+        .map(|offset| spans.span(range.start + offset));
+    let event_start = spans.span(range.start);
+    uris.into_iter()
+        .map(|uri| RawUri {
+            span: match (text_start, uri.span) {
+                (Some(text_start), Some(span)) => Some(span.shifted(text_start)),
+                _ => Some(event_start),
+            },
+            ..uri
+        })
         .collect()
 }
 
+/// The link reference definitions like `[label]: https://example.com` of a
+/// Markdown document which are never referenced, with the position of their
+/// URL
+pub(crate) fn unused_reference_definitions(input: &str) -> Vec<(String, RawUriSpan)> {
+    let parser = Parser::new_ext(input, Options::ENABLE_FOOTNOTES);
+    let mut definitions = ReferenceDefinitions::new(&parser, input);
+    for (event, range) in parser.into_offset_iter() {
+        if let Event::Start(Tag::Link(link_type, ..) | Tag::Image(link_type, ..)) = event {
+            definitions.resolve(link_type, &input[range]);
+        }
+    }
+    definitions
+        .unused()
+        .map(|definition| (definition.label.clone(), definition.span))
+        .collect()
+}
+
+/// A link reference definition like `[label]: https://example.com`
+struct ReferenceDefinition {
+    label: String,
+    dest: String,
+    /// Position of the URL of the definition
+    span: RawUriSpan,
+    /// Byte offset of the URL of the definition
+    offset: usize,
+}
+
+/// The link reference definitions of a document, keyed by their normalized
+/// label, and the labels which are referenced by links
+struct ReferenceDefinitions {
+    definitions: HashMap<String, ReferenceDefinition>,
+    used: HashSet<String>,
+}
+
+impl ReferenceDefinitions {
+    fn new(parser: &Parser, input: &str) -> Self {
+        let definitions = parser
+            .reference_definitions()
+            .iter()
+            .map(|(label, definition)| {
+                // The span starts at the label; find the URL after the colon
+                let source = &input[definition.span.clone()];
+                let offset = definition.span.start
+                    + source.find("]:").map_or(0, |colon| {
+                        source[colon..]
+                            .find(definition.dest.as_ref())
+                            .map_or(0, |dest| colon + dest)
+                    });
+                let definition = ReferenceDefinition {
+                    label: label.to_string(),
+                    dest: definition.dest.to_string(),
+                    span: RawUriSpan::at_offset(input, offset),
+                    offset,
+                };
+                (normalize_label(label), definition)
+            })
+            .collect();
+        Self {
+            definitions,
+            used: HashSet::new(),
+        }
+    }
+
+    /// The position of the definition a reference link refers to, given the
+    /// source of the link like `[text][label]`. Inline links have no
+    /// definition.
+    fn resolve(&mut self, link_type: LinkType, source: &str) -> Option<RawUriSpan> {
+        let label = normalize_label(reference_label(link_type, source)?);
+        let span = self.definitions.get(&label)?.span;
+        self.used.insert(label);
+        Some(span)
+    }
+
+    /// The definitions which were not referenced, in document order
+    fn unused(&self) -> impl Iterator<Item = &ReferenceDefinition> {
+        let mut unused: Vec<_> = self
+            .definitions
+            .iter()
+            .filter(|(label, _)| !self.used.contains(*label))
+            .map(|(_, definition)| definition)
+            .collect();
+        unused.sort_by_key(|definition| definition.offset);
+        unused.into_iter()
+    }
+}
+
+/// The label of a reference link like `[text][label]`, `[label][]` or
+/// `[label]`, or of the image variants thereof
+fn reference_label(link_type: LinkType, source: &str) -> Option<&str> {
+    let source = source.strip_prefix('!').unwrap_or(source);
+    match link_type {
+        LinkType::Reference => source
+            .strip_suffix(']')
+            .and_then(|source| source.rsplit_once('['))
+            .map(|(_, label)| label),
+        // The source of collapsed links doesn't include the trailing `[]`
+        LinkType::Collapsed | LinkType::Shortcut => source.strip_prefix('[')?.strip_suffix(']'),
+        _ => None,
+    }
+}
+
+/// Labels are matched case-insensitively and with consecutive whitespace
+/// collapsed
+fn normalize_label(label: &str) -> String {
+    label
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Whether the info string of a fenced code block starts with one of the
+/// given annotations. Additional attributes after the first word, as in
+/// ```` ```url title="links" ````, are ignored.
+fn is_annotated(kind: &CodeBlockKind, code_annotations: &[String]) -> bool {
+    let CodeBlockKind::Fenced(info) = kind else {
+        return false;
+    };
+    info.split(|c: char| c.is_whitespace() || c == ',')
+        .next()
+        .is_some_and(|annotation| code_annotations.iter().any(|a| a == annotation))
+}
+
 /// Extract fragments/anchors/fragments from a Markdown string.
 ///
 /// Fragments are generated from headings using the same unique kebab case method as GitHub.
@@ -210,15 +431,21 @@ or inline like `https://bar.org` for instance.
                 text: "https://foo.com".to_string(),
                 element: Some("a".to_string()),
                 attribute: Some("href".to_string()),
+                target: None,
+                rel: None,
+                span: None,
             },
             RawUri {
                 text: "http://example.com".to_string(),
                 element: Some("a".to_string()),
                 attribute: Some("href".to_string()),
+                target: None,
+                rel: None,
+                span: None,
             },
         ];
 
-        let uris = extract_markdown(MD_INPUT, false);
+        let uris = extract_markdown(MD_INPUT, false, &[], false);
         assert_eq!(uris, expected);
     }
 
@@ -229,28 +456,119 @@ or inline like `https://bar.org` for instance.
                 text: "https://foo.com".to_string(),
                 element: Some("a".to_string()),
                 attribute: Some("href".to_string()),
+                target: None,
+                rel: None,
+                span: None,
             },
             RawUri {
                 text: "https://bar.com/123".to_string(),
                 element: None,
                 attribute: None,
+                target: None,
+                rel: None,
+                span: None,
             },
             RawUri {
                 text: "https://bar.org".to_string(),
                 element: None,
                 attribute: None,
+                target: None,
+                rel: None,
+                span: None,
             },
             RawUri {
                 text: "http://example.com".to_string(),
                 element: Some("a".to_string()),
                 attribute: Some("href".to_string()),
+                target: None,
+                rel: None,
+                span: None,
             },
         ];
 
-        let uris = extract_markdown(MD_INPUT, true);
+        let uris = extract_markdown(MD_INPUT, true, &[], false);
         assert_eq!(uris, expected);
     }
 
+    #[test]
+    fn test_include_annotated_code_blocks() {
+        let input = "
+```url
+https://annotated.com
+```
+
+```url title=\"docs\"
+https://annotated-with-attributes.com
+```
+
+```bash
+https://bash.com
+```
+
+```
+https://plain.com
+```
+";
+        let expected = vec![
+            RawUri {
+                text: "https://annotated.com".to_string(),
+                element: None,
+                attribute: None,
+                target: None,
+                rel: None,
+                span: None,
+            },
+            RawUri {
+                text: "https://annotated-with-attributes.com".to_string(),
+                element: None,
+                attribute: None,
+                target: None,
+                rel: None,
+                span: None,
+            },
+        ];
+
+        let uris = extract_markdown(input, false, &["url".to_string()], false);
+        assert_eq!(uris, expected);
+    }
+
+    #[test]
+    fn test_reference_definitions() {
+        let input = "[docs][Docs], [shortcut] and ![logo][]\n\n\
+            [docs]: https://example.com/docs\n\
+            [shortcut]: <https://example.com/shortcut> \"Title\"\n\
+            [logo]: https://example.com/logo.png\n\
+            [unused]:\n  https://example.com/unused\n";
+
+        let uris: Vec<_> = extract_markdown(input, false, &[], false)
+            .into_iter()
+            .map(|uri| (uri.text, uri.span))
+            .collect();
+        let span = |line, column| Some(RawUriSpan { line, column });
+        assert_eq!(
+            uris,
+            vec![
+                ("https://example.com/docs".to_string(), span(3, 9)),
+                ("https://example.com/shortcut".to_string(), span(4, 14)),
+                ("https://example.com/logo.png".to_string(), span(5, 9)),
+                ("https://example.com/unused".to_string(), span(7, 3)),
+            ]
+        );
+        assert_eq!(
+            unused_reference_definitions(input),
+            vec![("unused".to_string(), RawUriSpan { line: 7, column: 3 })]
+        );
+    }
+
+    #[test]
+    fn test_footnotes() {
+        let input = "A claim.[^1]\n\n[^1]: See https://example.com/footnote\n";
+
+        let uris = extract_markdown(input, false, &[], false);
+        assert_eq!(uris, vec![RawUri::from("https://example.com/footnote")]);
+        assert!(unused_reference_definitions(input).is_empty());
+    }
+
     #[test]
     #[ignore]
     fn test_skip_verbatim_html() {
@@ -264,7 +582,7 @@ Some pre-formatted http://pre.com
 
         let expected = vec![];
 
-        let uris = extract_markdown(input, false);
+        let uris = extract_markdown(input, false, &[], false);
         assert_eq!(uris, expected);
     }
 
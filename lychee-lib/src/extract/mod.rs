@@ -1,19 +1,32 @@
+use std::{collections::HashSet, sync::Arc};
+
 use crate::types::{uri::raw::RawUri, FileType, InputContent};
 
+pub(crate) mod csv;
 pub mod html;
 pub mod markdown;
+#[cfg(feature = "pdf")]
+pub(crate) mod pdf;
 mod plaintext;
 
-use markdown::extract_markdown;
-use plaintext::extract_plaintext;
+use html::html5gum::extract_html_fragments;
+use markdown::{extract_markdown, extract_markdown_fragments};
+use plaintext::{extract_identifiers, extract_plaintext};
 
 /// A handler for extracting links from various input formats like Markdown and
 /// HTML. Allocations should be avoided if possible as this is a
 /// performance-critical section of the library.
-#[derive(Default, Debug, Clone, Copy)]
+///
+/// The extractor is cheap to clone, as the code block annotations are shared
+/// between clones.
+#[derive(Default, Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Extractor {
     use_html5ever: bool,
     include_verbatim: bool,
+    code_annotations: Option<Arc<[String]>>,
+    include_identifiers: bool,
+    include_embeds: bool,
 }
 
 impl Extractor {
@@ -34,15 +47,57 @@ impl Extractor {
         Self {
             use_html5ever,
             include_verbatim,
+            code_annotations: None,
+            include_identifiers: false,
+            include_embeds: false,
         }
     }
 
+    /// Extract links from Markdown code blocks annotated with one of the given
+    /// info strings (e.g. ```` ```url ````), even if `include_verbatim` is
+    /// disabled. This allows opting in individual code blocks.
+    #[must_use]
+    pub fn code_annotations(mut self, annotations: Vec<String>) -> Self {
+        self.code_annotations = Some(annotations.into());
+        self
+    }
+
+    /// Extract identifiers of scholarly works like `doi:10.1000/182`,
+    /// `arXiv:2101.00001` or `PMID:12345678` from the text of Markdown and
+    /// plaintext inputs.
+    #[must_use]
+    pub const fn include_identifiers(mut self, yes: bool) -> Self {
+        self.include_identifiers = yes;
+        self
+    }
+
+    /// Extract the URLs of embedded content, i.e. `<iframe src>`,
+    /// `<embed src>` and `<object data>`, from HTML and from HTML in Markdown
+    #[must_use]
+    pub const fn include_embeds(mut self, yes: bool) -> Self {
+        self.include_embeds = yes;
+        self
+    }
+
     /// Main entrypoint for extracting links from various sources
     /// (Markdown, HTML, and plaintext)
     #[must_use]
     pub fn extract(&self, input_content: &InputContent) -> Vec<RawUri> {
+        let mut uris = self.extract_uris(input_content);
+        if !self.include_embeds {
+            uris.retain(|uri| !uri.is_embed());
+        }
+        uris
+    }
+
+    fn extract_uris(&self, input_content: &InputContent) -> Vec<RawUri> {
         match input_content.file_type {
-            FileType::Markdown => extract_markdown(&input_content.content, self.include_verbatim),
+            FileType::Markdown => extract_markdown(
+                &input_content.content,
+                self.include_verbatim,
+                self.code_annotations.as_deref().unwrap_or_default(),
+                self.include_identifiers,
+            ),
             FileType::Html => {
                 if self.use_html5ever {
                     html::html5ever::extract_html(&input_content.content, self.include_verbatim)
@@ -50,7 +105,25 @@ impl Extractor {
                     html::html5gum::extract_html(&input_content.content, self.include_verbatim)
                 }
             }
-            FileType::Plaintext => extract_plaintext(&input_content.content),
+            FileType::Plaintext => {
+                let mut uris = extract_plaintext(&input_content.content);
+                if self.include_identifiers {
+                    uris.extend(extract_identifiers(&input_content.content));
+                }
+                uris
+            }
+        }
+    }
+
+    /// Extract all fragments (anchors) defined by an input, i.e. the
+    /// generated heading ids of Markdown files and the `id` attributes of
+    /// HTML files. Plaintext does not define any fragments.
+    #[must_use]
+    pub fn extract_fragments(&self, input_content: &InputContent) -> HashSet<String> {
+        match input_content.file_type {
+            FileType::Markdown => extract_markdown_fragments(&input_content.content),
+            FileType::Html => extract_html_fragments(&input_content.content),
+            FileType::Plaintext => HashSet::new(),
         }
     }
 }
@@ -63,6 +136,7 @@ mod tests {
     use super::*;
     use crate::{
         test_utils::{load_fixture, mail, website},
+        types::uri::raw::RawUriSpan,
         types::{FileType, InputContent, InputSource},
         utils::url::find_links,
         Uri,
@@ -96,6 +170,30 @@ mod tests {
         assert!(uris.is_empty());
     }
 
+    #[test]
+    fn test_include_embeds() {
+        let input = load_fixture("TEST_EMBEDS.html");
+        let embeds = [
+            website("https://example.com/iframe"),
+            website("https://example.com/embed.swf"),
+            website("https://example.com/object.pdf"),
+        ];
+
+        let uris = extract_uris(&input, FileType::Html);
+        assert_eq!(uris, HashSet::from([website("https://example.com/page")]));
+
+        let input_content = InputContent::from_string(&input, FileType::Html);
+        for use_html5ever in [false, true] {
+            let uris: HashSet<Uri> = Extractor::new(use_html5ever, false)
+                .include_embeds(true)
+                .extract(&input_content)
+                .into_iter()
+                .filter_map(|raw_uri| Uri::try_from(raw_uri).ok())
+                .collect();
+            assert!(embeds.iter().all(|embed| uris.contains(embed)), "{uris:?}");
+        }
+    }
+
     #[test]
     fn test_file_type() {
         assert_eq!(FileType::from(Path::new("/")), FileType::Plaintext);
@@ -305,4 +403,65 @@ mod tests {
 
         assert_eq!(links, expected_links);
     }
+
+    #[test]
+    fn test_extract_spans() {
+        let input = "# Links\n\nSee https://example.com and\n  https://example.org or https://example.com\n";
+        let input_content = InputContent::from_string(input, FileType::Plaintext);
+        let spans: Vec<_> = Extractor::new(false, false)
+            .extract(&input_content)
+            .into_iter()
+            .map(|raw_uri| (raw_uri.text, raw_uri.span))
+            .collect();
+
+        let span = |line, column| Some(RawUriSpan { line, column });
+        assert_eq!(
+            spans,
+            [
+                ("https://example.com".to_string(), span(3, 5)),
+                ("https://example.org".to_string(), span(4, 3)),
+                ("https://example.com".to_string(), span(4, 26)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_spans_from_parsers() {
+        let span = |line, column| Some(RawUriSpan { line, column });
+        let spans = |input: &str, file_type, use_html5ever| {
+            let input_content = InputContent::from_string(input, file_type);
+            Extractor::new(use_html5ever, false)
+                .extract(&input_content)
+                .into_iter()
+                .map(|raw_uri| raw_uri.span)
+                .collect::<Vec<_>>()
+        };
+
+        // The encoded link doesn't appear verbatim, and the link in the
+        // `srcset` is repeated as text before
+        let html = "<p>https://example.com/b.png</p>\n<p>\n  <a href=\"https://example.com/?a=1&amp;b=2\">x</a>\n  <img srcset=\"https://example.com/a.png 1x, https://example.com/b.png 2x\">\n</p>";
+        assert_eq!(
+            spans(html, FileType::Html, false),
+            [span(1, 4), span(3, 12), span(4, 16), span(4, 46)]
+        );
+        assert_eq!(
+            spans(html, FileType::Html, true),
+            [span(1, 4), span(3, 1), span(4, 16), span(4, 46)]
+        );
+
+        // Autolinks are extracted from both the link and its text, and text
+        // after an escape gets its exact position as well
+        let markdown = "# https://example.com/a\n\n[a](https://example.com/a) and\n<https://example.com/b> \\*https://example.com/c\n\n<a href=\"https://example.com/d\">d</a>\n";
+        assert_eq!(
+            spans(markdown, FileType::Markdown, false),
+            [
+                span(1, 3),
+                span(3, 5),
+                span(4, 2),
+                span(4, 2),
+                span(4, 27),
+                span(6, 10)
+            ]
+        );
+    }
 }
@@ -0,0 +1,78 @@
+//! Extract links from PDF documents.
+//!
+//! PDF files are binary, so they can't be handled like the other text-based
+//! formats. Instead, the text of all pages is extracted and combined with the
+//! targets of all URI actions (e.g. link annotations), so that the result can
+//! be checked like any plaintext input.
+
+use lopdf::{Document, Object};
+
+/// Extract the text and all link targets from the given PDF document
+///
+/// Pages whose text can't be decoded (e.g. because of an unsupported font
+/// encoding) are skipped, but their link annotations are still extracted.
+pub(crate) fn extract_pdf_text(buf: &[u8]) -> Result<String, lopdf::Error> {
+    let doc = Document::load_mem(buf)?;
+
+    let pages: Vec<u32> = doc.get_pages().into_keys().collect();
+    let mut content = doc.extract_text(&pages).unwrap_or_default();
+
+    for object in doc.objects.values() {
+        collect_uris(object, &mut content);
+    }
+    Ok(content)
+}
+
+/// Recursively collect the targets of URI actions, which may be nested in
+/// dictionaries such as the `/A` entry of a link annotation
+fn collect_uris(object: &Object, content: &mut String) {
+    match object {
+        Object::Dictionary(dict) => {
+            for (key, value) in dict {
+                if let (b"URI", Object::String(uri, _)) = (key.as_slice(), value) {
+                    content.push('\n');
+                    content.push_str(&String::from_utf8_lossy(uri));
+                } else {
+                    collect_uris(value, content);
+                }
+            }
+        }
+        Object::Array(array) => {
+            for value in array {
+                collect_uris(value, content);
+            }
+        }
+        Object::Stream(stream) => {
+            for (_, value) in &stream.dict {
+                collect_uris(value, content);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    fn test_extract_pdf_links() {
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("fixtures")
+            .join("TEST_PDF_LINKS.pdf");
+        let buf = std::fs::read(fixture).unwrap();
+        let content = extract_pdf_text(&buf).unwrap();
+
+        assert!(content.contains("https://example.com/pdf-text"));
+        assert!(content.contains("https://example.com/pdf-annotation"));
+    }
+
+    #[test]
+    fn test_extract_invalid_pdf() {
+        assert!(extract_pdf_text(b"not a pdf").is_err());
+    }
+}
@@ -1,9 +1,29 @@
-use crate::{types::uri::raw::RawUri, utils::url};
+use crate::{
+    types::uri::raw::{RawUri, SpanIndex},
+    utils::url,
+};
 
-/// Extract unparsed URL strings from plaintext
+/// Extract unparsed URL strings from plaintext, with their position in
+/// `input`
 pub(crate) fn extract_plaintext(input: &str) -> Vec<RawUri> {
+    let index = SpanIndex::new(input);
     url::find_links(input)
-        .map(|uri| RawUri::from(uri.as_str()))
+        .map(|link| RawUri {
+            span: Some(index.span(link.start())),
+            ..RawUri::from(link.as_str())
+        })
+        .collect()
+}
+
+/// Extract identifiers like `doi:10.1000/182` or `arXiv:2101.00001` from
+/// plaintext, with their position in `input`
+pub(crate) fn extract_identifiers(input: &str) -> Vec<RawUri> {
+    let index = SpanIndex::new(input);
+    url::find_identifiers(input)
+        .map(|(start, identifier)| RawUri {
+            span: Some(index.span(start)),
+            ..RawUri::from(identifier.as_str())
+        })
         .collect()
 }
 
@@ -32,4 +52,27 @@ mod tests {
         let uris: Vec<RawUri> = extract_plaintext(input);
         assert_eq!(vec![uri], uris);
     }
+
+    #[test]
+    fn test_extract_spans() {
+        let input = "See https://example.com/a\n  or ü https://example.com/b and doi:10.1000/182";
+        let spans: Vec<_> = extract_plaintext(input)
+            .into_iter()
+            .chain(extract_identifiers(input))
+            .map(|uri| uri.span.unwrap().to_string())
+            .collect();
+        assert_eq!(spans, ["1:5", "2:8", "2:34"]);
+    }
+
+    #[test]
+    fn test_extract_identifiers() {
+        let input = "Published as doi:10.1000/182 (preprint: arXiv:2101.00001).";
+        assert_eq!(
+            extract_identifiers(input),
+            [
+                RawUri::from("doi:10.1000/182"),
+                RawUri::from("arxiv:2101.00001")
+            ]
+        );
+    }
 }
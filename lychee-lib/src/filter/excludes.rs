@@ -1,4 +1,11 @@
+use std::{fmt::Debug, sync::Arc};
+
 use regex::RegexSet;
+use reqwest::Url;
+
+/// A predicate for custom exclusion logic, which returns `true` if the given
+/// URL should be excluded from checking
+pub type ExcludeFn = dyn Fn(&Url) -> bool + Send + Sync;
 
 /// Exclude configuration for the link checker.
 /// You can ignore links based on regex patterns.
@@ -17,6 +24,13 @@ impl Excludes {
         self.regex.is_match(input)
     }
 
+    #[must_use]
+    /// Returns the first excluded pattern which matches the given input string
+    pub fn first_match(&self, input: &str) -> Option<&str> {
+        let index = self.regex.matches(input).into_iter().next()?;
+        Some(&self.regex.patterns()[index])
+    }
+
     #[inline]
     #[must_use]
     /// Whether there were no regular expressions defined to be excluded
@@ -24,3 +38,29 @@ impl Excludes {
         self.regex.is_empty()
     }
 }
+
+/// Custom exclusion logic of library users, see [`ExcludeFn`].
+/// It is applied together with the [`Excludes`] patterns, so a URL is excluded
+/// if either of them matches, unless an include pattern matches as well.
+#[derive(Clone)]
+pub struct ExcludePredicate(Arc<ExcludeFn>);
+
+impl ExcludePredicate {
+    #[must_use]
+    /// Returns `true` if the given URL should be excluded from checking
+    pub fn is_match(&self, url: &Url) -> bool {
+        (self.0)(url)
+    }
+}
+
+impl From<Box<ExcludeFn>> for ExcludePredicate {
+    fn from(predicate: Box<ExcludeFn>) -> Self {
+        Self(predicate.into())
+    }
+}
+
+impl Debug for ExcludePredicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ExcludePredicate(..)")
+    }
+}
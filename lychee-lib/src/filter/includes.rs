@@ -17,6 +17,13 @@ impl Includes {
         self.regex.is_match(input)
     }
 
+    #[must_use]
+    /// Returns the first included pattern which matches the given input string
+    pub fn first_match(&self, input: &str) -> Option<&str> {
+        let index = self.regex.matches(input).into_iter().next()?;
+        Some(&self.regex.patterns()[index])
+    }
+
     #[inline]
     #[must_use]
     /// Whether there were no regular expressions defined for inclusion
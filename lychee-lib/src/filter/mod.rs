@@ -3,9 +3,9 @@ mod includes;
 
 use once_cell::sync::Lazy;
 use regex::RegexSet;
-use std::collections::HashSet;
+use std::{collections::HashSet, fmt::Display};
 
-pub use excludes::Excludes;
+pub use excludes::{ExcludeFn, ExcludePredicate, Excludes};
 pub use includes::Includes;
 
 use crate::Uri;
@@ -106,15 +106,87 @@ pub fn is_unsupported_domain(uri: &Uri) -> bool {
     }
 }
 
+/// The reason why a [`Filter`] checks or skips a given URI.
+/// See [`Filter::decide`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FilterDecision {
+    /// No rule excludes the URI
+    Included,
+    /// The URI matches this include pattern, which takes precedence over
+    /// all exclude patterns
+    IncludedByPattern(String),
+    /// The scheme of the URI is not one of the allowed schemes
+    ExcludedScheme,
+    /// The URI points to an excluded private, link-local or loopback address
+    ExcludedAddress,
+    /// Mail addresses are not checked unless requested
+    ExcludedMail,
+    /// Phone numbers are never checked
+    ExcludedTel,
+    /// The URI belongs to a reserved example domain
+    ExampleDomain,
+    /// The URI belongs to a domain which is known to not support checking
+    UnsupportedDomain,
+    /// The URI is a well-known false positive
+    FalsePositive,
+    /// Include patterns are defined, but none of them matches the URI
+    NotIncluded,
+    /// The URI matches this exclude pattern
+    ExcludedByPattern(String),
+    /// The custom exclude predicate returned `true` for the URI
+    ExcludedByPredicate,
+}
+
+impl FilterDecision {
+    /// Whether the URI gets skipped
+    #[must_use]
+    pub const fn is_excluded(&self) -> bool {
+        !matches!(self, Self::Included | Self::IncludedByPattern(_))
+    }
+}
+
+impl Display for FilterDecision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Included => write!(f, "included, no rule excludes it"),
+            Self::IncludedByPattern(pattern) => {
+                write!(f, "included by include pattern `{pattern}`")
+            }
+            Self::ExcludedScheme => write!(f, "excluded, scheme is not in the allowed schemes"),
+            Self::ExcludedAddress => {
+                write!(f, "excluded, private, link-local or loopback address")
+            }
+            Self::ExcludedMail => write!(f, "excluded, mail addresses are not checked"),
+            Self::ExcludedTel => write!(f, "excluded, phone numbers are not checked"),
+            Self::ExampleDomain => write!(f, "excluded, reserved example domain"),
+            Self::UnsupportedDomain => write!(f, "excluded, unsupported domain"),
+            Self::FalsePositive => write!(f, "excluded, known false positive"),
+            Self::NotIncluded => write!(f, "excluded, no include pattern matches"),
+            Self::ExcludedByPattern(pattern) => {
+                write!(f, "excluded by exclude pattern `{pattern}`")
+            }
+            Self::ExcludedByPredicate => write!(f, "excluded by custom exclude predicate"),
+        }
+    }
+}
+
 /// A generic URI filter
 /// Used to decide if a given URI should be checked or skipped
 #[allow(clippy::struct_excessive_bools)]
 #[derive(Clone, Debug, Default)]
 pub struct Filter {
     /// URIs explicitly included for checking. This takes precedence over excludes
+    /// unless `strict_includes` is set
     pub includes: Option<Includes>,
+    /// Treat `includes` as an allowlist: URIs matching no include pattern are
+    /// excluded even if exclude rules are given, and exclude rules take
+    /// precedence over includes
+    pub strict_includes: bool,
     /// URIs excluded from checking
     pub excludes: Option<Excludes>,
+    /// Custom logic to exclude URIs from checking, applied together with
+    /// `excludes`
+    pub exclude_predicate: Option<ExcludePredicate>,
     /// Only check URIs with the given schemes (e.g. `https` and `http`)
     // TODO: includes_scheme and excludes_scheme
     // TODO: excludes_mail should be an alias for exclude_scheme=mailto
@@ -176,19 +248,18 @@ impl Filter {
     #[inline]
     fn is_excludes_empty(&self) -> bool {
         !matches!(self.excludes, Some(ref excludes) if !excludes.is_empty())
+            && self.exclude_predicate.is_none()
     }
 
-    #[inline]
-    fn is_includes_match(&self, input: &str) -> bool {
-        matches!(self.includes, Some(ref includes) if includes.is_match(input))
-    }
-
-    #[inline]
-    fn is_excludes_match(&self, input: &str) -> bool {
-        matches!(self.excludes, Some(ref excludes) if excludes.is_match(input))
+    /// Determine whether a given [`Uri`] should be excluded.
+    ///
+    /// See [`Filter::decide`] for details.
+    #[must_use]
+    pub fn is_excluded(&self, uri: &Uri) -> bool {
+        self.decide(uri).is_excluded()
     }
 
-    /// Determine whether a given [`Uri`] should be excluded.
+    /// Decide whether a given [`Uri`] should be checked or skipped and why.
     ///
     /// # Details
     ///
@@ -205,48 +276,97 @@ impl Filter {
     /// 4. Decide whether the URI is *presumably excluded* or *explicitly excluded*:
     ///    - When excludes rules is empty, but includes rules doesn't match the URI, it's
     ///      *presumably excluded*.
-    ///    - When the excludes rules matches the URI, it's *explicitly excluded*.
-    ///    - When the excludes rules matches the URI, it's *explicitly excluded*.
+    ///    - When the excludes rules or the exclude predicate match the URI, it's
+    ///      *explicitly excluded*.
+    ///
+    /// With `strict_includes` and include rules given, steps 2 to 4 are
+    /// replaced: the URI is *explicitly excluded* if the exclude rules or the
+    /// exclude predicate match it, *explicitly included* if the include rules
+    /// match it and *presumably excluded* otherwise.
     #[must_use]
-    pub fn is_excluded(&self, uri: &Uri) -> bool {
+    pub fn decide(&self, uri: &Uri) -> FilterDecision {
         // Skip mail address, specific IP, specific host and scheme
-        if self.is_scheme_excluded(uri)
-            || self.is_host_excluded(uri)
-            || self.is_ip_excluded(uri)
-            || self.is_mail_excluded(uri)
-            || uri.is_tel()
-            || is_example_domain(uri)
-            || is_unsupported_domain(uri)
-        {
-            return true;
+        if self.is_scheme_excluded(uri) {
+            return FilterDecision::ExcludedScheme;
+        }
+        if self.is_host_excluded(uri) || self.is_ip_excluded(uri) {
+            return FilterDecision::ExcludedAddress;
+        }
+        if self.is_mail_excluded(uri) {
+            return FilterDecision::ExcludedMail;
+        }
+        if uri.is_tel() {
+            return FilterDecision::ExcludedTel;
+        }
+        if is_example_domain(uri) {
+            return FilterDecision::ExampleDomain;
+        }
+        if is_unsupported_domain(uri) {
+            return FilterDecision::UnsupportedDomain;
         }
 
         let input = uri.as_str();
 
+        if self.strict_includes && !self.is_includes_empty() {
+            return self.decide_allowlisted(uri);
+        }
+
         if self.is_includes_empty() {
             if self.is_excludes_empty() {
                 // Both excludes and includes rules are empty:
                 // *Presumably included* unless it's a false positive
-                return is_false_positive(input);
+                return if is_false_positive(input) {
+                    FilterDecision::FalsePositive
+                } else {
+                    FilterDecision::Included
+                };
             }
-        } else if self.is_includes_match(input) {
+        } else if let Some(pattern) = self.includes.as_ref().and_then(|i| i.first_match(input)) {
             // *Explicitly included* (Includes take precedence over excludes)
-            return false;
+            return FilterDecision::IncludedByPattern(pattern.to_string());
         }
 
         // Exclude well-known false-positives
         // Performed after checking includes to allow user-overwrites
-        if is_false_positive(input)
-                // Previous checks imply input is not explicitly included.
-                // If exclude rules are empty, then *presumably excluded*
-                || self.is_excludes_empty()
-                // If exclude rules match input, then *explicitly excluded*
-                || self.is_excludes_match(input)
-        {
-            return true;
+        if is_false_positive(input) {
+            return FilterDecision::FalsePositive;
         }
+        // Previous checks imply input is not explicitly included.
+        // If exclude rules are empty, then *presumably excluded*
+        if self.is_excludes_empty() {
+            return FilterDecision::NotIncluded;
+        }
+        // If exclude rules match input, then *explicitly excluded*
+        self.explicitly_excluded(uri)
+            .unwrap_or(FilterDecision::Included)
+    }
 
-        false
+    /// Decide whether `uri` is checked if the include rules are an allowlist,
+    /// so that exclude rules take precedence. See [`Filter::decide`]
+    fn decide_allowlisted(&self, uri: &Uri) -> FilterDecision {
+        if let Some(decision) = self.explicitly_excluded(uri) {
+            return decision;
+        }
+        let input = uri.as_str();
+        if let Some(pattern) = self.includes.as_ref().and_then(|i| i.first_match(input)) {
+            return FilterDecision::IncludedByPattern(pattern.to_string());
+        }
+        FilterDecision::NotIncluded
+    }
+
+    /// The decision if the exclude rules or the exclude predicate match `uri`
+    fn explicitly_excluded(&self, uri: &Uri) -> Option<FilterDecision> {
+        if let Some(pattern) = self
+            .excludes
+            .as_ref()
+            .and_then(|e| e.first_match(uri.as_str()))
+        {
+            return Some(FilterDecision::ExcludedByPattern(pattern.to_string()));
+        }
+        let predicate = self.exclude_predicate.as_ref()?;
+        predicate
+            .is_match(&uri.url)
+            .then_some(FilterDecision::ExcludedByPredicate)
     }
 }
 
@@ -256,7 +376,7 @@ mod tests {
     use reqwest::Url;
     use url::Host;
 
-    use super::{Excludes, Filter, Includes};
+    use super::{Excludes, Filter, FilterDecision, Includes};
     use crate::{
         test_utils::{mail, website},
         Uri,
@@ -377,6 +497,32 @@ mod tests {
         assert!(filter.is_excluded(&website("https://example.com")));
     }
 
+    #[test]
+    fn test_decide_reports_matching_pattern() {
+        let filter = Filter {
+            includes: Some(Includes {
+                regex: RegexSet::new([r"foo\.github\.com"]).unwrap(),
+            }),
+            excludes: Some(Excludes {
+                regex: RegexSet::new([r"example", r"github\.com"]).unwrap(),
+            }),
+            ..Filter::default()
+        };
+
+        assert_eq!(
+            filter.decide(&website("https://foo.github.com")),
+            FilterDecision::IncludedByPattern(r"foo\.github\.com".to_string())
+        );
+        assert_eq!(
+            filter.decide(&website("https://bar.github.com")),
+            FilterDecision::ExcludedByPattern(r"github\.com".to_string())
+        );
+        assert_eq!(
+            filter.decide(&website("https://lychee.cli.rs")),
+            FilterDecision::Included
+        );
+    }
+
     #[test]
     fn test_exclude_mail_by_default() {
         let filter = Filter {
@@ -438,6 +584,35 @@ mod tests {
         assert!(filter.is_excluded(&website("https://bar.example.com")));
     }
 
+    #[test]
+    fn test_strict_includes() {
+        let filter = Filter {
+            includes: Some(Includes {
+                regex: RegexSet::new([r"/docs/"]).unwrap(),
+            }),
+            excludes: Some(Excludes {
+                regex: RegexSet::new([r"/drafts/"]).unwrap(),
+            }),
+            strict_includes: true,
+            ..Filter::default()
+        };
+
+        assert_eq!(
+            filter.decide(&website("https://lychee.cli.rs/docs/guide")),
+            FilterDecision::IncludedByPattern("/docs/".to_string())
+        );
+        // Excludes take preference over includes
+        assert_eq!(
+            filter.decide(&website("https://lychee.cli.rs/docs/drafts/new")),
+            FilterDecision::ExcludedByPattern("/drafts/".to_string())
+        );
+        // Not included, even though exclude rules are given
+        assert_eq!(
+            filter.decide(&website("https://lychee.cli.rs/blog")),
+            FilterDecision::NotIncluded
+        );
+    }
+
     #[test]
     fn test_excludes_no_private_ips_by_default() {
         let filter = Filter::default();
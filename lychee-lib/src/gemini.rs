@@ -0,0 +1,190 @@
+//! A minimal Gemini client to check whether a resource on a Gemini server
+//! exists.
+//!
+//! A Gemini request is a single line containing the URL, sent over TLS. The
+//! server answers with a header line `<STATUS> <META>`, optionally followed by
+//! a body. Only the header is read.
+//!
+//! Gemini servers usually use self-signed certificates and clients are
+//! expected to trust them on first use. As lychee doesn't keep any state
+//! between runs, all certificates are accepted.
+
+use std::{sync::Arc, time::Duration};
+
+use http::StatusCode;
+use reqwest::Url;
+use rustls::{
+    client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+    crypto::{ring, verify_tls12_signature, verify_tls13_signature, WebPkiSupportedAlgorithms},
+    pki_types::{CertificateDer, ServerName, UnixTime},
+    ClientConfig, DigitallySignedStruct, SignatureScheme,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+};
+use tokio_rustls::TlsConnector;
+
+use crate::{ErrorKind, Status, Uri};
+
+/// Default port of Gemini servers
+const DEFAULT_PORT: u16 = 1965;
+
+/// Maximum length of a request URL, as defined by the specification
+const MAX_URL_LENGTH: usize = 1024;
+
+/// The result of a single Gemini request. The error contains the reason why
+/// the resource is unreachable.
+type GeminiResult<T> = std::result::Result<T, String>;
+
+/// Request the resource at the `gemini://` URL and return the response
+/// header.
+///
+/// Returns `None` if the request didn't finish within `timeout`.
+pub(crate) async fn request(url: &Url, timeout: Duration) -> Option<GeminiResult<String>> {
+    tokio::time::timeout(timeout, request_inner(url)).await.ok()
+}
+
+async fn request_inner(url: &Url) -> GeminiResult<String> {
+    let host = url.host_str().ok_or_else(|| "Missing host".to_string())?;
+    if url.as_str().len() > MAX_URL_LENGTH {
+        return Err(format!("URL is longer than {MAX_URL_LENGTH} bytes"));
+    }
+    let server_name = ServerName::try_from(host.trim_matches(['[', ']']))
+        .map_err(|e| format!("Invalid host {host}: {e}"))?
+        .to_owned();
+
+    let stream = TcpStream::connect((host, url.port().unwrap_or(DEFAULT_PORT)))
+        .await
+        .map_err(|e| format!("Cannot connect to {host}: {e}"))?;
+    let mut stream = connector()
+        .connect(server_name, stream)
+        .await
+        .map_err(|e| format!("TLS handshake with {host} failed: {e}"))?;
+
+    stream
+        .write_all(format!("{url}\r\n").as_bytes())
+        .await
+        .map_err(|e| format!("Cannot send Gemini request: {e}"))?;
+    let mut header = String::new();
+    BufReader::new(stream)
+        .read_line(&mut header)
+        .await
+        .map_err(|e| format!("Cannot read Gemini response: {e}"))?;
+    Ok(header.trim_end().to_string())
+}
+
+/// A TLS connector which accepts any server certificate
+fn connector() -> TlsConnector {
+    let verifier = AcceptAnyCertificate(ring::default_provider().signature_verification_algorithms);
+    let config = ClientConfig::builder()
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier))
+        .with_no_client_auth();
+    TlsConnector::from(Arc::new(config))
+}
+
+/// Map the response header of a request to `uri` to a [`Status`].
+///
+/// Input prompts (`1x`) mean that the resource exists, so they are treated
+/// like successful responses (`2x`). Redirects (`3x`) are reported as
+/// temporary or permanent redirects. Failures (`4x` and `5x`) and requests for
+/// a client certificate (`6x`) are errors.
+pub(crate) fn status_from_header(uri: &Uri, header: &str) -> Status {
+    let Some(code) = header
+        .get(..2)
+        .filter(|code| code.bytes().all(|b| b.is_ascii_digit()))
+        .and_then(|code| code.parse::<u8>().ok())
+    else {
+        return ErrorKind::UnreachableGeminiResource(
+            uri.clone(),
+            format!("Invalid response header `{header}`"),
+        )
+        .into();
+    };
+    match code {
+        10..=29 => Status::Ok(StatusCode::OK),
+        31 => Status::Redirected(StatusCode::PERMANENT_REDIRECT),
+        30..=39 => Status::Redirected(StatusCode::TEMPORARY_REDIRECT),
+        _ => ErrorKind::UnreachableGeminiResource(uri.clone(), header.to_string()).into(),
+    }
+}
+
+/// Trusts every server certificate, but still checks the handshake
+/// signatures
+#[derive(Debug)]
+struct AcceptAnyCertificate(WebPkiSupportedAlgorithms);
+
+impl ServerCertVerifier for AcceptAnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.0)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.0)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uri() -> Uri {
+        Uri::try_from("gemini://example.com/page.gmi").unwrap()
+    }
+
+    #[test]
+    fn test_status_from_header() {
+        assert_eq!(
+            status_from_header(&uri(), "20 text/gemini; lang=en"),
+            Status::Ok(StatusCode::OK)
+        );
+        assert_eq!(
+            status_from_header(&uri(), "10 Enter a search term"),
+            Status::Ok(StatusCode::OK)
+        );
+        assert_eq!(
+            status_from_header(&uri(), "30 gemini://example.com/new.gmi"),
+            Status::Redirected(StatusCode::TEMPORARY_REDIRECT)
+        );
+        assert_eq!(
+            status_from_header(&uri(), "31 gemini://example.com/new.gmi"),
+            Status::Redirected(StatusCode::PERMANENT_REDIRECT)
+        );
+        assert!(status_from_header(&uri(), "51 Not found").is_error());
+        assert!(status_from_header(&uri(), "44 Slow down").is_error());
+        assert!(status_from_header(&uri(), "60 Certificate required").is_error());
+    }
+
+    #[test]
+    fn test_status_from_invalid_header() {
+        for header in ["", "2", "OK", "-1 Negative"] {
+            assert!(status_from_header(&uri(), header).is_error(), "{header}");
+        }
+    }
+}
@@ -56,10 +56,22 @@ mod checker;
 mod client;
 /// A pool of clients, to handle concurrent checks
 pub mod collector;
+#[cfg(feature = "ftp")]
+mod ftp;
+#[cfg(feature = "gemini")]
+mod gemini;
+mod meta_refresh;
+#[cfg(feature = "verify-mail")]
+mod mx;
 mod quirks;
+mod recording;
+mod redirect;
 mod retry;
 mod types;
+mod user_agents;
 mod utils;
+#[cfg(feature = "websocket")]
+mod websocket;
 
 /// Functionality to extract URIs from inputs
 pub mod extract;
@@ -71,6 +83,7 @@ pub mod remap;
 /// local IPs or e-mail addresses
 pub mod filter;
 
+/// Helpers for setting up mock servers and clients in tests
 #[cfg(test)]
 #[macro_use]
 pub mod test_utils;
@@ -86,17 +99,21 @@ use openssl_sys as _; // required for vendored-openssl feature
 pub use crate::{
     basic_auth::BasicAuthExtractor,
     // Expose the `Handler` trait to allow defining external handlers (plugins)
-    chain::{ChainResult, Handler},
+    chain::{ChainResult, Handler, RetryHandler},
     // Constants get exposed so that the CLI can use the same defaults as the library
     client::{
-        check, Client, ClientBuilder, DEFAULT_MAX_REDIRECTS, DEFAULT_MAX_RETRIES,
+        check, CheckPlan, Client, ClientBuilder, OnResultFn, ResultCallback,
+        DEFAULT_FS_CONCURRENCY, DEFAULT_MAX_REDIRECTS, DEFAULT_MAX_RETRIES,
         DEFAULT_RETRY_WAIT_TIME_SECS, DEFAULT_TIMEOUT_SECS, DEFAULT_USER_AGENT,
     },
     collector::Collector,
-    filter::{Excludes, Filter, Includes},
+    filter::{ExcludeFn, ExcludePredicate, Excludes, Filter, FilterDecision, Includes},
     types::{
-        uri::valid::Uri, AcceptRange, AcceptRangeError, AcceptSelector, Base, BasicAuthCredentials,
-        BasicAuthSelector, CacheStatus, CookieJar, ErrorKind, FileType, Input, InputContent,
-        InputSource, Request, Response, ResponseBody, Result, Status,
+        uri::{raw::RawUriSpan, valid::Uri},
+        AcceptPatternSelector, AcceptRange, AcceptRangeError, AcceptSchemeSelector, AcceptSelector,
+        Base, BasicAuthCredentials, BasicAuthSelector, CacheStatus, CookieJar, Date, DateError,
+        ErrorKind, FetchLimits, FileType, HostTokens, HostTokensError, Input, InputContent,
+        InputSource, ProcessExt, ProcessExtParseError, Request, RequestMethods,
+        RequestMethodsError, Response, ResponseBody, Result, Status, UrlColumn, UrlColumnError,
     },
 };
@@ -0,0 +1,129 @@
+//! Tracking of `<meta http-equiv="refresh">` redirects.
+//!
+//! The checker only sees the response to a link, while following a meta
+//! refresh happens afterwards in the client. To avoid fetching every page a
+//! second time, the checker records the refresh target of successful HTML
+//! responses in a task-local which only lives while the link is checked, so
+//! that the client gets it back together with the status, see [`track`].
+
+use std::{cell::RefCell, future::Future};
+
+use http::header::CONTENT_TYPE;
+use reqwest::{Method, Url};
+
+use crate::extract::html::html5gum::extract_html;
+
+tokio::task_local! {
+    /// Meta refresh of the page which the current task is checking
+    static REFRESH: RefCell<Option<MetaRefresh>>;
+}
+
+/// What is known about the meta refresh of a checked HTML page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum MetaRefresh {
+    /// The page refreshes to this URL
+    Target(Url),
+    /// The page was requested without a body, e.g. with `HEAD`, so it needs
+    /// to be fetched to find out
+    Unknown,
+}
+
+/// Await `check`, which checks a single link, and return its output together
+/// with the meta refresh of the page, if the checker recorded one.
+///
+/// Concurrent checks never see each other's meta refresh, even if they check
+/// the same URL.
+pub(crate) async fn track<F: Future>(check: F) -> (F::Output, Option<MetaRefresh>) {
+    REFRESH
+        .scope(RefCell::default(), async {
+            let output = check.await;
+            (output, REFRESH.with(RefCell::take))
+        })
+        .await
+}
+
+/// Record the meta refresh of the successful `response` to a request with
+/// `method` for the check which is tracked by the current task. Other than
+/// HTML responses are ignored, as are HTML pages without a meta refresh.
+pub(crate) async fn record(method: &Method, response: reqwest::Response) {
+    if !is_html(&response) {
+        return;
+    }
+    let refresh = if method == Method::HEAD {
+        Some(MetaRefresh::Unknown)
+    } else {
+        let base = response.url().clone();
+        let Ok(body) = response.text().await else {
+            return;
+        };
+        target(&base, &body).map(MetaRefresh::Target)
+    };
+    // Fails outside of `track`, i.e. if nobody follows the refresh
+    let _ = REFRESH.try_with(|current| *current.borrow_mut() = refresh);
+}
+
+/// Whether the `Content-Type` of `response` is HTML
+fn is_html(response: &reqwest::Response) -> bool {
+    response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|content_type| {
+            content_type
+                .trim_start()
+                .to_ascii_lowercase()
+                .starts_with("text/html")
+        })
+}
+
+/// The target of the meta refresh in the HTML `body`, resolved against the
+/// URL `base` the page was served from
+fn target(base: &Url, body: &str) -> Option<Url> {
+    let refresh = extract_html(body, false).into_iter().find(|raw| {
+        raw.element.as_deref() == Some("meta") && raw.attribute.as_deref() == Some("content")
+    })?;
+    base.join(&refresh.text).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::ResponseBuilderExt;
+
+    use super::*;
+
+    fn response(url: &Url, content_type: &str, body: &str) -> reqwest::Response {
+        http::Response::builder()
+            .url(url.clone())
+            .header(CONTENT_TYPE, content_type)
+            .body(body.to_string())
+            .unwrap()
+            .into()
+    }
+
+    #[tokio::test]
+    async fn test_record() {
+        let url = Url::parse("https://example.com/docs/").unwrap();
+        let body = r#"<meta http-equiv="refresh" content="0; url=../new">"#;
+
+        let ((), refresh) = track(record(&Method::GET, response(&url, "text/plain", body))).await;
+        assert_eq!(refresh, None);
+
+        let ((), refresh) = track(record(&Method::GET, response(&url, "text/html", body))).await;
+        assert_eq!(
+            refresh,
+            Some(MetaRefresh::Target(
+                Url::parse("https://example.com/new").unwrap()
+            ))
+        );
+
+        let ((), refresh) = track(record(&Method::HEAD, response(&url, "text/html", ""))).await;
+        assert_eq!(refresh, Some(MetaRefresh::Unknown));
+
+        let ((), refresh) = track(record(
+            &Method::GET,
+            response(&url, "text/html", "<p>Hi</p>"),
+        ))
+        .await;
+        assert_eq!(refresh, None);
+    }
+}
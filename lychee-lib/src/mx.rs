@@ -0,0 +1,50 @@
+//! Check whether the domain of a mail address accepts mail by looking up its
+//! DNS records, without connecting to the mail server.
+
+use trust_dns_resolver::{
+    error::{ResolveError, ResolveErrorKind},
+    TokioAsyncResolver,
+};
+
+use crate::ErrorKind;
+
+/// Resolver for the MX and address records of mail domains
+#[derive(Debug)]
+pub(crate) struct MxResolver(TokioAsyncResolver);
+
+impl MxResolver {
+    /// Create a resolver using the system's DNS configuration
+    pub(crate) fn from_system_conf() -> Result<Self, ErrorKind> {
+        TokioAsyncResolver::tokio_from_system_conf()
+            .map(Self)
+            .map_err(|e| ErrorKind::BuildDnsResolver(e.to_string()))
+    }
+
+    /// Check whether `domain` accepts mail.
+    ///
+    /// This is the case if it has at least one MX record. A domain without MX
+    /// records is its own mail server if it has an address (RFC 5321, section
+    /// 5.1), so an A or AAAA record is sufficient as well. Otherwise, the
+    /// reason why mail can't be delivered is returned.
+    pub(crate) async fn check(&self, domain: &str) -> Result<(), String> {
+        let is_no_records =
+            |e: &ResolveError| matches!(e.kind(), ResolveErrorKind::NoRecordsFound { .. });
+        // A trailing dot makes the domain fully qualified, so that it doesn't
+        // get resolved relative to the search domains of the system
+        let fqdn = format!("{domain}.");
+        match self.0.mx_lookup(fqdn.as_str()).await {
+            Ok(lookup) if lookup.iter().next().is_some() => return Ok(()),
+            Err(e) if !is_no_records(&e) => {
+                return Err(format!("Cannot look up MX records of `{domain}`: {e}"))
+            }
+            _ => {}
+        }
+        match self.0.lookup_ip(fqdn.as_str()).await {
+            Ok(lookup) if lookup.iter().next().is_some() => Ok(()),
+            Err(e) if !is_no_records(&e) => {
+                Err(format!("Cannot look up the address of `{domain}`: {e}"))
+            }
+            _ => Err(format!("No MX or address records found for `{domain}`")),
+        }
+    }
+}
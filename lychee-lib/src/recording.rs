@@ -0,0 +1,155 @@
+//! Recording and replaying of HTTP responses.
+//!
+//! When recording, the status and headers of every response are stored in a
+//! directory, one JSON file per request method and URL. When replaying, the
+//! stored responses are returned instead of sending any requests, so that a
+//! run can be reproduced offline. Response bodies are not stored, as the
+//! status of a link only depends on the status code. Only the headers which
+//! the check looks at are stored, so that recordings don't leak cookies or
+//! authentication challenges.
+//!
+//! Requests which fail without a response, e.g. due to a timeout, are not
+//! recorded, so on replay they fail with [`ErrorKind::MissingRecording`].
+
+use std::path::{Path, PathBuf};
+
+use http::{
+    header::{HeaderName, ALLOW, CONTENT_TYPE, LOCATION, RETRY_AFTER},
+    Method, StatusCode,
+};
+use log::warn;
+use reqwest::{ResponseBuilderExt, Url};
+use serde::{Deserialize, Serialize};
+
+use crate::{ErrorKind, Result};
+
+/// Headers of a response which are recorded, as the check depends on them
+const RECORDED_HEADERS: [HeaderName; 4] = [ALLOW, CONTENT_TYPE, LOCATION, RETRY_AFTER];
+
+/// Whether responses are recorded to or replayed from a directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Recording {
+    /// Store all responses in the directory
+    Record(PathBuf),
+    /// Answer all requests with the responses stored in the directory
+    Replay(PathBuf),
+}
+
+/// A stored response together with the request it answers
+#[derive(Debug, Serialize, Deserialize)]
+struct Interaction {
+    method: String,
+    url: String,
+    status: u16,
+    headers: Vec<(String, String)>,
+}
+
+/// Store the status and headers of `response` to the request `method url`
+/// in `dir`. Failures are logged, as they must not affect the check itself.
+pub(crate) async fn record(dir: &Path, method: &Method, url: &Url, response: &reqwest::Response) {
+    let interaction = Interaction {
+        method: method.to_string(),
+        url: url.to_string(),
+        status: response.status().as_u16(),
+        headers: response
+            .headers()
+            .iter()
+            .filter(|(name, _)| RECORDED_HEADERS.contains(name))
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect(),
+    };
+    let path = dir.join(file_name(method, url));
+    let result = match serde_json::to_vec_pretty(&interaction) {
+        Ok(json) => match tokio::fs::create_dir_all(dir).await {
+            Ok(()) => tokio::fs::write(&path, json).await,
+            Err(e) => Err(e),
+        },
+        Err(e) => Err(e.into()),
+    };
+    if let Err(e) = result {
+        warn!("Cannot record response to {path:?}: {e}");
+    }
+}
+
+/// The response to the request `method url` stored in `dir`
+pub(crate) async fn replay(dir: &Path, method: &Method, url: &Url) -> Result<reqwest::Response> {
+    let missing = || ErrorKind::MissingRecording(format!("{method} {url}"));
+    let json = tokio::fs::read(dir.join(file_name(method, url)))
+        .await
+        .map_err(|_| missing())?;
+    let interaction: Interaction = serde_json::from_slice(&json).map_err(|_| missing())?;
+    if interaction.method != method.as_str() || interaction.url != url.as_str() {
+        return Err(missing());
+    }
+
+    let status = StatusCode::from_u16(interaction.status)
+        .map_err(|_| ErrorKind::InvalidStatusCode(interaction.status))?;
+    let mut response = http::Response::builder().status(status).url(url.clone());
+    for (name, value) in &interaction.headers {
+        response = response.header(name, value);
+    }
+    let response = response.body(Vec::new()).map_err(|_| missing())?;
+    Ok(response.into())
+}
+
+/// Name of the file storing the response to the request `method url`.
+///
+/// The URL is hashed with FNV-1a, which, unlike the hasher of the standard
+/// library, is stable across platforms and Rust versions, so recordings can be
+/// shared.
+fn file_name(method: &Method, url: &Url) -> String {
+    let hash = url
+        .as_str()
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    format!("{method}-{hash:016x}.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let dir = tempdir().unwrap();
+        let url = Url::parse("https://example.com/missing").unwrap();
+        let response: reqwest::Response = http::Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .header("content-type", "text/html")
+            .header("set-cookie", "session=secret")
+            .header("www-authenticate", "Basic realm=\"private\"")
+            .body(Vec::new())
+            .unwrap()
+            .into();
+
+        record(dir.path(), &Method::GET, &url, &response).await;
+        let replayed = replay(dir.path(), &Method::GET, &url).await.unwrap();
+
+        assert_eq!(replayed.status(), StatusCode::NOT_FOUND);
+        assert_eq!(replayed.url(), &url);
+        assert_eq!(replayed.headers()["content-type"], "text/html");
+        // Sensitive headers are never written to disk
+        assert!(!replayed.headers().contains_key("set-cookie"));
+        assert!(!replayed.headers().contains_key("www-authenticate"));
+        let recorded = std::fs::read_to_string(dir.path().join(file_name(&Method::GET, &url)));
+        assert!(!recorded.unwrap().contains("secret"));
+        assert!(matches!(
+            replay(dir.path(), &Method::HEAD, &url).await,
+            Err(ErrorKind::MissingRecording(_))
+        ));
+    }
+
+    #[test]
+    fn test_file_name() {
+        let url = Url::parse("https://example.com").unwrap();
+        assert_eq!(file_name(&Method::GET, &url), "GET-0c8b41cfdcb3c914.json");
+        assert_ne!(
+            file_name(&Method::GET, &url),
+            file_name(&Method::GET, &Url::parse("https://example.org").unwrap())
+        );
+    }
+}
@@ -0,0 +1,114 @@
+//! Tracking of the redirects followed by `reqwest`.
+//!
+//! `reqwest` follows redirects transparently and only exposes the final URL of
+//! a response. To find out how many hops a request took, the redirect policy
+//! records the chain of the request in a task-local which only lives while
+//! that request is sent, see [`track`].
+
+use std::{cell::RefCell, future::Future};
+
+use log::debug;
+use reqwest::{redirect, Url};
+
+tokio::task_local! {
+    /// Redirect chain of the request which the current task is sending
+    static CHAIN: RefCell<Vec<Url>>;
+}
+
+/// Await `send`, which sends a single request, and return its output together
+/// with the redirect chain the request took.
+///
+/// The chain starts with the URL of the request and ends with the final URL.
+/// It is empty if the request was not redirected. `reqwest` calls the
+/// redirect policy from the future of the request, so concurrent requests
+/// never see each other's chain, even if they are sent to the same URL.
+pub(crate) async fn track<F: Future>(send: F) -> (F::Output, Vec<Url>) {
+    CHAIN
+        .scope(RefCell::default(), async {
+            let output = send.await;
+            (output, CHAIN.with(RefCell::take))
+        })
+        .await
+}
+
+/// Record that the request which already visited `previous` gets redirected
+/// to `next`. Requests which are not tracked are ignored.
+fn record(previous: &[Url], next: &Url) {
+    // Fails outside of `track`, e.g. for the requests of the GitHub client
+    let _ = CHAIN.try_with(|chain| {
+        let mut chain = chain.borrow_mut();
+        chain.clear();
+        chain.extend_from_slice(previous);
+        chain.push(next.clone());
+    });
+}
+
+/// The first HTTP URL in `chain` which was reached by a redirect from an
+/// HTTPS URL
+pub(crate) fn https_downgrade(chain: &[Url]) -> Option<&Url> {
+    chain
+        .windows(2)
+        .find(|hop| hop[0].scheme() == "https" && hop[1].scheme() == "http")
+        .map(|hop| &hop[1])
+}
+
+/// Redirect policy which follows up to `max_redirects` redirects, logging
+/// each of them and recording the chains of tracked requests
+pub(crate) fn policy(max_redirects: usize) -> redirect::Policy {
+    redirect::Policy::custom(move |attempt| {
+        if attempt.previous().len() > max_redirects {
+            attempt.error("too many redirects")
+        } else {
+            debug!("Redirecting to {}", attempt.url());
+            record(attempt.previous(), attempt.url());
+            attempt.follow()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_track() {
+        let urls: Vec<Url> = [
+            "https://a.example/",
+            "https://b.example/",
+            "https://c.example/",
+        ]
+        .iter()
+        .map(|url| Url::parse(url).unwrap())
+        .collect();
+
+        let ((), chain) = track(async {
+            record(&urls[..1], &urls[1]);
+            record(&urls[..2], &urls[2]);
+        })
+        .await;
+        assert_eq!(chain, urls);
+
+        let ((), chain) = track(async {}).await;
+        assert!(chain.is_empty());
+
+        // Outside of `track`, nothing is recorded
+        record(&urls[..1], &urls[1]);
+    }
+
+    #[test]
+    fn test_https_downgrade() {
+        let urls: Vec<Url> = [
+            "http://a.example/",
+            "https://b.example/",
+            "http://c.example/",
+            "https://d.example/",
+        ]
+        .iter()
+        .map(|url| Url::parse(url).unwrap())
+        .collect();
+
+        assert_eq!(https_downgrade(&urls), Some(&urls[2]));
+        assert_eq!(https_downgrade(&urls[..2]), None);
+        assert_eq!(https_downgrade(&urls[2..]), None);
+    }
+}
@@ -18,21 +18,26 @@ pub(crate) trait RetryExt {
 
 impl RetryExt for reqwest::Response {
     /// Try to map a `reqwest` response into `Retryable`.
-    #[allow(clippy::if_same_then_else)]
     fn should_retry(&self) -> bool {
-        let status = self.status();
-        if status.is_server_error() {
-            true
-        } else if status.is_client_error()
-            && status != StatusCode::REQUEST_TIMEOUT
-            && status != StatusCode::TOO_MANY_REQUESTS
-        {
-            false
-        } else if status.is_success() {
-            false
-        } else {
-            status == StatusCode::REQUEST_TIMEOUT || status == StatusCode::TOO_MANY_REQUESTS
-        }
+        should_retry_status(self.status())
+    }
+}
+
+/// Whether a request which got a response with this status might succeed
+/// when being retried
+#[allow(clippy::if_same_then_else)]
+fn should_retry_status(status: StatusCode) -> bool {
+    if status.is_server_error() {
+        true
+    } else if status.is_client_error()
+        && status != StatusCode::REQUEST_TIMEOUT
+        && status != StatusCode::TOO_MANY_REQUESTS
+    {
+        false
+    } else if status.is_success() {
+        false
+    } else {
+        status == StatusCode::REQUEST_TIMEOUT || status == StatusCode::TOO_MANY_REQUESTS
     }
 }
 
@@ -71,10 +76,11 @@ impl RetryExt for reqwest::Error {
             } else {
                 false
             }
+        } else if let Some(status) = self.status() {
+            // Created by `Response::error_for_status`, which is how an error
+            // status of a response ends up in a `Status`
+            should_retry_status(status)
         } else {
-            // We omit checking if error.is_status() since we check that already.
-            // However, if Response::error_for_status is used the status will still
-            // remain in the response object.
             false
         }
     }
@@ -118,9 +124,10 @@ impl RetryExt for Status {
             Status::Timeout(_) => true,
             Status::Redirected(_) => false,
             Status::UnknownStatusCode(_) => false,
-            Status::Excluded => false,
+            Status::Excluded | Status::ExcludedWithReason(_) => false,
             Status::Unsupported(_) => false,
             Status::Cached(_) => false,
+            Status::Warning(..) => false,
         }
     }
 }
@@ -1,5 +1,9 @@
+mod pattern;
 mod range;
+mod scheme;
 mod selector;
 
+pub use pattern::*;
 pub use range::*;
+pub use scheme::*;
 pub use selector::*;
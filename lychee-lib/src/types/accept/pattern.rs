@@ -0,0 +1,92 @@
+use std::str::FromStr;
+
+use serde_with::DeserializeFromStr;
+use thiserror::Error;
+
+use crate::types::accept::{AcceptSelector, AcceptSelectorError};
+
+#[derive(Debug, Error)]
+pub enum AcceptPatternSelectorError {
+    #[error("Empty selector input")]
+    EmptyInput,
+
+    #[error("Missing URL pattern or status codes. Valid form is '<pattern>:<codes>'")]
+    InvalidSyntax,
+
+    #[error("Failed to parse accepted status codes: {0}")]
+    AcceptSelectorError(#[from] AcceptSelectorError),
+}
+
+/// [`AcceptPatternSelector`] accepts additional status codes for URLs which
+/// match the specified regex. This allows users to accept e.g. `403` for an
+/// auth-gated API host while still treating it as an error everywhere else.
+///
+/// The input form is `<pattern>:<codes>`, for example
+/// `api.example.com:401,403`. The codes use the same syntax as
+/// [`AcceptSelector`]. The pattern is split off at the last colon, so it may
+/// itself contain colons, e.g. `https://example.com:8080:403`.
+#[derive(Debug, Clone, DeserializeFromStr, PartialEq)]
+pub struct AcceptPatternSelector {
+    /// This regex matches URLs for which the status codes are accepted
+    pub raw_uri_regex: String,
+
+    /// The status codes accepted for matching URLs
+    pub selector: AcceptSelector,
+}
+
+impl FromStr for AcceptPatternSelector {
+    type Err = AcceptPatternSelectorError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+
+        if input.is_empty() {
+            return Err(AcceptPatternSelectorError::EmptyInput);
+        }
+
+        let (pattern, codes) = input
+            .rsplit_once(':')
+            .ok_or(AcceptPatternSelectorError::InvalidSyntax)?;
+
+        if pattern.is_empty() {
+            return Err(AcceptPatternSelectorError::InvalidSyntax);
+        }
+
+        Ok(Self {
+            raw_uri_regex: pattern.to_string(),
+            selector: codes.parse()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_valid_accept_pattern_selector() {
+        let selector: AcceptPatternSelector = "api.example.com:403,401".parse().unwrap();
+
+        assert_eq!(selector.raw_uri_regex, "api.example.com");
+        assert!(selector.selector.contains(401));
+        assert!(selector.selector.contains(403));
+        assert!(!selector.selector.contains(200));
+    }
+
+    #[test]
+    fn test_accept_pattern_selector_with_port() {
+        let selector: AcceptPatternSelector = "https://example.com:8080:200..=299".parse().unwrap();
+
+        assert_eq!(selector.raw_uri_regex, "https://example.com:8080");
+        assert!(selector.selector.contains(204));
+    }
+
+    #[test]
+    fn test_invalid_accept_pattern_selector() {
+        assert!(AcceptPatternSelector::from_str("").is_err());
+        assert!(AcceptPatternSelector::from_str("403").is_err());
+        assert!(AcceptPatternSelector::from_str(":403").is_err());
+        assert!(AcceptPatternSelector::from_str("example.com:").is_err());
+        assert!(AcceptPatternSelector::from_str("example.com:abc").is_err());
+    }
+}
@@ -4,8 +4,9 @@ use once_cell::sync::Lazy;
 use regex::Regex;
 use thiserror::Error;
 
-static RANGE_PATTERN: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"^([0-9]{3})?\.\.(=?)([0-9]{3})+$|^([0-9]{3})$").unwrap());
+static RANGE_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^([0-9]{3})?\.\.(=?)([0-9]{3})+$|^([0-9]{3})$|^([0-9]{3})-([0-9]{3})$").unwrap()
+});
 
 /// The [`AcceptRangeParseError`] indicates that the parsing process of an
 /// [`AcceptRange`]  from a string failed due to various underlying reasons.
@@ -20,7 +21,7 @@ pub enum AcceptRangeError {
     ParseIntError(#[from] ParseIntError),
 
     /// The start index is larger than the end index.
-    #[error("invalid range indices, only start < end supported")]
+    #[error("invalid range indices, the start must not be larger than the end")]
     InvalidRangeIndices,
 }
 
@@ -40,6 +41,9 @@ impl FromStr for AcceptRange {
         if let Some(value) = captures.get(4) {
             let value: u16 = value.as_str().parse()?;
             Self::new_from(value, value)
+        } else if let (Some(start), Some(end)) = (captures.get(5), captures.get(6)) {
+            // Dash ranges like `200-299` include the end
+            Self::new_from(start.as_str().parse()?, end.as_str().parse()?)
         } else {
             let start: u16 = match captures.get(1) {
                 Some(start) => start.as_str().parse().unwrap_or_default(),
@@ -164,6 +168,8 @@ mod test {
     #[case("100..200", vec![100, 150], vec![200, 250])]
     #[case("..100", vec![0, 50], vec![100, 150])]
     #[case("404", vec![404], vec![200, 304, 500])]
+    #[case("200-204", vec![200, 202, 204], vec![199, 205])]
+    #[case("404-404", vec![404], vec![403, 405])]
     fn test_from_str(
         #[case] input: &str,
         #[case] valid_values: Vec<u16>,
@@ -182,6 +188,10 @@ mod test {
 
     #[rstest]
     #[case("200..=100", AcceptRangeError::InvalidRangeIndices)]
+    #[case("204-200", AcceptRangeError::InvalidRangeIndices)]
+    #[case("200-", AcceptRangeError::NoRangePattern)]
+    #[case("-200", AcceptRangeError::NoRangePattern)]
+    #[case("200-300-400", AcceptRangeError::NoRangePattern)]
     #[case("-100..=100", AcceptRangeError::NoRangePattern)]
     #[case("-100..100", AcceptRangeError::NoRangePattern)]
     #[case("100..=-100", AcceptRangeError::NoRangePattern)]
@@ -0,0 +1,110 @@
+use std::str::FromStr;
+
+use serde_with::DeserializeFromStr;
+use thiserror::Error;
+
+use crate::types::accept::{AcceptSelector, AcceptSelectorError};
+
+#[derive(Debug, Error)]
+pub enum AcceptSchemeSelectorError {
+    #[error("Empty selector input")]
+    EmptyInput,
+
+    #[error("Missing scheme or status codes. Valid form is '<scheme>:<codes>'")]
+    InvalidSyntax,
+
+    #[error("Invalid URL scheme `{0}`")]
+    InvalidScheme(String),
+
+    #[error("Failed to parse accepted status codes: {0}")]
+    AcceptSelectorError(#[from] AcceptSelectorError),
+}
+
+/// [`AcceptSchemeSelector`] defines the accepted status codes for URLs with
+/// a certain scheme. These codes are used instead of the globally accepted
+/// ones, e.g. to accept different codes for `http` and `https` links.
+///
+/// The input form is `<scheme>:<codes>`, for example `https:200,204`. The
+/// codes use the same syntax as [`AcceptSelector`]. Schemes are compared
+/// case-insensitively.
+#[derive(Debug, Clone, DeserializeFromStr, PartialEq)]
+pub struct AcceptSchemeSelector {
+    /// The URL scheme, e.g. `https`, in lowercase
+    pub scheme: String,
+
+    /// The status codes accepted for URLs with this scheme
+    pub selector: AcceptSelector,
+}
+
+impl FromStr for AcceptSchemeSelector {
+    type Err = AcceptSchemeSelectorError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+
+        if input.is_empty() {
+            return Err(AcceptSchemeSelectorError::EmptyInput);
+        }
+
+        let (scheme, codes) = input
+            .split_once(':')
+            .ok_or(AcceptSchemeSelectorError::InvalidSyntax)?;
+
+        let scheme = scheme.trim();
+        if scheme.is_empty() {
+            return Err(AcceptSchemeSelectorError::InvalidSyntax);
+        }
+        if !is_valid_scheme(scheme) {
+            return Err(AcceptSchemeSelectorError::InvalidScheme(scheme.to_string()));
+        }
+
+        Ok(Self {
+            scheme: scheme.to_ascii_lowercase(),
+            selector: codes.parse()?,
+        })
+    }
+}
+
+/// Whether `scheme` is a valid URL scheme according to RFC 3986, i.e. a
+/// letter followed by letters, digits, `+`, `-` or `.`
+fn is_valid_scheme(scheme: &str) -> bool {
+    let mut chars = scheme.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_valid_accept_scheme_selector() {
+        let selector: AcceptSchemeSelector = "FTP:200,213".parse().unwrap();
+
+        assert_eq!(selector.scheme, "ftp");
+        assert!(selector.selector.contains(200));
+        assert!(selector.selector.contains(213));
+        assert!(!selector.selector.contains(226));
+
+        let selector: AcceptSchemeSelector = "https:200..=204".parse().unwrap();
+        assert_eq!(selector.scheme, "https");
+        assert!(selector.selector.contains(204));
+    }
+
+    #[test]
+    fn test_invalid_accept_scheme_selector() {
+        assert!(AcceptSchemeSelector::from_str("").is_err());
+        assert!(AcceptSchemeSelector::from_str("200").is_err());
+        assert!(AcceptSchemeSelector::from_str(":200").is_err());
+        assert!(AcceptSchemeSelector::from_str("https:").is_err());
+        assert!(AcceptSchemeSelector::from_str("https:abc").is_err());
+        assert!(matches!(
+            AcceptSchemeSelector::from_str("1http:200"),
+            Err(AcceptSchemeSelectorError::InvalidScheme(_))
+        ));
+        assert!(matches!(
+            AcceptSchemeSelector::from_str("api.example.com/path:200"),
+            Err(AcceptSchemeSelectorError::InvalidScheme(_))
+        ));
+    }
+}
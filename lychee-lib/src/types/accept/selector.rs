@@ -186,6 +186,7 @@ mod test {
     #[case("200..=300,100..=250", vec![100, 150, 200, 250, 300], vec![350], 1)]
     #[case("100..=200,150..=200", vec![100, 150, 200], vec![250, 300], 1)]
     #[case("100..=200,300", vec![100, 110, 200, 300], vec![250, 350], 2)]
+    #[case("200-299,403", vec![200, 250, 299, 403], vec![300, 404], 2)]
     fn test_from_str(
         #[case] input: &str,
         #[case] valid_values: Vec<u16>,
@@ -235,6 +236,15 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_from_str_inverted_range() {
+        let error = AcceptSelector::from_str("200,204-200").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "failed to parse accept range: invalid range indices, the start must not be larger than the end"
+        );
+    }
+
     #[rstest]
     #[case("100..=150,200..=300", "100..=150,200..=300")]
     #[case("100..=150,300", "100..=150,300..=300")]
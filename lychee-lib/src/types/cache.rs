@@ -70,8 +70,12 @@ impl From<&Status> for CacheStatus {
             // Reqwest treats unknown status codes as Ok(StatusCode).
             // TODO: Use accepted status codes to decide whether this is a
             // success or failure
-            Status::Ok(code) | Status::UnknownStatusCode(code) => Self::Ok(code.as_u16()),
-            Status::Excluded => Self::Excluded,
+            // Warnings are only reported for working links. They don't get
+            // cached though, so that they are reported again on the next run.
+            Status::Ok(code) | Status::UnknownStatusCode(code) | Status::Warning(code, _) => {
+                Self::Ok(code.as_u16())
+            }
+            Status::Excluded | Status::ExcludedWithReason(_) => Self::Excluded,
             Status::Unsupported(_) => Self::Unsupported,
             Status::Redirected(code) => Self::Error(Some(code.as_u16())),
             Status::Timeout(code) => Self::Error(code.map(|code| code.as_u16())),
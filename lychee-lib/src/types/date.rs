@@ -0,0 +1,74 @@
+use std::{fmt::Display, str::FromStr};
+
+use chrono::{Datelike, NaiveDate};
+use serde_with::DeserializeFromStr;
+use thiserror::Error;
+
+/// Errors which can occur when parsing a [`Date`]
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum DateError {
+    /// The input is not a calendar date given as `YYYY-MM-DD`
+    #[error("Invalid date `{0}`, expected YYYY-MM-DD")]
+    InvalidDate(String),
+}
+
+/// A calendar date, given as `YYYY-MM-DD`
+#[derive(Debug, Clone, Copy, DeserializeFromStr, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date(NaiveDate);
+
+impl FromStr for Date {
+    type Err = DateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // The parser also accepts single digit months and days, which are not
+        // allowed here
+        let is_padded = s.len() == 10
+            && s.char_indices().all(|(i, c)| match i {
+                4 | 7 => c == '-',
+                _ => c.is_ascii_digit(),
+            });
+        is_padded
+            .then(|| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok())
+            .flatten()
+            .map(Self)
+            .ok_or_else(|| DateError::InvalidDate(s.to_string()))
+    }
+}
+
+impl Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}",
+            self.0.year(),
+            self.0.month(),
+            self.0.day()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date() {
+        let date: Date = "2024-03-01".parse().unwrap();
+        assert_eq!(date.to_string(), "2024-03-01");
+        assert!(date < "2024-03-02".parse().unwrap());
+
+        for invalid in [
+            "2024-3-1",
+            "01.03.2024",
+            "2024-03-01T10:00:00Z",
+            "2024-99-99",
+        ] {
+            assert_eq!(
+                invalid.parse::<Date>(),
+                Err(DateError::InvalidDate(invalid.to_string()))
+            );
+        }
+        assert!("2023-02-29".parse::<Date>().is_err());
+        assert!("2024-02-29".parse::<Date>().is_ok());
+    }
+}
@@ -1,3 +1,4 @@
+use http::StatusCode;
 use serde::{Serialize, Serializer};
 use std::error::Error;
 use std::hash::Hash;
@@ -7,7 +8,7 @@ use tokio::task::JoinError;
 
 use super::InputContent;
 use crate::types::AcceptSelectorError;
-use crate::{basic_auth::BasicAuthExtractorError, utils, Uri};
+use crate::{basic_auth::BasicAuthExtractorError, utils, Request, Uri};
 
 /// Kinds of status errors
 /// Note: The error messages can change over time, so don't match on the output
@@ -17,6 +18,10 @@ pub enum ErrorKind {
     /// Network error while handling request
     #[error("Network error")]
     NetworkRequest(#[source] reqwest::Error),
+    /// The host actively refused the connection, which usually means that the
+    /// service is down rather than that the URL is wrong
+    #[error("Connection refused")]
+    ConnectionRefused(#[source] reqwest::Error),
     /// Cannot read the body of the received response
     #[error("Error reading response body: {0}")]
     ReadResponseBody(#[source] reqwest::Error),
@@ -48,6 +53,11 @@ pub enum ErrorKind {
     #[error("Error creating GitHub client")]
     BuildGithubClient(#[source] octocrab::Error),
 
+    /// The DNS resolver for looking up the MX records of mail domains cannot
+    /// be created
+    #[error("Error creating DNS resolver: {0}")]
+    BuildDnsResolver(String),
+
     /// Invalid GitHub URL
     #[error("GitHub URL is invalid: {0}")]
     InvalidGithubUrl(String),
@@ -68,6 +78,10 @@ pub enum ErrorKind {
     #[error("Cannot find fragment")]
     InvalidFragment(Uri),
 
+    /// The file the URI points to lies outside of the root directory
+    #[error("Link leads outside of the root directory `{1}`")]
+    OutsideRootDir(Uri, PathBuf),
+
     /// The given path cannot be converted to a URI
     #[error("Invalid path to URL conversion: {0}")]
     InvalidUrlFromPath(PathBuf),
@@ -76,6 +90,27 @@ pub enum ErrorKind {
     #[error("Unreachable mail address: {0}: {1}")]
     UnreachableEmailAddress(Uri, String),
 
+    /// The given FTP resource is unreachable
+    #[error("Unreachable FTP resource: {0}: {1}")]
+    UnreachableFtpResource(Uri, String),
+
+    /// The decoded credentials or path of an FTP URL contain control
+    /// characters, which would end up in the commands sent to the server
+    #[error("FTP URL contains control characters: {0}")]
+    FtpControlCharacters(Uri),
+
+    /// The Gemini server didn't serve the resource
+    #[error("Unreachable Gemini resource: {0}: {1}")]
+    UnreachableGeminiResource(Uri, String),
+
+    /// The server at the given WebSocket endpoint didn't switch protocols
+    #[error("WebSocket handshake rejected: {0} responded with {1}")]
+    RejectedWebSocketHandshake(Uri, StatusCode),
+
+    /// No response to the request was recorded, so it cannot be replayed
+    #[error("No recorded response for `{0}`")]
+    MissingRecording(String),
+
     /// The given header could not be parsed.
     /// A possible error when converting a `HeaderValue` from a string or byte
     /// slice.
@@ -90,6 +125,10 @@ pub enum ErrorKind {
     #[error("Error remapping URL: `{0}`")]
     InvalidUrlRemap(String),
 
+    /// The transform command failed to rewrite the URL of the given request
+    #[error("Transform command `{0}` failed for `{}`: {2}", .1.uri)]
+    TransformCommand(String, Box<Request>, String),
+
     /// The given path does not resolve to a valid file
     #[error("Cannot find local file {0}")]
     InvalidFile(PathBuf),
@@ -110,6 +149,15 @@ pub enum ErrorKind {
     #[error("This URI is available in HTTPS protocol, but HTTP is provided. Use '{0}' instead")]
     InsecureURL(Uri),
 
+    /// The URI points to localhost or a private network address
+    #[error("This URI points to a private or local address, which is not reachable for others")]
+    PrivateAddress(Uri),
+
+    /// The URI points to a host which is not allowed. Contains the remapped
+    /// URI if a remapping rule applies
+    #[error("Links to host '{0}' are not allowed{}", .1.as_ref().map(|u| format!(". Use '{u}' instead")).unwrap_or_default())]
+    DisallowedHost(String, Option<Uri>),
+
     /// Error while sending/receiving messages from MPSC channel
     #[error("Cannot send/receive message from channel")]
     Channel(#[from] tokio::sync::mpsc::error::SendError<InputContent>),
@@ -134,6 +182,16 @@ pub enum ErrorKind {
     #[error("Too many redirects")]
     TooManyRedirects(#[source] reqwest::Error),
 
+    /// A redirect stepped down from HTTPS to HTTP. Contains the first HTTP
+    /// URL of the redirect chain
+    #[error("Redirected from HTTPS to the insecure URL '{0}'")]
+    HttpsDowngrade(Uri),
+
+    /// A link to an image returned a response with a different content type,
+    /// e.g. an HTML error page
+    #[error("Expected an image, but '{0}' has the content type '{1}'")]
+    NotAnImage(Uri, String),
+
     /// Basic auth extractor error
     #[error("Basic auth extractor error")]
     BasicAuthExtractorError(#[from] BasicAuthExtractorError),
@@ -156,7 +214,7 @@ impl ErrorKind {
     #[must_use]
     pub fn details(&self) -> Option<String> {
         match self {
-            ErrorKind::NetworkRequest(e) => {
+            ErrorKind::NetworkRequest(e) | ErrorKind::ConnectionRefused(e) => {
                 if let Some(status) = e.status() {
                     Some(
                         status
@@ -206,6 +264,9 @@ impl PartialEq for ErrorKind {
             (Self::NetworkRequest(e1), Self::NetworkRequest(e2)) => {
                 e1.to_string() == e2.to_string()
             }
+            (Self::ConnectionRefused(e1), Self::ConnectionRefused(e2)) => {
+                e1.to_string() == e2.to_string()
+            }
             (Self::ReadResponseBody(e1), Self::ReadResponseBody(e2)) => {
                 e1.to_string() == e2.to_string()
             }
@@ -223,7 +284,23 @@ impl PartialEq for ErrorKind {
             (Self::UnreachableEmailAddress(u1, ..), Self::UnreachableEmailAddress(u2, ..)) => {
                 u1 == u2
             }
+            (Self::UnreachableFtpResource(u1, ..), Self::UnreachableFtpResource(u2, ..)) => {
+                u1 == u2
+            }
+            (Self::FtpControlCharacters(u1), Self::FtpControlCharacters(u2)) => u1 == u2,
+            (Self::UnreachableGeminiResource(u1, ..), Self::UnreachableGeminiResource(u2, ..)) => {
+                u1 == u2
+            }
+            (
+                Self::RejectedWebSocketHandshake(u1, ..),
+                Self::RejectedWebSocketHandshake(u2, ..),
+            ) => u1 == u2,
+            (Self::MissingRecording(r1), Self::MissingRecording(r2)) => r1 == r2,
             (Self::InsecureURL(u1), Self::InsecureURL(u2)) => u1 == u2,
+            (Self::PrivateAddress(u1), Self::PrivateAddress(u2)) => u1 == u2,
+            (Self::HttpsDowngrade(u1), Self::HttpsDowngrade(u2)) => u1 == u2,
+            (Self::NotAnImage(u1, t1), Self::NotAnImage(u2, t2)) => u1 == u2 && t1 == t2,
+            (Self::DisallowedHost(h1, r1), Self::DisallowedHost(h2, r2)) => h1 == h2 && r1 == r2,
             (Self::InvalidGlobPattern(e1), Self::InvalidGlobPattern(e2)) => {
                 e1.msg == e2.msg && e1.pos == e2.pos
             }
@@ -261,9 +338,11 @@ impl Hash for ErrorKind {
             Self::ReadFileInput(e, s) => (e.kind(), s).hash(state),
             Self::ReadStdinInput(e) => e.kind().hash(state),
             Self::NetworkRequest(e) => e.to_string().hash(state),
+            Self::ConnectionRefused(e) => e.to_string().hash(state),
             Self::ReadResponseBody(e) => e.to_string().hash(state),
             Self::BuildRequestClient(e) => e.to_string().hash(state),
             Self::BuildGithubClient(e) => e.to_string().hash(state),
+            Self::BuildDnsResolver(e) => e.hash(state),
             Self::GithubRequest(e) => e.to_string().hash(state),
             Self::InvalidGithubUrl(s) => s.hash(state),
             Self::DirTraversal(e) => e.to_string().hash(state),
@@ -275,10 +354,21 @@ impl Hash for ErrorKind {
             Self::Utf8(e) => e.to_string().hash(state),
             Self::InvalidFilePath(u) => u.hash(state),
             Self::InvalidFragment(u) => u.hash(state),
+            Self::OutsideRootDir(u, _) => u.hash(state),
             Self::UnreachableEmailAddress(u, ..) => u.hash(state),
+            Self::UnreachableFtpResource(u, ..) => u.hash(state),
+            Self::FtpControlCharacters(u) => u.hash(state),
+            Self::UnreachableGeminiResource(u, ..) => u.hash(state),
+            Self::RejectedWebSocketHandshake(u, ..) => u.hash(state),
+            Self::MissingRecording(r) => r.hash(state),
             Self::InsecureURL(u, ..) => u.hash(state),
+            Self::PrivateAddress(u) => u.hash(state),
+            Self::HttpsDowngrade(u) => u.hash(state),
+            Self::NotAnImage(u, t) => (u, t).hash(state),
+            Self::DisallowedHost(h, _) => h.hash(state),
             Self::InvalidBase(base, e) => (base, e).hash(state),
             Self::InvalidUrlRemap(remap) => (remap).hash(state),
+            Self::TransformCommand(command, request, _) => (command, &request.uri).hash(state),
             Self::InvalidHeader(e) => e.to_string().hash(state),
             Self::InvalidGlobPattern(e) => e.to_string().hash(state),
             Self::InvalidStatusCode(c) => c.hash(state),
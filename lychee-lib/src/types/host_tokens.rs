@@ -0,0 +1,214 @@
+use std::{collections::HashMap, str::FromStr, sync::Arc};
+
+use async_trait::async_trait;
+use http::header::{HeaderValue, AUTHORIZATION};
+use reqwest::Request;
+use secrecy::{ExposeSecret, SecretString};
+use thiserror::Error;
+
+use crate::{
+    chain::{ChainResult, Handler},
+    Status,
+};
+
+/// Prefix of the variables which hold the token of a host
+const PREFIX: &str = "HOST_TOKEN_";
+
+/// Errors which can occur when parsing [`HostTokens`]
+#[derive(Debug, Clone, Copy, Error, PartialEq, Eq)]
+pub enum HostTokensError {
+    /// A line is neither empty, a comment nor a `NAME=value` assignment
+    #[error("Line {0} is not of the form NAME=value")]
+    InvalidLine(usize),
+
+    /// A `HOST_TOKEN_` variable doesn't name a host
+    #[error("Missing host after `{PREFIX}` in line {0}")]
+    MissingHost(usize),
+}
+
+/// [`HostTokens`] sends bearer tokens to the hosts they belong to.
+///
+/// The tokens are read from a `.env`-style file with one `NAME=value`
+/// assignment per line. Empty lines, comments starting with `#` and
+/// variables without the `HOST_TOKEN_` prefix are ignored, so the file can be
+/// shared with other tools. Values may be quoted with `"` or `'`.
+///
+/// The variable of a host is `HOST_TOKEN_` followed by the host, with every
+/// `.` replaced by `_` and every `-` replaced by `__`. For example, the token
+/// of `api.example.com` is read from `HOST_TOKEN_api_example_com` and the one
+/// of `my-api.example.com` from `HOST_TOKEN_my__api_example_com`. Hosts are
+/// matched exactly and case-insensitively, so subdomains need a variable of
+/// their own.
+///
+/// The token is sent as `Authorization: Bearer <token>`, unless the request
+/// already has an `Authorization` header, e.g. from basic auth credentials.
+/// Tokens are only sent over HTTPS, so that they never travel in plain text.
+/// Hosts which are checked over HTTP, e.g. local development servers, get no
+/// token at all.
+#[derive(Debug, Clone, Default)]
+pub struct HostTokens {
+    tokens: Arc<HashMap<String, SecretString>>,
+    /// Send the tokens over HTTP as well, so that tests can check them
+    /// against a plain mock server
+    plain_text: bool,
+}
+
+impl HostTokens {
+    /// The token which is sent to `host`, if any
+    #[must_use]
+    pub fn token(&self, host: &str) -> Option<&SecretString> {
+        self.tokens.get(&host.to_ascii_lowercase())
+    }
+
+    /// The number of hosts with a token
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    /// Whether there are no tokens at all
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Whether the token may be sent with `request`
+    fn is_secure(&self, request: &Request) -> bool {
+        self.plain_text || request.url().scheme() == "https"
+    }
+
+    /// Send the tokens over HTTP as well
+    #[cfg(test)]
+    pub(crate) fn plain_text(mut self) -> Self {
+        self.plain_text = true;
+        self
+    }
+}
+
+/// The host named by the part of a variable name after the prefix
+fn host_name(suffix: &str) -> String {
+    suffix
+        .to_ascii_lowercase()
+        .replace("__", "-")
+        .replace('_', ".")
+}
+
+impl FromStr for HostTokens {
+    type Err = HostTokensError;
+
+    fn from_str(contents: &str) -> Result<Self, Self::Err> {
+        let mut tokens = HashMap::new();
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (name, value) = line
+                .split_once('=')
+                .ok_or(HostTokensError::InvalidLine(index + 1))?;
+            let Some(host) = name.trim().strip_prefix(PREFIX) else {
+                continue;
+            };
+            if host.is_empty() {
+                return Err(HostTokensError::MissingHost(index + 1));
+            }
+            let value = value.trim();
+            let value = ['"', '\'']
+                .iter()
+                .find_map(|quote| value.strip_prefix(*quote)?.strip_suffix(*quote))
+                .unwrap_or(value);
+            tokens.insert(host_name(host), SecretString::new(value.to_string()));
+        }
+        Ok(Self {
+            tokens: Arc::new(tokens),
+            ..Self::default()
+        })
+    }
+}
+
+#[async_trait]
+impl Handler<Request, Status> for HostTokens {
+    async fn handle(&mut self, mut request: Request) -> ChainResult<Request, Status> {
+        if !self.is_secure(&request) || request.headers().contains_key(AUTHORIZATION) {
+            return ChainResult::Next(request);
+        }
+        let token = request.url().host_str().and_then(|host| self.token(host));
+        let header = token.and_then(|token| {
+            HeaderValue::from_str(&format!("Bearer {}", token.expose_secret())).ok()
+        });
+        if let Some(mut header) = header {
+            header.set_sensitive(true);
+            request.headers_mut().insert(AUTHORIZATION, header);
+        }
+        ChainResult::Next(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_host_tokens() {
+        let tokens: HostTokens = r#"
+            # Tokens for link checking
+            HOST_TOKEN_example_com=abc123
+            export HOST_TOKEN_api_my__host_org="def 456"
+            OTHER_VARIABLE=ignored
+        "#
+        .parse()
+        .unwrap();
+
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(
+            tokens.token("example.com").unwrap().expose_secret(),
+            "abc123"
+        );
+        assert_eq!(
+            tokens.token("API.my-host.org").unwrap().expose_secret(),
+            "def 456"
+        );
+        assert!(tokens.token("sub.example.com").is_none());
+        assert!(tokens.token("api.my.host.org").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_token_only_sent_to_its_host_over_https() {
+        let mut tokens: HostTokens = "HOST_TOKEN_api_example_com=abc123".parse().unwrap();
+
+        for (url, expected) in [
+            ("https://api.example.com/", Some("Bearer abc123")),
+            ("https://api-example.com/", None),
+            ("https://api_example_com/", None),
+            ("http://api.example.com/", None),
+        ] {
+            let request = Request::new(reqwest::Method::GET, url.parse().unwrap());
+            let ChainResult::Next(request) = tokens.handle(request).await else {
+                panic!("HostTokens never finishes the chain");
+            };
+            assert_eq!(
+                request
+                    .headers()
+                    .get(AUTHORIZATION)
+                    .map(|header| header.to_str().unwrap()),
+                expected,
+                "{url}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid_host_tokens() {
+        assert_eq!(
+            "HOST_TOKEN_example_com=abc\nnot an assignment"
+                .parse::<HostTokens>()
+                .unwrap_err(),
+            HostTokensError::InvalidLine(2)
+        );
+        assert_eq!(
+            "HOST_TOKEN_=abc".parse::<HostTokens>().unwrap_err(),
+            HostTokensError::MissingHost(1)
+        );
+    }
+}
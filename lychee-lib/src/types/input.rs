@@ -3,13 +3,17 @@ use crate::{utils, ErrorKind, Result};
 use async_stream::try_stream;
 use futures::stream::Stream;
 use glob::glob_with;
+use http::header::ACCEPT_ENCODING;
 use jwalk::WalkDir;
+use log::warn;
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
 use shellexpand::tilde;
+use std::collections::{HashSet, VecDeque};
 use std::fmt::Display;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tokio::io::{stdin, AsyncReadExt};
 
 const STDIN: &str = "-";
@@ -18,6 +22,13 @@ const STDIN: &str = "-";
 // file extensions
 fn valid_extension(p: &Path) -> bool {
     matches!(FileType::from(p), FileType::Markdown | FileType::Html)
+        || (cfg!(feature = "pdf") && is_pdf(p))
+}
+
+// PDF files can only be read if lychee was built with the `pdf` feature
+fn is_pdf(p: &Path) -> bool {
+    p.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"))
 }
 
 #[derive(Debug)]
@@ -104,6 +115,20 @@ impl Display for InputSource {
     }
 }
 
+/// Limits for fetching remote inputs to extract links from them. These are
+/// independent of the limits for checking links, as extraction needs the
+/// whole body.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FetchLimits {
+    /// Time from connect until the body is read, after which fetching stops
+    pub timeout: Option<Duration>,
+    /// Number of body bytes after which reading stops
+    pub max_body_size: Option<usize>,
+    /// Value of the `Accept-Encoding` header, if it should differ from the
+    /// encodings `reqwest` can decode
+    pub accept_encoding: Option<String>,
+}
+
 /// Lychee Input with optional file hint for parsing
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Input {
@@ -113,6 +138,9 @@ pub struct Input {
     pub file_type_hint: Option<FileType>,
     /// Excluded paths that will be skipped when reading content
     pub excluded_paths: Option<Vec<PathBuf>>,
+    /// Follow symlinks when traversing a directory. Symlinks which lead back
+    /// into a directory that is already being traversed are skipped
+    pub follow_symlinks: bool,
 }
 
 impl Input {
@@ -136,7 +164,8 @@ impl Input {
             InputSource::RemoteUrl(Box::new(url))
         } else {
             // this seems to be the only way to determine if this is a glob pattern
-            let is_glob = glob::Pattern::escape(value) != value;
+            let is_glob = glob::Pattern::escape(value) != value
+                || utils::glob::expand_braces(value).len() > 1;
 
             if is_glob {
                 InputSource::FsGlob {
@@ -170,6 +199,7 @@ impl Input {
             source,
             file_type_hint,
             excluded_paths,
+            follow_symlinks: false,
         })
     }
 
@@ -181,10 +211,30 @@ impl Input {
     /// because of an underlying I/O error (e.g. an error while making a
     /// network request or retrieving the contents from the file system)
     pub fn get_contents(self, skip_missing: bool) -> impl Stream<Item = Result<InputContent>> {
+        self.get_contents_with_limits(skip_missing, FetchLimits::default())
+    }
+
+    /// Retrieve the contents from the input, fetching remote inputs within
+    /// the given `limits`.
+    ///
+    /// If a remote input takes too long or is too large, only the part of
+    /// the body read so far is returned and a warning with the HTTP status of
+    /// the response is logged.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the contents can not be retrieved
+    /// because of an underlying I/O error (e.g. an error while making a
+    /// network request or retrieving the contents from the file system)
+    pub fn get_contents_with_limits(
+        self,
+        skip_missing: bool,
+        limits: FetchLimits,
+    ) -> impl Stream<Item = Result<InputContent>> {
         try_stream! {
             match self.source {
                 InputSource::RemoteUrl(ref url) => {
-                    let content = Self::url_contents(url).await;
+                    let content = Self::url_contents(url, &limits).await;
                     match content {
                         Err(_) if skip_missing => (),
                         Err(e) => Err(e)?,
@@ -202,8 +252,10 @@ impl Input {
                 }
                 InputSource::FsPath(ref path) => {
                     if path.is_dir() {
+                        let follow_symlinks = self.follow_symlinks;
                         for entry in WalkDir::new(path).skip_hidden(true)
-                        .process_read_dir(move |_, _, (), children| {
+                        .follow_links(follow_symlinks)
+                        .process_read_dir(move |_, dir, (), children| {
                             children.retain(|child| {
                                 let Ok(entry) = child.as_ref() else { return true };
 
@@ -214,6 +266,10 @@ impl Input {
                                 let file_type = entry.file_type();
 
                                 if file_type.is_dir() {
+                                    if entry.path_is_symlink() && is_symlink_loop(dir, &entry.path()) {
+                                        warn!("Skipping symlink {}, which leads back into a parent directory", entry.path().display());
+                                        return false;
+                                    }
                                     // Required for recursion
                                     return true;
                                 }
@@ -226,7 +282,14 @@ impl Input {
                                 valid_extension(&entry.path())
                             });
                         }) {
-                            let entry = entry?;
+                            let entry = match entry {
+                                // Broken symlinks and loops detected by `jwalk`
+                                Err(e) if follow_symlinks && e.path().is_some_and(Path::is_symlink) => {
+                                    warn!("Skipping symlink: {e}");
+                                    continue;
+                                }
+                                entry => entry?,
+                            };
                             if entry.file_type().is_dir() {
                                 continue;
                             }
@@ -257,6 +320,69 @@ impl Input {
         }
     }
 
+    /// Retrieve the pages listed in the sitemaps of the site a remote input
+    /// belongs to.
+    ///
+    /// The sitemaps are looked up in the `Sitemap:` lines of the site's
+    /// `/robots.txt`; sitemap indexes are followed. Each sitemap is returned
+    /// as a plaintext input containing the URLs of its pages, so that they
+    /// are reported as found in the sitemap. Other inputs are retrieved like
+    /// with [`get_contents_with_limits`](Self::get_contents_with_limits).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `robots.txt` or one of the sitemaps can not be
+    /// retrieved, unless `skip_missing` is set
+    pub fn get_sitemap_contents(
+        self,
+        skip_missing: bool,
+        limits: FetchLimits,
+    ) -> impl Stream<Item = Result<InputContent>> {
+        try_stream! {
+            let InputSource::RemoteUrl(ref url) = self.source else {
+                for await content in self.get_contents_with_limits(skip_missing, limits) {
+                    yield content?;
+                }
+                return;
+            };
+            let Some(robots_url) = utils::sitemap::robots_url(url) else {
+                return;
+            };
+            let robots = match Self::url_contents(&robots_url, &limits).await {
+                Ok(robots) => robots,
+                Err(_) if skip_missing => return,
+                Err(e) => Err(e)?,
+            };
+
+            let mut sitemaps: VecDeque<Url> =
+                utils::sitemap::sitemaps_in_robots(&robots.content).into();
+            if sitemaps.is_empty() {
+                warn!("{robots_url} doesn't reference any sitemap");
+            }
+            let mut seen = HashSet::new();
+            while let Some(sitemap_url) = sitemaps.pop_front() {
+                if !seen.insert(sitemap_url.clone()) {
+                    continue;
+                }
+                let sitemap = match Self::url_contents(&sitemap_url, &limits).await {
+                    Ok(sitemap) => sitemap,
+                    Err(_) if skip_missing => continue,
+                    Err(e) => Err(e)?,
+                };
+                let locations = utils::sitemap::locations(&sitemap.content);
+                if utils::sitemap::is_index(&sitemap.content) {
+                    sitemaps.extend(locations.iter().filter_map(|l| Url::parse(l).ok()));
+                } else {
+                    yield InputContent {
+                        source: InputSource::RemoteUrl(Box::new(sitemap_url)),
+                        file_type: FileType::Plaintext,
+                        content: locations.join("\n"),
+                    };
+                }
+            }
+        }
+    }
+
     /// Retrieve all sources from this input. The output depends on the type of
     /// input:
     ///
@@ -278,10 +404,12 @@ impl Input {
 
                     match_opts.case_sensitive = !ignore_case;
 
-                    for entry in glob_with(&glob_expanded, match_opts)? {
-                        match entry {
-                            Ok(path) => yield path.to_string_lossy().to_string(),
-                            Err(e) => eprintln!("{e:?}")
+                    for pattern in utils::glob::expand_braces(&glob_expanded) {
+                        for entry in glob_with(&pattern, match_opts)? {
+                            match entry {
+                                Ok(path) => yield path.to_string_lossy().to_string(),
+                                Err(e) => eprintln!("{e:?}")
+                            }
                         }
                     }
                 },
@@ -292,7 +420,7 @@ impl Input {
         }
     }
 
-    async fn url_contents(url: &Url) -> Result<InputContent> {
+    async fn url_contents(url: &Url, limits: &FetchLimits) -> Result<InputContent> {
         // Assume HTML for default paths
         let file_type = if url.path().is_empty() || url.path() == "/" {
             FileType::Html
@@ -300,13 +428,40 @@ impl Input {
             FileType::from(url.as_str())
         };
 
-        let res = reqwest::get(url.clone())
-            .await
-            .map_err(ErrorKind::NetworkRequest)?;
+        let mut request = reqwest::Client::new().get(url.clone());
+        if let Some(timeout) = limits.timeout {
+            request = request.timeout(timeout);
+        }
+        if let Some(accept_encoding) = &limits.accept_encoding {
+            request = request.header(ACCEPT_ENCODING, accept_encoding);
+        }
+        let mut res = request.send().await.map_err(ErrorKind::NetworkRequest)?;
+
+        let status = res.status();
+        let mut body = Vec::new();
+        let truncated = loop {
+            match res.chunk().await {
+                Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+                Ok(None) => break None,
+                Err(e) if e.is_timeout() => break Some("timed out"),
+                Err(e) => Err(ErrorKind::ReadResponseBody(e))?,
+            }
+            if let Some(max_body_size) = limits.max_body_size.filter(|&max| body.len() > max) {
+                body.truncate(max_body_size);
+                break Some("exceeded the maximum body size");
+            }
+        };
+        if let Some(reason) = truncated {
+            warn!(
+                "{url} responded with {status}, but reading the body {reason}. Only links in the first {} bytes are extracted",
+                body.len()
+            );
+        }
+
         let input_content = InputContent {
             source: InputSource::RemoteUrl(Box::new(url.clone())),
             file_type,
-            content: res.text().await.map_err(ErrorKind::ReadResponseBody)?,
+            content: String::from_utf8_lossy(&body).into_owned(),
         };
 
         Ok(input_content)
@@ -323,24 +478,26 @@ impl Input {
         match_opts.case_sensitive = !ignore_case;
 
         try_stream! {
-            for entry in glob_with(&glob_expanded, match_opts)? {
-                match entry {
-                    Ok(path) => {
-                        // Directories can have a suffix which looks like
-                        // a file extension (like `foo.html`). This can lead to
-                        // unexpected behavior with glob patterns like
-                        // `**/*.html`. Therefore filter these out.
-                        // See <https://github.com/lycheeverse/lychee/pull/262#issuecomment-913226819>
-                        if path.is_dir() {
-                            continue;
-                        }
-                        if self.is_excluded_path(&path) {
-                            continue;
+            for pattern in utils::glob::expand_braces(&glob_expanded) {
+                for entry in glob_with(&pattern, match_opts)? {
+                    match entry {
+                        Ok(path) => {
+                            // Directories can have a suffix which looks like
+                            // a file extension (like `foo.html`). This can lead to
+                            // unexpected behavior with glob patterns like
+                            // `**/*.html`. Therefore filter these out.
+                            // See <https://github.com/lycheeverse/lychee/pull/262#issuecomment-913226819>
+                            if path.is_dir() {
+                                continue;
+                            }
+                            if self.is_excluded_path(&path) {
+                                continue;
+                            }
+                            let content: InputContent = Self::path_content(&path).await?;
+                            yield content;
                         }
-                        let content: InputContent = Self::path_content(&path).await?;
-                        yield content;
+                        Err(e) => eprintln!("{e:?}"),
                     }
-                    Err(e) => eprintln!("{e:?}"),
                 }
             }
         }
@@ -362,6 +519,10 @@ impl Input {
         path: P,
     ) -> Result<InputContent> {
         let path = path.into();
+        #[cfg(feature = "pdf")]
+        if is_pdf(&path) {
+            return Self::pdf_content(path).await;
+        }
         let content = tokio::fs::read_to_string(&path)
             .await
             .map_err(|e| ErrorKind::ReadFileInput(e, path.clone()))?;
@@ -374,6 +535,26 @@ impl Input {
         Ok(input_content)
     }
 
+    /// Read the text and link targets of a PDF file as plaintext content
+    #[cfg(feature = "pdf")]
+    async fn pdf_content(path: PathBuf) -> Result<InputContent> {
+        let buf = tokio::fs::read(&path)
+            .await
+            .map_err(|e| ErrorKind::ReadFileInput(e, path.clone()))?;
+        let content = crate::extract::pdf::extract_pdf_text(&buf).map_err(|e| {
+            ErrorKind::ReadFileInput(
+                std::io::Error::new(std::io::ErrorKind::InvalidData, e),
+                path.clone(),
+            )
+        })?;
+
+        Ok(InputContent {
+            file_type: FileType::Plaintext,
+            source: InputSource::FsPath(path),
+            content,
+        })
+    }
+
     async fn stdin_content(file_type_hint: Option<FileType>) -> Result<InputContent> {
         let mut content = String::new();
         let mut stdin = stdin();
@@ -393,6 +574,17 @@ impl Input {
     }
 }
 
+/// Whether the symlinked directory `link` in `dir` leads back into `dir` or
+/// one of its parents, so that following it would never end
+fn is_symlink_loop(dir: &Path, link: &Path) -> bool {
+    let Ok(target) = fs::canonicalize(link) else {
+        return false;
+    };
+    dir.ancestors().any(|ancestor| {
+        fs::canonicalize(ancestor).is_ok_and(|ancestor| ancestor.starts_with(&target))
+    })
+}
+
 /// Function for path exclusion tests
 ///
 /// This is a standalone function to allow for easier testing
@@ -424,7 +616,8 @@ mod tests {
             Ok(Input {
                 source: InputSource::FsPath(PathBuf { .. }),
                 file_type_hint: None,
-                excluded_paths: None
+                excluded_paths: None,
+                follow_symlinks: false
             })
         ));
     }
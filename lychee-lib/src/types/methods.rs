@@ -0,0 +1,164 @@
+use std::{fmt::Display, str::FromStr};
+
+use http::{header::ALLOW, HeaderMap, StatusCode};
+use reqwest::Method;
+use serde_with::DeserializeFromStr;
+use thiserror::Error;
+
+/// Errors which can occur when parsing [`RequestMethods`]
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum RequestMethodsError {
+    /// No method was given
+    #[error("Empty list of request methods")]
+    EmptyInput,
+
+    /// The given method is not a valid HTTP method
+    #[error("Invalid request method `{0}`")]
+    InvalidMethod(String),
+}
+
+/// [`RequestMethods`] is the ordered list of HTTP methods a link is checked
+/// with, e.g. `HEAD,GET`.
+///
+/// The first method is tried first. If the server doesn't support it and
+/// responds with `405 Method Not Allowed` or `501 Not Implemented`, the next
+/// method is tried. If a `405` response lists the supported methods in its
+/// `Allow` header, the first of them in this list is tried instead. This allows checking links with cheap `HEAD` requests
+/// while still supporting servers which only answer `GET` requests.
+#[derive(Debug, Clone, DeserializeFromStr, PartialEq, Eq)]
+pub struct RequestMethods(Vec<Method>);
+
+impl RequestMethods {
+    /// Creates [`RequestMethods`] which try `methods` in the given order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `methods` is empty.
+    pub fn new(methods: Vec<Method>) -> Result<Self, RequestMethodsError> {
+        if methods.is_empty() {
+            return Err(RequestMethodsError::EmptyInput);
+        }
+        Ok(Self(methods))
+    }
+
+    /// Returns an iterator over the methods in the order they are tried.
+    pub fn iter(&self) -> impl Iterator<Item = &Method> {
+        self.0.iter()
+    }
+
+    /// Whether a response with `code` means that the server doesn't support
+    /// the method of the request, so that the next method should be tried.
+    #[must_use]
+    pub(crate) fn is_unsupported_method(code: StatusCode) -> bool {
+        code == StatusCode::METHOD_NOT_ALLOWED || code == StatusCode::NOT_IMPLEMENTED
+    }
+}
+
+/// The methods listed in the `Allow` header of a response with `code`, if it
+/// is a `405 Method Not Allowed`
+pub(crate) fn allowed_methods(code: StatusCode, headers: &HeaderMap) -> Option<Vec<Method>> {
+    if code != StatusCode::METHOD_NOT_ALLOWED {
+        return None;
+    }
+    let allow = headers.get(ALLOW)?.to_str().ok()?;
+    Some(parse_allow(allow))
+}
+
+/// Parse the value of an `Allow` header, e.g. `GET, POST`, skipping
+/// invalid methods
+fn parse_allow(allow: &str) -> Vec<Method> {
+    allow
+        .split(',')
+        .map(str::trim)
+        .filter_map(|method| Method::from_str(&method.to_uppercase()).ok())
+        .collect()
+}
+
+impl Default for RequestMethods {
+    fn default() -> Self {
+        Self(vec![Method::GET])
+    }
+}
+
+impl From<Method> for RequestMethods {
+    fn from(method: Method) -> Self {
+        Self(vec![method])
+    }
+}
+
+impl FromStr for RequestMethods {
+    type Err = RequestMethodsError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let methods = input
+            .split(',')
+            .map(str::trim)
+            .filter(|method| !method.is_empty())
+            .map(|method| {
+                Method::from_str(&method.to_uppercase())
+                    .map_err(|_| RequestMethodsError::InvalidMethod(method.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::new(methods)
+    }
+}
+
+impl Display for RequestMethods {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let methods: Vec<_> = self.0.iter().map(Method::as_str).collect();
+        write!(f, "{}", methods.join(","))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_request_methods() {
+        let methods: RequestMethods = "head, Get".parse().unwrap();
+
+        assert_eq!(
+            methods.iter().collect::<Vec<_>>(),
+            vec![&Method::HEAD, &Method::GET]
+        );
+        assert_eq!(methods.to_string(), "HEAD,GET");
+    }
+
+    #[test]
+    fn test_parse_invalid_request_methods() {
+        assert_eq!(
+            "".parse::<RequestMethods>(),
+            Err(RequestMethodsError::EmptyInput)
+        );
+        assert_eq!(
+            " , ".parse::<RequestMethods>(),
+            Err(RequestMethodsError::EmptyInput)
+        );
+        assert_eq!(
+            "HEAD,G ET".parse::<RequestMethods>(),
+            Err(RequestMethodsError::InvalidMethod("G ET".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_allowed_methods() {
+        let mut headers = HeaderMap::new();
+        headers.insert(ALLOW, "get, POST,,G ET".parse().unwrap());
+
+        assert_eq!(allowed_methods(StatusCode::NOT_FOUND, &headers), None);
+        assert_eq!(
+            allowed_methods(StatusCode::METHOD_NOT_ALLOWED, &HeaderMap::new()),
+            None
+        );
+        assert_eq!(
+            allowed_methods(StatusCode::METHOD_NOT_ALLOWED, &headers),
+            Some(vec![Method::GET, Method::POST])
+        );
+    }
+
+    #[test]
+    fn test_default_request_methods() {
+        assert_eq!(RequestMethods::default(), RequestMethods::from(Method::GET));
+    }
+}
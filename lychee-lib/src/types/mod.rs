@@ -5,26 +5,36 @@ mod base;
 mod basic_auth;
 mod cache;
 mod cookies;
+mod date;
 mod error;
 mod file;
+mod host_tokens;
 mod input;
 pub(crate) mod mail;
+pub(crate) mod methods;
+mod process_ext;
 mod request;
 mod response;
 mod status;
 pub(crate) mod uri;
+mod url_column;
 
 pub use accept::*;
 pub use base::Base;
 pub use basic_auth::{BasicAuthCredentials, BasicAuthSelector};
 pub use cache::CacheStatus;
 pub use cookies::CookieJar;
+pub use date::{Date, DateError};
 pub use error::ErrorKind;
 pub use file::FileType;
-pub use input::{Input, InputContent, InputSource};
+pub use host_tokens::{HostTokens, HostTokensError};
+pub use input::{FetchLimits, Input, InputContent, InputSource};
+pub use methods::{RequestMethods, RequestMethodsError};
+pub use process_ext::{ProcessExt, ProcessExtParseError};
 pub use request::Request;
 pub use response::{Response, ResponseBody};
 pub use status::Status;
+pub use url_column::{UrlColumn, UrlColumnError};
 
 /// The lychee `Result` type
 pub type Result<T> = std::result::Result<T, crate::ErrorKind>;
@@ -0,0 +1,131 @@
+use std::{path::Path, str::FromStr};
+
+use serde_with::DeserializeFromStr;
+use thiserror::Error;
+
+/// Placeholder for the path of the file in the argument template
+const PLACEHOLDER: &str = "{}";
+
+/// Errors which can occur when parsing a [`ProcessExt`]
+#[derive(Debug, Clone, Copy, Error, PartialEq, Eq)]
+pub enum ProcessExtParseError {
+    /// The input doesn't separate the extension and the command with a colon
+    #[error("Missing colon. Valid form is 'EXT:command {{}} args', e.g. 'pdf:pdftotext {{}} -'")]
+    MissingColon,
+
+    /// The extension in front of the colon is empty
+    #[error("Missing file extension in front of the colon")]
+    MissingExtension,
+
+    /// There is no command after the colon
+    #[error("Missing command after the colon")]
+    MissingCommand,
+
+    /// None of the arguments contains the `{}` placeholder for the file path
+    #[error("Missing `{{}}` placeholder for the file path in the arguments")]
+    MissingPlaceholder,
+}
+
+/// [`ProcessExt`] describes a command which converts files with a certain
+/// extension to text, so that links can be extracted from file types lychee
+/// can't read itself.
+///
+/// The input form is `EXT:command {} args`, for example
+/// `pdf:pdftotext {} -`. The `{}` placeholder is replaced with the path of
+/// the file; it may also be part of a larger argument like `--input={}`.
+/// The text the command writes to stdout is used as the content of the file.
+#[derive(Debug, Clone, DeserializeFromStr, PartialEq, Eq)]
+pub struct ProcessExt {
+    /// File extension without the leading dot, e.g. `pdf`
+    pub extension: String,
+
+    /// The program to run, e.g. `pdftotext`
+    pub command: String,
+
+    /// The arguments of the program, one of which contains the `{}`
+    /// placeholder
+    pub args: Vec<String>,
+}
+
+impl FromStr for ProcessExt {
+    type Err = ProcessExtParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let (extension, template) = input
+            .trim()
+            .split_once(':')
+            .ok_or(ProcessExtParseError::MissingColon)?;
+
+        let extension = extension.trim().trim_start_matches('.');
+        if extension.is_empty() {
+            return Err(ProcessExtParseError::MissingExtension);
+        }
+
+        let mut tokens = template.split_whitespace().map(ToString::to_string);
+        let command = tokens.next().ok_or(ProcessExtParseError::MissingCommand)?;
+        let args: Vec<String> = tokens.collect();
+        if !args.iter().any(|arg| arg.contains(PLACEHOLDER)) {
+            return Err(ProcessExtParseError::MissingPlaceholder);
+        }
+
+        Ok(Self {
+            extension: extension.to_string(),
+            command,
+            args,
+        })
+    }
+}
+
+impl ProcessExt {
+    /// Whether files at `path` are converted by this command
+    #[must_use]
+    pub fn matches(&self, path: &Path) -> bool {
+        path.extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case(&self.extension))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_process_ext() {
+        let process_ext: ProcessExt = "pdf:pdftotext {} -".parse().unwrap();
+
+        assert_eq!(
+            process_ext,
+            ProcessExt {
+                extension: "pdf".to_string(),
+                command: "pdftotext".to_string(),
+                args: vec!["{}".to_string(), "-".to_string()],
+            }
+        );
+        assert!(process_ext.matches(Path::new("docs/manual.PDF")));
+        assert!(!process_ext.matches(Path::new("docs/manual.md")));
+    }
+
+    #[test]
+    fn test_parse_process_ext_missing_colon() {
+        assert_eq!(
+            "pdftotext {} -".parse::<ProcessExt>(),
+            Err(ProcessExtParseError::MissingColon)
+        );
+    }
+
+    #[test]
+    fn test_parse_process_ext_missing_placeholder() {
+        assert_eq!(
+            "pdf:pdftotext -".parse::<ProcessExt>(),
+            Err(ProcessExtParseError::MissingPlaceholder)
+        );
+        assert_eq!(
+            "pdf:".parse::<ProcessExt>(),
+            Err(ProcessExtParseError::MissingCommand)
+        );
+        assert_eq!(
+            ":pdftotext {} -".parse::<ProcessExt>(),
+            Err(ProcessExtParseError::MissingExtension)
+        );
+    }
+}
@@ -1,11 +1,19 @@
-use std::{convert::TryFrom, fmt::Display};
+use std::{
+    convert::TryFrom,
+    fmt::Display,
+    hash::{Hash, Hasher},
+};
 
-use crate::{BasicAuthCredentials, ErrorKind, Uri};
+use crate::{types::uri::raw::RawUriSpan, BasicAuthCredentials, ErrorKind, Uri};
 
 use super::InputSource;
 
 /// A request type that can be handle by lychee
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+///
+/// The span is not taken into account when comparing requests, so that a link
+/// which is repeated within a document is only checked once.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct Request {
     /// A valid Uniform Resource Identifier of a given endpoint, which can be
     /// checked with lychee
@@ -22,8 +30,43 @@ pub struct Request {
     /// Specifies the attribute (e.g. `href`) that contained the URI
     pub attribute: Option<String>,
 
+    /// The `target` attribute of the element that contained the URI, if any
+    pub target: Option<String>,
+
+    /// The `rel` attribute of the element that contained the URI, if any
+    pub rel: Option<String>,
+
     /// Basic auth credentials
     pub credentials: Option<BasicAuthCredentials>,
+
+    /// Position of the URI in the document, if known
+    pub span: Option<RawUriSpan>,
+}
+
+impl PartialEq for Request {
+    fn eq(&self, other: &Self) -> bool {
+        self.uri == other.uri
+            && self.source == other.source
+            && self.element == other.element
+            && self.attribute == other.attribute
+            && self.target == other.target
+            && self.rel == other.rel
+            && self.credentials == other.credentials
+    }
+}
+
+impl Eq for Request {}
+
+impl Hash for Request {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.uri.hash(state);
+        self.source.hash(state);
+        self.element.hash(state);
+        self.attribute.hash(state);
+        self.target.hash(state);
+        self.rel.hash(state);
+        self.credentials.hash(state);
+    }
 }
 
 impl Request {
@@ -42,6 +85,9 @@ impl Request {
             source,
             element,
             attribute,
+            target: None,
+            rel: None,
+            span: None,
             credentials,
         }
     }
@@ -3,7 +3,7 @@ use std::fmt::Display;
 use http::StatusCode;
 use serde::Serialize;
 
-use crate::{InputSource, Status, Uri};
+use crate::{types::uri::raw::RawUriSpan, InputSource, Status, Uri};
 
 /// Response type returned by lychee after checking a URI
 #[derive(Debug)]
@@ -14,7 +14,15 @@ impl Response {
     #[must_use]
     /// Create new response
     pub const fn new(uri: Uri, status: Status, source: InputSource) -> Self {
-        Response(source, ResponseBody { uri, status })
+        Response(source, ResponseBody::new(uri, status))
+    }
+
+    #[inline]
+    #[must_use]
+    /// Set the position of the checked URI in its source document
+    pub const fn with_span(mut self, span: Option<RawUriSpan>) -> Self {
+        self.1.span = span;
+        self
     }
 
     #[inline]
@@ -43,12 +51,29 @@ impl Serialize for Response {
 #[allow(clippy::module_name_repetitions)]
 #[derive(Debug, Serialize, Hash, PartialEq, Eq)]
 /// Encapsulates the state of a URI check
+#[non_exhaustive]
 pub struct ResponseBody {
     #[serde(flatten)]
     /// The URI which was checked
     pub uri: Uri,
     /// The status of the check
     pub status: Status,
+    /// Position of the URI in the source document, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub span: Option<RawUriSpan>,
+}
+
+impl ResponseBody {
+    #[inline]
+    #[must_use]
+    /// Create a new response body for a URI without a known position
+    pub const fn new(uri: Uri, status: Status) -> Self {
+        ResponseBody {
+            uri,
+            status,
+            span: None,
+        }
+    }
 }
 
 // Extract as much information from the underlying error conditions as possible
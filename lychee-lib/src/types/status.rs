@@ -5,7 +5,7 @@ use reqwest::Response;
 use serde::ser::SerializeStruct;
 use serde::{Serialize, Serializer};
 
-use crate::ErrorKind;
+use crate::{utils, ErrorKind};
 
 use super::CacheStatus;
 
@@ -17,6 +17,11 @@ const ICON_UNKNOWN: &str = "\u{003f}"; // ?
 const ICON_ERROR: &str = "\u{2717}"; // ✗
 const ICON_TIMEOUT: &str = "\u{29d6}"; // ⧖
 const ICON_CACHED: &str = "\u{21bb}"; // ↻
+const ICON_WARNING: &str = "\u{26a0}"; // ⚠
+
+/// Pseudo status code of requests where the host refused the connection, in
+/// the spirit of the `000` reported by curl
+const CONNECTION_REFUSED_CODE: u16 = 0;
 
 /// Response status of the request.
 #[allow(variant_size_differences)]
@@ -34,12 +39,18 @@ pub enum Status {
     UnknownStatusCode(StatusCode),
     /// Resource was excluded from checking
     Excluded,
+    /// Resource was excluded from checking for the given reason, e.g. by a
+    /// [`Handler`](crate::chain::Handler) in a plugin request chain
+    ExcludedWithReason(String),
     /// The request type is currently not supported,
     /// for example when the URL scheme is `slack://`.
     /// See <https://github.com/lycheeverse/lychee/issues/199>
     Unsupported(ErrorKind),
     /// Cached request status from previous run
     Cached(CacheStatus),
+    /// The link works, but has an issue worth pointing out (e.g. a long
+    /// redirect chain). Warnings don't fail a run unless requested.
+    Warning(StatusCode, String),
 }
 
 impl Display for Status {
@@ -49,11 +60,13 @@ impl Display for Status {
             Status::Redirected(code) => write!(f, "Redirect ({code})"),
             Status::UnknownStatusCode(code) => write!(f, "Unknown status ({code})"),
             Status::Excluded => f.write_str("Excluded"),
+            Status::ExcludedWithReason(reason) => write!(f, "Excluded: {reason}"),
             Status::Timeout(Some(code)) => write!(f, "Timeout ({code})"),
             Status::Timeout(None) => f.write_str("Timeout"),
             Status::Unsupported(e) => write!(f, "Unsupported: {e}"),
             Status::Error(e) => write!(f, "Failed: {e}"),
             Status::Cached(status) => write!(f, "Cached: {status}"),
+            Status::Warning(_, message) => write!(f, "Warning: {message}"),
         }
     }
 }
@@ -65,9 +78,20 @@ impl Serialize for Status {
     {
         let mut s;
         if let Some(code) = self.code() {
-            s = serializer.serialize_struct("Status", 2)?;
+            s = serializer.serialize_struct("Status", 3)?;
             s.serialize_field("text", &self.to_string())?;
             s.serialize_field("code", &code.as_u16())?;
+            if let Some(reason) = code.canonical_reason() {
+                s.serialize_field("reason", reason)?;
+            }
+        } else if self.is_connection_refused() {
+            s = serializer.serialize_struct("Status", 4)?;
+            s.serialize_field("text", &self.to_string())?;
+            s.serialize_field("code", &CONNECTION_REFUSED_CODE)?;
+            s.serialize_field("error_kind", "connection_refused")?;
+            if let Some(details) = self.details() {
+                s.serialize_field("details", &details)?;
+            }
         } else if let Some(details) = self.details() {
             s = serializer.serialize_struct("Status", 2)?;
             s.serialize_field("text", &self.to_string())?;
@@ -146,7 +170,8 @@ impl Status {
             Status::UnknownStatusCode(_) => None,
             Status::Unsupported(_) => None,
             Status::Cached(_) => None,
-            Status::Excluded => None,
+            Status::Excluded | Status::ExcludedWithReason(_) => None,
+            Status::Warning(..) => None,
         }
     }
 
@@ -173,10 +198,19 @@ impl Status {
     pub const fn is_excluded(&self) -> bool {
         matches!(
             self,
-            Status::Excluded | Status::Cached(CacheStatus::Excluded)
+            Status::Excluded
+                | Status::ExcludedWithReason(_)
+                | Status::Cached(CacheStatus::Excluded)
         )
     }
 
+    #[inline]
+    #[must_use]
+    /// Returns `true` if the check resulted in a warning
+    pub const fn is_warning(&self) -> bool {
+        matches!(self, Status::Warning(..))
+    }
+
     #[inline]
     #[must_use]
     /// Returns `true` if a check took too long to complete
@@ -184,6 +218,13 @@ impl Status {
         matches!(self, Status::Timeout(_))
     }
 
+    #[inline]
+    #[must_use]
+    /// Returns `true` if the host refused the connection
+    pub const fn is_connection_refused(&self) -> bool {
+        matches!(self, Status::Error(ErrorKind::ConnectionRefused(_)))
+    }
+
     #[inline]
     #[must_use]
     /// Returns `true` if a URI is unsupported
@@ -201,11 +242,12 @@ impl Status {
             Status::Ok(_) => ICON_OK,
             Status::Redirected(_) => ICON_REDIRECTED,
             Status::UnknownStatusCode(_) => ICON_UNKNOWN,
-            Status::Excluded => ICON_EXCLUDED,
+            Status::Excluded | Status::ExcludedWithReason(_) => ICON_EXCLUDED,
             Status::Error(_) => ICON_ERROR,
             Status::Timeout(_) => ICON_TIMEOUT,
             Status::Unsupported(_) => ICON_UNSUPPORTED,
             Status::Cached(_) => ICON_CACHED,
+            Status::Warning(..) => ICON_WARNING,
         }
     }
 
@@ -216,7 +258,8 @@ impl Status {
             Status::Ok(code)
             | Status::Redirected(code)
             | Status::UnknownStatusCode(code)
-            | Status::Timeout(Some(code)) => Some(*code),
+            | Status::Timeout(Some(code))
+            | Status::Warning(code, _) => Some(*code),
             Status::Error(kind) | Status::Unsupported(kind) => {
                 if let Some(error) = kind.reqwest_error() {
                     error.status()
@@ -237,37 +280,38 @@ impl Status {
         }
     }
 
-    /// Return the HTTP status code as string (if any)
+    /// Return the canonical reason phrase of the HTTP status code (if any),
+    /// e.g. `Not Found` for 404
+    #[must_use]
+    pub fn reason(&self) -> Option<&'static str> {
+        self.code().and_then(|code| code.canonical_reason())
+    }
+
+    /// Return the HTTP status code followed by its canonical reason phrase,
+    /// e.g. `418 I'm a teapot`. Codes without a canonical reason phrase are
+    /// returned on their own. Refused connections get the pseudo-code `000`.
+    /// Other statuses without a code are described by a short label like
+    /// `ERR` or `TIMEOUT` instead.
     #[must_use]
     pub fn code_as_string(&self) -> String {
+        if let Some(code) = self.code() {
+            return match code.canonical_reason() {
+                Some(reason) => format!("{} {reason}", code.as_str()),
+                None => code.as_str().to_string(),
+            };
+        }
         match self {
-            Status::Ok(code) | Status::Redirected(code) | Status::UnknownStatusCode(code) => {
-                code.as_str().to_string()
+            Status::Excluded
+            | Status::ExcludedWithReason(_)
+            | Status::Cached(CacheStatus::Excluded) => "EXCLUDED".to_string(),
+            Status::Timeout(_) => "TIMEOUT".to_string(),
+            Status::Unsupported(_) | Status::Cached(CacheStatus::Unsupported) => {
+                "IGNORED".to_string()
             }
-            Status::Excluded => "EXCLUDED".to_string(),
-            Status::Error(e) => match e {
-                ErrorKind::NetworkRequest(e)
-                | ErrorKind::ReadResponseBody(e)
-                | ErrorKind::BuildRequestClient(e) => match e.status() {
-                    Some(code) => code.as_str().to_string(),
-                    None => "ERR".to_string(),
-                },
-                _ => "ERR".to_string(),
-            },
-            Status::Timeout(code) => match code {
-                Some(code) => code.as_str().to_string(),
-                None => "TIMEOUT".to_string(),
-            },
-            Status::Unsupported(_) => "IGNORED".to_string(),
-            Status::Cached(cache_status) => match cache_status {
-                CacheStatus::Ok(code) => code.to_string(),
-                CacheStatus::Error(code) => match code {
-                    Some(code) => code.to_string(),
-                    None => "ERR".to_string(),
-                },
-                CacheStatus::Excluded => "EXCLUDED".to_string(),
-                CacheStatus::Unsupported => "IGNORED".to_string(),
-            },
+            Status::Error(ErrorKind::ConnectionRefused(_)) => {
+                format!("{CONNECTION_REFUSED_CODE:03} Connection refused")
+            }
+            _ => "ERR".to_string(),
         }
     }
 
@@ -292,6 +336,8 @@ impl From<reqwest::Error> for Status {
     fn from(e: reqwest::Error) -> Self {
         if e.is_timeout() {
             Self::Timeout(e.status())
+        } else if utils::reqwest::is_connection_refused(&e) {
+            Self::Error(ErrorKind::ConnectionRefused(e))
         } else if e.is_redirect() {
             Self::Error(ErrorKind::TooManyRedirects(e))
         } else if e.is_builder() {
@@ -314,13 +360,52 @@ mod tests {
         let status_ok = Status::Ok(StatusCode::from_u16(200).unwrap());
         let serialized_with_code = serde_json::to_string(&status_ok).unwrap();
         assert_eq!(
-            "{\"text\":\"OK (200 OK)\",\"code\":200}",
+            "{\"text\":\"OK (200 OK)\",\"code\":200,\"reason\":\"OK\"}",
             serialized_with_code
         );
 
+        let status_unknown = Status::UnknownStatusCode(StatusCode::from_u16(999).unwrap());
+        let serialized_without_reason = serde_json::to_string(&status_unknown).unwrap();
+        assert_eq!(
+            "{\"text\":\"Unknown status (999 <unknown status code>)\",\"code\":999}",
+            serialized_without_reason
+        );
+
         let status_timeout = Status::Timeout(None);
         let serialized_without_code = serde_json::to_string(&status_timeout).unwrap();
         assert_eq!("{\"text\":\"Timeout\"}", serialized_without_code);
+
+        let status_warning = Status::Warning(StatusCode::OK, "long redirect chain".to_string());
+        let serialized_warning = serde_json::to_string(&status_warning).unwrap();
+        assert_eq!(
+            "{\"text\":\"Warning: long redirect chain\",\"code\":200,\"reason\":\"OK\"}",
+            serialized_warning
+        );
+    }
+
+    #[test]
+    fn test_code_as_string() {
+        let teapot = StatusCode::IM_A_TEAPOT;
+        assert_eq!(Status::Ok(teapot).code_as_string(), "418 I'm a teapot");
+        assert_eq!(Status::Ok(teapot).reason(), Some("I'm a teapot"));
+        assert_eq!(
+            Status::Cached(CacheStatus::Ok(418)).code_as_string(),
+            "418 I'm a teapot"
+        );
+        assert_eq!(
+            Status::Cached(CacheStatus::Error(Some(418))).code_as_string(),
+            "418 I'm a teapot"
+        );
+        assert_eq!(
+            Status::UnknownStatusCode(StatusCode::from_u16(999).unwrap()).code_as_string(),
+            "999"
+        );
+        assert_eq!(Status::Timeout(None).code_as_string(), "TIMEOUT");
+        assert_eq!(Status::Excluded.code_as_string(), "EXCLUDED");
+        assert_eq!(
+            Status::Cached(CacheStatus::Error(None)).code_as_string(),
+            "ERR"
+        );
     }
 
     #[test]
@@ -0,0 +1,119 @@
+//! Persistent identifiers of scholarly works, like DOIs, arXiv IDs and
+//! `PubMed` IDs.
+//!
+//! Identifiers are checked through their canonical resolvers instead of the
+//! site they redirect to. Publishers often block automated requests, which
+//! would otherwise make perfectly valid identifiers look broken.
+
+use crate::Uri;
+
+/// Hosts of the DOI resolver
+const DOI_HOSTS: [&str; 3] = ["doi.org", "dx.doi.org", "www.doi.org"];
+
+/// A persistent identifier of a scholarly work
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Identifier {
+    /// A Digital Object Identifier, e.g. `10.1000/182`
+    Doi(String),
+    /// An arXiv identifier, e.g. `2101.00001` or `hep-th/9901001`
+    Arxiv(String),
+    /// A `PubMed` identifier, e.g. `12345678`
+    Pmid(String),
+}
+
+impl Identifier {
+    /// Recognize an identifier URI like `doi:10.1000/182`, `arxiv:2101.00001`
+    /// or `pmid:12345678`, as well as links to the DOI resolver like
+    /// `https://doi.org/10.1000/182`
+    pub(crate) fn from_uri(uri: &Uri) -> Option<Self> {
+        let identifier = match uri.scheme() {
+            "doi" => Self::Doi(uri.path().to_string()),
+            "arxiv" => Self::Arxiv(uri.path().to_string()),
+            "pmid" => Self::Pmid(uri.path().to_string()),
+            "http" | "https" if uri.domain().is_some_and(|d| DOI_HOSTS.contains(&d)) => {
+                Self::Doi(uri.path().trim_start_matches('/').to_string())
+            }
+            _ => return None,
+        };
+        identifier.is_valid().then_some(identifier)
+    }
+
+    fn is_valid(&self) -> bool {
+        match self {
+            Self::Doi(doi) => doi.starts_with("10.") && doi.contains('/'),
+            Self::Arxiv(id) => !id.is_empty(),
+            Self::Pmid(id) => !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit()),
+        }
+    }
+
+    /// The canonical URL of the identifier, which gets reported to the user
+    pub(crate) fn resolver_url(&self) -> String {
+        match self {
+            Self::Doi(doi) => format!("https://doi.org/{doi}"),
+            Self::Arxiv(id) => format!("https://arxiv.org/abs/{id}"),
+            Self::Pmid(id) => format!("https://pubmed.ncbi.nlm.nih.gov/{id}/"),
+        }
+    }
+
+    /// The URL which is requested to find out whether the identifier exists.
+    ///
+    /// DOIs are looked up through the handle API of the resolver, which
+    /// answers with a 404 for unknown DOIs without redirecting to the
+    /// publisher.
+    pub(crate) fn lookup_url(&self) -> String {
+        match self {
+            Self::Doi(doi) => format!("https://doi.org/api/handles/{doi}"),
+            _ => self.resolver_url(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identifier(uri: &str) -> Option<Identifier> {
+        Identifier::from_uri(&Uri::try_from(uri).unwrap())
+    }
+
+    #[test]
+    fn test_from_uri() {
+        assert_eq!(
+            identifier("doi:10.1000/182"),
+            Some(Identifier::Doi("10.1000/182".to_string()))
+        );
+        assert_eq!(
+            identifier("https://doi.org/10.1000/182"),
+            Some(Identifier::Doi("10.1000/182".to_string()))
+        );
+        assert_eq!(
+            identifier("arXiv:hep-th/9901001"),
+            Some(Identifier::Arxiv("hep-th/9901001".to_string()))
+        );
+        assert_eq!(
+            identifier("PMID:12345678"),
+            Some(Identifier::Pmid("12345678".to_string()))
+        );
+
+        assert_eq!(identifier("doi:foo"), None);
+        assert_eq!(identifier("pmid:abc"), None);
+        assert_eq!(identifier("https://doi.org/"), None);
+        assert_eq!(identifier("https://example.com/10.1000/182"), None);
+    }
+
+    #[test]
+    fn test_resolver_and_lookup_url() {
+        let doi = Identifier::Doi("10.1000/182".to_string());
+        assert_eq!(doi.resolver_url(), "https://doi.org/10.1000/182");
+        assert_eq!(doi.lookup_url(), "https://doi.org/api/handles/10.1000/182");
+
+        let arxiv = Identifier::Arxiv("2101.00001v2".to_string());
+        assert_eq!(arxiv.lookup_url(), "https://arxiv.org/abs/2101.00001v2");
+
+        let pmid = Identifier::Pmid("12345678".to_string());
+        assert_eq!(
+            pmid.resolver_url(),
+            "https://pubmed.ncbi.nlm.nih.gov/12345678/"
+        );
+    }
+}
@@ -1,3 +1,4 @@
 pub(crate) mod github;
+pub(crate) mod identifier;
 pub(crate) mod raw;
 pub(crate) mod valid;
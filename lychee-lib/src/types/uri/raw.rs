@@ -1,8 +1,13 @@
 use std::fmt::Display;
 
+use serde::Serialize;
+
 /// A raw URI that got extracted from a document with a fuzzy parser.
 /// Note that this can still be invalid according to stricter URI standards
-#[derive(Clone, Debug, PartialEq, Eq)]
+///
+/// The span is not taken into account when comparing raw URIs.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
 pub struct RawUri {
     /// Unparsed URI represented as a `String`. There is no guarantee that it
     /// can be parsed into a URI object
@@ -17,6 +22,111 @@ pub struct RawUri {
     /// that will be checked e.g. by trying to filter out links that were found
     /// in unwanted attributes like `srcset` or `manifest`.
     pub attribute: Option<String>,
+    /// Value of the `target` attribute of the element that contained the URI
+    /// (e.g. `_blank`), if any
+    pub target: Option<String>,
+    /// Value of the `rel` attribute of the element that contained the URI
+    /// (e.g. `noopener`), if any
+    pub rel: Option<String>,
+    /// Position of the URI in the document, if known
+    pub span: Option<RawUriSpan>,
+}
+
+impl PartialEq for RawUri {
+    fn eq(&self, other: &Self) -> bool {
+        self.text == other.text
+            && self.element == other.element
+            && self.attribute == other.attribute
+            && self.target == other.target
+            && self.rel == other.rel
+    }
+}
+
+impl Eq for RawUri {}
+
+/// The position of a [`RawUri`] in the document it was extracted from
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+pub struct RawUriSpan {
+    /// Line of the first character of the URI, starting at 1
+    pub line: usize,
+    /// Column of the first character of the URI, starting at 1 and counted
+    /// in characters
+    pub column: usize,
+}
+
+impl RawUriSpan {
+    /// The position of the byte `offset` in `input`
+    pub(crate) fn at_offset(input: &str, offset: usize) -> Self {
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+
+    /// The position in a document of this span, which is relative to a part
+    /// of the document starting at `start`
+    pub(crate) const fn shifted(self, start: RawUriSpan) -> Self {
+        if self.line == 1 {
+            Self {
+                line: start.line,
+                column: start.column + self.column - 1,
+            }
+        } else {
+            Self {
+                line: start.line + self.line - 1,
+                column: self.column,
+            }
+        }
+    }
+}
+
+/// Looks up the [`RawUriSpan`] of byte offsets in a document. Unlike
+/// [`RawUriSpan::at_offset`], this only scans the document once.
+#[derive(Clone)]
+pub(crate) struct SpanIndex<'a> {
+    input: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> SpanIndex<'a> {
+    pub(crate) fn new(input: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(input.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { input, line_starts }
+    }
+
+    /// The position of the byte `offset`, or of the character containing it
+    pub(crate) fn span(&self, offset: usize) -> RawUriSpan {
+        let mut offset = offset.min(self.input.len());
+        while !self.input.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line = self
+            .line_starts
+            .partition_point(|&line_start| line_start <= offset);
+        let line_start = self.line_starts[line - 1];
+        RawUriSpan {
+            line,
+            column: self.input[line_start..offset].chars().count() + 1,
+        }
+    }
+
+    /// The byte offset at which `line` ends, including its line break
+    pub(crate) fn line_end(&self, line: usize) -> usize {
+        self.line_starts
+            .get(line)
+            .copied()
+            .unwrap_or(self.input.len())
+    }
+}
+
+impl Display for RawUriSpan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
 }
 
 impl RawUri {
@@ -24,6 +134,15 @@ impl RawUri {
     pub(crate) fn is_anchor(&self) -> bool {
         self.text.starts_with('#')
     }
+
+    /// Whether the URI embeds external content into an HTML page, i.e. it is
+    /// the `src` of an `<iframe>` or `<embed>` or the `data` of an `<object>`
+    pub(crate) fn is_embed(&self) -> bool {
+        matches!(
+            (self.element.as_deref(), self.attribute.as_deref()),
+            (Some("iframe" | "embed"), Some("src")) | (Some("object"), Some("data"))
+        )
+    }
 }
 impl Display for RawUri {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -37,10 +156,37 @@ impl From<&str> for RawUri {
             text: text.to_string(),
             element: None,
             attribute: None,
+            target: None,
+            rel: None,
+            span: None,
         }
     }
 }
 
+/// Returns whether a link opens in a new browsing context (i.e.
+/// `target="_blank"`) without `rel="noopener"` or `rel="noreferrer"`.
+/// Such links give the opened page access to `window.opener`, which allows
+/// tab-nabbing.
+pub(crate) fn is_missing_noopener(target: Option<&str>, rel: Option<&str>) -> bool {
+    let opens_new_tab = target.is_some_and(|target| target.eq_ignore_ascii_case("_blank"));
+    let noopener = rel.is_some_and(|rel| {
+        rel.split_ascii_whitespace().any(|value| {
+            value.eq_ignore_ascii_case("noopener") || value.eq_ignore_ascii_case("noreferrer")
+        })
+    });
+    opens_new_tab && !noopener
+}
+
+/// Returns whether a link is the source of an image, i.e. the `src` or
+/// `srcset` of an `<img>`, the `srcset` of a `<source>` in a `<picture>`, or
+/// a Markdown image.
+pub(crate) fn is_image_source(element: Option<&str>, attribute: Option<&str>) -> bool {
+    matches!(
+        (element, attribute),
+        (Some("img"), Some("src" | "srcset")) | (Some("source"), Some("srcset"))
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -53,4 +199,27 @@ mod tests {
         let raw_uri = RawUri::from("notan#anchor");
         assert!(!raw_uri.is_anchor());
     }
+
+    #[test]
+    fn test_is_missing_noopener() {
+        assert!(is_missing_noopener(Some("_blank"), None));
+        assert!(is_missing_noopener(Some("_BLANK"), Some("external")));
+        assert!(!is_missing_noopener(Some("_blank"), Some("noopener")));
+        assert!(!is_missing_noopener(
+            Some("_blank"),
+            Some("external noreferrer")
+        ));
+        assert!(!is_missing_noopener(Some("_self"), None));
+        assert!(!is_missing_noopener(None, None));
+    }
+
+    #[test]
+    fn test_is_image_source() {
+        assert!(is_image_source(Some("img"), Some("src")));
+        assert!(is_image_source(Some("source"), Some("srcset")));
+        assert!(!is_image_source(Some("source"), Some("src")));
+        assert!(!is_image_source(Some("img"), Some("longdesc")));
+        assert!(!is_image_source(Some("a"), Some("href")));
+        assert!(!is_image_source(None, None));
+    }
 }
@@ -1,11 +1,10 @@
 use std::{convert::TryFrom, fmt::Display, net::IpAddr};
 
-use email_address::EmailAddress;
 use ip_network::Ipv6Network;
 use serde::{Deserialize, Serialize};
 use url::Url;
 
-use crate::{ErrorKind, Result};
+use crate::{utils, ErrorKind, Result};
 
 use super::raw::RawUri;
 
@@ -107,6 +106,13 @@ impl Uri {
         self.scheme() == "file"
     }
 
+    #[inline]
+    #[must_use]
+    /// Check if the URI is a WebSocket endpoint (`ws://` or `wss://`)
+    pub fn is_websocket(&self) -> bool {
+        matches!(self.scheme(), "ws" | "wss")
+    }
+
     #[inline]
     #[must_use]
     /// Check if the URI is a `data` URI
@@ -243,13 +249,13 @@ impl TryFrom<&str> for Uri {
                 // else entirely. Try the mail address check first, as it's the
                 // most common case. Note that we use a relatively weak check
                 // here because
-                // - `fast_chemail::parse_email` does not accept parameters
-                //   (`foo@example?subject=bar`), which are common for website
-                //   contact forms
+                // - parameters (`foo@example?subject=bar`) are common for
+                //   website contact forms, so they are split off before the
+                //   address is validated
                 // - `check_if_email_exists` does additional spam detection,
                 //   which we only want to execute when checking the email
                 //   addresses, but not when printing all links with `--dump`.
-                if EmailAddress::is_valid(s) {
+                if utils::mail::is_valid(s) {
                     // Use the `mailto:` scheme for mail addresses,
                     // which will allow `Url::parse` to parse them.
                     if let Ok(uri) = Url::parse(&format!("mailto:{s}")) {
@@ -327,6 +333,11 @@ mod tests {
             Uri::try_from("mail@example.com?foo=bar"),
             Ok(mail("mail@example.com?foo=bar"))
         );
+        assert_eq!(
+            Uri::try_from("mail+tag@example.com?subject=Hi&cc=other@example.com"),
+            Ok(mail("mail+tag@example.com?subject=Hi&cc=other@example.com"))
+        );
+        assert!(Uri::try_from("mail@example.com?cc=invalid").is_err());
     }
 
     #[test]
@@ -337,6 +348,13 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_uri_websocket() {
+        assert!(website("ws://example.com/socket").is_websocket());
+        assert!(website("wss://example.com/socket").is_websocket());
+        assert!(!website("https://example.com/socket").is_websocket());
+    }
+
     #[test]
     fn test_uri_host_ip_v4() {
         assert_eq!(
@@ -0,0 +1,64 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde_with::DeserializeFromStr;
+use thiserror::Error;
+
+/// Errors which can occur when parsing a [`UrlColumn`]
+#[derive(Debug, Clone, Copy, Error, PartialEq, Eq)]
+pub enum UrlColumnError {
+    /// No column was given
+    #[error("Empty URL column")]
+    EmptyInput,
+
+    /// Columns are numbered starting at 1
+    #[error("Invalid URL column `0`, columns are numbered starting at 1")]
+    ZeroIndex,
+}
+
+/// The column of a CSV input which contains the URLs to check, given either
+/// by its number, starting at 1, or by its name in the header row
+#[derive(Debug, Clone, DeserializeFromStr, PartialEq, Eq)]
+pub enum UrlColumn {
+    /// Number of the column, starting at 1
+    Index(usize),
+    /// Name of the column in the header row
+    Name(String),
+}
+
+impl FromStr for UrlColumn {
+    type Err = UrlColumnError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(UrlColumnError::EmptyInput);
+        }
+        match s.parse::<usize>() {
+            Ok(0) => Err(UrlColumnError::ZeroIndex),
+            Ok(index) => Ok(Self::Index(index)),
+            Err(_) => Ok(Self::Name(s.to_string())),
+        }
+    }
+}
+
+impl Display for UrlColumn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Index(index) => write!(f, "{index}"),
+            Self::Name(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_url_column() {
+        assert_eq!("2".parse(), Ok(UrlColumn::Index(2)));
+        assert_eq!(" url ".parse(), Ok(UrlColumn::Name("url".to_string())));
+        assert_eq!("".parse::<UrlColumn>(), Err(UrlColumnError::EmptyInput));
+        assert_eq!("0".parse::<UrlColumn>(), Err(UrlColumnError::ZeroIndex));
+    }
+}
@@ -0,0 +1,72 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use async_trait::async_trait;
+use http::header::{HeaderValue, USER_AGENT};
+use reqwest::Request;
+
+use crate::{
+    chain::{ChainResult, Handler},
+    Status,
+};
+
+/// Sets the user agent of each request to the next one of a fixed list.
+///
+/// Some hosts block repeated requests with an identical user agent, so
+/// cycling through several of them helps when checking many links to the same
+/// host. Clones share their position in the list. Without any user agents,
+/// requests are passed on unchanged.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct UserAgentRotation {
+    user_agents: Arc<[HeaderValue]>,
+    next: Arc<AtomicUsize>,
+}
+
+impl UserAgentRotation {
+    pub(crate) fn new(user_agents: Vec<HeaderValue>) -> Self {
+        Self {
+            user_agents: user_agents.into(),
+            next: Arc::default(),
+        }
+    }
+
+    fn next_user_agent(&self) -> Option<HeaderValue> {
+        if self.user_agents.is_empty() {
+            return None;
+        }
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.user_agents.len();
+        Some(self.user_agents[index].clone())
+    }
+}
+
+#[async_trait]
+impl Handler<Request, Status> for UserAgentRotation {
+    async fn handle(&mut self, mut input: Request) -> ChainResult<Request, Status> {
+        if let Some(user_agent) = self.next_user_agent() {
+            input.headers_mut().insert(USER_AGENT, user_agent);
+        }
+        ChainResult::Next(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation() {
+        assert_eq!(UserAgentRotation::default().next_user_agent(), None);
+
+        let rotation = UserAgentRotation::new(vec![
+            HeaderValue::from_static("a"),
+            HeaderValue::from_static("b"),
+        ]);
+        let clone = rotation.clone();
+
+        assert_eq!(rotation.next_user_agent().unwrap(), "a");
+        assert_eq!(clone.next_user_agent().unwrap(), "b");
+        assert_eq!(rotation.next_user_agent().unwrap(), "a");
+    }
+}
@@ -42,12 +42,27 @@ impl FragmentChecker {
     /// Returns false, if there is a fragment in the link and the path is to a markdown file which
     /// doesn't contain the given fragment.
     ///
+    /// An empty fragment or `#top` always points to the top of the document
+    /// (see the [HTML spec]), so these are accepted as well. Line ranges like
+    /// `#10-20`, as linked on the source pages generated by rustdoc, are
+    /// accepted if the first line exists.
+    ///
+    /// Both the fragment of the link and the fragments of the document are
+    /// percent-decoded before comparing them, so `#über` and `#%C3%BCber`
+    /// match no matter how either side is written. The comparison itself is
+    /// case-sensitive, like `id` attributes in HTML.
+    ///
     /// In all other cases, returns true.
+    ///
+    /// [HTML spec]: https://html.spec.whatwg.org/multipage/browsing-the-web.html#scroll-to-the-fragment-identifier
     pub(crate) async fn check(&self, path: &Path, url: &Url) -> Result<bool> {
         let Some(fragment) = url.fragment() else {
             return Ok(true);
         };
         let fragment = percent_decode_str(fragment).decode_utf8()?;
+        if fragment.is_empty() || fragment.eq_ignore_ascii_case("top") {
+            return Ok(true);
+        }
         let url_without_frag = Self::remove_fragment(url.clone());
 
         let extractor = match FileType::from(path) {
@@ -55,13 +70,20 @@ impl FragmentChecker {
             FileType::Html => extract_html_fragments,
             FileType::Plaintext => return Ok(true),
         };
+        let contains = |file_frags: &HashSet<String>| {
+            file_frags.contains(&fragment as &str)
+                || line_range_start(&fragment).is_some_and(|line| file_frags.contains(line))
+        };
         match self.cache.lock().await.entry(url_without_frag) {
             Entry::Vacant(entry) => {
                 let content = fs::read_to_string(path).await?;
-                let file_frags = extractor(&content);
-                Ok(entry.insert(file_frags).contains(&fragment as &str))
+                let file_frags = extractor(&content)
+                    .iter()
+                    .map(|frag| percent_decode_str(frag).decode_utf8_lossy().into_owned())
+                    .collect();
+                Ok(contains(entry.insert(file_frags)))
             }
-            Entry::Occupied(entry) => Ok(entry.get().contains(&fragment as &str)),
+            Entry::Occupied(entry) => Ok(contains(entry.get())),
         }
     }
 
@@ -70,3 +92,11 @@ impl FragmentChecker {
         url.into()
     }
 }
+
+/// The first line of a line range like `10-20`. The source pages of rustdoc
+/// only have elements for single lines, while links highlight whole ranges.
+fn line_range_start(fragment: &str) -> Option<&str> {
+    let (start, end) = fragment.split_once('-')?;
+    let is_line = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    (is_line(start) && is_line(end)).then_some(start)
+}
@@ -0,0 +1,63 @@
+use crate::Date;
+
+/// Keys of the YAML frontmatter which hold the date a document was last
+/// changed, in order of preference
+const DATE_KEYS: [&str; 2] = ["lastmod", "date"];
+
+/// The date of the last change of a Markdown document, taken from the
+/// `lastmod` or `date` field of its YAML frontmatter.
+///
+/// Times and time zones of the value are ignored. Returns `None` if there is
+/// no frontmatter or neither field holds a date.
+pub(crate) fn last_modified(content: &str) -> Option<Date> {
+    let mut lines = content.trim_start_matches('\u{feff}').lines();
+    if lines.next()?.trim_end() != "---" {
+        return None;
+    }
+    let fields: Vec<(&str, &str)> = lines
+        .take_while(|line| !matches!(line.trim_end(), "---" | "..."))
+        .filter_map(|line| line.split_once(':'))
+        .collect();
+
+    DATE_KEYS.iter().find_map(|key| {
+        fields
+            .iter()
+            .filter(|(name, _)| name == key)
+            .find_map(|(_, value)| date(value))
+    })
+}
+
+/// The leading `YYYY-MM-DD` date of a frontmatter value
+fn date(value: &str) -> Option<Date> {
+    let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+    value.get(..10)?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_last_modified() {
+        assert_eq!(
+            last_modified("---\ntitle: Post\ndate: 2024-03-01\n---\n# Post"),
+            Some("2024-03-01".parse().unwrap())
+        );
+        assert_eq!(
+            last_modified("---\ndate: 2024-03-01\nlastmod: \"2024-05-02T10:00:00Z\"\n---\n"),
+            Some("2024-05-02".parse().unwrap())
+        );
+        assert_eq!(
+            last_modified("---\r\nlastmod: '2024-05-02'\r\n---\r\n"),
+            Some("2024-05-02".parse().unwrap())
+        );
+        assert_eq!(last_modified("---\ndate: yesterday\n---\n"), None);
+        assert_eq!(last_modified("---\ndate: 2024-99-99\n---\n"), None);
+        assert_eq!(last_modified("# Post\n\ndate: 2024-03-01\n"), None);
+        assert_eq!(
+            last_modified("---\ntitle: Post\n---\ndate: 2024-03-01\n"),
+            None
+        );
+        assert_eq!(last_modified("---\n  date: 2024-03-01\n---\n"), None);
+    }
+}
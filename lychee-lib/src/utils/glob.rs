@@ -0,0 +1,82 @@
+/// Expand the brace alternatives of a glob pattern, like a shell would.
+///
+/// `docs/{guide,api}/*.md` becomes `docs/guide/*.md` and `docs/api/*.md`.
+/// Braces can be nested, e.g. `{a,b{c,d}}` expands to `a`, `bc` and `bd`.
+/// Braces without a comma, like `{a}`, and unbalanced braces are kept as-is.
+pub(crate) fn expand_braces(pattern: &str) -> Vec<String> {
+    let Some((open, close, alternatives)) = find_alternatives(pattern) else {
+        return vec![pattern.to_owned()];
+    };
+    let (prefix, suffix) = (&pattern[..open], &pattern[close + 1..]);
+    alternatives
+        .into_iter()
+        .flat_map(|alternative| expand_braces(&format!("{prefix}{alternative}{suffix}")))
+        .collect()
+}
+
+/// Find the first pair of braces which contains a comma on its own nesting
+/// level. Returns the positions of both braces and the alternatives in
+/// between.
+fn find_alternatives(pattern: &str) -> Option<(usize, usize, Vec<&str>)> {
+    pattern.match_indices('{').find_map(|(open, _)| {
+        let mut depth = 0;
+        let mut start = open + 1;
+        let mut alternatives = Vec::new();
+        for (i, c) in pattern[open..].char_indices() {
+            let i = open + i;
+            match c {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        if alternatives.is_empty() {
+                            return None;
+                        }
+                        alternatives.push(&pattern[start..i]);
+                        return Some((open, i, alternatives));
+                    }
+                }
+                ',' if depth == 1 => {
+                    alternatives.push(&pattern[start..i]);
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_braces() {
+        assert_eq!(expand_braces("*.md"), vec!["*.md"]);
+        assert_eq!(
+            expand_braces("docs/{guide,api}/**/*.md"),
+            vec!["docs/guide/**/*.md", "docs/api/**/*.md"]
+        );
+        assert_eq!(
+            expand_braces("{a,b}/{c,d}"),
+            vec!["a/c", "a/d", "b/c", "b/d"]
+        );
+    }
+
+    #[test]
+    fn test_expand_nested_braces() {
+        assert_eq!(
+            expand_braces("{a,b{c,d}}.md"),
+            vec!["a.md", "bc.md", "bd.md"]
+        );
+        assert_eq!(expand_braces("x{{a,b}}"), vec!["x{a}", "x{b}"]);
+    }
+
+    #[test]
+    fn test_keep_braces_without_alternatives() {
+        assert_eq!(expand_braces("{a}.md"), vec!["{a}.md"]);
+        assert_eq!(expand_braces("{a,b.md"), vec!["{a,b.md"]);
+        assert_eq!(expand_braces("a}{b,c"), vec!["a}{b,c"]);
+    }
+}
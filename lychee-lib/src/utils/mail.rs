@@ -0,0 +1,155 @@
+use email_address::EmailAddress;
+use percent_encoding::percent_decode_str;
+use reqwest::Url;
+
+/// Query parameters of `mailto:` URIs which contain further addresses
+const ADDRESS_PARAMS: [&str; 3] = ["to", "cc", "bcc"];
+
+/// Split a mail address like `mailto:user@example.com?subject=Hello` into the
+/// address and the query parameters, if any
+pub(crate) fn split_address(s: &str) -> (&str, Option<&str>) {
+    let s = s.strip_prefix("mailto:").unwrap_or(s);
+    match s.split_once('?') {
+        Some((address, query)) => (address, Some(query)),
+        None => (s, None),
+    }
+}
+
+/// Whether `s` is a syntactically valid mail address, with or without the
+/// `mailto:` scheme.
+///
+/// Query parameters like `?subject=Hello` are not part of the address and
+/// therefore allowed. Addresses in the `to`, `cc` and `bcc` parameters must be
+/// valid as well. Plus-addressing (`user+tag@example.com`) is accepted, as `+`
+/// is a valid character of the local part.
+pub(crate) fn is_valid(s: &str) -> bool {
+    let (address, query) = split_address(s);
+    is_valid_address(address)
+        && query
+            .into_iter()
+            .flat_map(|query| query.split('&'))
+            .filter_map(|param| param.split_once('='))
+            .filter(|(name, _)| ADDRESS_PARAMS.contains(&name.to_ascii_lowercase().as_str()))
+            .flat_map(|(_, addresses)| addresses.split(','))
+            .all(|address| address.is_empty() || is_valid_address(address))
+}
+
+/// Whether a single, possibly percent-encoded address is valid
+fn is_valid_address(address: &str) -> bool {
+    EmailAddress::is_valid(&percent_decode_str(address).decode_utf8_lossy())
+}
+
+/// The domain of a mail address like `mailto:user@example.com?subject=Hello`
+#[cfg_attr(not(feature = "verify-mail"), allow(dead_code))]
+pub(crate) fn domain(s: &str) -> Option<&str> {
+    let (address, _) = split_address(s);
+    address
+        .rsplit_once('@')
+        .map(|(_, domain)| domain)
+        .filter(|domain| !domain.is_empty())
+}
+
+/// Reduce a `mailto:` URL to the canonical form of its address, so that
+/// spellings of the same mailbox are checked once: query parameters and the
+/// tag of plus-addressing (`user+tag@`) are removed and the address is
+/// lowercased. Other URLs are left unchanged.
+///
+/// Strictly speaking, the local part is case-sensitive, but virtually all mail
+/// servers treat it case-insensitively.
+pub(crate) fn canonicalize(url: &mut Url) {
+    if url.scheme() != "mailto" {
+        return;
+    }
+    let Some((local, domain)) = url.path().rsplit_once('@') else {
+        return;
+    };
+    let local = local.split_once('+').map_or(local, |(local, _tag)| local);
+    let address = format!("{local}@{domain}").to_lowercase();
+    url.set_path(&address);
+    url.set_query(None);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_domain() {
+        assert_eq!(
+            domain("mailto:user@example.com?subject=Hi"),
+            Some("example.com")
+        );
+        assert_eq!(domain("\"a@b\"@example.org"), Some("example.org"));
+        assert_eq!(domain("user@"), None);
+        assert_eq!(domain("user"), None);
+    }
+
+    #[test]
+    fn test_is_valid() {
+        for address in [
+            "user@example.com",
+            "mailto:user@example.com",
+            "User.Name@Example.COM",
+            "user+tag@example.com",
+            "mailto:user+tag+more@example.com",
+            "user@example.com?subject=Hello",
+            "mailto:user@example.com?subject=%5BHello%5D&body=Hi%20there",
+            "mailto:user@example.com?cc=other@example.com&bcc=a@example.org,b@example.org",
+            "mailto:user@example.com?subject=Hello&cc=",
+            "mailto:user%2Btag@example.com",
+        ] {
+            assert!(is_valid(address), "{address}");
+        }
+    }
+
+    #[test]
+    fn test_is_invalid() {
+        for address in [
+            "",
+            "mailto:",
+            "user",
+            "user@",
+            "@example.com",
+            "user@@example.com",
+            "us er@example.com",
+            "user@exa mple.com",
+            "mailto:user@example.com?cc=not-an-address",
+            "mailto:user@example.com?subject=Hi&bcc=a@example.org,invalid",
+        ] {
+            assert!(!is_valid(address), "{address}");
+        }
+    }
+
+    #[test]
+    fn test_split_address() {
+        assert_eq!(
+            split_address("mailto:user@example.com?subject=Hi&cc=a@example.org"),
+            ("user@example.com", Some("subject=Hi&cc=a@example.org"))
+        );
+        assert_eq!(
+            split_address("user@example.com"),
+            ("user@example.com", None)
+        );
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        for (input, expected) in [
+            ("mailto:user@example.com", "mailto:user@example.com"),
+            ("mailto:User@Example.COM", "mailto:user@example.com"),
+            ("mailto:user+tag@example.com", "mailto:user@example.com"),
+            (
+                "mailto:User+Tag+More@example.com?subject=Hi&cc=a@example.org",
+                "mailto:user@example.com",
+            ),
+            (
+                "https://example.com/User+Tag",
+                "https://example.com/User+Tag",
+            ),
+        ] {
+            let mut url = Url::parse(input).unwrap();
+            canonicalize(&mut url);
+            assert_eq!(url.as_str(), expected, "{input}");
+        }
+    }
+}
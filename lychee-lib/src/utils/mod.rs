@@ -1,5 +1,9 @@
 pub(crate) mod fragment_checker;
+pub(crate) mod frontmatter;
+pub(crate) mod glob;
+pub(crate) mod mail;
 pub(crate) mod path;
 pub(crate) mod request;
 pub(crate) mod reqwest;
+pub(crate) mod sitemap;
 pub(crate) mod url;
@@ -98,6 +98,21 @@ pub(crate) fn contains(parent: &PathBuf, child: &PathBuf) -> Result<bool> {
     Ok(child.starts_with(parent))
 }
 
+/// Whether `path` lies within the directory `root`.
+///
+/// Both paths are compared after resolving `..` segments. If both exist, they
+/// are additionally compared after resolving symlinks, so that a symlink can't
+/// lead out of `root`.
+pub(crate) fn is_within(root: &Path, path: &Path) -> bool {
+    if !absolute_path(path.to_path_buf()).starts_with(absolute_path(root.to_path_buf())) {
+        return false;
+    }
+    match (fs::canonicalize(root), fs::canonicalize(path)) {
+        (Ok(root), Ok(path)) => path.starts_with(root),
+        _ => true,
+    }
+}
+
 #[cfg(test)]
 mod test_path {
     use super::*;
@@ -216,4 +231,18 @@ mod test_path {
 
         assert_eq!(contains(&parent.to_owned(), &child), Ok(true));
     }
+
+    #[test]
+    fn test_is_within() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        std::fs::create_dir(root.join("docs")).unwrap();
+
+        assert!(is_within(root, &root.join("docs")));
+        assert!(is_within(root, &root.join("docs/missing.md")));
+        assert!(is_within(root, &root.join("docs/../index.md")));
+        assert!(!is_within(root, &root.join("../outside.md")));
+        assert!(!is_within(root, &root.join("docs/../../etc/passwd")));
+        assert!(!is_within(&root.join("docs"), root));
+    }
 }
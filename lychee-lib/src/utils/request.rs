@@ -1,10 +1,7 @@
 use log::info;
 use percent_encoding::percent_decode_str;
 use reqwest::Url;
-use std::{
-    collections::HashSet,
-    path::{Path, PathBuf},
-};
+use std::path::{Path, PathBuf};
 
 use crate::{
     basic_auth::BasicAuthExtractor,
@@ -20,14 +17,14 @@ fn credentials(extractor: &Option<BasicAuthExtractor>, uri: &Uri) -> Option<Basi
     extractor.as_ref().and_then(|ext| ext.matches(uri))
 }
 
-/// Create requests out of the collected URLs.
+/// Create requests out of the collected URLs, in the order they were found.
 /// Only keeps "valid" URLs. This filters out anchors for example.
 pub(crate) fn create(
     uris: Vec<RawUri>,
     input_content: &InputContent,
     base: &Option<Base>,
     extractor: &Option<BasicAuthExtractor>,
-) -> Result<HashSet<Request>> {
+) -> Result<Vec<Request>> {
     let base_url = Base::from_source(&input_content.source);
 
     let requests: Result<Vec<Option<Request>>> = uris
@@ -48,27 +45,25 @@ pub(crate) fn create(
                 c => c.clone(),
             };
 
-            if let Ok(uri) = Uri::try_from(raw_uri) {
+            // Creates the request for the resolved URI. Only one of the
+            // branches below calls this.
+            let target = raw_uri.target.clone();
+            let rel = raw_uri.rel.clone();
+            let span = raw_uri.span;
+            let new_request = move |uri: Uri| {
                 let credentials = credentials(extractor, &uri);
+                Request {
+                    target,
+                    rel,
+                    span,
+                    ..Request::new(uri, source, element, attribute, credentials)
+                }
+            };
 
-                Ok(Some(Request::new(
-                    uri,
-                    source,
-                    element,
-                    attribute,
-                    credentials,
-                )))
+            if let Ok(uri) = Uri::try_from(raw_uri) {
+                Ok(Some(new_request(uri)))
             } else if let Some(url) = base.as_ref().and_then(|u| u.join(&text)) {
-                let uri = Uri { url };
-                let credentials = credentials(extractor, &uri);
-
-                Ok(Some(Request::new(
-                    uri,
-                    source,
-                    element,
-                    attribute,
-                    credentials,
-                )))
+                Ok(Some(new_request(Uri { url })))
             } else if let InputSource::FsPath(root) = &input_content.source {
                 let path = if is_anchor {
                     match root.file_name() {
@@ -83,16 +78,7 @@ pub(crate) fn create(
                 };
 
                 if let Some(url) = create_uri_from_path(root, &path, base)? {
-                    let uri = Uri { url };
-                    let credentials = credentials(extractor, &uri);
-
-                    Ok(Some(Request::new(
-                        uri,
-                        source,
-                        element,
-                        attribute,
-                        credentials,
-                    )))
+                    Ok(Some(new_request(Uri { url })))
                 } else {
                     // In case we cannot create a URI from a path but we didn't receive an error,
                     // it means that some preconditions were not met, e.g. the `base_url` wasn't set.
@@ -102,16 +88,7 @@ pub(crate) fn create(
                 if base.is_some() {
                     Ok(None)
                 } else {
-                    let uri = Uri { url: url? };
-                    let credentials = credentials(extractor, &uri);
-
-                    Ok(Some(Request::new(
-                        uri,
-                        source,
-                        element,
-                        attribute,
-                        credentials,
-                    )))
+                    Ok(Some(new_request(Uri { url: url? })))
                 }
             } else {
                 info!("Handling of `{}` not implemented yet", text);
@@ -119,8 +96,7 @@ pub(crate) fn create(
             }
         })
         .collect();
-    let requests: Vec<Request> = requests?.into_iter().flatten().collect();
-    Ok(HashSet::from_iter(requests))
+    Ok(requests?.into_iter().flatten().collect())
 }
 
 fn construct_url(base: &Option<Url>, text: &str) -> Option<Result<Url>> {
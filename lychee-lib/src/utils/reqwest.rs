@@ -1,3 +1,5 @@
+use std::{error::Error, io};
+
 /// Extract the most relevant parts from a reqwest error
 ///
 /// The reqwest `Error` fields aren't public as they are an implementation
@@ -23,6 +25,18 @@ fn trim_inner(text: String) -> String {
     text
 }
 
+/// Whether the request failed because the host refused the connection
+pub(crate) fn is_connection_refused(e: &reqwest::Error) -> bool {
+    let mut source = e.source();
+    while let Some(err) = source {
+        if let Some(io_error) = err.downcast_ref::<io::Error>() {
+            return io_error.kind() == io::ErrorKind::ConnectionRefused;
+        }
+        source = err.source();
+    }
+    false
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,121 @@
+use reqwest::Url;
+
+/// The `robots.txt` of the site `url` belongs to
+pub(crate) fn robots_url(url: &Url) -> Option<Url> {
+    url.join("/robots.txt").ok()
+}
+
+/// The sitemaps referenced by the `Sitemap:` lines of a `robots.txt`.
+///
+/// The field name is case-insensitive and lines which don't contain an
+/// absolute URL are ignored.
+pub(crate) fn sitemaps_in_robots(robots: &str) -> Vec<Url> {
+    robots
+        .lines()
+        .map(|line| line.split_once('#').map_or(line, |(line, _comment)| line))
+        .filter_map(|line| line.split_once(':'))
+        .filter(|(field, _)| field.trim().eq_ignore_ascii_case("sitemap"))
+        .filter_map(|(_, value)| Url::parse(value.trim()).ok())
+        .collect()
+}
+
+/// Whether `sitemap` is a sitemap index, which lists further sitemaps instead
+/// of pages
+pub(crate) fn is_index(sitemap: &str) -> bool {
+    sitemap.contains("<sitemapindex")
+}
+
+/// The URLs in the `<loc>` elements of a sitemap or sitemap index
+pub(crate) fn locations(sitemap: &str) -> Vec<String> {
+    let mut locations = Vec::new();
+    let mut rest = sitemap;
+    while let Some((_, after)) = rest.split_once("<loc>") {
+        let Some((location, after)) = after.split_once("</loc>") else {
+            break;
+        };
+        let location = location.trim();
+        let location = location
+            .strip_prefix("<![CDATA[")
+            .and_then(|location| location.strip_suffix("]]>"))
+            .map_or_else(|| unescape(location), ToString::to_string);
+        if !location.is_empty() {
+            locations.push(location);
+        }
+        rest = after;
+    }
+    locations
+}
+
+/// Replace the predefined XML entities, which sitemaps must use for `&`, `'`,
+/// `"`, `<` and `>` in URLs
+fn unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_robots_url() {
+        let url = Url::parse("https://example.com/docs/index.html?q=1").unwrap();
+        assert_eq!(
+            robots_url(&url).unwrap().as_str(),
+            "https://example.com/robots.txt"
+        );
+    }
+
+    #[test]
+    fn test_sitemaps_in_robots() {
+        let robots = "User-agent: *
+Disallow: /private
+# Sitemap: https://example.com/commented.xml
+Sitemap: https://example.com/sitemap.xml
+sitemap:https://example.com/news.xml # news only
+Sitemap: /relative.xml";
+        assert_eq!(
+            sitemaps_in_robots(robots),
+            vec![
+                Url::parse("https://example.com/sitemap.xml").unwrap(),
+                Url::parse("https://example.com/news.xml").unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_locations() {
+        let sitemap = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url><loc>https://example.com/</loc><lastmod>2024-01-01</lastmod></url>
+  <url>
+    <loc>
+      https://example.com/search?q=a&amp;page=2
+    </loc>
+  </url>
+  <url><loc><![CDATA[https://example.com/?a=1&b=2]]></loc></url>
+  <url><loc></loc></url>
+</urlset>"#;
+        assert!(!is_index(sitemap));
+        assert_eq!(
+            locations(sitemap),
+            vec![
+                "https://example.com/",
+                "https://example.com/search?q=a&page=2",
+                "https://example.com/?a=1&b=2",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_index_locations() {
+        let index = r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <sitemap><loc>https://example.com/sitemap-1.xml</loc></sitemap>
+</sitemapindex>"#;
+        assert!(is_index(index));
+        assert_eq!(locations(index), vec!["https://example.com/sitemap-1.xml"]);
+    }
+}
@@ -1,9 +1,26 @@
+use glob::Pattern;
 use linkify::LinkFinder;
 
 use once_cell::sync::Lazy;
+use percent_encoding::percent_decode_str;
+use regex::Regex;
+use reqwest::Url;
 
 static LINK_FINDER: Lazy<LinkFinder> = Lazy::new(LinkFinder::new);
 
+/// Identifiers in running text, like `doi:10.1000/182`, `arXiv:2101.00001`
+/// or `PMID: 12345678`
+static IDENTIFIER_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?ix)\b(?:
+            doi:\s?(?P<doi>10\.\d{4,9}/[^\s"'<>\]]+)
+            | arxiv:\s?(?P<arxiv>\d{4}\.\d{4,5}(?:v\d+)?|[a-z][a-z.-]*/\d{7}(?:v\d+)?)
+            | pmid:\s?(?P<pmid>\d{1,9})\b
+        )"#,
+    )
+    .expect("identifier regex is valid")
+});
+
 /// Remove all GET parameters from a URL and separates out the fragment.
 /// The link is not a URL but a String as it may not have a base domain.
 pub(crate) fn remove_get_params_and_separate_fragment(url: &str) -> (&str, Option<&str>) {
@@ -18,11 +35,65 @@ pub(crate) fn remove_get_params_and_separate_fragment(url: &str) -> (&str, Optio
     (path, frag)
 }
 
+/// Remove all query parameters whose name matches one of `patterns` from
+/// `url`. The encoding of the remaining parameters is preserved.
+pub(crate) fn strip_query_params(url: &mut Url, patterns: &[Pattern]) {
+    let Some(query) = url.query() else {
+        return;
+    };
+    let is_stripped = |param: &str| {
+        let name = param.split_once('=').map_or(param, |(name, _)| name);
+        let name = percent_decode_str(name).decode_utf8_lossy();
+        patterns.iter().any(|pattern| pattern.matches(&name))
+    };
+    let kept: Vec<&str> = query.split('&').filter(|p| !is_stripped(p)).collect();
+    if kept.len() == query.split('&').count() {
+        return;
+    }
+    let kept = kept.join("&");
+    url.set_query((!kept.is_empty()).then_some(kept.as_str()));
+}
+
 // Use `LinkFinder` to offload the raw link searching in plaintext
 pub(crate) fn find_links(input: &str) -> impl Iterator<Item = linkify::Link> {
     LINK_FINDER.links(input)
 }
 
+/// Find all identifiers in `text` and return them as URIs, e.g.
+/// `doi:10.1000/182`, together with their byte offset in `text`
+pub(crate) fn find_identifiers(text: &str) -> impl Iterator<Item = (usize, String)> + '_ {
+    IDENTIFIER_REGEX.captures_iter(text).filter_map(|captures| {
+        let identifier = if let Some(doi) = captures.name("doi") {
+            format!("doi:{}", trim_trailing_punctuation(doi.as_str()))
+        } else if let Some(id) = captures.name("arxiv") {
+            format!("arxiv:{}", id.as_str())
+        } else {
+            format!("pmid:{}", captures.name("pmid")?.as_str())
+        };
+        Some((captures.get(0)?.start(), identifier))
+    })
+}
+
+/// Remove punctuation which ends the surrounding sentence from a DOI.
+/// Closing parentheses are only removed if they are unbalanced, as they are
+/// valid in DOIs like `10.1016/S0140-6736(20)30183-5`.
+fn trim_trailing_punctuation(doi: &str) -> &str {
+    let mut doi = doi;
+    loop {
+        let trimmed = doi.trim_end_matches(['.', ',', ';', ':', '!', '?']);
+        let trimmed = match trimmed.strip_suffix(')') {
+            Some(stripped) if trimmed.matches('(').count() < trimmed.matches(')').count() => {
+                stripped
+            }
+            _ => trimmed,
+        };
+        if trimmed == doi {
+            return doi;
+        }
+        doi = trimmed;
+    }
+}
+
 #[cfg(test)]
 mod test_fs_tree {
     use super::*;
@@ -74,4 +145,54 @@ mod test_fs_tree {
             ("test.png", Some("anchor?anchor!"))
         );
     }
+
+    #[test]
+    fn test_strip_query_params() {
+        let patterns = [
+            Pattern::new("utm_*").unwrap(),
+            Pattern::new("fbclid").unwrap(),
+        ];
+        let strip = |url: &str| {
+            let mut url = Url::parse(url).unwrap();
+            strip_query_params(&mut url, &patterns);
+            url.to_string()
+        };
+
+        assert_eq!(
+            strip("https://example.com/?utm_source=news&utm_medium=email"),
+            "https://example.com/"
+        );
+        assert_eq!(
+            strip("https://example.com/?id=a%20b&fbclid=123#top"),
+            "https://example.com/?id=a%20b#top"
+        );
+        assert_eq!(
+            strip("https://example.com/?utm=1&q=x+y"),
+            "https://example.com/?utm=1&q=x+y"
+        );
+        assert_eq!(strip("https://example.com/"), "https://example.com/");
+    }
+
+    #[test]
+    fn test_find_identifiers() {
+        let text = "See doi:10.1016/S0140-6736(20)30183-5. Also (DOI: 10.1000/182), \
+                    arXiv:2101.00001v2, arXiv:hep-th/9901001 and PMID: 12345678.";
+        assert_eq!(
+            find_identifiers(text)
+                .map(|(_, identifier)| identifier)
+                .collect::<Vec<_>>(),
+            [
+                "doi:10.1016/S0140-6736(20)30183-5",
+                "doi:10.1000/182",
+                "arxiv:2101.00001v2",
+                "arxiv:hep-th/9901001",
+                "pmid:12345678",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_no_identifiers() {
+        assert_eq!(find_identifiers("doi: none, arXiv paper, pmid:").count(), 0);
+    }
 }
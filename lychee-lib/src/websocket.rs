@@ -0,0 +1,108 @@
+//! Check whether a WebSocket endpoint accepts connections.
+//!
+//! Only the opening handshake is performed: an HTTP request asking to upgrade
+//! the connection, as described in RFC 6455. If the server agrees to switch
+//! protocols, the connection is closed right away without sending any frames.
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use http::{
+    header::{CONNECTION, SEC_WEBSOCKET_KEY, SEC_WEBSOCKET_VERSION, UPGRADE},
+    HeaderValue, Method, StatusCode,
+};
+use reqwest::{Request, Url};
+
+use crate::{
+    chain::{ChainResult, Handler},
+    ErrorKind, Status, Uri,
+};
+
+/// The only WebSocket protocol version defined by RFC 6455
+const VERSION: &str = "13";
+
+/// Build the request for the opening handshake with the WebSocket endpoint
+/// at `url`. Returns `None` if `url` is not a `ws://` or `wss://` URL.
+pub(crate) fn upgrade_request(url: &Url) -> Option<Request> {
+    let scheme = match url.scheme() {
+        "ws" => "http",
+        "wss" => "https",
+        _ => return None,
+    };
+    let mut url = url.clone();
+    url.set_scheme(scheme).ok()?;
+
+    let mut request = Request::new(Method::GET, url);
+    let headers = request.headers_mut();
+    headers.insert(CONNECTION, HeaderValue::from_static("Upgrade"));
+    headers.insert(UPGRADE, HeaderValue::from_static("websocket"));
+    headers.insert(SEC_WEBSOCKET_VERSION, HeaderValue::from_static(VERSION));
+    headers.insert(SEC_WEBSOCKET_KEY, HeaderValue::from_str(&key()).ok()?);
+    Some(request)
+}
+
+/// The last handler of the request chain for WebSocket links: sends the
+/// opening handshake and expects the server to switch protocols
+#[derive(Debug, Clone)]
+pub(crate) struct Handshake {
+    reqwest_client: reqwest::Client,
+    uri: Uri,
+}
+
+impl Handshake {
+    pub(crate) const fn new(reqwest_client: reqwest::Client, uri: Uri) -> Self {
+        Self {
+            reqwest_client,
+            uri,
+        }
+    }
+}
+
+#[async_trait]
+impl Handler<Request, Status> for Handshake {
+    async fn handle(&mut self, request: Request) -> ChainResult<Request, Status> {
+        let status = match self.reqwest_client.execute(request).await {
+            Ok(response) if response.status() == StatusCode::SWITCHING_PROTOCOLS => {
+                Status::Ok(StatusCode::SWITCHING_PROTOCOLS)
+            }
+            Ok(response) => {
+                ErrorKind::RejectedWebSocketHandshake(self.uri.clone(), response.status()).into()
+            }
+            Err(e) => e.into(),
+        };
+        ChainResult::Done(status)
+    }
+}
+
+/// A new random handshake key: 16 bytes, base64-encoded
+fn key() -> String {
+    STANDARD.encode(rand::random::<[u8; 16]>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upgrade_request() {
+        let url = Url::parse("wss://example.com:8443/socket?token=1").unwrap();
+        let request = upgrade_request(&url).unwrap();
+
+        assert_eq!(request.method(), Method::GET);
+        assert_eq!(
+            request.url().as_str(),
+            "https://example.com:8443/socket?token=1"
+        );
+        let headers = request.headers();
+        assert_eq!(headers[CONNECTION], "Upgrade");
+        assert_eq!(headers[UPGRADE], "websocket");
+        assert_eq!(headers[SEC_WEBSOCKET_VERSION], "13");
+        assert_eq!(headers[SEC_WEBSOCKET_KEY].len(), 24);
+
+        let url = Url::parse("ws://example.com/socket").unwrap();
+        let request = upgrade_request(&url).unwrap();
+        assert_eq!(request.url().as_str(), "http://example.com/socket");
+
+        let url = Url::parse("https://example.com/socket").unwrap();
+        assert!(upgrade_request(&url).is_none());
+    }
+}